@@ -0,0 +1,375 @@
+//! Human-in-the-loop approval gate for tool calls.
+//!
+//! Agents declare `approvals` rules in `config.yaml`.  When the first
+//! matching rule for a call says `ask`, [`request_approval`] parks the call,
+//! announces it on the gateway (`approval_request` event) and on the
+//! originating Discord channel (✅/❌ reactions), then waits for
+//! [`resolve`], the timeout or the turn's cancellation — whichever comes
+//! first.
+//!
+//! On Discord only the agent's configured `approvers`, or else the user
+//! whose message started the turn ([`TURN_REQUESTER`]), may answer.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::comm::ChannelConnector;
+use crate::config::{ApprovalMode, ApprovalRule};
+
+use super::types::epoch_secs;
+
+tokio::task_local! {
+    /// Discord user id of the user whose message started the running turn.
+    pub static TURN_REQUESTER: Option<String>;
+}
+
+/// The requester of the current turn (see [`TURN_REQUESTER`]).
+pub fn current_requester() -> Option<String> {
+    TURN_REQUESTER.try_with(|r| r.clone()).ok().flatten()
+}
+
+/// Seconds to wait for a decision when `approval_timeout_secs` is unset.
+pub const DEFAULT_APPROVAL_TIMEOUT_SECS: u64 = 300;
+
+/// Outcome of an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approved,
+    Denied,
+    TimedOut,
+    /// The turn was cancelled while waiting.
+    Cancelled,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Approved => "approved",
+            Decision::Denied => "denied",
+            Decision::TimedOut => "timed_out",
+            Decision::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A tool call waiting for a human decision.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingApproval {
+    pub id: String,
+    pub agent: String,
+    pub session: Option<String>,
+    pub channel: String,
+    pub tool: String,
+    pub args_summary: String,
+    /// Discord user id of the user who started the turn that made the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<String>,
+    pub requested_at: u64,
+    pub expires_at: u64,
+}
+
+/// Pending request plus the sender that wakes the paused tool call.
+type PendingEntry = (PendingApproval, oneshot::Sender<bool>);
+
+static PENDING: LazyLock<Mutex<HashMap<String, PendingEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// ---------------------------------------------------------------------------
+// Policy evaluation
+// ---------------------------------------------------------------------------
+
+/// Return the mode of the first rule matching `tool` + `args`, or
+/// [`ApprovalMode::Always`] when no rule matches.
+pub fn evaluate(rules: &[ApprovalRule], tool: &str, args: &serde_json::Value) -> ApprovalMode {
+    rules
+        .iter()
+        .find(|r| rule_matches(r, tool, args))
+        .map(|r| r.mode)
+        .unwrap_or(ApprovalMode::Always)
+}
+
+fn rule_matches(rule: &ApprovalRule, tool: &str, args: &serde_json::Value) -> bool {
+    if !crate::utils::glob_match(&rule.tool, tool) {
+        return false;
+    }
+    rule.args.iter().all(|(key, pattern)| match args.get(key) {
+        Some(serde_json::Value::String(s)) => crate::utils::glob_match(pattern, s),
+        Some(v) => crate::utils::glob_match(pattern, &v.to_string()),
+        None => false,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Pending approvals
+// ---------------------------------------------------------------------------
+
+/// Announce an approval request and wait for a decision.
+///
+/// Returns [`Decision::TimedOut`] if nobody answers within `timeout`, and
+/// [`Decision::Cancelled`] as soon as `cancel` fires; either way the
+/// request is withdrawn.
+pub async fn request_approval(
+    agent_id: &str,
+    session_id: &Option<String>,
+    channel: &str,
+    tool: &str,
+    args_summary: &str,
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> Decision {
+    let now = epoch_secs();
+    let pending = PendingApproval {
+        id: uuid::Uuid::new_v4().to_string(),
+        agent: agent_id.to_string(),
+        session: session_id.clone(),
+        channel: channel.to_string(),
+        tool: tool.to_string(),
+        args_summary: args_summary.to_string(),
+        requested_by: current_requester(),
+        requested_at: now,
+        expires_at: now + timeout.as_secs(),
+    };
+    let id = pending.id.clone();
+
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut map) = PENDING.lock() {
+        map.insert(id.clone(), (pending.clone(), tx));
+    }
+
+    crate::gateway::publish_event_json(&serde_json::json!({
+        "type": "approval_request",
+        "agent": agent_id,
+        "session": session_id,
+        "approval": pending,
+    }));
+
+    if crate::discord::is_enabled() && crate::discord::DiscordConnector.matches(channel) {
+        if let Err(e) = crate::discord::send_approval_prompt(channel, &pending).await {
+            warn!(error = %e, approval = %id, "failed to post approval prompt to Discord");
+        }
+    }
+
+    let decision = tokio::select! {
+        answer = tokio::time::timeout(timeout, rx) => match answer {
+            Ok(Ok(true)) => Decision::Approved,
+            Ok(Ok(false)) | Ok(Err(_)) => Decision::Denied,
+            Err(_) => Decision::TimedOut,
+        },
+        _ = cancel.cancelled() => Decision::Cancelled,
+    };
+    if matches!(decision, Decision::TimedOut | Decision::Cancelled) {
+        if let Ok(mut map) = PENDING.lock() {
+            map.remove(&id);
+        }
+    }
+
+    crate::gateway::publish_event_json(&serde_json::json!({
+        "type": "approval_resolved",
+        "agent": agent_id,
+        "session": session_id,
+        "id": id,
+        "tool": tool,
+        "decision": decision.as_str(),
+    }));
+
+    decision
+}
+
+/// Approve or deny a pending request.  Returns `false` if the id is unknown
+/// (already resolved or timed out).
+pub fn resolve(id: &str, approved: bool) -> bool {
+    let entry = match PENDING.lock() {
+        Ok(mut map) => map.remove(id),
+        Err(_) => None,
+    };
+    match entry {
+        Some((_, tx)) => tx.send(approved).is_ok(),
+        None => false,
+    }
+}
+
+/// Whether the Discord user `user_id` may answer the pending request
+/// `id`: one of the agent's `approvers` or, when none are configured, the
+/// user who started the turn.  Usernames can change, so only ids count.
+pub fn may_answer(id: &str, approvers: &[String], user_id: &str) -> bool {
+    if !approvers.is_empty() {
+        return approvers.iter().any(|a| a == user_id);
+    }
+    let requester = match PENDING.lock() {
+        Ok(map) => map.get(id).and_then(|(p, _)| p.requested_by.clone()),
+        Err(_) => None,
+    };
+    requester.is_some_and(|r| r == user_id)
+}
+
+/// The pending request `id`, if it is still waiting.
+pub fn get_pending(id: &str) -> Option<PendingApproval> {
+    PENDING.lock().ok()?.get(id).map(|(p, _)| p.clone())
+}
+
+/// Snapshot of all pending approvals, oldest first.
+pub fn list_pending() -> Vec<PendingApproval> {
+    let mut list: Vec<PendingApproval> = match PENDING.lock() {
+        Ok(map) => map.values().map(|(p, _)| p.clone()).collect(),
+        Err(_) => Vec::new(),
+    };
+    list.sort_by_key(|p| p.requested_at);
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(tool: &str, args: &[(&str, &str)], mode: ApprovalMode) -> ApprovalRule {
+        ApprovalRule {
+            tool: tool.into(),
+            args: args
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            mode,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = vec![
+            rule("exec_shell", &[("command", "ls*")], ApprovalMode::Always),
+            rule("exec_shell", &[], ApprovalMode::Ask),
+            rule("*", &[], ApprovalMode::Never),
+        ];
+        let ls = serde_json::json!({"command": "ls -la"});
+        let rm = serde_json::json!({"command": "rm -rf build"});
+        assert_eq!(evaluate(&rules, "exec_shell", &ls), ApprovalMode::Always);
+        assert_eq!(evaluate(&rules, "exec_shell", &rm), ApprovalMode::Ask);
+        assert_eq!(
+            evaluate(&rules, "write_file", &serde_json::json!({})),
+            ApprovalMode::Never
+        );
+    }
+
+    #[test]
+    fn no_rules_means_always() {
+        assert_eq!(
+            evaluate(&[], "self_update", &serde_json::json!({})),
+            ApprovalMode::Always
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_wakes_pending_request() {
+        let waiter = tokio::spawn(async {
+            request_approval(
+                "test-agent",
+                &None,
+                "gateway:ws-client",
+                "write_file",
+                "{}",
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+        });
+        let id = loop {
+            if let Some(p) = list_pending().into_iter().find(|p| p.agent == "test-agent") {
+                break p.id;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert!(resolve(&id, false));
+        assert_eq!(waiter.await.unwrap(), Decision::Denied);
+        assert!(!resolve(&id, true));
+    }
+
+    #[tokio::test]
+    async fn only_approvers_or_the_requester_may_answer() {
+        let waiter = tokio::spawn(TURN_REQUESTER.scope(Some("1".into()), async {
+            request_approval(
+                "answer-agent",
+                &None,
+                "123",
+                "exec_shell",
+                "{}",
+                Duration::from_secs(5),
+                &CancellationToken::new(),
+            )
+            .await
+        }));
+        let id = loop {
+            if let Some(p) = list_pending()
+                .into_iter()
+                .find(|p| p.agent == "answer-agent")
+            {
+                break p.id;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert!(may_answer(&id, &[], "1"));
+        assert!(!may_answer(&id, &[], "2"));
+        let approvers = vec!["42".to_string()];
+        assert!(may_answer(&id, &approvers, "42"));
+        assert!(!may_answer(&id, &approvers, "1"));
+        assert!(resolve(&id, true));
+        assert_eq!(waiter.await.unwrap(), Decision::Approved);
+        // Without a requester, nobody may answer unless configured.
+        assert!(!may_answer("gone", &[], "1"));
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_times_out() {
+        let decision = request_approval(
+            "timeout-agent",
+            &None,
+            "gateway:ws-client",
+            "exec_shell",
+            "{}",
+            Duration::from_millis(20),
+            &CancellationToken::new(),
+        )
+        .await;
+        assert_eq!(decision, Decision::TimedOut);
+        assert!(list_pending().iter().all(|p| p.agent != "timeout-agent"));
+    }
+
+    #[tokio::test]
+    async fn cancelling_the_turn_withdraws_the_request() {
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let waiter = tokio::spawn(async move {
+            request_approval(
+                "cancel-agent",
+                &None,
+                "gateway:ws-client",
+                "exec_shell",
+                "{}",
+                Duration::from_secs(300),
+                &token,
+            )
+            .await
+        });
+        let id = loop {
+            if let Some(p) = list_pending()
+                .into_iter()
+                .find(|p| p.agent == "cancel-agent")
+            {
+                break p.id;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        cancel.cancel();
+        let decision = tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("approval wait ignored the cancellation")
+            .unwrap();
+        assert_eq!(decision, Decision::Cancelled);
+        assert!(get_pending(&id).is_none());
+        assert!(!resolve(&id, true));
+    }
+}
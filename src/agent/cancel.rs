@@ -0,0 +1,114 @@
+//! User-initiated cancellation of in-flight turns.
+//!
+//! Each running turn registers a [`CancellationToken`] under its agent id
+//! (turns for one agent are serialised, so there is normally one; a nested
+//! turn, such as a delegate to itself, stacks on top of its caller).  `/stop`,
+//! the gateway cancel endpoint / WS action and the Discord 🛑 reaction all
+//! go through [`cancel`]; the turn notices at its next await point, stops
+//! the model call or tool that is running, and records the turn as
+//! cancelled in its receipt.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use tokio_util::sync::CancellationToken;
+
+/// Reply recorded for a turn that was stopped.
+pub const CANCELLED_REPLY: &str = "⏹️ Stopped.";
+
+/// An agent's running turns: (turn sequence number, token), outermost first.
+type Turns = Vec<(u64, CancellationToken)>;
+
+/// Running turns by agent id.
+static ACTIVE: LazyLock<Mutex<HashMap<String, Turns>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+
+/// Registration of a running turn; unregisters on drop.
+pub struct TurnGuard {
+    agent_id: String,
+    seq: u64,
+    token: CancellationToken,
+}
+
+impl TurnGuard {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        let mut active = ACTIVE.lock().expect("cancel registry poisoned");
+        // Only remove our own entry — the turn that started a nested one
+        // for the same agent is still running and must stay reachable.
+        if let Some(turns) = active.get_mut(&self.agent_id) {
+            turns.retain(|(seq, _)| *seq != self.seq);
+            if turns.is_empty() {
+                active.remove(&self.agent_id);
+            }
+        }
+    }
+}
+
+/// Register a turn for `agent_id` and return its guard.
+pub fn begin(agent_id: &str) -> TurnGuard {
+    let seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+    let token = CancellationToken::new();
+    ACTIVE
+        .lock()
+        .expect("cancel registry poisoned")
+        .entry(agent_id.to_string())
+        .or_default()
+        .push((seq, token.clone()));
+    TurnGuard {
+        agent_id: agent_id.to_string(),
+        seq,
+        token,
+    }
+}
+
+/// Cancel the running turn for `agent_id`, along with any nested turns it
+/// started.  Returns `false` when the agent is idle or its turn is already
+/// being cancelled.
+pub fn cancel(agent_id: &str) -> bool {
+    let active = ACTIVE.lock().expect("cancel registry poisoned");
+    let mut cancelled = false;
+    for (_, token) in active.get(agent_id).into_iter().flatten() {
+        if !token.is_cancelled() {
+            token.cancel();
+            cancelled = true;
+        }
+    }
+    cancelled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_only_reaches_the_registered_turn() {
+        assert!(!cancel("cancel-test"));
+        let outer = begin("cancel-test");
+        let inner = begin("cancel-test");
+        drop(outer);
+        assert!(cancel("cancel-test"));
+        assert!(inner.token().is_cancelled());
+        assert!(!cancel("cancel-test"));
+        drop(inner);
+        assert!(!cancel("cancel-test"));
+    }
+
+    #[test]
+    fn outer_turn_stays_cancellable_after_a_nested_one() {
+        let outer = begin("cancel-nested");
+        let inner = begin("cancel-nested");
+        drop(inner);
+        assert!(cancel("cancel-nested"));
+        assert!(outer.token().is_cancelled());
+        drop(outer);
+        assert!(!cancel("cancel-nested"));
+    }
+}
@@ -4,6 +4,8 @@
 //! The implementation is split across submodules:
 //!
 //! - [`types`]     – Agent struct, receipt types, shared constants/helpers
+//! - [`approval`]  – Human-in-the-loop approval gate for tool calls
+//! - [`cancel`]    – User-initiated cancellation of running turns
 //! - [`debug`]     – Debug payload ring buffer and model-request logging
 //! - [`dispatch`]  – Message bus subscription, routing, in-flight tracking
 //! - [`tool_exec`] – Single tool invocation, corrective helpers
@@ -11,6 +13,8 @@
 //! - [`turn`]      – Turn execution, bootstrap, history, enforcement retry
 //! - [`persist`]   – Session exchange and receipt persistence
 
+pub mod approval;
+pub mod cancel;
 mod debug;
 mod dispatch;
 mod persist;
@@ -116,6 +120,7 @@ mod tests {
         let msg = crate::comm::IncomingMessage {
             agent_id: Some("test-agent".into()),
             author: "tester".into(),
+            author_id: None,
             content: "hello world".into(),
             channel: "test".into(),
             timestamp: 0,
@@ -1,11 +1,19 @@
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
+use crate::config::{AgentConfig, ApprovalMode, Config};
 use crate::models::{ChatMessage, ProviderManager, ProviderResponse, TokenUsage};
 use crate::tools;
+use crate::tools::builtins::artifacts;
 
+use super::approval;
 use super::debug::emit_model_request_debug;
 use super::types::{
     truncate_tool_result, uuid_like_id, ModelCallDetail, TokenUsageSummary, ToolCallRecord,
+    MAX_TOOL_RESULT_BYTES,
 };
 
 // ---------------------------------------------------------------------------
@@ -24,21 +32,100 @@ pub struct ToolResult {
     pub result_json: String,
     pub failed: bool,
     pub record: ToolCallRecord,
+    /// Images (data URIs) the tool asked to show the model; see
+    /// [`tools::attach_image`].
+    pub images: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
 // Execution
 // ---------------------------------------------------------------------------
 
+/// Extra time a tool gets past its limit, so tools that enforce the same
+/// limit themselves (`exec_shell`) can report it before being dropped.
+const TIMEOUT_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
 pub async fn execute_tool(
     inv: &ToolInvocation,
     workspace: &std::path::Path,
     agent_id: &str,
     session_id: &Option<String>,
+    channel: &str,
+    cancel: &CancellationToken,
 ) -> ToolResult {
-    let args: serde_json::Value =
-        serde_json::from_str(&inv.args_str).unwrap_or(serde_json::json!({}));
+    let parsed = if inv.args_str.trim().is_empty() {
+        Ok(serde_json::json!({}))
+    } else {
+        serde_json::from_str::<serde_json::Value>(&inv.args_str)
+    };
     let args_summary = crate::utils::truncate_str(&inv.args_str, 200);
+    let violations = match &parsed {
+        Ok(args) => tools::args_schema(&inv.name)
+            .map(|schema| tools::schema::validate(&schema, args))
+            .unwrap_or_default(),
+        Err(e) => vec![format!("arguments are not valid JSON: {e}")],
+    };
+    let args = parsed.unwrap_or(serde_json::json!({}));
+
+    let cfg = Config::current().await;
+    let agent_cfg = cfg
+        .as_ref()
+        .ok()
+        .and_then(Option::as_ref)
+        .and_then(|c| c.agents.iter().find(|a| a.id == agent_id));
+    let enabled_skills = agent_cfg.and_then(|a| a.enabled_skills.as_deref());
+    let denial = if !crate::mcp::tool_visible_to(&inv.name, agent_id)
+        || !tools::skill_tool_enabled(&inv.name, enabled_skills)
+    {
+        // Another agent's MCP tool, or a tool of a skill this agent has
+        // not enabled — indistinguishable from a missing one.
+        Some(format!("unknown tool: {}", inv.name))
+    } else if !violations.is_empty() {
+        Some(invalid_args_corrective(&inv.name, &violations))
+    } else {
+        match &cfg {
+            Err(e) => Some(format!(
+                "tool `{}` was not run: this agent's tool rules could not be checked ({e})",
+                inv.name
+            )),
+            Ok(None) => None,
+            Ok(Some(cfg)) => match check_policy(inv, &args, workspace, agent_id, cfg) {
+                Some(denial) => Some(denial),
+                None => {
+                    check_approval(
+                        inv,
+                        &args,
+                        &args_summary,
+                        agent_id,
+                        session_id,
+                        channel,
+                        cfg,
+                        cancel,
+                    )
+                    .await
+                }
+            },
+        }
+    };
+    if let Some(denial) = denial {
+        return ToolResult {
+            call_id: inv.call_id.clone(),
+            name: inv.name.clone(),
+            result_json: serde_json::to_string(&serde_json::json!({"error": &denial}))
+                .unwrap_or_default(),
+            failed: true,
+            record: ToolCallRecord {
+                tool: inv.name.clone(),
+                args_summary,
+                success: false,
+                duration_ms: 0,
+                error: Some(denial),
+                artifact_id: None,
+                invalid_args: !violations.is_empty(),
+            },
+            images: Vec::new(),
+        };
+    }
 
     crate::gateway::publish_event_json(&serde_json::json!({
         "type": "tool_start",
@@ -48,12 +135,16 @@ pub async fn execute_tool(
     }));
 
     let timer = std::time::Instant::now();
-    let result = tools::call_skill(&inv.name, args, workspace).await;
+    let (result, mut images) = tools::collect_images(run_limited(
+        inv, args, workspace, agent_cfg, channel, cancel,
+    ))
+    .await;
     let elapsed = timer.elapsed().as_millis() as u64;
 
-    let (result_json, failed, error) = match result {
+    let (mut result_json, failed, error) = match result {
         Ok(v) => (serde_json::to_string(&v).unwrap_or_default(), false, None),
         Err(e) => {
+            images.clear();
             let err_msg = format!("{e}");
             crate::gateway::publish_event_json(&serde_json::json!({
                 "type": "tool_error",
@@ -70,11 +161,14 @@ pub async fn execute_tool(
         }
     };
 
+    let artifact_id = store_oversized(&inv.name, &mut result_json, workspace);
+
     crate::gateway::publish_event_json(&serde_json::json!({
         "type": "tool_end",
         "agent": agent_id,
         "session": session_id,
         "tool": inv.name,
+        "artifact_id": artifact_id,
     }));
 
     ToolResult {
@@ -88,7 +182,244 @@ pub async fn execute_tool(
             success: !failed,
             duration_ms: elapsed,
             error,
+            artifact_id,
+            invalid_args: false,
         },
+        images,
+    }
+}
+
+/// Move a result larger than `MAX_TOOL_RESULT_BYTES` into the artifact
+/// store, replacing it with a head/tail summary.  Returns the artifact id.
+/// On storage failure the result is left for `truncate_tool_result`.
+fn store_oversized(
+    tool: &str,
+    result_json: &mut String,
+    workspace: &std::path::Path,
+) -> Option<String> {
+    if result_json.len() <= MAX_TOOL_RESULT_BYTES || tool == "read_artifact" {
+        return None;
+    }
+    let text = serde_json::from_str(result_json)
+        .map(|v| artifacts::render(&v))
+        .unwrap_or_else(|_| result_json.clone());
+    match artifacts::store(workspace, &text) {
+        Ok(id) => {
+            let summary = artifacts::summary(&id, tool, &text);
+            *result_json = serde_json::to_string(&summary).unwrap_or_default();
+            Some(id)
+        }
+        Err(e) => {
+            warn!(tool, error = %e, "failed to store oversized tool result");
+            None
+        }
+    }
+}
+
+/// Call the tool, stopping it when its time limit (see
+/// [`tools::timeout_for`]) runs out or the turn is cancelled.
+async fn run_limited(
+    inv: &ToolInvocation,
+    args: serde_json::Value,
+    workspace: &std::path::Path,
+    agent_cfg: Option<&AgentConfig>,
+    channel: &str,
+    cancel: &CancellationToken,
+) -> anyhow::Result<serde_json::Value> {
+    if cancel.is_cancelled() {
+        anyhow::bail!("cancelled by the user before `{}` ran", inv.name);
+    }
+    let limit = tools::timeout_for(&inv.name, agent_cfg);
+    let call = tools::CALL_CHANNEL.scope(
+        channel.to_string(),
+        tools::call_skill(&inv.name, args, workspace),
+    );
+    let limited = async {
+        match limit {
+            Some(limit) => match tokio::time::timeout(limit + TIMEOUT_GRACE, call).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!(
+                    "tool `{}` timed out after {}s and was stopped",
+                    inv.name,
+                    limit.as_secs()
+                )),
+            },
+            None => call.await,
+        }
+    };
+    tokio::select! {
+        result = limited => result,
+        _ = cancel.cancelled() => Err(anyhow::anyhow!("`{}` was cancelled by the user", inv.name)),
+    }
+}
+
+/// Run the tool calls from one model response.  Consecutive non-exclusive
+/// calls run concurrently, at most `max_parallel` at a time; an exclusive
+/// call (see `ToolMeta::exclusive`) waits for everything before it and
+/// runs alone.  Results come back in invocation order.
+pub async fn execute_batch(
+    invocations: Vec<ToolInvocation>,
+    workspace: &std::path::Path,
+    agent_id: &str,
+    session_id: &Option<String>,
+    channel: &str,
+    max_parallel: usize,
+    cancel: &CancellationToken,
+) -> Vec<ToolResult> {
+    let limit = Arc::new(Semaphore::new(max_parallel.max(1)));
+    let mut results = Vec::with_capacity(invocations.len());
+    let mut running: Vec<(String, String, JoinHandle<ToolResult>)> = Vec::new();
+
+    for inv in invocations {
+        if max_parallel <= 1 || tools::is_exclusive(&inv.name) {
+            join_running(&mut running, &mut results).await;
+            results
+                .push(execute_tool(&inv, workspace, agent_id, session_id, channel, cancel).await);
+            continue;
+        }
+        let limit = limit.clone();
+        let ws = workspace.to_path_buf();
+        let aid = agent_id.to_string();
+        let sid = session_id.clone();
+        let ch = channel.to_string();
+        let cancel = cancel.clone();
+        let (call_id, name) = (inv.call_id.clone(), inv.name.clone());
+        // Task-locals do not cross `spawn`; carry them over.
+        let requester = approval::current_requester();
+        let turn_cfg = crate::config::turn_config();
+        running.push((
+            call_id,
+            name,
+            tokio::spawn(crate::config::TURN_CONFIG.scope(
+                turn_cfg,
+                approval::TURN_REQUESTER.scope(requester, async move {
+                    let _permit = limit.acquire_owned().await;
+                    execute_tool(&inv, &ws, &aid, &sid, &ch, &cancel).await
+                }),
+            )),
+        ));
+    }
+    join_running(&mut running, &mut results).await;
+    results
+}
+
+async fn join_running(
+    running: &mut Vec<(String, String, JoinHandle<ToolResult>)>,
+    results: &mut Vec<ToolResult>,
+) {
+    for (call_id, name, handle) in running.drain(..) {
+        let result = match handle.await {
+            Ok(tr) => tr,
+            Err(join_err) => {
+                let error = format!("tool task panicked: {join_err}");
+                ToolResult {
+                    call_id,
+                    name: name.clone(),
+                    result_json: serde_json::to_string(&serde_json::json!({"error": &error}))
+                        .unwrap_or_default(),
+                    failed: true,
+                    record: ToolCallRecord {
+                        tool: name,
+                        args_summary: String::new(),
+                        success: false,
+                        duration_ms: 0,
+                        error: Some(error),
+                        artifact_id: None,
+                        invalid_args: false,
+                    },
+                    images: Vec::new(),
+                }
+            }
+        };
+        results.push(result);
+    }
+}
+
+/// Apply the agent's `tools:` policy to a tool call.  Returns the denial
+/// message when the call is not permitted.
+fn check_policy(
+    inv: &ToolInvocation,
+    args: &serde_json::Value,
+    workspace: &std::path::Path,
+    agent_id: &str,
+    cfg: &Config,
+) -> Option<String> {
+    let policy = cfg
+        .agents
+        .iter()
+        .find(|a| a.id == agent_id)?
+        .tools
+        .as_ref()?;
+    let default_channel = cfg
+        .channels
+        .default_channel
+        .as_ref()
+        .map(|dc| dc.to_channel_string());
+    tools::policy::check_call(
+        policy,
+        &inv.name,
+        args,
+        workspace,
+        default_channel.as_deref(),
+    )
+    .err()
+}
+
+/// Apply the agent's approval rules to a tool call.  Returns the denial
+/// message when the call must not run, `None` when it may proceed.  A
+/// pending approval is given up when the turn is cancelled.
+#[allow(clippy::too_many_arguments)]
+async fn check_approval(
+    inv: &ToolInvocation,
+    args: &serde_json::Value,
+    args_summary: &str,
+    agent_id: &str,
+    session_id: &Option<String>,
+    channel: &str,
+    cfg: &Config,
+    cancel: &CancellationToken,
+) -> Option<String> {
+    let agent_cfg = cfg.agents.iter().find(|a| a.id == agent_id)?;
+
+    match approval::evaluate(&agent_cfg.approvals, &inv.name, args) {
+        ApprovalMode::Always => None,
+        ApprovalMode::Never => Some(format!(
+            "tool `{}` is blocked by this agent's approval policy",
+            inv.name
+        )),
+        ApprovalMode::Ask => {
+            let timeout = std::time::Duration::from_secs(
+                agent_cfg
+                    .approval_timeout_secs
+                    .unwrap_or(approval::DEFAULT_APPROVAL_TIMEOUT_SECS),
+            );
+            match approval::request_approval(
+                agent_id,
+                session_id,
+                channel,
+                &inv.name,
+                args_summary,
+                timeout,
+                cancel,
+            )
+            .await
+            {
+                approval::Decision::Approved => None,
+                approval::Decision::Denied => Some(format!(
+                    "a human denied the `{}` call — do not retry it; ask the user how to proceed",
+                    inv.name
+                )),
+                approval::Decision::TimedOut => Some(format!(
+                    "approval for the `{}` call timed out after {}s — it was not run",
+                    inv.name,
+                    timeout.as_secs()
+                )),
+                approval::Decision::Cancelled => Some(format!(
+                    "`{}` was cancelled by the user while awaiting approval",
+                    inv.name
+                )),
+            }
+        }
     }
 }
 
@@ -96,6 +427,24 @@ pub async fn execute_tool(
 // Shared helpers used by the tool loop
 // ---------------------------------------------------------------------------
 
+/// User message showing the model images attached by tool calls, or
+/// `None` when there are none.  Tool-role messages cannot carry images
+/// in the OpenAI format, so they follow the tool results as a user turn.
+pub fn tool_images_message(images: Vec<String>) -> Option<ChatMessage> {
+    if images.is_empty() {
+        return None;
+    }
+    let text = format!(
+        "[Tool output, not a message from the user: the {} requested above.]",
+        if images.len() == 1 {
+            "image".to_string()
+        } else {
+            format!("{} images", images.len())
+        }
+    );
+    Some(ChatMessage::user_with_images(text, images))
+}
+
 pub fn unknown_tool_corrective(bad_name: &str, function_defs: &[serde_json::Value]) -> String {
     let valid_names: Vec<&str> = function_defs
         .iter()
@@ -111,6 +460,17 @@ pub fn unknown_tool_corrective(bad_name: &str, function_defs: &[serde_json::Valu
     )
 }
 
+/// Error returned for a call whose arguments fail schema validation.
+pub fn invalid_args_corrective(tool: &str, violations: &[String]) -> String {
+    format!(
+        "CORRECTIVE: The arguments for `{tool}` do not match its schema, so it was NOT run. \
+         Violations:\n- {}\n\
+         Fix every listed problem and call `{tool}` again with arguments that follow \
+         its parameter schema exactly. Do NOT claim it worked.",
+        violations.join("\n- ")
+    )
+}
+
 pub fn emit_and_accumulate_usage(
     usage: &Option<TokenUsage>,
     agent_id: &str,
@@ -258,3 +618,208 @@ pub fn push_fc_messages(
         images: Vec::new(),
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    fn register_test_tools() {
+        for (name, exclusive) in [
+            ("batch_test_slow", false),
+            ("batch_test_exclusive", true),
+            ("batch_test_hang", false),
+        ] {
+            tools::register_tool(tools::ToolMeta {
+                name: name.into(),
+                description: "test".into(),
+                args_schema: serde_json::json!({"type": "object", "properties": {}}),
+                exclusive,
+                timeout_secs: None,
+            });
+        }
+        tools::register_handler(
+            "batch_test_slow",
+            Arc::new(|args, _ws| {
+                Box::pin(async move {
+                    let now = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+                    PEAK.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+                    ACTIVE.fetch_sub(1, Ordering::SeqCst);
+                    Ok(args)
+                })
+            }),
+        );
+        tools::register_handler(
+            "batch_test_exclusive",
+            Arc::new(|_args, _ws| {
+                Box::pin(async move {
+                    Ok(serde_json::json!({ "active": ACTIVE.load(Ordering::SeqCst) }))
+                })
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_limits_concurrency_and_keeps_order() {
+        register_test_tools();
+        let tmp = tempfile::tempdir().unwrap();
+        let calls = [
+            "batch_test_slow",
+            "batch_test_slow",
+            "batch_test_slow",
+            "batch_test_exclusive",
+            "batch_test_slow",
+        ];
+        let invocations: Vec<ToolInvocation> = calls
+            .iter()
+            .enumerate()
+            .map(|(i, name)| make_invocation(&format!("c{i}"), name, &format!("{{\"n\":{i}}}")))
+            .collect();
+
+        let timer = std::time::Instant::now();
+        let cancel = CancellationToken::new();
+        let results =
+            execute_batch(invocations, tmp.path(), "test", &None, "test", 2, &cancel).await;
+        let elapsed = timer.elapsed();
+
+        let ids: Vec<&str> = results.iter().map(|r| r.call_id.as_str()).collect();
+        assert_eq!(ids, ["c0", "c1", "c2", "c3", "c4"]);
+        assert_eq!(results[1].result_json, r#"{"n":1}"#);
+        // The exclusive call saw nothing else running.
+        assert_eq!(results[3].result_json, r#"{"active":0}"#);
+        assert!(PEAK.load(Ordering::SeqCst) <= 2);
+        // Two waves of slow calls before the exclusive one, one after —
+        // well under five sequential sleeps.
+        assert!(
+            elapsed < std::time::Duration::from_millis(700),
+            "{elapsed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_running_tools() {
+        register_test_tools();
+        tools::register_handler(
+            "batch_test_hang",
+            Arc::new(|_args, _ws| {
+                Box::pin(async move {
+                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                    Ok(serde_json::json!({}))
+                })
+            }),
+        );
+        let tmp = tempfile::tempdir().unwrap();
+        let invocations = vec![
+            make_invocation("h0", "batch_test_hang", "{}"),
+            make_invocation("h1", "batch_test_hang", "{}"),
+        ];
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            trigger.cancel();
+        });
+
+        let timer = std::time::Instant::now();
+        let results =
+            execute_batch(invocations, tmp.path(), "test", &None, "test", 2, &cancel).await;
+
+        assert!(timer.elapsed() < std::time::Duration::from_secs(10));
+        assert_eq!(results.len(), 2);
+        for r in &results {
+            assert!(r.failed);
+            assert!(r.result_json.contains("cancelled"), "{}", r.result_json);
+        }
+    }
+
+    #[tokio::test]
+    async fn attached_images_follow_the_tool_results() {
+        tools::register_tool(tools::ToolMeta {
+            name: "image_test_tool".into(),
+            description: "test".into(),
+            args_schema: serde_json::json!({"type": "object", "properties": {}}),
+            exclusive: false,
+            timeout_secs: None,
+        });
+        tools::register_handler(
+            "image_test_tool",
+            Arc::new(|_args, _ws| {
+                Box::pin(async move {
+                    assert!(tools::attach_image("data:image/png;base64,AAAA".into()));
+                    Ok(serde_json::json!({"attached": true}))
+                })
+            }),
+        );
+        let tmp = tempfile::tempdir().unwrap();
+        let invocations = vec![
+            make_invocation("i0", "image_test_tool", "{}"),
+            make_invocation("i1", "image_test_tool", "{}"),
+        ];
+        let cancel = CancellationToken::new();
+        let results =
+            execute_batch(invocations, tmp.path(), "test", &None, "test", 2, &cancel).await;
+
+        let images: Vec<String> = results.into_iter().flat_map(|r| r.images).collect();
+        assert_eq!(images.len(), 2);
+        let msg = tool_images_message(images).unwrap();
+        assert_eq!(msg.role, "user");
+        assert_eq!(msg.images.len(), 2);
+        assert!(msg.content.contains("2 images"));
+        assert!(tool_images_message(Vec::new()).is_none());
+        assert!(!tools::attach_image("data:image/png;base64,AAAA".into()));
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_are_rejected_before_dispatch() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        tools::register_tool(tools::ToolMeta {
+            name: "schema_test_tool".into(),
+            description: "test".into(),
+            args_schema: serde_json::json!({
+                "type": "object",
+                "properties": { "patch": { "type": "string" } },
+                "required": ["patch"],
+            }),
+            exclusive: false,
+            timeout_secs: None,
+        });
+        tools::register_handler(
+            "schema_test_tool",
+            Arc::new(|args, _ws| {
+                Box::pin(async move {
+                    CALLS.fetch_add(1, Ordering::SeqCst);
+                    Ok(args)
+                })
+            }),
+        );
+        let tmp = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        let run = |args: &str| {
+            let inv = make_invocation("v", "schema_test_tool", args);
+            let (tmp, cancel) = (tmp.path().to_path_buf(), cancel.clone());
+            async move { execute_tool(&inv, &tmp, "test", &None, "test", &cancel).await }
+        };
+
+        let bad = run(r#"{"patch": 3}"#).await;
+        assert!(bad.failed && bad.record.invalid_args);
+        let err = bad.record.error.unwrap();
+        assert!(err.starts_with("CORRECTIVE"), "{err}");
+        assert!(
+            err.contains("`patch`: expected string, got integer"),
+            "{err}"
+        );
+
+        let unparsable = run("{patch:").await;
+        assert!(unparsable.record.invalid_args);
+        assert!(unparsable.result_json.contains("not valid JSON"));
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+        let good = run(r#"{"patch": "x"}"#).await;
+        assert!(!good.failed && !good.record.invalid_args);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Context as _;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
 
 use crate::models::{ChatMessage, ProviderManager, ProviderResponse};
@@ -15,7 +16,10 @@ pub async fn run_tool_loop(
     workspace: &std::path::Path,
     agent_id: &str,
     session_id: &Option<String>,
+    channel: &str,
     max_iters: usize,
+    max_parallel: usize,
+    cancel: &CancellationToken,
     receipt_tokens: &mut TokenUsageSummary,
     receipt_model_calls: &mut u32,
     call_details: &mut Vec<ModelCallDetail>,
@@ -30,7 +34,10 @@ pub async fn run_tool_loop(
         workspace,
         agent_id,
         session_id,
+        channel,
         max_iters,
+        max_parallel,
+        cancel,
         receipt_tokens,
         receipt_model_calls,
         call_details,
@@ -56,7 +63,10 @@ async fn run_tool_loop_inner(
     workspace: &std::path::Path,
     agent_id: &str,
     session_id: &Option<String>,
+    channel: &str,
     max_iters: usize,
+    max_parallel: usize,
+    cancel: &CancellationToken,
     receipt_tokens: &mut TokenUsageSummary,
     receipt_model_calls: &mut u32,
     call_details: &mut Vec<ModelCallDetail>,
@@ -80,9 +90,11 @@ async fn run_tool_loop_inner(
                 debug!(tool = %name, "invoking tool (function-call)");
 
                 let inv = make_invocation(id, name, arguments);
-                let tr = execute_tool(&inv, workspace, agent_id, session_id).await;
+                let mut tr =
+                    execute_tool(&inv, workspace, agent_id, session_id, channel, cancel).await;
 
                 push_fc_messages(messages, &inv, name, arguments, &tr);
+                messages.extend(tool_images_message(std::mem::take(&mut tr.images)));
 
                 let should_break = handle_unknown_tool(
                     &tr,
@@ -137,49 +149,35 @@ async fn run_tool_loop_inner(
                     images: Vec::new(),
                 });
 
-                let mut handles = Vec::new();
-                for inv in invocations {
-                    let ws = workspace.to_path_buf();
-                    let aid = agent_id.to_string();
-                    let sid = session_id.clone();
-                    handles.push(tokio::spawn(async move {
-                        execute_tool(&inv, &ws, &aid, &sid).await
-                    }));
-                }
+                let results = execute_batch(
+                    invocations,
+                    workspace,
+                    agent_id,
+                    session_id,
+                    channel,
+                    max_parallel,
+                    cancel,
+                )
+                .await;
 
                 let mut fail_count = 0u32;
-                let mut total_count = 0u32;
-                for handle in handles {
-                    match handle.await {
-                        Ok(tr) => {
-                            total_count += 1;
-                            if tr.failed {
-                                fail_count += 1;
-                            }
-                            messages.push(ChatMessage {
-                                role: "tool".into(),
-                                content: truncate_tool_result(tr.result_json),
-                                tool_calls: None,
-                                tool_call_id: Some(tr.call_id),
-                                images: Vec::new(),
-                            });
-                            tool_calls.push(tr.record);
-                        }
-                        Err(join_err) => {
-                            total_count += 1;
-                            fail_count += 1;
-                            messages.push(ChatMessage {
-                                role: "tool".into(),
-                                content: format!(
-                                    "{{\"error\": \"tool task panicked: {join_err}\"}}"
-                                ),
-                                tool_calls: None,
-                                tool_call_id: None,
-                                images: Vec::new(),
-                            });
-                        }
+                let total_count = results.len() as u32;
+                let mut images = Vec::new();
+                for mut tr in results {
+                    images.append(&mut tr.images);
+                    if tr.failed {
+                        fail_count += 1;
                     }
+                    messages.push(ChatMessage {
+                        role: "tool".into(),
+                        content: truncate_tool_result(tr.result_json),
+                        tool_calls: None,
+                        tool_call_id: Some(tr.call_id),
+                        images: Vec::new(),
+                    });
+                    tool_calls.push(tr.record);
                 }
+                messages.extend(tool_images_message(images));
                 // Only count as a consecutive failure if the majority of
                 // the batch failed (#12).
                 if fail_count > 0 && fail_count * 2 >= total_count {
@@ -200,7 +198,13 @@ async fn run_tool_loop_inner(
             }
         }
 
-        *response = requery_provider(
+        // A cancelled turn ends here: the tool results are already in
+        // `messages`, so history stays well-formed.
+        if cancel.is_cancelled() {
+            *response = ProviderResponse::Final(String::new());
+            break;
+        }
+        let requery = requery_provider(
             manager,
             messages,
             function_defs,
@@ -211,9 +215,16 @@ async fn run_tool_loop_inner(
             call_details,
             provider,
             model,
-        )
-        .await
-        .context("model call failed in tool loop")?;
+        );
+        let next = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => ProviderResponse::Final(String::new()),
+            r = requery => r.context("model call failed in tool loop")?,
+        };
+        *response = next;
+        if cancel.is_cancelled() {
+            break;
+        }
     }
 
     Ok(tool_calls)
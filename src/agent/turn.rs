@@ -4,13 +4,14 @@ use std::time::SystemTime;
 use anyhow::Context as _;
 use tracing::{debug, info, warn};
 
-use crate::comm::IncomingMessage;
+use crate::comm::{ChannelConnector as _, IncomingMessage};
 use crate::config::Config;
 use crate::models::{
     build_provider_manager, ChatMessage, ModelProvider, ProviderManager, ProviderResponse,
 };
 use crate::tools;
 
+use super::cancel;
 use super::debug::emit_model_request_debug;
 use super::tool_exec::emit_and_accumulate_usage;
 use super::tool_loop::run_tool_loop;
@@ -172,7 +173,10 @@ impl Agent {
         };
 
         let config_path = crate::pinchy_home().join("config.yaml");
-        let turn_cfg = crate::config::Config::load(&config_path).await.ok();
+        let turn_cfg = crate::config::Config::load(&config_path)
+            .await
+            .ok()
+            .map(std::sync::Arc::new);
 
         // Refresh agent settings from config if available.
         if let Some(ref c) = turn_cfg {
@@ -180,6 +184,9 @@ impl Agent {
                 if let Some(mti) = ac.max_tool_iterations {
                     self.max_tool_iterations = mti;
                 }
+                if let Some(mpt) = ac.max_parallel_tools {
+                    self.max_parallel_tools = mpt;
+                }
                 if let Some(ref mid) = ac.model {
                     if let Some(mc) = c.models.iter().find(|m| m.id == *mid) {
                         self.provider = mc.provider.clone();
@@ -195,11 +202,15 @@ impl Agent {
             }
         }
 
-        let manager = std::sync::Arc::new(self.build_provider_manager(turn_cfg.as_ref()));
+        let manager = std::sync::Arc::new(self.build_provider_manager(turn_cfg.as_deref()));
         crate::models::set_global_providers(manager.clone());
 
-        let result = self
-            .run_turn_with_provider(msg, &manager, turn_cfg.as_ref())
+        // Tool calls in this turn check the config loaded above.
+        let result = crate::config::TURN_CONFIG
+            .scope(
+                turn_cfg.clone(),
+                self.run_turn_with_provider(msg, &manager, turn_cfg.as_deref()),
+            )
             .await;
 
         // Always restore session even on error/panic.
@@ -222,6 +233,8 @@ impl Agent {
             heartbeat_secs: None,
             cron_jobs: Vec::new(),
             max_tool_iterations: Some(self.max_tool_iterations),
+            max_parallel_tools: Some(self.max_parallel_tools),
+            tool_timeouts: Default::default(),
             enabled_skills: self.enabled_skills.clone(),
             fallback_models: self.fallback_models.clone(),
             webhook_secret: None,
@@ -232,6 +245,15 @@ impl Agent {
             timezone: None,
             watch_paths: Vec::new(),
             reasoning_effort: self.reasoning_effort.clone(),
+            approvals: Vec::new(),
+            approvers: Vec::new(),
+            approval_timeout_secs: None,
+            mcp_servers: Vec::new(),
+            http_fetch: None,
+            tools: None,
+            sandbox: None,
+            journal_exec_shell: false,
+            shared_memory: None,
         };
         match cfg {
             Some(c) => crate::models::build_provider_manager_from_config(&agent_cfg, c),
@@ -247,6 +269,10 @@ impl Agent {
     ) -> anyhow::Result<String> {
         let bootstrap = self.load_bootstrap().await?;
 
+        // Registered for the whole turn so `/stop` and friends can end it.
+        let turn_guard = cancel::begin(&self.id);
+        let cancel = turn_guard.token().clone();
+
         crate::gateway::publish_event_json(&serde_json::json!({
             "type": "typing_start",
             "agent": self.id,
@@ -284,13 +310,36 @@ impl Agent {
         }
         crate::context::manage_context(&mut messages, &budget, manager).await;
 
+        // -- Pick deferred tools and skills from recent conversation context --
+        let pluck_text = self.build_pluck_text(&msg.content, &messages);
+        let selection = tools::retrieval::select(
+            &pluck_text,
+            manager,
+            self.db.as_ref(),
+            self.enabled_skills.as_deref(),
+        )
+        .await;
+        if let Some(hint) = tools::retrieval::skill_hint(&selection.skills) {
+            // Just ahead of the user message it was picked for.
+            messages.insert(messages.len().saturating_sub(1), ChatMessage::system(hint));
+        }
+
         // -- Build function definitions --
         let tool_metas = tools::list_tools_core();
-        let function_defs = self.build_function_defs(&tool_metas, &msg, &messages);
+        let tool_policy = turn_cfg
+            .and_then(|cfg| cfg.agents.iter().find(|a| a.id == self.id))
+            .and_then(|a| a.tools.as_ref());
+        let vision = turn_cfg
+            .and_then(|cfg| cfg.agent_model(&self.id))
+            .is_some_and(|m| m.supports_vision());
+        let function_defs =
+            self.build_function_defs(&tool_metas, &msg, &selection.tools, tool_policy, vision);
 
         // -- Receipt tracking --
         let turn_start = SystemTime::now();
         let turn_start_ms = epoch_millis();
+        let turn_id = uuid_like_id();
+        let journal_guard = crate::journal::begin(&self.workspace, &turn_id);
         let mut receipt_tokens = TokenUsageSummary::default();
         let mut receipt_model_calls: u32 = 0;
         let mut call_details: Vec<ModelCallDetail> = Vec::new();
@@ -305,10 +354,13 @@ impl Agent {
             &self.model_id,
         );
         let initial_timer = std::time::Instant::now();
-        let (mut response, usage) = manager
-            .send_chat_with_functions(&messages, &function_defs)
-            .await
-            .context("model call failed")?;
+        let (mut response, usage) = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => (ProviderResponse::Final(String::new()), None),
+            r = manager.send_chat_with_functions(&messages, &function_defs) => {
+                r.context("model call failed")?
+            }
+        };
         let initial_latency = initial_timer.elapsed().as_millis() as u64;
         receipt_model_calls += 1;
         emit_and_accumulate_usage(
@@ -321,17 +373,19 @@ impl Agent {
         );
 
         // -- Enforcement retry --
-        self.maybe_enforcement_retry(
-            &mut response,
-            &mut messages,
-            &function_defs,
-            manager,
-            &msg,
-            &mut receipt_tokens,
-            &mut receipt_model_calls,
-            &mut call_details,
-        )
-        .await;
+        if !cancel.is_cancelled() {
+            self.maybe_enforcement_retry(
+                &mut response,
+                &mut messages,
+                &function_defs,
+                manager,
+                &msg,
+                &mut receipt_tokens,
+                &mut receipt_model_calls,
+                &mut call_details,
+            )
+            .await;
+        }
 
         // -- Tool loop --
         // Persist the user message BEFORE the tool loop so the JSONL
@@ -339,22 +393,34 @@ impl Agent {
         self.persist_user_message(&msg).await?;
 
         let pre_tool_msg_count = messages.len();
-        let receipt_tool_calls = run_tool_loop(
-            &mut response,
-            &mut messages,
-            &function_defs,
-            manager,
-            &self.workspace,
-            &self.id,
-            &self.current_session,
-            self.max_tool_iterations,
-            &mut receipt_tokens,
-            &mut receipt_model_calls,
-            &mut call_details,
-            &self.provider,
-            &self.model_id,
-        )
-        .await;
+        // Approval prompts on Discord may be answered by the author.
+        let requester = crate::discord::DiscordConnector
+            .matches(&msg.channel)
+            .then(|| msg.author_id.clone())
+            .flatten();
+        let receipt_tool_calls = super::approval::TURN_REQUESTER
+            .scope(
+                requester,
+                run_tool_loop(
+                    &mut response,
+                    &mut messages,
+                    &function_defs,
+                    manager,
+                    &self.workspace,
+                    &self.id,
+                    &self.current_session,
+                    &msg.channel,
+                    self.max_tool_iterations,
+                    self.max_parallel_tools,
+                    &cancel,
+                    &mut receipt_tokens,
+                    &mut receipt_model_calls,
+                    &mut call_details,
+                    &self.provider,
+                    &self.model_id,
+                ),
+            )
+            .await;
 
         // Persist tool-loop messages (assistant tool_calls + tool results)
         // so they survive in session history for future turns.
@@ -364,7 +430,21 @@ impl Agent {
         }
 
         // -- Extract final reply --
-        let final_reply = self.extract_final_reply(response).await;
+        let cancelled = cancel.is_cancelled();
+        let final_reply = if cancelled {
+            info!(agent = %self.id, "turn cancelled by user");
+            crate::gateway::publish_event_json(&serde_json::json!({
+                "type": "turn_cancelled",
+                "agent": self.id,
+                "session": self.current_session,
+            }));
+            self.stream_reply_to_gateway(cancel::CANCELLED_REPLY).await;
+            cancel::CANCELLED_REPLY.to_string()
+        } else {
+            self.extract_final_reply(response).await
+        };
+        drop(turn_guard);
+        drop(journal_guard);
 
         // -- Persist final assistant reply --
         self.persist_assistant_reply(&final_reply).await?;
@@ -390,6 +470,8 @@ impl Agent {
                 None
             }
         };
+        let invalid_tool_calls =
+            receipt_tool_calls.iter().filter(|r| r.invalid_args).count() as u32;
         let receipt = TurnReceipt {
             agent: self.id.clone(),
             session: self.current_session.clone(),
@@ -403,6 +485,10 @@ impl Agent {
             model_id: self.model_id.clone(),
             estimated_cost_usd: estimated_cost,
             call_details,
+            cancelled,
+            invalid_tool_calls,
+            changed_files: crate::journal::changed_files(&self.workspace, &turn_id),
+            turn_id,
         };
         self.persist_receipt(&receipt).await;
 
@@ -473,6 +559,28 @@ impl Agent {
             }
         }
 
+        // Shared memory scopes this agent can read.
+        let shared_scopes = turn_cfg
+            .and_then(|cfg| cfg.agents.iter().find(|a| a.id == self.id))
+            .and_then(|a| a.shared_memory.as_ref())
+            .map(|s| s.readable())
+            .unwrap_or_default();
+        for scope in shared_scopes {
+            // Opening may wait on another agent's write lock, so it runs on
+            // the blocking pool along with the query.
+            let user_content = msg.content.clone();
+            let mem_block = tokio::task::spawn_blocking(move || {
+                crate::memory::MemoryStore::open_shared(&scope)
+                    .map(|store| store.prompt_block_for_scope(&user_content, 2000, Some(&scope)))
+                    .unwrap_or_default()
+            })
+            .await
+            .unwrap_or_default();
+            if !mem_block.is_empty() {
+                messages.push(ChatMessage::system(mem_block));
+            }
+        }
+
         // Session history.
         let history_limit = turn_cfg
             .and_then(|cfg| {
@@ -501,17 +609,27 @@ impl Agent {
         &self,
         tool_metas: &[crate::tools::ToolMeta],
         msg: &IncomingMessage,
-        messages: &[ChatMessage],
+        plucked: &[crate::tools::ToolMeta],
+        policy: Option<&crate::config::ToolPolicy>,
+        vision: bool,
     ) -> Vec<serde_json::Value> {
         // When running inside a delegate context, suppress tools that
         // would cause the sub-agent to send messages externally instead
         // of returning results via its reply text.
         let is_delegated = msg.channel.starts_with("delegate:");
         let suppress_in_delegation: &[&str] = &["send_message"];
+        // `view_image` is only offered to models known to accept images.
+        let permitted = |name: &str| {
+            policy.is_none_or(|p| crate::tools::policy::tool_allowed(p, name))
+                && (vision || name != "view_image")
+                && crate::tools::skill_tool_enabled(name, self.enabled_skills.as_deref())
+        };
 
         let mut function_defs: Vec<serde_json::Value> = tool_metas
             .iter()
             .filter(|meta| !(is_delegated && suppress_in_delegation.contains(&meta.name.as_str())))
+            .filter(|meta| crate::mcp::tool_visible_to(&meta.name, &self.id))
+            .filter(|meta| permitted(&meta.name))
             .map(|meta| {
                 serde_json::json!({
                     "name": meta.name,
@@ -521,16 +639,16 @@ impl Agent {
             })
             .collect();
 
-        // Auto-pluck deferred tools from recent conversation context.
-        let pluck_text = self.build_pluck_text(&msg.content, messages);
-        let plucked = tools::auto_pluck_deferred(&pluck_text);
+        // Deferred tools picked for this turn.
         let existing_names: HashSet<String> = function_defs
             .iter()
             .filter_map(|fd| fd.get("name").and_then(|n| n.as_str()).map(String::from))
             .collect();
-        for meta in &plucked {
+        for meta in plucked {
             if !(existing_names.contains(&meta.name)
-                || is_delegated && suppress_in_delegation.contains(&meta.name.as_str()))
+                || is_delegated && suppress_in_delegation.contains(&meta.name.as_str())
+                || !crate::mcp::tool_visible_to(&meta.name, &self.id)
+                || !permitted(&meta.name))
             {
                 function_defs.push(serde_json::json!({
                     "name": meta.name,
@@ -543,7 +661,7 @@ impl Agent {
             debug!(
                 count = plucked.len(),
                 tools = ?plucked.iter().map(|m| &m.name).collect::<Vec<_>>(),
-                "auto-plucked deferred tools from user message"
+                "plucked deferred tools for this turn"
             );
         }
         function_defs
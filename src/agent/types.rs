@@ -12,6 +12,7 @@ use crate::store::PinchyDb;
 pub const DEFAULT_PROVIDER: &str = "openai";
 pub const DEFAULT_MODEL_ID: &str = "openai-default";
 pub const MAX_TOOL_RESULT_BYTES: usize = 16_000;
+pub const DEFAULT_MAX_PARALLEL_TOOLS: usize = 4;
 
 pub fn epoch_nanos() -> u128 {
     SystemTime::now()
@@ -66,6 +67,14 @@ pub struct ToolCallRecord {
     pub duration_ms: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Id of the stored full output when the result exceeded
+    /// `MAX_TOOL_RESULT_BYTES` (see `tools::builtins::artifacts`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<String>,
+    /// The call was rejected because its arguments did not match the
+    /// tool's schema.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub invalid_args: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -89,6 +98,22 @@ pub struct TurnReceipt {
     /// Per model-call usage breakdown.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub call_details: Vec<ModelCallDetail>,
+    /// The turn was stopped by the user before it finished.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Tool calls rejected for arguments that failed schema validation.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub invalid_tool_calls: u32,
+    /// Id of the turn's workspace change journal (see [`crate::journal`]).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub turn_id: String,
+    /// Workspace files the turn changed, which `/undo` restores.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_files: Vec<String>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -136,6 +161,7 @@ pub struct Agent {
     pub model_id: String,
     pub current_session: Option<String>,
     pub max_tool_iterations: usize,
+    pub max_parallel_tools: usize,
     pub enabled_skills: Option<Vec<String>>,
     pub fallback_models: Vec<String>,
     pub model_config_ref: Option<String>,
@@ -160,6 +186,7 @@ impl Agent {
             model_id: DEFAULT_MODEL_ID.to_string(),
             current_session,
             max_tool_iterations: 25,
+            max_parallel_tools: DEFAULT_MAX_PARALLEL_TOOLS,
             enabled_skills: None,
             fallback_models: Vec::new(),
             model_config_ref: None,
@@ -195,6 +222,9 @@ impl Agent {
             model_id,
             current_session,
             max_tool_iterations: agent_cfg.max_tool_iterations.unwrap_or(25),
+            max_parallel_tools: agent_cfg
+                .max_parallel_tools
+                .unwrap_or(DEFAULT_MAX_PARALLEL_TOOLS),
             enabled_skills: agent_cfg.enabled_skills.clone(),
             fallback_models: agent_cfg.fallback_models.clone(),
             model_config_ref: agent_cfg.model.clone(),
@@ -16,6 +16,7 @@ use tracing::debug;
 
 pub mod backup;
 pub mod service;
+pub mod skill;
 
 // ── Public types ─────────────────────────────────────────────────────────────
 
@@ -325,6 +326,7 @@ pub fn interactive_onboard_tui(
                             embedding_deployment: None,
                             embedding_model: None,
                             headers: None,
+                            vision: None,
                         });
                         new_id
                     };
@@ -340,6 +342,8 @@ pub fn interactive_onboard_tui(
                             heartbeat_secs: None,
                             cron_jobs: vec![],
                             max_tool_iterations: None,
+                            max_parallel_tools: None,
+                            tool_timeouts: Default::default(),
                             enabled_skills: None,
                             fallback_models: Vec::new(),
                             webhook_secret: None,
@@ -350,6 +354,15 @@ pub fn interactive_onboard_tui(
                             timezone: None,
                             watch_paths: Vec::new(),
                             reasoning_effort: None,
+                            approvals: Vec::new(),
+                            approvers: Vec::new(),
+                            approval_timeout_secs: None,
+                            mcp_servers: Vec::new(),
+                            http_fetch: None,
+                            tools: None,
+                            sandbox: None,
+                            journal_exec_shell: false,
+                            shared_memory: None,
                         });
                     }
 
@@ -510,6 +523,7 @@ pub async fn app_onboard(config_path: &Path) -> anyhow::Result<()> {
                             embedding_deployment: None,
                             embedding_model: None,
                             headers: None,
+                            vision: None,
                         });
                     }
                     // Update agent model reference if it doesn't match any model
@@ -571,6 +585,7 @@ pub async fn app_onboard(config_path: &Path) -> anyhow::Result<()> {
                         embedding_deployment: embed,
                         embedding_model: None,
                         headers: None,
+                        vision: None,
                     });
                     let yaml_out = serde_yaml_ng::to_string(&cfg).unwrap_or_default();
                     sync_backup_file(config_path).ok();
@@ -621,6 +636,7 @@ pub async fn app_onboard(config_path: &Path) -> anyhow::Result<()> {
                                 embedding_deployment: None,
                                 embedding_model: None,
                                 headers: None,
+                                vision: None,
                             });
                         }
                         // Update agent model reference if it doesn't match any model
@@ -1386,6 +1402,7 @@ pub async fn debug_run_turn(
     let msg = comm::IncomingMessage {
         agent_id: Some(agent_id.to_string()),
         author: "cli".into(),
+        author_id: None,
         content: message.to_string(),
         channel: "cli:debug".to_string(),
         timestamp: 0,
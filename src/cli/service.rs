@@ -18,6 +18,8 @@ WorkingDirectory={pinchy_home}
 ExecStart={bin_path}
 Restart=always
 RestartSec=5
+# Leave exec_shell background processes running across restarts.
+KillMode=process
 Environment=PINCHY_HOME={pinchy_home}
 Environment=RUST_LOG=info
 
@@ -0,0 +1,131 @@
+//! Skill packages — `pinchy skill install|export|versions|rollback`.
+//!
+//! Thin wrappers that print the results of [`crate::skills::package`].
+//! A running daemon picks up the change on its next skill reload (any
+//! skill edit from the gateway, or a restart).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::config;
+use crate::skills::package;
+
+/// Fail unless `agent` is configured — agent ids become paths.
+async fn check_agent(config_path: &Path, agent: &str) -> anyhow::Result<()> {
+    let cfg = config::Config::load(config_path)
+        .await
+        .with_context(|| format!("loading {}", config_path.display()))?;
+    if !cfg.agents.iter().any(|a| a.id == agent) {
+        anyhow::bail!("unknown agent '{agent}'");
+    }
+    Ok(())
+}
+
+/// `pinchy skill install <source> --agent <id>`
+pub async fn install(config_path: &Path, agent: &str, source: &Path) -> anyhow::Result<()> {
+    check_agent(config_path, agent).await?;
+    let (agent_id, source) = (agent.to_string(), source.to_path_buf());
+    let report = tokio::task::spawn_blocking(move || package::install(&agent_id, &source))
+        .await
+        .context("install task panicked")??;
+
+    if report.unchanged {
+        println!(
+            "✅ {} {} is already installed for {agent}",
+            report.name, report.version
+        );
+        return Ok(());
+    }
+    match &report.previous_version {
+        Some(prev) => println!(
+            "⬆️  Upgraded {} {prev} → {} for {agent}",
+            report.name, report.version
+        ),
+        None => println!(
+            "✅ Installed {} {} for {agent}",
+            report.name, report.version
+        ),
+    }
+    println!("   Path:     {}", report.path.display());
+    println!("   Checksum: {}", report.checksum);
+    if report.previous_version.is_some() {
+        println!(
+            "   Undo with: pinchy skill rollback {} --agent {agent}",
+            report.name
+        );
+    }
+    Ok(())
+}
+
+/// `pinchy skill export <name> --agent <id> [-o dir]`
+pub async fn export(
+    config_path: &Path,
+    agent: &str,
+    name: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    check_agent(config_path, agent).await?;
+    let out_dir = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    std::fs::create_dir_all(&out_dir)
+        .with_context(|| format!("create output dir {}", out_dir.display()))?;
+
+    let (agent_id, skill) = (agent.to_string(), name.to_string());
+    let tmp = out_dir.join(format!(".{name}.tar.gz.part"));
+    let tmp_owned = tmp.clone();
+    let version = tokio::task::spawn_blocking(move || -> anyhow::Result<String> {
+        let file = std::fs::File::create(&tmp_owned)?;
+        package::export(&agent_id, &skill, file)
+    })
+    .await
+    .context("export task panicked")?;
+    let version = match version {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+    let path = out_dir.join(package::archive_name(name, &version));
+    std::fs::rename(&tmp, &path)?;
+    println!("📦 Exported {name} {version} → {}", path.display());
+    println!(
+        "   Install elsewhere with: pinchy skill install {} --agent <id>",
+        path.display()
+    );
+    Ok(())
+}
+
+/// `pinchy skill versions <name> --agent <id>`
+pub async fn versions(config_path: &Path, agent: &str, name: &str) -> anyhow::Result<()> {
+    check_agent(config_path, agent).await?;
+    let saved = package::versions(agent, name)?;
+    if saved.is_empty() {
+        println!("No earlier versions of {name} saved for {agent}.");
+        return Ok(());
+    }
+    println!("📋 Saved versions of {name} (newest first):\n");
+    for v in &saved {
+        println!(
+            "  {:<12} {}  {}",
+            v.version,
+            v.checksum.as_deref().map_or("-", |c| &c[..c.len().min(12)]),
+            v.id
+        );
+    }
+    Ok(())
+}
+
+/// `pinchy skill rollback <name> --agent <id>`
+pub async fn rollback(config_path: &Path, agent: &str, name: &str) -> anyhow::Result<()> {
+    check_agent(config_path, agent).await?;
+    let report = package::rollback(agent, name)?;
+    println!(
+        "↩️  Rolled back {name} {} → {} for {agent}",
+        report.previous_version.as_deref().unwrap_or("(none)"),
+        report.version
+    );
+    Ok(())
+}
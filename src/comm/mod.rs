@@ -99,6 +99,9 @@ pub struct IncomingMessage {
     pub channel: String,
     /// Display name of the message author.
     pub author: String,
+    /// Stable platform id of the author (e.g. Discord user id), when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<String>,
     /// Raw message content / text.
     pub content: String,
     /// Unix-epoch timestamp (seconds).
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use std::time::SystemTime;
 
 use anyhow::Context;
@@ -43,6 +43,19 @@ pub fn invalidate_config_cache() {
     }
 }
 
+tokio::task_local! {
+    /// Config loaded at the start of the running turn, shared with its
+    /// tool calls so each does not re-read config.yaml (see
+    /// [`Config::current`]).  `None` when the turn could not load it.
+    pub static TURN_CONFIG: Option<Arc<Config>>;
+}
+
+/// The running turn's config (see [`TURN_CONFIG`]), for carrying it
+/// across `tokio::spawn`.
+pub fn turn_config() -> Option<Arc<Config>> {
+    TURN_CONFIG.try_with(|c| c.clone()).ok().flatten()
+}
+
 /// A reference to a secret value.
 ///
 /// Supports three YAML forms:
@@ -141,6 +154,33 @@ pub struct Config {
     pub cron_events_max_keep: Option<usize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chromium_path: Option<String>,
+    /// MCP servers whose tools are available to every agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// What `pinchy mcp serve` exposes. Nothing is exposed when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_serve: Option<McpServeConfig>,
+}
+
+/// Allowlist for `pinchy mcp serve`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct McpServeConfig {
+    /// Registry tools to expose, by name or glob (e.g. `"recall_memory"`,
+    /// `"*_cron_job*"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Agents reachable through `chat_with_agent` and whose memories are
+    /// exposed as resources, by id or glob.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    /// Agent whose workspace exposed tools run in.
+    /// Defaults to the first allowed agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_agent: Option<String>,
+    /// Seconds to wait for a `chat_with_agent` reply. Defaults to 300.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_timeout_secs: Option<u64>,
 }
 
 fn default_session_expiry_days() -> Option<u64> {
@@ -195,6 +235,51 @@ pub struct ModelConfig {
     /// Extra HTTP headers to send with every request to this provider.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub headers: Option<std::collections::HashMap<String, String>>,
+    /// Whether the model accepts image inputs (needed by `view_image`).
+    /// If unset, it is guessed from the model name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+}
+
+impl ModelConfig {
+    /// Whether images can be sent to this model: the `vision` setting, or
+    /// else a guess from the model name covering the common vision-capable
+    /// families (GPT-4o/4.1/5, o-series, Claude 3+, Gemini, LLaVA, Qwen-VL, …).
+    pub fn supports_vision(&self) -> bool {
+        if let Some(vision) = self.vision {
+            return vision;
+        }
+        let name = self.model.as_deref().unwrap_or(&self.id).to_lowercase();
+        let name = name.rsplit('/').next().unwrap_or(&name);
+        if [
+            "vision", "-vl", "llava", "pixtral", "gemini", "gemma-3", "gemma3",
+        ]
+        .iter()
+        .any(|k| name.contains(k))
+        {
+            return true;
+        }
+        if name.starts_with("claude") {
+            // claude-2 and claude-instant are text-only.
+            return !name.starts_with("claude-2") && !name.starts_with("claude-instant");
+        }
+        [
+            "gpt-4o",
+            "gpt-4.1",
+            "gpt-4-turbo",
+            "gpt-4.5",
+            "gpt-5",
+            "o1",
+            "o3",
+            "o4",
+        ]
+        .iter()
+        .any(|p| name.starts_with(p))
+            && name != "o1-mini"
+            && !name.starts_with("o1-mini-")
+            && name != "o3-mini"
+            && !name.starts_with("o3-mini-")
+    }
 }
 
 /// Channel connector settings.
@@ -326,6 +411,14 @@ pub struct AgentConfig {
     /// Maximum tool call iterations per agent turn.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tool_iterations: Option<usize>,
+    /// Maximum tool calls from one model response run concurrently
+    /// (default 4; 1 runs them one at a time).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_tools: Option<usize>,
+    /// Per-tool time limits in seconds (tool name → secs), overriding the
+    /// tool's default.  A call that runs longer is stopped and fails.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub tool_timeouts: std::collections::BTreeMap<String, u64>,
     /// Skills explicitly enabled for this agent.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enabled_skills: Option<Vec<String>>,
@@ -361,6 +454,214 @@ pub struct AgentConfig {
     /// Controls extended thinking budget for Claude and reasoning effort for OpenAI.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning_effort: Option<String>,
+    /// Human-in-the-loop approval rules, evaluated in order before each
+    /// tool call.  The first matching rule decides; unmatched calls run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<ApprovalRule>,
+    /// Seconds to wait for an approval decision before denying the call.
+    /// Defaults to 300 if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_timeout_secs: Option<u64>,
+    /// Discord user ids of the people who may answer this agent's
+    /// approval prompts with reactions.  When empty, only the user whose
+    /// message started the turn can.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers: Vec<String>,
+    /// MCP servers whose tools are only visible to this agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Domain restrictions for the `http_fetch` tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_fetch: Option<HttpFetchConfig>,
+    /// Tool allow/deny policy and argument constraints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolPolicy>,
+    /// OS-level sandbox for `exec_shell` (Linux only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+    /// Snapshot the workspace around foreground `exec_shell` commands so
+    /// `/undo` also reverts files they change.  Costs a walk of the
+    /// workspace per command; files over 1 MiB are not restorable.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub journal_exec_shell: bool,
+    /// Shared memory scopes this agent may read or write besides its own
+    /// memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_memory: Option<SharedMemoryConfig>,
+}
+
+/// A stdio Model Context Protocol server launched as a child process.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct McpServerConfig {
+    /// Server name, used to namespace its tools (`mcp__<name>__<tool>`).
+    pub name: String,
+    /// Executable to launch (e.g. `"npx"`).
+    pub command: String,
+    /// Command-line arguments.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Extra environment variables for the server process.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    /// Working directory for the server process.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Register the server's tools as deferred (injected only when the
+    /// server name is mentioned) instead of always-on.
+    #[serde(default)]
+    pub deferred: bool,
+    /// Per-request timeout in seconds. Defaults to 60 if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// What to do when an [`ApprovalRule`] matches a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalMode {
+    /// Run the tool without asking.
+    Always,
+    /// Refuse the call outright.
+    Never,
+    /// Pause the turn until a human approves or denies the call.
+    Ask,
+}
+
+/// A per-agent approval rule for tool calls.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalRule {
+    /// Tool name or glob pattern (e.g. `"exec_shell"`, `"*_file"`).
+    pub tool: String,
+    /// Argument patterns: argument name → glob matched against its value.
+    /// Non-string values are matched against their JSON text.  Every
+    /// listed argument must match for the rule to apply.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub args: std::collections::BTreeMap<String, String>,
+    /// Decision applied when the rule matches.
+    pub mode: ApprovalMode,
+}
+
+/// Per-agent tool policy: which tools an agent may see and call, and
+/// constraints on their arguments.
+///
+/// Name patterns support `*` and `?`.  Deny wins over allow; an empty
+/// `allow` list means every tool not denied.  Empty constraint lists
+/// leave that argument unconstrained.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolPolicy {
+    /// Tools the agent may use (names or globs).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Tools the agent may never use (names or globs).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Workspace-relative path prefixes file tools may touch
+    /// (e.g. `"notes/"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Commands `exec_shell` may run; every command in a pipeline or
+    /// chain must be listed.  While set, newlines, background `&`,
+    /// substitutions and subshells are refused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    /// Channels `send_message` may target (globs, e.g. `"discord:123*"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+    /// `remote/branch` targets the `git` tool may push to (globs, e.g.
+    /// `"origin/feature-*"`).  Empty allows any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub git_push: Vec<String>,
+}
+
+/// Opt-in Linux sandbox applied to every `exec_shell` command.
+///
+/// Combines landlock (writes confined to the workspace plus
+/// `writable_paths`), a user + network namespace when `network` is off,
+/// rlimits, and a seccomp filter blocking privileged syscalls.  If any
+/// layer cannot be set up the command is refused rather than run
+/// unsandboxed.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SandboxConfig {
+    /// Turn the sandbox on.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Allow network access (otherwise commands get an empty network
+    /// namespace).
+    #[serde(default)]
+    pub network: bool,
+    /// Extra absolute paths commands may write to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub writable_paths: Vec<String>,
+    /// CPU time limit per command in seconds.  Defaults to 60.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+    /// Address-space limit in MiB.  Defaults to 2048.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Maximum processes for the user.  Defaults to 512.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_processes: Option<u64>,
+    /// Largest file a command may write, in MiB.  Defaults to 256.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_mb: Option<u64>,
+    /// Install the seccomp filter.  Defaults to true.
+    #[serde(default = "default_true")]
+    pub seccomp: bool,
+}
+
+/// Per-agent domain policy for the `http_fetch` tool.
+///
+/// Patterns are host names (`"example.com"`, which also covers its
+/// subdomains) or globs (`"*.internal"`).  Deny wins over allow; when
+/// `allow_domains` is non-empty only matching hosts may be fetched.
+/// Loopback, private and link-local addresses are refused unless they
+/// match an `allow_domains` entry.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HttpFetchConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_domains: Vec<String>,
+}
+
+/// Shared memory scopes an agent may use.
+///
+/// Each scope (`global`, a team name such as `family`, …) is a memory
+/// database under `PINCHY_HOME/memory/` that every permitted agent sees.
+/// Scopes listed in `write` are readable too.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SharedMemoryConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write: Vec<String>,
+}
+
+impl SharedMemoryConfig {
+    pub fn can_read(&self, scope: &str) -> bool {
+        self.can_write(scope) || self.read.iter().any(|s| s == scope)
+    }
+
+    pub fn can_write(&self, scope: &str) -> bool {
+        self.write.iter().any(|s| s == scope)
+    }
+
+    /// Every readable scope, writable ones first, without duplicates.
+    pub fn readable(&self) -> Vec<String> {
+        let mut scopes: Vec<String> = Vec::new();
+        for scope in self.write.iter().chain(&self.read) {
+            if !scopes.contains(scope) {
+                scopes.push(scope.clone());
+            }
+        }
+        scopes
+    }
 }
 
 /// A cron job definition attached to an agent.
@@ -391,6 +692,51 @@ impl Config {
         raw.parse::<Tz>().unwrap_or(UTC)
     }
 
+    /// The model configured for agent `agent_id`, when known.  Agents may
+    /// name a `models:` entry or a bare model name.
+    pub fn agent_model(&self, agent_id: &str) -> Option<ModelConfig> {
+        let model_ref = self
+            .agents
+            .iter()
+            .find(|a| a.id == agent_id)?
+            .model
+            .clone()?;
+        Some(
+            self.models
+                .iter()
+                .find(|m| m.id == model_ref)
+                .cloned()
+                .unwrap_or(ModelConfig {
+                    id: model_ref.clone(),
+                    provider: String::new(),
+                    model: Some(model_ref),
+                    api_key: None,
+                    endpoint: None,
+                    api_version: None,
+                    embedding_deployment: None,
+                    embedding_model: None,
+                    headers: None,
+                    vision: None,
+                }),
+        )
+    }
+
+    /// Find the agent whose workspace is `workspace`.
+    ///
+    /// Tools only receive their workspace path, so this is how they
+    /// recover per-agent settings.
+    pub fn agent_for_workspace(&self, workspace: &Path) -> Option<&AgentConfig> {
+        let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+        let ws = canonical(workspace);
+        self.agents
+            .iter()
+            .find(|a| canonical(&Path::new(&a.root).join("workspace")) == ws)
+            .or_else(|| {
+                let id = workspace.parent()?.file_name()?.to_str()?;
+                self.agents.iter().find(|a| a.id == id)
+            })
+    }
+
     /// Resolve the global timezone (ignoring per-agent overrides).
     pub fn resolve_global_timezone(&self) -> Tz {
         self.timezone
@@ -399,6 +745,21 @@ impl Config {
             .unwrap_or(UTC)
     }
 
+    /// The config tool code should check: the running turn's copy when
+    /// there is one, otherwise `PINCHY_HOME/config.yaml`.  `Ok(None)`
+    /// when no config file exists; a file that fails to load is an
+    /// error, so callers can fail closed.
+    pub async fn current() -> anyhow::Result<Option<Arc<Config>>> {
+        if let Some(cfg) = turn_config() {
+            return Ok(Some(cfg));
+        }
+        let path = crate::pinchy_home().join("config.yaml");
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::load(&path).await.map(|c| Some(Arc::new(c)))
+    }
+
     /// Read and parse a YAML configuration file.
     ///
     /// Results are cached by file path and mtime — repeated calls within
@@ -582,6 +943,30 @@ impl Config {
             }
         }
 
+        // Validate MCP server names (they become part of tool names)
+        let validate_mcp = |servers: &[McpServerConfig], scope: &str| -> anyhow::Result<()> {
+            let mut names = HashSet::new();
+            for srv in servers {
+                if srv.name.is_empty()
+                    || !srv
+                        .name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    anyhow::bail!(
+                        "config: {scope} MCP server name '{}' must be non-empty \
+                         and contain only letters, digits, '-' or '_'",
+                        srv.name
+                    );
+                }
+                if !names.insert(srv.name.as_str()) {
+                    anyhow::bail!("config: {scope} has duplicate MCP server '{}'", srv.name);
+                }
+            }
+            Ok(())
+        };
+        validate_mcp(&self.mcp_servers, "global")?;
+
         // Check for duplicate agent IDs
         let mut agent_ids = HashSet::new();
         for agent in &self.agents {
@@ -646,6 +1031,24 @@ impl Config {
                     );
                 }
             }
+
+            if let Some(shared) = &agent.shared_memory {
+                for scope in shared.write.iter().chain(&shared.read) {
+                    crate::memory::validate_scope(scope)
+                        .map_err(|e| anyhow::anyhow!("config: agent '{}': {e}", agent.id))?;
+                }
+            }
+
+            validate_mcp(&agent.mcp_servers, &format!("agent '{}'", agent.id))?;
+            for srv in &agent.mcp_servers {
+                if self.mcp_servers.iter().any(|g| g.name == srv.name) {
+                    anyhow::bail!(
+                        "config: agent '{}' MCP server '{}' shadows a global MCP server",
+                        agent.id,
+                        srv.name
+                    );
+                }
+            }
         }
 
         Ok(())
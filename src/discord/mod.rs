@@ -10,7 +10,7 @@ use serenity::async_trait as serenity_async_trait;
 use serenity::builder::{CreateAttachment, CreateEmbed, CreateEmbedFooter, CreateMessage};
 use serenity::client::{Client, Context, EventHandler};
 use serenity::http::Http;
-use serenity::model::channel::Message;
+use serenity::model::channel::{Message, Reaction, ReactionType};
 use serenity::model::gateway::GatewayIntents;
 use serenity::model::id::{ChannelId, UserId};
 use std::collections::BTreeMap;
@@ -80,8 +80,66 @@ tokio::task_local! {
     pub static CURRENT_REPLY_CONTEXT: ReplyContext;
 }
 
+// ---------------------------------------------------------------------------
+// Approval prompts — maps Discord message IDs to pending approval ids so a
+// ✅/❌ reaction on the prompt resolves the paused tool call.
+// ---------------------------------------------------------------------------
+
+const APPROVE_EMOJI: &str = "✅";
+const DENY_EMOJI: &str = "❌";
+/// Reacting with this to an agent's message (or any message in a channel
+/// routed to an agent) stops the agent's running turn.
+const STOP_EMOJI: &str = "🛑";
+
+static APPROVAL_PROMPTS: OnceLock<RwLock<BTreeMap<u64, String>>> = OnceLock::new();
+
+fn approval_prompts() -> &'static RwLock<BTreeMap<u64, String>> {
+    APPROVAL_PROMPTS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
 #[serenity_async_trait]
 impl EventHandler for Handler {
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let approved = match &reaction.emoji {
+            ReactionType::Unicode(e) if e == APPROVE_EMOJI => true,
+            ReactionType::Unicode(e) if e == DENY_EMOJI => false,
+            ReactionType::Unicode(e) if e == STOP_EMOJI => {
+                return stop_turn_from_reaction(&ctx, &reaction).await;
+            }
+            _ => return,
+        };
+        let message_id = reaction.message_id.get();
+        let Some(approval_id) = approval_prompts().read().await.get(&message_id).cloned() else {
+            return;
+        };
+        // Our own seed reactions arrive here too — ignore bots.
+        let user = match reaction.user(&ctx).await {
+            Ok(u) if !u.bot => u,
+            _ => return,
+        };
+        let approvers = match crate::agent::approval::get_pending(&approval_id) {
+            Some(pending) => agent_approvers(&pending.agent).await,
+            None => Vec::new(),
+        };
+        if !crate::agent::approval::may_answer(&approval_id, &approvers, &user.id.get().to_string())
+        {
+            debug!(approval = %approval_id, user = %user.name, "ignoring approval reaction from a user who may not answer");
+            return;
+        }
+        approval_prompts().write().await.remove(&message_id);
+
+        let verdict = if approved { "approved" } else { "denied" };
+        let text = if crate::agent::approval::resolve(&approval_id, approved) {
+            info!(approval = %approval_id, user = %user.name, verdict, "tool call {verdict} via Discord");
+            format!("Tool call {verdict} by {}.", user.name)
+        } else {
+            "That approval request has already been resolved or expired.".to_string()
+        };
+        if let Err(e) = reaction.channel_id.say(&ctx.http, text).await {
+            warn!(error = %e, "failed to acknowledge approval reaction");
+        }
+    }
+
     async fn message(&self, ctx: Context, msg: Message) {
         // Ignore messages from bots (including ourselves).
         if msg.author.bot {
@@ -99,35 +157,7 @@ impl EventHandler for Handler {
 
         // Dispatch slash commands through the channel-agnostic registry.
         if trimmed.starts_with('/') {
-            // Resolve agent_id for this channel via config routing.
-            let (agent_id, workspace) = match crate::config::Config::load(
-                &crate::pinchy_home().join("config.yaml"),
-            )
-            .await
-            {
-                Ok(cfg) => {
-                    let aid = cfg
-                        .routing
-                        .as_ref()
-                        .and_then(|r| {
-                            let key = format!("discord:{}", msg.channel_id);
-                            r.channels
-                                .get(&key)
-                                .cloned()
-                                .or_else(|| r.default_agent.clone())
-                        })
-                        .unwrap_or_else(|| "default".to_string());
-
-                    let ws = cfg
-                        .agents
-                        .iter()
-                        .find(|a| a.id == aid)
-                        .map(|a| PathBuf::from(&a.root))
-                        .unwrap_or_else(|| crate::utils::agent_root(&aid));
-                    (aid, ws)
-                }
-                Err(_) => ("default".to_string(), crate::utils::agent_root("default")),
-            };
+            let (agent_id, workspace) = channel_agent(msg.channel_id).await;
 
             let slash_ctx = slash::Context {
                 agent_id,
@@ -167,11 +197,23 @@ impl EventHandler for Handler {
             None
         };
 
+        let mut agent_id = reply_meta.as_ref().map(|r| r.agent_id.clone());
+        let mut content = msg.content.clone();
+        if let Some((agent, paths)) = save_attachments(&msg, agent_id.as_deref()).await {
+            // Route to the agent whose workspace now holds the files.
+            agent_id = Some(agent);
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&crate::documents::attachment_note(&paths));
+        }
+
         let incoming = IncomingMessage {
-            agent_id: reply_meta.as_ref().map(|r| r.agent_id.clone()),
+            agent_id,
             channel: msg.channel_id.to_string(),
             author: msg.author.name.clone(),
-            content: msg.content.clone(),
+            author_id: Some(msg.author.id.get().to_string()),
+            content,
             timestamp: msg.timestamp.unix_timestamp(),
             session_id: reply_meta.as_ref().and_then(|r| r.session_id.clone()),
             images: Vec::new(),
@@ -201,6 +243,121 @@ impl EventHandler for Handler {
     }
 }
 
+/// Resolve the agent a Discord channel routes to (config `routing`, else
+/// the default agent) and its root directory.
+async fn channel_agent(channel_id: ChannelId) -> (String, PathBuf) {
+    match crate::config::Config::load(&crate::pinchy_home().join("config.yaml")).await {
+        Ok(cfg) => {
+            let aid = cfg
+                .routing
+                .as_ref()
+                .and_then(|r| {
+                    let key = format!("discord:{channel_id}");
+                    r.channels
+                        .get(&key)
+                        .cloned()
+                        .or_else(|| r.default_agent.clone())
+                })
+                .unwrap_or_else(|| "default".to_string());
+
+            let root = cfg
+                .agents
+                .iter()
+                .find(|a| a.id == aid)
+                .map(|a| PathBuf::from(&a.root))
+                .unwrap_or_else(|| crate::utils::agent_root(&aid));
+            (aid, root)
+        }
+        Err(_) => ("default".to_string(), crate::utils::agent_root("default")),
+    }
+}
+
+/// The configured `approvers` of `agent_id`.
+async fn agent_approvers(agent_id: &str) -> Vec<String> {
+    match crate::config::Config::load(&crate::pinchy_home().join("config.yaml")).await {
+        Ok(cfg) => cfg
+            .agents
+            .iter()
+            .find(|a| a.id == agent_id)
+            .map(|a| a.approvers.clone())
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Save a message's readable attachments (documents and text files) into
+/// the workspace of the agent that will handle it: `agent` when the
+/// message is a reply, otherwise the agent its channel routes to.
+/// Returns that agent and the saved workspace-relative paths.
+async fn save_attachments(msg: &Message, agent: Option<&str>) -> Option<(String, Vec<String>)> {
+    let readable: Vec<_> = msg
+        .attachments
+        .iter()
+        .filter(|a| {
+            crate::documents::is_readable_attachment(&a.filename, a.content_type.as_deref())
+        })
+        .collect();
+    if readable.is_empty() {
+        return None;
+    }
+    let (agent_id, root) = match agent {
+        Some(id) => {
+            let root = Config::load(&crate::pinchy_home().join("config.yaml"))
+                .await
+                .ok()
+                .and_then(|cfg| {
+                    let agent = cfg.agents.iter().find(|a| a.id == id)?;
+                    Some(PathBuf::from(&agent.root))
+                })
+                .unwrap_or_else(|| crate::utils::agent_root(id));
+            (id.to_string(), root)
+        }
+        None => channel_agent(msg.channel_id).await,
+    };
+    let workspace = root.join("workspace");
+
+    let mut paths = Vec::new();
+    for attachment in readable {
+        if attachment.size as usize > crate::documents::MAX_ATTACHMENT_BYTES {
+            warn!(file = %attachment.filename, size = attachment.size, "attachment too large to save");
+            continue;
+        }
+        let saved = match attachment.download().await {
+            Ok(bytes) => {
+                crate::documents::save_attachment(&workspace, &attachment.filename, &bytes).await
+            }
+            Err(e) => Err(anyhow!("download failed: {e}")),
+        };
+        match saved {
+            Ok(path) => paths.push(path),
+            Err(e) => warn!(file = %attachment.filename, error = %e, "failed to save attachment"),
+        }
+    }
+    (!paths.is_empty()).then_some((agent_id, paths))
+}
+
+/// 🛑 reaction: cancel the running turn of the agent that sent the message,
+/// or of the agent the channel routes to.
+async fn stop_turn_from_reaction(ctx: &Context, reaction: &Reaction) {
+    let user = match reaction.user(ctx).await {
+        Ok(u) if !u.bot => u,
+        _ => return,
+    };
+    let agent_id = match lookup_reply(reaction.message_id.get()).await {
+        Some(reply) => reply.agent_id,
+        None => channel_agent(reaction.channel_id).await.0,
+    };
+    if !crate::agent::cancel::cancel(&agent_id) {
+        debug!(agent = %agent_id, "stop reaction with no running turn");
+        return;
+    }
+    info!(agent = %agent_id, user = %user.name, "turn cancelled via Discord reaction");
+    let text = format!("Stopping {agent_id}'s current turn ({}).", user.name);
+    if let Err(e) = reaction.channel_id.say(&ctx.http, text).await {
+        warn!(error = %e, "failed to acknowledge stop reaction");
+    }
+}
+
 /// Channel connector that delivers replies via Discord.
 pub struct DiscordConnector;
 
@@ -273,6 +430,8 @@ pub fn init(cfg: &Config) {
         // bot still receives DMs.
         let intents = GatewayIntents::GUILD_MESSAGES
             | GatewayIntents::DIRECT_MESSAGES
+            | GatewayIntents::GUILD_MESSAGE_REACTIONS
+            | GatewayIntents::DIRECT_MESSAGE_REACTIONS
             | GatewayIntents::MESSAGE_CONTENT;
 
         let mut client = match Client::builder(&token, intents)
@@ -297,7 +456,10 @@ pub fn init(cfg: &Config) {
                     warn!("Retrying without MESSAGE_CONTENT intent. If you need message content, enable the 'Message Content Intent' in the Discord developer portal for your bot.");
 
                     // Retry with reduced intents (drop MESSAGE_CONTENT)
-                    let reduced = GatewayIntents::GUILD_MESSAGES | GatewayIntents::DIRECT_MESSAGES;
+                    let reduced = GatewayIntents::GUILD_MESSAGES
+                        | GatewayIntents::DIRECT_MESSAGES
+                        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+                        | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
                     match Client::builder(&token, reduced)
                         .event_handler(Handler)
                         .await
@@ -515,6 +677,74 @@ pub(crate) async fn send_rich_channel_message(
     Ok(())
 }
 
+/// Post an approval prompt for a paused tool call and seed it with ✅/❌
+/// reactions.  `channel` is a numeric channel id or `dm:<user_id>`.
+pub(crate) async fn send_approval_prompt(
+    channel: &str,
+    approval: &crate::agent::approval::PendingApproval,
+) -> anyhow::Result<()> {
+    let http = HTTP_CLIENT
+        .get()
+        .ok_or_else(|| anyhow!("discord http client not initialised"))?;
+
+    let ch = if let Some(uid) = channel.strip_prefix("dm:") {
+        let uid: u64 = uid
+            .parse()
+            .with_context(|| format!("invalid user id: {uid}"))?;
+        UserId::new(uid)
+            .create_dm_channel(http)
+            .await
+            .map_err(|e| anyhow!("failed to create DM channel for user {uid}: {e:?}"))?
+            .id
+    } else {
+        let cid: u64 = channel
+            .parse()
+            .with_context(|| format!("invalid channel id: {}", channel))?;
+        ChannelId::new(cid)
+    };
+
+    let embed = CreateEmbed::new()
+        .title(truncate(&format!("Approval needed: {}", approval.tool), 256))
+        .description(truncate(
+            &format!(
+                "Agent **{}** wants to run `{}`.\nReact {APPROVE_EMOJI} to approve or {DENY_EMOJI} to deny.",
+                approval.agent, approval.tool
+            ),
+            4096,
+        ))
+        .field("Arguments", truncate(&format!("```\n{}\n```", approval.args_summary), 1024), false)
+        .colour(0xF1C40F)
+        .footer(CreateEmbedFooter::new(format!(
+            "Expires in {}s · id {}",
+            approval.expires_at.saturating_sub(approval.requested_at),
+            approval.id
+        )));
+
+    let sent = ch
+        .send_message(http, CreateMessage::new().embed(embed))
+        .await
+        .map_err(|e| anyhow!("discord approval prompt send error: {e:?}"))?;
+
+    let mut prompts = approval_prompts().write().await;
+    if prompts.len() >= REPLY_TRACKER_CAPACITY {
+        if let Some(&old_key) = prompts.keys().next() {
+            prompts.remove(&old_key);
+        }
+    }
+    prompts.insert(sent.id.get(), approval.id.clone());
+    drop(prompts);
+
+    for emoji in [APPROVE_EMOJI, DENY_EMOJI] {
+        if let Err(e) = sent
+            .react(http, ReactionType::Unicode(emoji.to_string()))
+            .await
+        {
+            warn!(error = %e, "failed to seed approval reaction");
+        }
+    }
+    Ok(())
+}
+
 /// Parse a `#RRGGBB` hex string into a u32 colour value.
 fn parse_hex_color(s: &str) -> Option<u32> {
     let hex = s.strip_prefix('#').unwrap_or(s);
@@ -0,0 +1,642 @@
+//! Document text extraction — PDF, DOCX, ODT, HTML and EPUB.
+//!
+//! [`extract`] turns a document into plain text split into pages: PDF
+//! pages, DOCX/ODT page breaks, EPUB chapters (HTML is a single page).
+//! `read_file` returns the text with page ranges, the memory file watcher
+//! ingests it, and the Discord and gateway connectors save attachments of
+//! these types into the agent workspace with [`save_attachment`].
+//!
+//! Extraction is blocking; call it from `spawn_blocking`.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context;
+
+/// Largest document read for extraction (50 MiB).
+const MAX_DOCUMENT_BYTES: u64 = 50 * 1024 * 1024;
+/// Largest decompressed archive member read (zip-bomb guard).
+const MAX_MEMBER_BYTES: u64 = 64 * 1024 * 1024;
+/// Largest attachment saved into a workspace (25 MiB).
+pub const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+/// Workspace directory attachments are saved to.
+pub const UPLOADS_DIR: &str = "uploads";
+
+/// A document format with a text extractor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Odt,
+    Html,
+    Epub,
+}
+
+impl DocumentKind {
+    /// Detect the format from a file name's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match ext.as_str() {
+            "pdf" => Self::Pdf,
+            "docx" => Self::Docx,
+            "odt" => Self::Odt,
+            "html" | "htm" | "xhtml" => Self::Html,
+            "epub" => Self::Epub,
+            _ => return None,
+        })
+    }
+
+    /// Short lower-case format name, e.g. `"pdf"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Odt => "odt",
+            Self::Html => "html",
+            Self::Epub => "epub",
+        }
+    }
+
+    /// Whether the format is unreadable without extraction.  HTML is
+    /// text already, so tools only extract it when asked to.
+    pub fn is_binary(self) -> bool {
+        !matches!(self, Self::Html)
+    }
+
+    /// What one of this format's pages is called.
+    pub fn page_name(self) -> &'static str {
+        match self {
+            Self::Epub => "chapter",
+            _ => "page",
+        }
+    }
+}
+
+/// Extracted text of a document.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub kind: DocumentKind,
+    /// Text of each page (chapter for EPUB); never empty.
+    pub pages: Vec<String>,
+}
+
+impl Document {
+    /// Text of pages `start..=end` (1-based).  Multi-page documents get a
+    /// `--- page N of M ---` marker before each page.
+    pub fn page_range(&self, start: usize, end: usize) -> anyhow::Result<String> {
+        let total = self.pages.len();
+        if start < 1 || end < start || start > total {
+            anyhow::bail!(
+                "{} range {start}..{end} out of bounds (document has {total} {}s)",
+                self.kind.page_name(),
+                self.kind.page_name()
+            );
+        }
+        let end = end.min(total);
+        if total == 1 {
+            return Ok(self.pages[0].clone());
+        }
+        let parts: Vec<String> = (start..=end)
+            .map(|n| {
+                format!(
+                    "--- {} {n} of {total} ---\n{}",
+                    self.kind.page_name(),
+                    self.pages[n - 1]
+                )
+            })
+            .collect();
+        Ok(parts.join("\n\n"))
+    }
+
+    /// The whole text, page markers included.
+    pub fn text(&self) -> String {
+        self.page_range(1, self.pages.len()).unwrap_or_default()
+    }
+}
+
+/// Extract the text of the document at `path`, whose format is taken
+/// from its extension.
+pub fn extract(path: &Path) -> anyhow::Result<Document> {
+    let kind = DocumentKind::from_path(path)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a supported document type", path.display()))?;
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("cannot read {}", path.display()))?
+        .len();
+    if size > MAX_DOCUMENT_BYTES {
+        anyhow::bail!(
+            "{} is too large to extract ({size} bytes, limit {MAX_DOCUMENT_BYTES})",
+            path.display()
+        );
+    }
+    let bytes = std::fs::read(path).with_context(|| format!("cannot read {}", path.display()))?;
+    extract_bytes(kind, &bytes).with_context(|| format!("cannot extract {}", path.display()))
+}
+
+/// Extract the text of an in-memory document.
+pub fn extract_bytes(kind: DocumentKind, bytes: &[u8]) -> anyhow::Result<Document> {
+    let pages = match kind {
+        DocumentKind::Pdf => pdf_pages(bytes)?,
+        DocumentKind::Docx => {
+            let xml = zip_member(bytes, "word/document.xml")?;
+            xml_pages(&xml, docx_node)?
+        }
+        DocumentKind::Odt => {
+            let xml = zip_member(bytes, "content.xml")?;
+            xml_pages(&xml, odt_node)?
+        }
+        DocumentKind::Html => {
+            let html = String::from_utf8_lossy(bytes);
+            let (title, body) = crate::tools::builtins::http_fetch::render_html(&html);
+            vec![match title {
+                Some(t) if !body.starts_with('#') => format!("# {t}\n\n{body}"),
+                _ => body,
+            }]
+        }
+        DocumentKind::Epub => epub_chapters(bytes)?,
+    };
+    let mut pages: Vec<String> = pages.iter().map(|p| tidy(p)).collect();
+    // Keep interior empty pages (page numbers must line up) but not a
+    // trailing run of them.
+    while pages.len() > 1 && pages.last().is_some_and(|p| p.is_empty()) {
+        pages.pop();
+    }
+    if pages.is_empty() {
+        pages.push(String::new());
+    }
+    Ok(Document { kind, pages })
+}
+
+fn pdf_pages(bytes: &[u8]) -> anyhow::Result<Vec<String>> {
+    // The PDF parser panics on some malformed files.
+    let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+        .map_err(|_| anyhow::anyhow!("malformed PDF"))?
+        .map_err(|e| anyhow::anyhow!("PDF: {e}"))?;
+    Ok(pages)
+}
+
+/// Read one member of a zip archive as UTF-8.
+fn zip_member(bytes: &[u8], name: &str) -> anyhow::Result<String> {
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(bytes)).context("not a valid zip container")?;
+    let member = archive
+        .by_name(name)
+        .with_context(|| format!("missing {name}"))?;
+    let mut text = String::new();
+    member
+        .take(MAX_MEMBER_BYTES)
+        .read_to_string(&mut text)
+        .with_context(|| format!("cannot read {name}"))?;
+    Ok(text)
+}
+
+fn parse_xml(xml: &str) -> anyhow::Result<roxmltree::Document<'_>> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    roxmltree::Document::parse_with_options(xml, options).context("invalid XML")
+}
+
+/// Accumulates text and splits it at page breaks.
+#[derive(Default)]
+struct Pages {
+    done: Vec<String>,
+    current: String,
+}
+
+impl Pages {
+    fn push(&mut self, text: &str) {
+        self.current.push_str(text);
+    }
+
+    /// Start a new page, unless nothing has been written to this one
+    /// (an explicit break and the rendered break that follows it count
+    /// once).
+    fn page_break(&mut self) {
+        if !self.current.trim().is_empty() {
+            self.done.push(std::mem::take(&mut self.current));
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.trim().is_empty() || self.done.is_empty() {
+            self.done.push(self.current);
+        }
+        self.done
+    }
+}
+
+/// Walks one XML node, writing its text.  `in_cell` is set inside table
+/// cells, where paragraphs run together.
+type NodeWriter = fn(roxmltree::Node<'_, '_>, &mut Pages, bool);
+
+fn xml_pages(xml: &str, write: NodeWriter) -> anyhow::Result<Vec<String>> {
+    let doc = parse_xml(xml)?;
+    let mut pages = Pages::default();
+    write(doc.root_element(), &mut pages, false);
+    Ok(pages.finish())
+}
+
+fn attr<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn children(node: roxmltree::Node<'_, '_>, pages: &mut Pages, in_cell: bool, write: NodeWriter) {
+    for child in node.children() {
+        write(child, pages, in_cell);
+    }
+}
+
+/// End a paragraph: a newline, or a space inside a table cell.
+fn end_paragraph(pages: &mut Pages, in_cell: bool) {
+    pages.push(if in_cell { " " } else { "\n" });
+}
+
+/// WordprocessingML (`word/document.xml`).
+fn docx_node(node: roxmltree::Node<'_, '_>, pages: &mut Pages, in_cell: bool) {
+    if !node.is_element() {
+        return;
+    }
+    match node.tag_name().name() {
+        "t" => pages.push(node.text().unwrap_or("")),
+        "tab" => pages.push("\t"),
+        "br" | "cr" => {
+            if attr(node, "type") == Some("page") {
+                pages.page_break();
+            } else {
+                pages.push("\n");
+            }
+        }
+        "lastRenderedPageBreak" => pages.page_break(),
+        // Field instructions, deleted revisions and document properties.
+        "instrText" | "delText" | "del" | "pPr" | "rPr" | "sectPr" => {}
+        "p" => {
+            let level = node
+                .children()
+                .find(|c| c.tag_name().name() == "pPr")
+                .and_then(|ppr| ppr.children().find(|c| c.tag_name().name() == "pStyle"))
+                .and_then(|style| attr(style, "val"))
+                .and_then(heading_level);
+            if let Some(level) = level.filter(|_| !in_cell) {
+                pages.push(&"#".repeat(level));
+                pages.push(" ");
+            }
+            children(node, pages, in_cell, docx_node);
+            end_paragraph(pages, in_cell);
+        }
+        "tc" => {
+            children(node, pages, true, docx_node);
+            pages.push("\t");
+        }
+        "tr" => {
+            children(node, pages, in_cell, docx_node);
+            pages.push("\n");
+        }
+        _ => children(node, pages, in_cell, docx_node),
+    }
+}
+
+/// Heading level of a Word paragraph style (`Title`, `Heading2`, …).
+fn heading_level(style: &str) -> Option<usize> {
+    if style.eq_ignore_ascii_case("title") {
+        return Some(1);
+    }
+    let n: usize = style
+        .strip_prefix("Heading")
+        .or_else(|| style.strip_prefix("heading"))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(n.clamp(1, 6))
+}
+
+/// OpenDocument text (`content.xml`).
+fn odt_node(node: roxmltree::Node<'_, '_>, pages: &mut Pages, in_cell: bool) {
+    if node.is_text() {
+        pages.push(node.text().unwrap_or(""));
+        return;
+    }
+    if !node.is_element() {
+        return;
+    }
+    match node.tag_name().name() {
+        "s" => {
+            let n = attr(node, "c").and_then(|c| c.parse().ok()).unwrap_or(1);
+            pages.push(&" ".repeat(n));
+        }
+        "tab" => pages.push("\t"),
+        "line-break" => pages.push("\n"),
+        "soft-page-break" => pages.page_break(),
+        // Comments, styles and other non-body content.
+        "annotation" | "tracked-changes" | "automatic-styles" | "font-face-decls" | "scripts" => {}
+        "h" | "p" => {
+            if node.tag_name().name() == "h" && !in_cell {
+                let level = attr(node, "outline-level")
+                    .and_then(|l| l.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .clamp(1, 6);
+                pages.push(&"#".repeat(level));
+                pages.push(" ");
+            }
+            children(node, pages, in_cell, odt_node);
+            end_paragraph(pages, in_cell);
+        }
+        "table-cell" => {
+            children(node, pages, true, odt_node);
+            pages.push("\t");
+        }
+        "table-row" => {
+            children(node, pages, in_cell, odt_node);
+            pages.push("\n");
+        }
+        _ => children(node, pages, in_cell, odt_node),
+    }
+}
+
+/// EPUB: one page per spine item, in reading order.
+fn epub_chapters(bytes: &[u8]) -> anyhow::Result<Vec<String>> {
+    let container = zip_member(bytes, "META-INF/container.xml")?;
+    let opf_path = {
+        let doc = parse_xml(&container)?;
+        doc.descendants()
+            .find(|n| n.tag_name().name() == "rootfile")
+            .and_then(|n| attr(n, "full-path"))
+            .map(str::to_string)
+            .context("container.xml names no rootfile")?
+    };
+    let opf = zip_member(bytes, &opf_path)?;
+    let doc = parse_xml(&opf)?;
+    let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let manifest: std::collections::HashMap<&str, &str> = doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "item")
+        .filter_map(|n| Some((attr(n, "id")?, attr(n, "href")?)))
+        .collect();
+    let mut chapters = Vec::new();
+    for itemref in doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "itemref")
+    {
+        let Some(href) = attr(itemref, "idref").and_then(|id| manifest.get(id)) else {
+            continue;
+        };
+        let href = href.split('#').next().unwrap_or(href).replace("%20", " ");
+        let path = join_zip_path(base, &href);
+        let Ok(html) = zip_member(bytes, &path) else {
+            continue;
+        };
+        let (_, text) = crate::tools::builtins::http_fetch::render_html(&html);
+        if !text.trim().is_empty() {
+            chapters.push(text);
+        }
+    }
+    if chapters.is_empty() {
+        anyhow::bail!("EPUB has no readable chapters");
+    }
+    Ok(chapters)
+}
+
+/// Resolve `href` against the archive directory `base`.
+fn join_zip_path(base: &str, href: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            p => parts.push(p),
+        }
+    }
+    parts.join("/")
+}
+
+/// Trim trailing spaces and squeeze runs of blank lines.
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_run = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim().to_string()
+}
+
+// ── Attachments ─────────────────────────────────────────────
+
+/// Whether an attachment is saved for the agent to read: a supported
+/// document or any text file.
+pub fn is_readable_attachment(filename: &str, content_type: Option<&str>) -> bool {
+    DocumentKind::from_path(Path::new(filename)).is_some()
+        || content_type.is_some_and(|ct| ct.starts_with("text/"))
+}
+
+/// Save an attachment under [`UPLOADS_DIR`] in `workspace`, renaming it
+/// if the name is taken.  Returns the workspace-relative path.
+pub async fn save_attachment(
+    workspace: &Path,
+    filename: &str,
+    bytes: &[u8],
+) -> anyhow::Result<String> {
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        anyhow::bail!(
+            "attachment {filename} is too large ({} bytes, limit {MAX_ATTACHMENT_BYTES})",
+            bytes.len()
+        );
+    }
+    let dir = workspace.join(UPLOADS_DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+
+    let name = sanitize_filename(filename);
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{ext}")),
+        _ => (name.clone(), String::new()),
+    };
+    let mut candidate = name;
+    let mut n = 1;
+    while tokio::fs::try_exists(dir.join(&candidate))
+        .await
+        .unwrap_or(false)
+    {
+        n += 1;
+        candidate = format!("{stem}-{n}{ext}");
+    }
+    tokio::fs::write(dir.join(&candidate), bytes).await?;
+    Ok(format!("{UPLOADS_DIR}/{candidate}"))
+}
+
+/// Keep a file name to a safe subset: no directories, no leading dots.
+fn sanitize_filename(filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned.chars().take(128).collect()
+    }
+}
+
+/// Line appended to a message telling the agent where its attachments
+/// were saved.
+pub fn attachment_note(paths: &[String]) -> String {
+    let listed: Vec<String> = paths.iter().map(|p| format!("`{p}`")).collect();
+    format!(
+        "[Attached files saved to the workspace: {} — read them with read_file]",
+        listed.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_of(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buf);
+            let options = zip::write::SimpleFileOptions::default();
+            for (name, body) in files {
+                zip.start_file(*name, options).unwrap();
+                zip.write_all(body.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        buf.into_inner()
+    }
+
+    /// A two-page PDF with one line of Helvetica text per page.
+    fn two_page_pdf() -> Vec<u8> {
+        let page = |text: &str| {
+            let stream = format!("BT /F1 12 Tf 72 720 Td ({text}) Tj ET");
+            format!(
+                "<< /Length {} >>\nstream\n{stream}\nendstream",
+                stream.len()
+            )
+        };
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 5 0 R /Resources << /Font << /F1 7 0 R >> >> >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 6 0 R /Resources << /Font << /F1 7 0 R >> >> >>".to_string(),
+            page("First page text"),
+            page("Second page text"),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        ];
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, obj) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{obj}\nendobj\n", i + 1));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            pdf.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        ));
+        pdf.into_bytes()
+    }
+
+    #[test]
+    fn extracts_pdf_pages() {
+        let doc = extract_bytes(DocumentKind::Pdf, &two_page_pdf()).unwrap();
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.pages[0].contains("First page text"), "{:?}", doc.pages);
+        assert!(doc.pages[1].contains("Second page text"));
+        let second = doc.page_range(2, 2).unwrap();
+        assert!(second.starts_with("--- page 2 of 2 ---\n"));
+        assert!(!second.contains("First"));
+        assert!(doc.page_range(3, 4).is_err());
+        assert!(extract_bytes(DocumentKind::Pdf, b"not a pdf").is_err());
+    }
+
+    #[test]
+    fn extracts_docx_paragraphs_headings_tables_and_page_breaks() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Report</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Hello </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>world</w:t></w:r></w:p>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>a</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>b</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:p><w:r><w:br w:type="page"/></w:r><w:r><w:lastRenderedPageBreak/><w:t>Second page</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        let docx = zip_of(&[("word/document.xml", body)]);
+        let doc = extract_bytes(DocumentKind::Docx, &docx).unwrap();
+        assert_eq!(doc.pages, ["# Report\nHello world\na \tb", "Second page"]);
+    }
+
+    #[test]
+    fn extracts_odt_and_epub() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"><office:body><office:text><text:h text:outline-level="2">Notes</text:h><text:p>One<text:s text:c="2"/>two<text:tab/>three</text:p><text:soft-page-break/><text:p>Next</text:p></office:text></office:body></office:document-content>"#;
+        let doc = extract_bytes(DocumentKind::Odt, &zip_of(&[("content.xml", content)])).unwrap();
+        assert_eq!(doc.pages, ["## Notes\nOne  two\tthree", "Next"]);
+
+        let container = r#"<?xml version="1.0"?><container xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#;
+        let opf = r#"<?xml version="1.0"?><package xmlns="http://www.idpf.org/2007/opf"><manifest><item id="c1" href="text/one.xhtml" media-type="application/xhtml+xml"/><item id="c2" href="text/two.xhtml" media-type="application/xhtml+xml"/></manifest><spine><itemref idref="c2"/><itemref idref="c1"/></spine></package>"#;
+        let chapter = |title: &str| {
+            format!("<html><head><title>Book</title></head><body><h1>{title}</h1><p>Body of {title}.</p></body></html>")
+        };
+        let epub = zip_of(&[
+            ("META-INF/container.xml", container),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/text/one.xhtml", &chapter("One")),
+            ("OEBPS/text/two.xhtml", &chapter("Two")),
+        ]);
+        let doc = extract_bytes(DocumentKind::Epub, &epub).unwrap();
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.pages[0].starts_with("# Two"), "{:?}", doc.pages);
+        assert!(doc.text().contains("--- chapter 2 of 2 ---\n# One"));
+    }
+
+    #[tokio::test]
+    async fn saves_attachments_under_unique_safe_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(is_readable_attachment("Report.PDF", None));
+        assert!(is_readable_attachment(
+            "notes",
+            Some("text/plain; charset=utf-8")
+        ));
+        assert!(!is_readable_attachment("photo.png", Some("image/png")));
+
+        let first = save_attachment(tmp.path(), "../../Q3 report.pdf", b"%PDF")
+            .await
+            .unwrap();
+        let second = save_attachment(tmp.path(), "Q3 report.pdf", b"%PDF")
+            .await
+            .unwrap();
+        assert_eq!(first, "uploads/Q3_report.pdf");
+        assert_eq!(second, "uploads/Q3_report-2.pdf");
+        assert!(tmp.path().join(&second).exists());
+        assert!(attachment_note(&[first]).contains("`uploads/Q3_report.pdf`"));
+    }
+}
@@ -61,6 +61,11 @@ pub(crate) async fn api_agents_list() -> impl IntoResponse {
                         "max_tool_iterations".into(),
                         serde_json::json!(ac.max_tool_iterations),
                     );
+                    m.insert(
+                        "max_parallel_tools".into(),
+                        serde_json::json!(ac.max_parallel_tools),
+                    );
+                    m.insert("tool_timeouts".into(), serde_json::json!(ac.tool_timeouts));
                     m.insert(
                         "enabled_skills".into(),
                         serde_json::json!(ac.enabled_skills),
@@ -109,6 +114,26 @@ pub(crate) struct CloneAgentRequest {
     pub new_id: String,
 }
 
+/// `POST /api/agents/:id/cancel` — stop the agent's running turn.
+pub(crate) async fn api_agent_cancel(Path(agent_id): Path<String>) -> impl IntoResponse {
+    if let Err(e) = validate_path_segment(&agent_id) {
+        return e.into_response();
+    }
+    if crate::agent::cancel::cancel(&agent_id) {
+        (
+            StatusCode::OK,
+            Json(serde_json::json!({ "agent": agent_id, "cancelled": true })),
+        )
+            .into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "no running turn for this agent" })),
+        )
+            .into_response()
+    }
+}
+
 /// `POST /api/agents/:id/clone` — clone an agent's definition and configuration.
 pub(crate) async fn api_agent_clone(
     Path(agent_id): Path<String>,
@@ -237,6 +262,11 @@ pub(crate) async fn api_agent_get(Path(agent_id): Path<String>) -> impl IntoResp
                 "max_tool_iterations".into(),
                 serde_json::json!(ac.max_tool_iterations),
             );
+            m.insert(
+                "max_parallel_tools".into(),
+                serde_json::json!(ac.max_parallel_tools),
+            );
+            m.insert("tool_timeouts".into(), serde_json::json!(ac.tool_timeouts));
             m.insert(
                 "enabled_skills".into(),
                 serde_json::json!(ac.enabled_skills),
@@ -360,6 +390,8 @@ pub(crate) async fn api_agent_create(Json(body): Json<CreateAgentRequest>) -> im
                         heartbeat_secs: body.heartbeat_secs,
                         cron_jobs: Vec::new(),
                         max_tool_iterations: None,
+                        max_parallel_tools: None,
+                        tool_timeouts: Default::default(),
                         enabled_skills: None,
                         fallback_models: Vec::new(),
                         webhook_secret: None,
@@ -370,6 +402,15 @@ pub(crate) async fn api_agent_create(Json(body): Json<CreateAgentRequest>) -> im
                         timezone: None,
                         watch_paths: Vec::new(),
                         reasoning_effort: None,
+                        approvals: Vec::new(),
+                        approvers: Vec::new(),
+                        approval_timeout_secs: None,
+                        mcp_servers: Vec::new(),
+                        http_fetch: None,
+                        tools: None,
+                        sandbox: None,
+                        journal_exec_shell: false,
+                        shared_memory: None,
                     });
                     if let Err(e) = cfg.save(&config_path).await {
                         tracing::warn!(error = %e, "failed to save config after agent creation");
@@ -411,6 +452,10 @@ pub(crate) struct UpdateAgentRequest {
     #[serde(default)]
     max_tool_iterations: Option<usize>,
     #[serde(default)]
+    max_parallel_tools: Option<usize>,
+    #[serde(default)]
+    tool_timeouts: Option<std::collections::BTreeMap<String, u64>>,
+    #[serde(default)]
     enabled_skills: Option<Vec<String>>,
     #[serde(default)]
     max_turns: Option<usize>,
@@ -498,6 +543,8 @@ pub(crate) async fn api_agent_update(
     if body.model.is_some()
         || body.heartbeat_secs.is_some()
         || body.max_tool_iterations.is_some()
+        || body.max_parallel_tools.is_some()
+        || body.tool_timeouts.is_some()
         || body.enabled_skills.is_some()
         || body.max_turns.is_some()
         || body.compact_keep_recent_turns.is_some()
@@ -522,6 +569,14 @@ pub(crate) async fn api_agent_update(
                         ac.max_tool_iterations = Some(mti);
                         updated.push("max_tool_iterations");
                     }
+                    if let Some(mpt) = body.max_parallel_tools {
+                        ac.max_parallel_tools = Some(mpt);
+                        updated.push("max_parallel_tools");
+                    }
+                    if let Some(tt) = body.tool_timeouts {
+                        ac.tool_timeouts = tt;
+                        updated.push("tool_timeouts");
+                    }
                     if let Some(skills) = body.enabled_skills {
                         ac.enabled_skills = if skills.is_empty() {
                             None
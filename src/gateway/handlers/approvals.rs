@@ -0,0 +1,51 @@
+//! Approval endpoints.
+//!
+//! Unlike Discord reactions, these do not check `approvers`: anyone who
+//! can reach the API may answer any pending request.  They are only as
+//! protected as the gateway itself, so set `PINCHY_API_TOKEN` when
+//! approvals gate anything that matters.
+
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, Json};
+
+use super::super::auth::validate_path_segment;
+
+/// `GET /api/approvals` — list tool calls waiting for a human decision.
+pub(crate) async fn api_approvals_list() -> impl IntoResponse {
+    let pending = crate::agent::approval::list_pending();
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "approvals": pending })),
+    )
+}
+
+/// `POST /api/approvals/:id/approve` — let a paused tool call run.
+pub(crate) async fn api_approval_approve(Path(id): Path<String>) -> impl IntoResponse {
+    decide(&id, true)
+}
+
+/// `POST /api/approvals/:id/deny` — refuse a paused tool call.
+pub(crate) async fn api_approval_deny(Path(id): Path<String>) -> impl IntoResponse {
+    decide(&id, false)
+}
+
+fn decide(id: &str, approved: bool) -> axum::response::Response {
+    if let Err(e) = validate_path_segment(id) {
+        return e.into_response();
+    }
+    if crate::agent::approval::resolve(id, approved) {
+        (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "id": id,
+                "decision": if approved { "approved" } else { "denied" },
+            })),
+        )
+            .into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "no pending approval with that id" })),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, Json};
+
+use super::super::auth::validate_path_segment;
+use crate::tools::builtins::artifacts::{self, ArtifactError};
+
+/// `GET /api/agents/:id/artifacts/:artifact_id` — full output of a tool
+/// call that was stored as an artifact (see `ToolCallRecord::artifact_id`).
+pub(crate) async fn api_artifact_get(
+    Path((agent_id, artifact_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_path_segment(&agent_id) {
+        return e.into_response();
+    }
+    let workspace = super::agent_workspace(&agent_id).await;
+    let loaded = tokio::task::spawn_blocking(move || {
+        artifacts::load(&workspace, &artifact_id).map(|content| (artifact_id, content))
+    })
+    .await;
+    match loaded {
+        Ok(Ok((id, content))) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "artifact_id": id,
+                "bytes": content.len(),
+                "content": content,
+            })),
+        )
+            .into_response(),
+        Ok(Err(e)) => {
+            let status = match e.downcast_ref::<ArtifactError>() {
+                Some(ArtifactError::NotFound(_)) => StatusCode::NOT_FOUND,
+                Some(ArtifactError::InvalidId(_)) => StatusCode::BAD_REQUEST,
+                None => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({ "error": format!("{e}") }))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("{e}") })),
+        )
+            .into_response(),
+    }
+}
@@ -149,6 +149,7 @@ pub(crate) async fn api_cron_jobs_create(
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
 
     // Try to register via scheduler handle
@@ -1,4 +1,6 @@
 pub(crate) mod agents;
+pub(crate) mod approvals;
+pub(crate) mod artifacts;
 pub(crate) mod config;
 pub(crate) mod cron;
 pub(crate) mod debug;
@@ -10,5 +12,25 @@ pub(crate) mod receipts;
 pub(crate) mod sessions;
 pub(crate) mod skills;
 pub(crate) mod slash_cmds;
+pub(crate) mod turns;
 pub(crate) mod usage;
 pub(crate) mod webhook;
+
+/// Workspace of agent `id`: under its configured `root`, or the default
+/// `<pinchy_home>/agents/<id>/workspace` for an agent not in the config.
+pub(crate) async fn agent_workspace(id: &str) -> std::path::PathBuf {
+    configured_workspace(id)
+        .await
+        .unwrap_or_else(|| crate::utils::agent_workspace(id))
+}
+
+/// Workspace of agent `id` if the config defines that agent.
+pub(crate) async fn configured_workspace(id: &str) -> Option<std::path::PathBuf> {
+    let cfg = crate::config::Config::load(&crate::pinchy_home().join("config.yaml"))
+        .await
+        .ok()?;
+    cfg.agents
+        .iter()
+        .find(|a| a.id == id)
+        .map(|a| std::path::PathBuf::from(&a.root).join("workspace"))
+}
@@ -1,4 +1,72 @@
-use axum::{extract::Path, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use super::super::auth::validate_path_segment;
+use crate::skills::package;
+
+#[derive(Deserialize)]
+pub(crate) struct SkillAgentQuery {
+    /// Agent whose skills folder to use; defaults to the skills agent.
+    pub agent: Option<String>,
+}
+
+/// Resolve the target agent of a package request.
+fn package_agent(query: &SkillAgentQuery) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let agent = query
+        .agent
+        .clone()
+        .or_else(crate::tools::get_skill_agent_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "no agent given or configured" })),
+            )
+        })?;
+    validate_path_segment(&agent)?;
+    Ok(agent)
+}
+
+fn package_error(status: StatusCode, msg: impl std::fmt::Display) -> axum::response::Response {
+    (
+        status,
+        Json(serde_json::json!({ "error": msg.to_string() })),
+    )
+        .into_response()
+}
+
+/// Map a package operation's result to a response, reloading the
+/// registry after changes.
+fn package_result<T: serde::Serialize>(
+    result: Result<anyhow::Result<T>, tokio::task::JoinError>,
+    reload: bool,
+) -> axum::response::Response {
+    match result {
+        Ok(Ok(value)) => {
+            if reload {
+                crate::tools::reload_skills(None);
+            }
+            Json(serde_json::json!(value)).into_response()
+        }
+        Ok(Err(e)) => {
+            let missing = e
+                .chain()
+                .any(|cause| cause.downcast_ref::<package::PackageError>().is_some());
+            let status = if missing {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            package_error(status, format!("{e:#}"))
+        }
+        Err(e) => package_error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
 
 /// `GET /api/skills` — list all loaded skills.
 pub(crate) async fn api_skills_list() -> impl IntoResponse {
@@ -60,3 +128,107 @@ pub(crate) async fn api_skills_delete(Path(name): Path<String>) -> impl IntoResp
 
     Json(serde_json::json!({ "status": "deleted", "name": name })).into_response()
 }
+
+#[derive(Deserialize)]
+pub(crate) struct InstallSourceRequest {
+    /// Path on the daemon's machine: a skill directory, git checkout or
+    /// `.tar.gz` package.
+    pub source: String,
+}
+
+/// `POST /api/skills/install?agent=<id>` — install or upgrade a skill.
+///
+/// The body is either a `.tar.gz` package, or JSON `{ "source": "<path>" }`
+/// naming a package on the daemon's machine.
+pub(crate) async fn api_skills_install(
+    Query(query): Query<SkillAgentQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let agent = match package_agent(&query) {
+        Ok(a) => a,
+        Err(e) => return e.into_response(),
+    };
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let result = if is_json {
+        let req: InstallSourceRequest = match serde_json::from_slice(&body) {
+            Ok(r) => r,
+            Err(e) => return package_error(StatusCode::BAD_REQUEST, format!("invalid body: {e}")),
+        };
+        tokio::task::spawn_blocking(move || {
+            package::install(&agent, std::path::Path::new(&req.source))
+        })
+        .await
+    } else {
+        if body.is_empty() {
+            return package_error(StatusCode::BAD_REQUEST, "empty package");
+        }
+        tokio::task::spawn_blocking(move || package::install_archive(&agent, &body)).await
+    };
+    package_result(result, true)
+}
+
+/// `GET /api/skills/:name/export?agent=<id>` — download a skill as `.tar.gz`.
+pub(crate) async fn api_skills_export(
+    Path(name): Path<String>,
+    Query(query): Query<SkillAgentQuery>,
+) -> impl IntoResponse {
+    let agent = match package_agent(&query) {
+        Ok(a) => a,
+        Err(e) => return e.into_response(),
+    };
+    let exported = tokio::task::spawn_blocking(move || {
+        let mut buf = Vec::new();
+        package::export(&agent, &name, &mut buf).map(|version| (name, version, buf))
+    })
+    .await;
+    match exported {
+        Ok(Ok((name, version, buf))) => (
+            [
+                (header::CONTENT_TYPE, "application/gzip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}\"",
+                        package::archive_name(&name, &version)
+                    ),
+                ),
+            ],
+            buf,
+        )
+            .into_response(),
+        other => package_result(other.map(|r| r.map(|_| ())), false),
+    }
+}
+
+/// `GET /api/skills/:name/versions?agent=<id>` — saved earlier versions.
+pub(crate) async fn api_skills_versions(
+    Path(name): Path<String>,
+    Query(query): Query<SkillAgentQuery>,
+) -> impl IntoResponse {
+    let agent = match package_agent(&query) {
+        Ok(a) => a,
+        Err(e) => return e.into_response(),
+    };
+    let result = tokio::task::spawn_blocking(move || {
+        package::versions(&agent, &name).map(|v| serde_json::json!({ "versions": v }))
+    })
+    .await;
+    package_result(result, false)
+}
+
+/// `POST /api/skills/:name/rollback?agent=<id>` — restore the previous version.
+pub(crate) async fn api_skills_rollback(
+    Path(name): Path<String>,
+    Query(query): Query<SkillAgentQuery>,
+) -> impl IntoResponse {
+    let agent = match package_agent(&query) {
+        Ok(a) => a,
+        Err(e) => return e.into_response(),
+    };
+    let result = tokio::task::spawn_blocking(move || package::rollback(&agent, &name)).await;
+    package_result(result, true)
+}
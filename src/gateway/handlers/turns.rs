@@ -0,0 +1,39 @@
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, Json};
+
+use super::super::auth::validate_path_segment;
+use crate::journal::JournalError;
+
+/// `POST /api/agents/:id/turns/:turn_id/revert` — restore the agent's
+/// workspace as it was before a turn (see `TurnReceipt::turn_id`),
+/// undoing that turn's file changes and those of every later turn.
+pub(crate) async fn api_turn_revert(
+    Path((agent_id, turn_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_path_segment(&agent_id) {
+        return e.into_response();
+    }
+    let workspace = super::agent_workspace(&agent_id).await;
+    let reverted =
+        tokio::task::spawn_blocking(move || crate::journal::revert_to(&workspace, &turn_id)).await;
+    match reverted {
+        Ok(Ok(report)) => (StatusCode::OK, Json(serde_json::json!(report))).into_response(),
+        Ok(Err(e)) => {
+            let status = match e.downcast_ref::<JournalError>() {
+                Some(JournalError::NotFound(_)) => StatusCode::NOT_FOUND,
+                Some(JournalError::AlreadyUndone(_) | JournalError::TurnRunning) => {
+                    StatusCode::CONFLICT
+                }
+                Some(JournalError::InvalidTurnId(_) | JournalError::NothingToUndo) => {
+                    StatusCode::BAD_REQUEST
+                }
+                None => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({ "error": format!("{e}") }))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("{e}") })),
+        )
+            .into_response(),
+    }
+}
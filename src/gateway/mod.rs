@@ -193,6 +193,10 @@ pub async fn start_gateway_with_config(
             "/agents/:agent_id/clone",
             post(handlers::agents::api_agent_clone),
         )
+        .route(
+            "/agents/:agent_id/cancel",
+            post(handlers::agents::api_agent_cancel),
+        )
         // Agent files
         .route(
             "/agents/:agent_id/files/:filename",
@@ -222,6 +226,26 @@ pub async fn start_gateway_with_config(
             "/agents/:agent_id/receipts/:session_id",
             get(handlers::receipts::api_receipts_by_session),
         )
+        // Turn journal (undo)
+        .route(
+            "/agents/:agent_id/turns/:turn_id/revert",
+            post(handlers::turns::api_turn_revert),
+        )
+        // Artifacts (oversized tool results)
+        .route(
+            "/agents/:agent_id/artifacts/:artifact_id",
+            get(handlers::artifacts::api_artifact_get),
+        )
+        // Approvals
+        .route("/approvals", get(handlers::approvals::api_approvals_list))
+        .route(
+            "/approvals/:approval_id/approve",
+            post(handlers::approvals::api_approval_approve),
+        )
+        .route(
+            "/approvals/:approval_id/deny",
+            post(handlers::approvals::api_approval_deny),
+        )
         // Heartbeat
         .route(
             "/heartbeat/status",
@@ -268,6 +292,22 @@ pub async fn start_gateway_with_config(
         // Skills
         .route("/skills", get(handlers::skills::api_skills_list))
         .route("/skills/:name", delete(handlers::skills::api_skills_delete))
+        .route(
+            "/skills/install",
+            post(handlers::skills::api_skills_install),
+        )
+        .route(
+            "/skills/:name/export",
+            get(handlers::skills::api_skills_export),
+        )
+        .route(
+            "/skills/:name/versions",
+            get(handlers::skills::api_skills_versions),
+        )
+        .route(
+            "/skills/:name/rollback",
+            post(handlers::skills::api_skills_rollback),
+        )
         // AI
         .route(
             "/ai/enhance-prompt",
@@ -474,10 +514,51 @@ fn resolve_ui_paths() -> (PathBuf, PathBuf, &'static str) {
 // Command forwarding (WS client commands → comm bus)
 // ---------------------------------------------------------------------------
 
+/// Save readable WebSocket attachments (`{"name", "data", "type"?}`, where
+/// `data` is base64 or a data URI) into `agent`'s workspace; returns the
+/// saved paths.  The agent id comes from the client, so nothing is saved
+/// unless it names an agent in the config.
+async fn save_ws_attachments(agent: &str, attachments: &[serde_json::Value]) -> Vec<String> {
+    use base64::Engine;
+
+    if attachments.is_empty() {
+        return Vec::new();
+    }
+    let Some(workspace) = handlers::configured_workspace(agent).await else {
+        warn!(agent = %agent, "gateway: ignoring attachments for an agent not in the config");
+        return Vec::new();
+    };
+    let mut saved = Vec::new();
+    for attachment in attachments {
+        let name = attachment["name"].as_str().unwrap_or("attachment");
+        let Some(data) = attachment["data"].as_str() else {
+            continue;
+        };
+        if !crate::documents::is_readable_attachment(name, attachment["type"].as_str()) {
+            debug!(file = %name, "gateway: ignoring attachment of unsupported type");
+            continue;
+        }
+        let data = data.split_once(";base64,").map_or(data, |(_, b64)| b64);
+        let result = match base64::engine::general_purpose::STANDARD.decode(data.trim()) {
+            Ok(bytes) => crate::documents::save_attachment(&workspace, name, &bytes).await,
+            Err(e) => Err(anyhow::anyhow!("invalid base64: {e}")),
+        };
+        match result {
+            Ok(path) => saved.push(path),
+            Err(e) => warn!(file = %name, error = %e, "gateway: failed to save attachment"),
+        }
+    }
+    saved
+}
+
 /// Spawn a background task that reads commands from `commands_rx` and
 /// forwards each one into the [`crate::comm`] message bus as an
 /// `IncomingMessage` from the `"gateway"` channel.
 ///
+/// `{"type": "cancel"}` messages stop the target agent's running turn.
+/// `attachments: [{"name", "data", "type"?}]` (base64 or a data URI) that
+/// are documents or text files are saved into the target agent's
+/// workspace and noted in the message.
 /// Slash commands (messages starting with `/`) are intercepted and
 /// dispatched through the [`crate::slash::Registry`] so they are never
 /// forwarded to the LLM.
@@ -492,8 +573,26 @@ pub fn spawn_command_forwarder(mut commands_rx: mpsc::Receiver<String>) {
     tokio::spawn(async move {
         debug!("gateway command forwarder started");
         while let Some(text) = commands_rx.recv().await {
+            // `{"type": "cancel", "target_agent": …}` stops a running turn.
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&text) {
+                if parsed.get("type").and_then(|v| v.as_str()) == Some("cancel") {
+                    let agent = parsed
+                        .get("target_agent")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("default");
+                    let cancelled = crate::agent::cancel::cancel(agent);
+                    debug!(agent = %agent, cancelled, "cancel requested via gateway");
+                    publish_event_json(&serde_json::json!({
+                        "type": "cancel_response",
+                        "agent": agent,
+                        "cancelled": cancelled,
+                    }));
+                    continue;
+                }
+            }
+
             // Try to parse as JSON payload from the web client.
-            let (command, target_agent, session_id, images) =
+            let (command, target_agent, session_id, images, attachments) =
                 if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&text) {
                     let cmd = parsed
                         .get("command")
@@ -519,9 +618,20 @@ pub fn spawn_command_forwarder(mut commands_rx: mpsc::Receiver<String>) {
                                 .collect()
                         })
                         .unwrap_or_default();
-                    (cmd, agent, session, imgs)
+                    let files = parsed
+                        .get("attachments")
+                        .and_then(|v| v.as_array())
+                        .cloned()
+                        .unwrap_or_default();
+                    (cmd, agent, session, imgs, files)
                 } else {
-                    (text.clone(), "default".to_string(), None, Vec::new())
+                    (
+                        text.clone(),
+                        "default".to_string(),
+                        None,
+                        Vec::new(),
+                        Vec::new(),
+                    )
                 };
 
             // Intercept slash commands — dispatch via registry.
@@ -558,10 +668,18 @@ pub fn spawn_command_forwarder(mut commands_rx: mpsc::Receiver<String>) {
                 continue; // consumed — do NOT forward to comm bus
             }
 
+            let mut command = command;
+            let saved = save_ws_attachments(&target_agent, &attachments).await;
+            if !saved.is_empty() {
+                command.push_str("\n\n");
+                command.push_str(&crate::documents::attachment_note(&saved));
+            }
+
             let msg = comm::IncomingMessage {
                 agent_id: Some(target_agent.clone()),
                 channel: "gateway:ws-client".to_string(),
                 author: "ws-client".to_string(),
+                author_id: None,
                 content: command,
                 timestamp: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
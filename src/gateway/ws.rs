@@ -136,8 +136,8 @@ async fn handle_ws(mut socket: WebSocket, state: AppState) {
             msg = socket.recv() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if state.commands_tx.send(text).await.is_err() {
-                            warn!("commands channel closed");
+                        if let Err(e) = state.commands_tx.send(text).await {
+                            warn!("commands channel closed: {e}");
                         }
                     }
                     Some(Ok(Message::Pong(_))) => {
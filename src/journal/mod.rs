@@ -0,0 +1,825 @@
+//! Per-turn journal of workspace changes, for `/undo`.
+//!
+//! While a turn runs, its id is registered for the agent's workspace
+//! ([`begin`]).  File tools call [`record`] before touching a path; the
+//! first time a path is seen in a turn its current state is saved under
+//! `<workspace>/.journal/<turn>/`: the file's bytes, or a note that it
+//! (and any missing parent directories) did not exist yet.  With
+//! `journal_exec_shell` enabled on the agent, `exec_shell` also takes a
+//! [`Snapshot`] of the workspace before each command and journals what
+//! the command changed.
+//!
+//! [`revert_to`] puts every journalled path back as it was before a
+//! turn, undoing that turn and any later ones, newest first.  The turn id
+//! and changed paths are recorded on the turn's receipt.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Workspace-relative directory holding turn journals (hidden from
+/// `list_files` / `search_files`).
+pub const JOURNAL_DIR: &str = ".journal";
+/// Journals kept per workspace; older ones are pruned when a turn starts.
+const KEEP_TURNS: usize = 50;
+/// Snapshot limits: files larger than this are tracked by size and
+/// modification time only, and can't be restored if a command changes them.
+const SNAPSHOT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Total file content held by one snapshot.
+const SNAPSHOT_MAX_TOTAL_BYTES: u64 = 64 * 1024 * 1024;
+/// Most files a snapshot walks before giving up on the rest.
+const SNAPSHOT_MAX_FILES: usize = 20_000;
+/// Directories a snapshot does not descend into.
+const SNAPSHOT_SKIP_DIRS: &[&str] = &[
+    JOURNAL_DIR,
+    crate::tools::builtins::artifacts::ARTIFACT_DIR,
+    ".git",
+    "node_modules",
+    "target",
+    "venv",
+    ".venv",
+    "__pycache__",
+];
+
+/// Why a journal could not be read or reverted.  Carried inside the
+/// `anyhow::Error` so callers can tell the cases apart without string
+/// matching.
+#[derive(Debug)]
+pub enum JournalError {
+    InvalidTurnId(String),
+    /// No changes were journalled for the turn (or it was pruned).
+    NotFound(String),
+    AlreadyUndone(String),
+    /// A turn is running in the workspace.
+    TurnRunning,
+    NothingToUndo,
+}
+
+impl std::fmt::Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTurnId(id) => write!(f, "invalid turn id '{id}'"),
+            Self::NotFound(id) => {
+                write!(f, "no changes were journalled for turn '{id}' (not found)")
+            }
+            Self::AlreadyUndone(id) => write!(f, "turn '{id}' was already undone"),
+            Self::TurnRunning => {
+                f.write_str("a turn is running in this workspace; stop it or wait before undoing")
+            }
+            Self::NothingToUndo => f.write_str("nothing to undo"),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+/// One journalled path and its state before the turn touched it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Workspace-relative path.
+    pub path: String,
+    /// The path existed before the turn.
+    pub existed: bool,
+    /// The path is (or was created as) a directory.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dir: bool,
+    /// Saved copy of the file under the journal's `files/` directory.
+    /// `None` for an existing file means its contents were not captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    /// Tool that first changed the path.
+    pub source: String,
+}
+
+impl Entry {
+    /// The earlier contents of this file are not available to restore.
+    fn is_lost(&self) -> bool {
+        self.existed && !self.dir && self.blob.is_none()
+    }
+}
+
+/// Journal of one turn's workspace changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnJournal {
+    pub turn_id: String,
+    /// Milliseconds since the epoch of the first recorded change.
+    pub started_at: u64,
+    pub entries: Vec<Entry>,
+    /// When the turn was undone, if it has been.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_at: Option<u64>,
+}
+
+impl TurnJournal {
+    /// Paths the turn changed (excluding directories it created).
+    pub fn changed_files(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| !e.dir)
+            .map(|e| e.path.clone())
+            .collect()
+    }
+}
+
+/// Outcome of [`revert_to`] / [`undo_last`].
+#[derive(Debug, Default, Serialize)]
+pub struct RevertReport {
+    /// Turns undone, newest first.
+    pub turns: Vec<String>,
+    /// Files put back to their earlier contents.
+    pub restored: Vec<String>,
+    /// Files and directories the turns created, now removed.
+    pub removed: Vec<String>,
+    /// Paths that could not be reverted, with the reason.
+    pub skipped: Vec<String>,
+}
+
+impl RevertReport {
+    /// One-paragraph summary for chat replies.
+    pub fn summary(&self) -> String {
+        let turns = match self.turns.as_slice() {
+            [one] => format!("turn {one}"),
+            many => format!("{} turns", many.len()),
+        };
+        let mut out = format!(
+            "undid {turns}: {} file(s) restored, {} removed",
+            self.restored.len(),
+            self.removed.len()
+        );
+        for path in self.restored.iter().chain(&self.removed) {
+            out.push_str(&format!("\n  {path}"));
+        }
+        if !self.skipped.is_empty() {
+            out.push_str(&format!("\nnot reverted ({}):", self.skipped.len()));
+            for s in &self.skipped {
+                out.push_str(&format!("\n  {s}"));
+            }
+        }
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Active turns
+// ---------------------------------------------------------------------------
+
+/// Running turns: canonical workspace → (registration number, turn id).
+static ACTIVE: LazyLock<Mutex<HashMap<PathBuf, (u64, String)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+/// Serialises journal reads and writes across concurrent tool calls.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Registration of a turn's journal; unregisters on drop.
+pub struct JournalGuard {
+    workspace: PathBuf,
+    seq: u64,
+}
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        let mut active = ACTIVE.lock().expect("journal registry poisoned");
+        if active
+            .get(&self.workspace)
+            .is_some_and(|(seq, _)| *seq == self.seq)
+        {
+            active.remove(&self.workspace);
+        }
+    }
+}
+
+fn canonical(workspace: &Path) -> PathBuf {
+    workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf())
+}
+
+/// Journal changes made in `workspace` under `turn_id` until the guard
+/// is dropped.  Also prunes old journals.
+pub fn begin(workspace: &Path, turn_id: &str) -> JournalGuard {
+    let ws = canonical(workspace);
+    prune(&ws);
+    let seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+    ACTIVE
+        .lock()
+        .expect("journal registry poisoned")
+        .insert(ws.clone(), (seq, turn_id.to_string()));
+    JournalGuard { workspace: ws, seq }
+}
+
+/// Id of the turn currently running in `workspace`, if any.
+pub fn active_turn(workspace: &Path) -> Option<String> {
+    ACTIVE
+        .lock()
+        .expect("journal registry poisoned")
+        .get(&canonical(workspace))
+        .map(|(_, turn)| turn.clone())
+}
+
+fn prune(ws: &Path) {
+    let ids = journal_ids(ws);
+    for old in ids.iter().take(ids.len().saturating_sub(KEEP_TURNS)) {
+        let _ = std::fs::remove_dir_all(ws.join(JOURNAL_DIR).join(old));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+/// State of a path before the turn changed it.
+enum Before<'a> {
+    /// Read it from disk now (the change has not happened yet).
+    Disk,
+    Missing,
+    Dir,
+    Bytes(&'a [u8]),
+    /// An existing file whose contents were not captured.
+    Lost,
+}
+
+/// Journal `path` (inside `workspace`) before `source` changes it.  Does
+/// nothing outside a turn or when the path was already journalled this
+/// turn.  Failures are logged: journalling never blocks a tool.
+///
+/// Copying the old contents can take a while for large files, so it runs
+/// on the blocking pool.
+pub async fn record(workspace: &Path, path: &Path, source: &str) {
+    let (workspace, path, source) = (
+        workspace.to_path_buf(),
+        path.to_path_buf(),
+        source.to_string(),
+    );
+    let _ = tokio::task::spawn_blocking(move || record_now(&workspace, &path, &source)).await;
+}
+
+fn record_now(workspace: &Path, path: &Path, source: &str) {
+    let Some(turn) = active_turn(workspace) else {
+        return;
+    };
+    let ws = canonical(workspace);
+    let Some(rel) = path
+        .strip_prefix(&ws)
+        .or_else(|_| path.strip_prefix(workspace))
+        .ok()
+        .map(|r| r.to_string_lossy().replace('\\', "/"))
+    else {
+        return;
+    };
+    if rel.is_empty() || rel.split('/').next() == Some(JOURNAL_DIR) {
+        return;
+    }
+    if let Err(e) = record_state(&ws, &turn, &rel, Before::Disk, source) {
+        warn!(path = %rel, error = %e, "failed to journal workspace change");
+    }
+}
+
+fn record_state(
+    ws: &Path,
+    turn: &str,
+    rel: &str,
+    before: Before<'_>,
+    source: &str,
+) -> anyhow::Result<()> {
+    let _lock = LOCK.lock().expect("journal lock poisoned");
+    let dir = ws.join(JOURNAL_DIR).join(turn);
+    let mut journal = read_journal(&dir).unwrap_or_else(|| TurnJournal {
+        turn_id: turn.to_string(),
+        started_at: crate::agent::types::epoch_millis(),
+        entries: Vec::new(),
+        reverted_at: None,
+    });
+    if journal.entries.iter().any(|e| e.path == rel) {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir.join("files"))?;
+
+    let entry = |path: &str, existed: bool, is_dir: bool, blob: Option<String>| Entry {
+        path: path.to_string(),
+        existed,
+        dir: is_dir,
+        blob,
+        source: source.to_string(),
+    };
+    // Entries only ever grow, so their count names the next blob uniquely.
+    let blob_name = journal.entries.len().to_string();
+    let blob_path = dir.join("files").join(&blob_name);
+
+    let new_entries = match before {
+        Before::Disk => {
+            let full = ws.join(rel);
+            match std::fs::symlink_metadata(&full) {
+                Ok(meta) if meta.is_dir() => vec![entry(rel, true, true, None)],
+                Ok(_) => {
+                    std::fs::copy(&full, &blob_path)?;
+                    vec![entry(rel, true, false, Some(blob_name))]
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    // Parent directories the change will create, outermost first.
+                    let mut created: Vec<String> = Path::new(rel)
+                        .ancestors()
+                        .skip(1)
+                        .filter(|a| !a.as_os_str().is_empty() && !ws.join(a).exists())
+                        .map(|a| a.to_string_lossy().replace('\\', "/"))
+                        .filter(|a| !journal.entries.iter().any(|e| &e.path == a))
+                        .collect();
+                    created.reverse();
+                    let mut entries: Vec<Entry> = created
+                        .iter()
+                        .map(|d| entry(d, false, true, None))
+                        .collect();
+                    entries.push(entry(rel, false, false, None));
+                    entries
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Before::Missing => vec![entry(rel, false, false, None)],
+        Before::Dir => vec![entry(rel, false, true, None)],
+        Before::Bytes(bytes) => {
+            std::fs::write(&blob_path, bytes)?;
+            vec![entry(rel, true, false, Some(blob_name))]
+        }
+        Before::Lost => vec![entry(rel, true, false, None)],
+    };
+    journal.entries.extend(new_entries);
+    write_journal(&dir, &journal)?;
+    debug!(turn, path = rel, source, "journalled workspace change");
+    Ok(())
+}
+
+fn read_journal(dir: &Path) -> Option<TurnJournal> {
+    let text = std::fs::read_to_string(dir.join("journal.json")).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn write_journal(dir: &Path, journal: &TurnJournal) -> anyhow::Result<()> {
+    let tmp = dir.join("journal.json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(journal)?)?;
+    std::fs::rename(&tmp, dir.join("journal.json"))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// exec_shell snapshots
+// ---------------------------------------------------------------------------
+
+/// A file as seen by a [`Snapshot`].
+struct Seen {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+    /// Contents, when within the snapshot limits.
+    bytes: Option<Vec<u8>>,
+}
+
+/// Workspace state before a shell command, compared afterwards to
+/// journal what the command changed.
+pub struct Snapshot {
+    ws: PathBuf,
+    turn: String,
+    files: BTreeMap<String, Seen>,
+    dirs: BTreeSet<String>,
+}
+
+impl Snapshot {
+    /// Snapshot `workspace`, or `None` when no turn is running in it.
+    pub fn take(workspace: &Path) -> Option<Self> {
+        let turn = active_turn(workspace)?;
+        let ws = canonical(workspace);
+        let mut snapshot = Self {
+            files: BTreeMap::new(),
+            dirs: BTreeSet::new(),
+            ws,
+            turn,
+        };
+        let mut budget = SNAPSHOT_MAX_TOTAL_BYTES;
+        let ws = snapshot.ws.clone();
+        walk(&ws, &ws, &mut |rel, meta, path| {
+            if meta.is_dir() {
+                snapshot.dirs.insert(rel);
+                return;
+            }
+            let bytes = (meta.len() <= SNAPSHOT_MAX_FILE_BYTES && meta.len() <= budget)
+                .then(|| std::fs::read(path).ok())
+                .flatten();
+            if let Some(b) = &bytes {
+                budget -= b.len() as u64;
+            }
+            snapshot.files.insert(
+                rel,
+                Seen {
+                    len: meta.len(),
+                    modified: meta.modified().ok(),
+                    bytes,
+                },
+            );
+        });
+        Some(snapshot)
+    }
+
+    /// Journal everything that changed since the snapshot was taken.
+    pub fn record_changes(self, source: &str) {
+        let mut after_files = BTreeMap::new();
+        let mut after_dirs = BTreeSet::new();
+        walk(&self.ws, &self.ws, &mut |rel, meta, _| {
+            if meta.is_dir() {
+                after_dirs.insert(rel);
+            } else {
+                after_files.insert(rel, (meta.len(), meta.modified().ok()));
+            }
+        });
+
+        let mut changes: Vec<(String, Before<'_>)> = Vec::new();
+        // New directories first, outermost first, so undo removes them last.
+        for dir in after_dirs.difference(&self.dirs) {
+            changes.push((dir.clone(), Before::Dir));
+        }
+        for (rel, seen) in &self.files {
+            let unchanged = after_files
+                .get(rel)
+                .is_some_and(|(len, modified)| *len == seen.len && *modified == seen.modified);
+            if !unchanged {
+                let before = match &seen.bytes {
+                    Some(bytes) => Before::Bytes(bytes),
+                    None => Before::Lost,
+                };
+                changes.push((rel.clone(), before));
+            }
+        }
+        for rel in after_files.keys() {
+            if !self.files.contains_key(rel) {
+                changes.push((rel.clone(), Before::Missing));
+            }
+        }
+        for (rel, before) in changes {
+            if let Err(e) = record_state(&self.ws, &self.turn, &rel, before, source) {
+                warn!(path = %rel, error = %e, "failed to journal workspace change");
+            }
+        }
+    }
+}
+
+/// Visit every file and directory under `dir` (skipping
+/// [`SNAPSHOT_SKIP_DIRS`] and symlinks), up to [`SNAPSHOT_MAX_FILES`].
+fn walk(ws: &Path, dir: &Path, visit: &mut dyn FnMut(String, std::fs::Metadata, &Path)) {
+    let mut stack = vec![dir.to_path_buf()];
+    let mut seen = 0usize;
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.file_type().is_symlink() {
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(ws) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if meta.is_dir() {
+                let name = entry.file_name();
+                if SNAPSHOT_SKIP_DIRS.contains(&name.to_string_lossy().as_ref()) {
+                    continue;
+                }
+                stack.push(path.clone());
+            }
+            seen += 1;
+            if seen > SNAPSHOT_MAX_FILES {
+                return;
+            }
+            visit(rel, meta, &path);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reading and reverting
+// ---------------------------------------------------------------------------
+
+fn validate_turn_id(turn_id: &str) -> anyhow::Result<()> {
+    if turn_id.is_empty()
+        || turn_id.len() > 64
+        || !turn_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(JournalError::InvalidTurnId(turn_id.to_string()).into());
+    }
+    Ok(())
+}
+
+/// Journalled turn ids in `ws`, oldest first.
+fn journal_ids(ws: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(ws.join(JOURNAL_DIR)) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .filter(|id| validate_turn_id(id).is_ok())
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Load one turn's journal.
+pub fn load(workspace: &Path, turn_id: &str) -> anyhow::Result<TurnJournal> {
+    validate_turn_id(turn_id)?;
+    read_journal(&canonical(workspace).join(JOURNAL_DIR).join(turn_id))
+        .ok_or_else(|| JournalError::NotFound(turn_id.to_string()).into())
+}
+
+/// All journals in `workspace`, oldest first.
+pub fn list(workspace: &Path) -> Vec<TurnJournal> {
+    let ws = canonical(workspace);
+    journal_ids(&ws)
+        .iter()
+        .filter_map(|id| read_journal(&ws.join(JOURNAL_DIR).join(id)))
+        .collect()
+}
+
+/// Paths changed by `turn_id`, for its receipt.
+pub fn changed_files(workspace: &Path, turn_id: &str) -> Vec<String> {
+    load(workspace, turn_id)
+        .map(|j| j.changed_files())
+        .unwrap_or_default()
+}
+
+/// Undo the most recent turn that changed files and has not been undone.
+pub fn undo_last(workspace: &Path) -> anyhow::Result<RevertReport> {
+    let last = list(workspace)
+        .into_iter()
+        .rev()
+        .find(|j| j.reverted_at.is_none() && !j.entries.is_empty())
+        .ok_or(JournalError::NothingToUndo)?;
+    revert_to(workspace, &last.turn_id)
+}
+
+/// Restore the workspace as it was before `turn_id`: undo that turn and
+/// every later one that has not been undone yet, newest first.
+pub fn revert_to(workspace: &Path, turn_id: &str) -> anyhow::Result<RevertReport> {
+    let target = load(workspace, turn_id)?;
+    if target.reverted_at.is_some() {
+        return Err(JournalError::AlreadyUndone(turn_id.to_string()).into());
+    }
+    if active_turn(workspace).is_some() {
+        return Err(JournalError::TurnRunning.into());
+    }
+    let ws = canonical(workspace);
+    let _lock = LOCK.lock().expect("journal lock poisoned");
+    let mut report = RevertReport::default();
+    let mut ids = journal_ids(&ws);
+    ids.retain(|id| id.as_str() >= turn_id);
+    for id in ids.iter().rev() {
+        let dir = ws.join(JOURNAL_DIR).join(id);
+        let Some(mut journal) = read_journal(&dir) else {
+            continue;
+        };
+        if journal.reverted_at.is_some() {
+            continue;
+        }
+        for entry in journal.entries.iter().rev() {
+            revert_entry(&ws, &dir, entry, &mut report);
+        }
+        journal.reverted_at = Some(crate::agent::types::epoch_millis());
+        write_journal(&dir, &journal)?;
+        report.turns.push(id.clone());
+    }
+    // A path changed by several turns is listed once, for its final state.
+    let exists = |p: &String| ws.join(p.trim_end_matches('/')).symlink_metadata().is_ok();
+    let restored: BTreeSet<String> = report.restored.drain(..).collect();
+    let removed: BTreeSet<String> = report.removed.drain(..).collect();
+    report.restored = restored.into_iter().filter(|p| exists(p)).collect();
+    report.removed = removed.into_iter().filter(|p| !exists(p)).collect();
+    Ok(report)
+}
+
+fn revert_entry(ws: &Path, dir: &Path, entry: &Entry, report: &mut RevertReport) {
+    let path = ws.join(&entry.path);
+    let current = std::fs::symlink_metadata(&path).ok();
+    let result: std::io::Result<()> = if entry.is_lost() {
+        report.skipped.push(format!(
+            "{} (too large to snapshot before `{}` changed it)",
+            entry.path, entry.source
+        ));
+        return;
+    } else if entry.dir && entry.existed {
+        std::fs::create_dir_all(&path)
+    } else if entry.dir {
+        match current {
+            Some(meta) if meta.is_dir() => match std::fs::remove_dir(&path) {
+                Ok(()) => {
+                    report.removed.push(format!("{}/", entry.path));
+                    return;
+                }
+                // Still holds files the journal doesn't know about.
+                Err(_) => return,
+            },
+            _ => return,
+        }
+    } else if !entry.existed {
+        match current {
+            None => return,
+            Some(meta) if meta.is_dir() => {
+                report
+                    .skipped
+                    .push(format!("{} (is now a directory)", entry.path));
+                return;
+            }
+            Some(_) if escapes(ws, &path) => {
+                report
+                    .skipped
+                    .push(format!("{} (now leads outside the workspace)", entry.path));
+                return;
+            }
+            Some(_) => {
+                std::fs::remove_file(&path).map(|()| report.removed.push(entry.path.clone()))
+            }
+        }
+    } else {
+        let blob = dir
+            .join("files")
+            .join(entry.blob.as_deref().unwrap_or_default());
+        if current.as_ref().is_some_and(|m| m.is_dir()) {
+            report
+                .skipped
+                .push(format!("{} (is now a directory)", entry.path));
+            return;
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if escapes(ws, &path) {
+            report
+                .skipped
+                .push(format!("{} (now leads outside the workspace)", entry.path));
+            return;
+        }
+        // Copying onto a symlink would write through it to its target, so
+        // put the file back in place of the link instead.
+        if current.is_some_and(|m| m.file_type().is_symlink()) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                report.skipped.push(format!("{} ({e})", entry.path));
+                return;
+            }
+        }
+        std::fs::copy(&blob, &path).map(|_| report.restored.push(entry.path.clone()))
+    };
+    if let Err(e) = result {
+        report.skipped.push(format!("{} ({e})", entry.path));
+    }
+}
+
+/// Whether the directory holding `path` resolves outside `ws`, e.g.
+/// because a turn replaced one of its parents with a symlink.
+fn escapes(ws: &Path, path: &Path) -> bool {
+    path.parent()
+        .and_then(|p| p.canonicalize().ok())
+        .is_none_or(|p| !p.starts_with(ws))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(ws: &Path, rel: &str) -> Option<String> {
+        std::fs::read_to_string(ws.join(rel)).ok()
+    }
+
+    #[test]
+    fn undo_restores_edits_and_removes_created_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        std::fs::write(ws.join("notes.txt"), "original").unwrap();
+
+        {
+            let _guard = begin(ws, "0000000000000001");
+            record_now(ws, &ws.join("notes.txt"), "edit_file");
+            std::fs::write(ws.join("notes.txt"), "edited").unwrap();
+            record_now(ws, &ws.join("notes.txt"), "edit_file");
+            std::fs::write(ws.join("notes.txt"), "edited twice").unwrap();
+            record_now(ws, &ws.join("src/deep/new.rs"), "write_file");
+            std::fs::create_dir_all(ws.join("src/deep")).unwrap();
+            std::fs::write(ws.join("src/deep/new.rs"), "fn main() {}").unwrap();
+        }
+        record_now(ws, &ws.join("notes.txt"), "edit_file");
+        assert_eq!(
+            changed_files(ws, "0000000000000001"),
+            ["notes.txt", "src/deep/new.rs"]
+        );
+
+        let report = undo_last(ws).unwrap();
+        assert_eq!(report.turns, ["0000000000000001"]);
+        assert_eq!(report.restored, ["notes.txt"]);
+        assert_eq!(report.removed, ["src/", "src/deep/", "src/deep/new.rs"]);
+        assert!(report.skipped.is_empty());
+        assert_eq!(read(ws, "notes.txt").as_deref(), Some("original"));
+        assert!(!ws.join("src").exists());
+
+        assert!(undo_last(ws).unwrap_err().to_string().contains("nothing"));
+        assert!(revert_to(ws, "0000000000000001").is_err());
+    }
+
+    #[test]
+    fn reverting_a_turn_also_reverts_later_turns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        std::fs::write(ws.join("a.txt"), "v0").unwrap();
+        for (turn, text) in [("0000000000000001", "v1"), ("0000000000000002", "v2")] {
+            let _guard = begin(ws, turn);
+            record_now(ws, &ws.join("a.txt"), "write_file");
+            std::fs::write(ws.join("a.txt"), text).unwrap();
+        }
+        {
+            let _guard = begin(ws, "0000000000000003");
+            record_now(ws, &ws.join("b.txt"), "write_file");
+            std::fs::write(ws.join("b.txt"), "new").unwrap();
+        }
+
+        let report = revert_to(ws, "0000000000000002").unwrap();
+        assert_eq!(report.turns, ["0000000000000003", "0000000000000002"]);
+        assert_eq!(read(ws, "a.txt").as_deref(), Some("v1"));
+        assert!(!ws.join("b.txt").exists());
+
+        let report = revert_to(ws, "0000000000000001").unwrap();
+        assert_eq!(report.turns, ["0000000000000001"]);
+        assert_eq!(read(ws, "a.txt").as_deref(), Some("v0"));
+        assert!(load(ws, "../etc").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn undo_does_not_write_through_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let ws = &tmp.path().canonicalize().unwrap();
+        std::fs::write(outside.path().join("target.txt"), "untouched").unwrap();
+        std::fs::write(ws.join("link.txt"), "original").unwrap();
+        std::fs::create_dir(ws.join("sub")).unwrap();
+        std::fs::write(ws.join("sub/inner.txt"), "inner").unwrap();
+
+        {
+            let _guard = begin(ws, "0000000000000001");
+            record_now(ws, &ws.join("link.txt"), "write_file");
+            std::fs::remove_file(ws.join("link.txt")).unwrap();
+            std::os::unix::fs::symlink(outside.path().join("target.txt"), ws.join("link.txt"))
+                .unwrap();
+            record_now(ws, &ws.join("sub/inner.txt"), "write_file");
+            std::fs::remove_dir_all(ws.join("sub")).unwrap();
+            std::os::unix::fs::symlink(outside.path(), ws.join("sub")).unwrap();
+        }
+
+        let report = undo_last(ws).unwrap();
+        assert_eq!(report.restored, ["link.txt"]);
+        assert_eq!(report.skipped.len(), 1, "{:?}", report.skipped);
+        assert!(report.skipped[0].starts_with("sub/inner.txt"));
+        assert!(!std::fs::symlink_metadata(ws.join("link.txt"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(read(ws, "link.txt").as_deref(), Some("original"));
+        assert_eq!(
+            std::fs::read_to_string(outside.path().join("target.txt")).unwrap(),
+            "untouched"
+        );
+        assert!(!outside.path().join("inner.txt").exists());
+    }
+
+    #[test]
+    fn snapshots_journal_shell_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        std::fs::write(ws.join("keep.txt"), "same").unwrap();
+        std::fs::write(ws.join("edit.txt"), "before").unwrap();
+        std::fs::write(ws.join("gone.txt"), "doomed").unwrap();
+        assert!(Snapshot::take(ws).is_none(), "no turn running");
+
+        {
+            let _guard = begin(ws, "0000000000000001");
+            let snapshot = Snapshot::take(ws).unwrap();
+            std::fs::write(ws.join("edit.txt"), "after, and longer").unwrap();
+            std::fs::remove_file(ws.join("gone.txt")).unwrap();
+            std::fs::create_dir(ws.join("out")).unwrap();
+            std::fs::write(ws.join("out/made.txt"), "x").unwrap();
+            snapshot.record_changes("exec_shell");
+        }
+        let mut changed = changed_files(ws, "0000000000000001");
+        changed.sort();
+        assert_eq!(changed, ["edit.txt", "gone.txt", "out/made.txt"]);
+
+        undo_last(ws).unwrap();
+        assert_eq!(read(ws, "edit.txt").as_deref(), Some("before"));
+        assert_eq!(read(ws, "gone.txt").as_deref(), Some("doomed"));
+        assert_eq!(read(ws, "keep.txt").as_deref(), Some("same"));
+        assert!(!ws.join("out").exists());
+    }
+}
@@ -10,8 +10,11 @@ pub mod comm;
 pub mod config;
 pub mod context;
 pub mod discord;
+pub mod documents;
 pub mod gateway;
+pub mod journal;
 pub mod logs;
+pub mod mcp;
 pub mod memory;
 pub mod models;
 pub mod scheduler;
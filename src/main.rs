@@ -4,6 +4,7 @@ use mini_claw::cli;
 use mini_claw::comm;
 use mini_claw::config;
 use mini_claw::discord;
+use mini_claw::mcp;
 use mini_claw::models;
 use mini_claw::scheduler;
 use mini_claw::tools;
@@ -76,6 +77,16 @@ enum Command {
         #[arg(long)]
         list: bool,
     },
+    /// Install, export and roll back skill packages
+    Skill {
+        #[command(subcommand)]
+        action: SkillAction,
+    },
+    /// Model Context Protocol integration
+    Mcp {
+        #[command(subcommand)]
+        action: McpAction,
+    },
     /// Restore a backup snapshot into PINCHY_HOME
     Restore {
         /// Path to the backup .tar.gz file
@@ -127,6 +138,51 @@ enum ServiceAction {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum SkillAction {
+    /// Install or upgrade a skill from a directory, git checkout or .tar.gz
+    Install {
+        /// Skill directory or .tar.gz package
+        source: PathBuf,
+        /// Agent to install the skill for
+        #[arg(long)]
+        agent: String,
+    },
+    /// Package an installed skill as <name>-<version>.tar.gz
+    Export {
+        /// Skill name
+        name: String,
+        /// Agent the skill belongs to
+        #[arg(long)]
+        agent: String,
+        /// Output directory (default: current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List saved earlier versions of a skill
+    Versions {
+        /// Skill name
+        name: String,
+        /// Agent the skill belongs to
+        #[arg(long)]
+        agent: String,
+    },
+    /// Restore the previous version of a skill
+    Rollback {
+        /// Skill name
+        name: String,
+        /// Agent the skill belongs to
+        #[arg(long)]
+        agent: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum McpAction {
+    /// Serve allowlisted tools, agents and memories over stdio
+    Serve,
+}
+
 #[derive(Subcommand, Debug)]
 enum CopilotCmd {
     /// Authenticate via GitHub device flow
@@ -215,6 +271,8 @@ fn main() -> anyhow::Result<()> {
 }
 
 async fn async_main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
     // Initialize tracing with layered subscriber (fmt + log broadcast)
     {
         use tracing_subscriber::layer::SubscriberExt;
@@ -223,7 +281,13 @@ async fn async_main() -> anyhow::Result<()> {
         let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
             .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
 
-        let fmt_layer = tracing_subscriber::fmt::layer();
+        // `mcp serve` owns stdout for protocol frames — log to stderr there.
+        let writer = if matches!(cli.command, Some(Command::Mcp { .. })) {
+            tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stderr)
+        } else {
+            tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stdout)
+        };
+        let fmt_layer = tracing_subscriber::fmt::layer().with_writer(writer);
 
         let logs_tx = mini_claw::logs::init_broadcast();
         let broadcast_layer = mini_claw::logs::BroadcastLayer::new(logs_tx);
@@ -235,7 +299,6 @@ async fn async_main() -> anyhow::Result<()> {
             .init();
     }
 
-    let cli = Cli::parse();
     let config_path = cli
         .config
         .unwrap_or_else(|| mini_claw::pinchy_home().join("config.yaml"));
@@ -299,6 +362,9 @@ async fn async_main() -> anyhow::Result<()> {
                         Ok(())
                     }
                 },
+                Command::Mcp { action } => match action {
+                    McpAction::Serve => mcp::server::serve(&config_path).await,
+                },
                 Command::Status => cli::check_status().await,
                 Command::Update { no_pull, restart } => cli::self_update(no_pull, restart).await,
                 Command::Onboard => cli::app_onboard(&config_path).await,
@@ -330,6 +396,22 @@ async fn async_main() -> anyhow::Result<()> {
                         cli::backup::create(&home, output.as_deref()).await
                     }
                 }
+                Command::Skill { action } => match action {
+                    SkillAction::Install { source, agent } => {
+                        cli::skill::install(&config_path, &agent, &source).await
+                    }
+                    SkillAction::Export {
+                        name,
+                        agent,
+                        output,
+                    } => cli::skill::export(&config_path, &agent, &name, output.as_deref()).await,
+                    SkillAction::Versions { name, agent } => {
+                        cli::skill::versions(&config_path, &agent, &name).await
+                    }
+                    SkillAction::Rollback { name, agent } => {
+                        cli::skill::rollback(&config_path, &agent, &name).await
+                    }
+                },
                 Command::Restore {
                     file,
                     yes,
@@ -404,6 +486,12 @@ async fn async_main() -> anyhow::Result<()> {
     agent::init(&cfg, bus.clone(), cancel.clone());
     models::init();
     tools::init();
+    mcp::init(&cfg);
+
+    let reattached = tools::builtins::exec_shell::restore_background_processes();
+    if reattached > 0 {
+        info!(count = reattached, "re-attached background processes");
+    }
 
     // --- Housekeeping janitor ---
     // Run an immediate cleanup pass at startup, then spawn a background
@@ -0,0 +1,656 @@
+//! MCP client: mounts tools from stdio MCP servers as agent tools.
+//!
+//! Each configured server is launched as a child process.  After the
+//! `initialize` handshake its `tools/list` entries are registered as
+//! [`ToolMeta`](crate::tools::ToolMeta) under `mcp__<server>__<tool>`, with
+//! a handler that proxies to `tools/call`.  When a server exits it is
+//! restarted with exponential backoff and its tools are re-registered.
+
+use std::collections::{HashMap, HashSet};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+use crate::config::{Config, McpServerConfig};
+use crate::tools::{self, ToolMeta};
+
+/// Default per-request timeout.
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// Consecutive failed restarts before a server is left down until its
+/// next tool call.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// Upper bound on the restart backoff delay.
+const MAX_BACKOFF_SECS: u64 = 60;
+/// OpenAI-style function names are limited to 64 characters.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+type PendingReply = oneshot::Sender<Result<Value, String>>;
+type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// A live JSON-RPC session with one server process.
+struct Connection {
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: Mutex<HashMap<u64, PendingReply>>,
+    next_id: AtomicU64,
+    alive: AtomicBool,
+    _child: tokio::sync::Mutex<Child>,
+}
+
+/// A configured server plus its current connection and registered tools.
+struct Server {
+    config: McpServerConfig,
+    /// Agents allowed to see this server's tools (`None` = every agent).
+    owners: Option<HashSet<String>>,
+    conn: tokio::sync::Mutex<Option<Arc<Connection>>>,
+    tools: Mutex<Vec<String>>,
+    restarting: AtomicBool,
+}
+
+/// Configured servers keyed by name.
+static SERVERS: LazyLock<Mutex<HashMap<String, Arc<Server>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Registered tool name → owning server name.
+static TOOL_INDEX: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Launch every MCP server from `cfg` (global and per-agent) in the
+/// background and register their tools as they come up.
+pub fn init(cfg: &Config) {
+    let mut servers: HashMap<String, Server> = HashMap::new();
+
+    for srv in &cfg.mcp_servers {
+        servers.insert(srv.name.clone(), Server::new(srv.clone(), None));
+    }
+    for agent in &cfg.agents {
+        for srv in &agent.mcp_servers {
+            match servers.get_mut(&srv.name) {
+                // Several agents declaring the same server share one process.
+                Some(existing) if existing.config == *srv => {
+                    if let Some(owners) = existing.owners.as_mut() {
+                        owners.insert(agent.id.clone());
+                    }
+                }
+                Some(_) => warn!(
+                    server = %srv.name,
+                    agent = %agent.id,
+                    "MCP server name already used with a different command — skipped"
+                ),
+                None => {
+                    let owners = HashSet::from([agent.id.clone()]);
+                    servers.insert(srv.name.clone(), Server::new(srv.clone(), Some(owners)));
+                }
+            }
+        }
+    }
+
+    if servers.is_empty() {
+        return;
+    }
+
+    let mut registry = SERVERS.lock().expect("mcp server registry poisoned");
+    for (name, server) in servers {
+        let server = Arc::new(server);
+        registry.insert(name, server.clone());
+        tokio::spawn(async move {
+            if let Err(e) = start(&server).await {
+                warn!(server = %server.config.name, error = %e, "MCP server failed to start");
+                schedule_restart(server);
+            }
+        });
+    }
+    info!(count = registry.len(), "MCP servers launching");
+}
+
+/// Whether `tool` may be offered to `agent_id`.  Non-MCP tools and tools
+/// from global servers are visible to every agent.
+pub fn tool_visible_to(tool: &str, agent_id: &str) -> bool {
+    let server_name = match TOOL_INDEX.lock() {
+        Ok(index) => index.get(tool).cloned(),
+        Err(_) => None,
+    };
+    let Some(server_name) = server_name else {
+        return true;
+    };
+    match server(&server_name) {
+        Some(s) => s
+            .owners
+            .as_ref()
+            .is_none_or(|owners| owners.contains(agent_id)),
+        None => true,
+    }
+}
+
+/// Server names whose tools are registered as deferred.
+pub(crate) fn deferred_server_names() -> Vec<String> {
+    SERVERS
+        .lock()
+        .map(|reg| {
+            reg.values()
+                .filter(|s| s.config.deferred)
+                .map(|s| s.config.name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Server {
+    fn new(config: McpServerConfig, owners: Option<HashSet<String>>) -> Self {
+        Self {
+            config,
+            owners,
+            conn: tokio::sync::Mutex::new(None),
+            tools: Mutex::new(Vec::new()),
+            restarting: AtomicBool::new(false),
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+}
+
+fn server(name: &str) -> Option<Arc<Server>> {
+    SERVERS.lock().ok().and_then(|reg| reg.get(name).cloned())
+}
+
+/// Connect to the server and (re-)register its tools.
+///
+/// Boxed because restarts and tool handlers call back into it recursively.
+fn start(server: &Arc<Server>) -> BoxFuture<'_, anyhow::Result<Arc<Connection>>> {
+    Box::pin(async move {
+        let conn = connect(server).await?;
+        *server.conn.lock().await = Some(conn.clone());
+        register_tools(server, &conn).await?;
+        Ok(conn)
+    })
+}
+
+/// Restart a dead server in the background with exponential backoff.
+fn schedule_restart(server: Arc<Server>) {
+    if server.restarting.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async move {
+        for attempt in 0..MAX_RESTART_ATTEMPTS {
+            let delay = (1u64 << attempt).min(MAX_BACKOFF_SECS);
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            match start(&server).await {
+                Ok(_) => {
+                    info!(server = %server.config.name, attempt, "MCP server restarted");
+                    server.restarting.store(false, Ordering::SeqCst);
+                    return;
+                }
+                Err(e) => {
+                    warn!(server = %server.config.name, attempt, error = %e, "MCP server restart failed")
+                }
+            }
+        }
+        warn!(
+            server = %server.config.name,
+            "MCP server still down after {MAX_RESTART_ATTEMPTS} restarts — will retry on next call"
+        );
+        server.restarting.store(false, Ordering::SeqCst);
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Connection
+// ---------------------------------------------------------------------------
+
+async fn connect(server: &Arc<Server>) -> anyhow::Result<Arc<Connection>> {
+    let cfg = &server.config;
+    let mut cmd = Command::new(&cfg.command);
+    cmd.args(&cfg.args)
+        .envs(&cfg.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(ref cwd) = cfg.cwd {
+        cmd.current_dir(cwd);
+    }
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("spawn MCP server '{}' ({})", cfg.name, cfg.command))?;
+
+    let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("MCP server stdout unavailable")?;
+    if let Some(stderr) = child.stderr.take() {
+        let name = cfg.name.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!(server = %name, "mcp stderr: {line}");
+            }
+        });
+    }
+
+    let conn = Arc::new(Connection {
+        stdin: tokio::sync::Mutex::new(stdin),
+        pending: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+        alive: AtomicBool::new(true),
+        _child: tokio::sync::Mutex::new(child),
+    });
+
+    tokio::spawn(read_loop(server.clone(), conn.clone(), stdout));
+
+    let init = conn
+        .request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": super::PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "pinchy", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            server.timeout(),
+        )
+        .await
+        .with_context(|| format!("initialize MCP server '{}'", cfg.name))?;
+    debug!(server = %cfg.name, info = %init["serverInfo"], "MCP server initialised");
+    conn.notify("notifications/initialized", serde_json::json!({}))
+        .await?;
+
+    Ok(conn)
+}
+
+/// Route responses to waiting requests and answer server-initiated pings.
+/// On EOF the connection is marked dead and a restart is scheduled.
+async fn read_loop(
+    server: Arc<Server>,
+    conn: Arc<Connection>,
+    stdout: tokio::process::ChildStdout,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let msg: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => {
+                debug!(server = %server.config.name, "ignoring non-JSON line from MCP server");
+                continue;
+            }
+        };
+
+        match (msg.get("id"), msg.get("method").and_then(|m| m.as_str())) {
+            // Response to one of our requests.
+            (Some(id), None) => {
+                let Some(id) = id.as_u64() else { continue };
+                let waiter = conn.pending.lock().ok().and_then(|mut p| p.remove(&id));
+                if let Some(tx) = waiter {
+                    let reply = match msg.get("error") {
+                        Some(err) => Err(err["message"]
+                            .as_str()
+                            .unwrap_or("unknown error")
+                            .to_string()),
+                        None => Ok(msg.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = tx.send(reply);
+                }
+            }
+            // Server-initiated request.
+            (Some(id), Some(method)) => {
+                let reply = if method == "ping" {
+                    super::rpc_result(id.clone(), serde_json::json!({}))
+                } else {
+                    super::rpc_error(id.clone(), -32601, "method not supported by client")
+                };
+                let _ = conn.send(&reply).await;
+            }
+            // Notification.
+            (None, Some("notifications/tools/list_changed")) => {
+                let (server, conn) = (server.clone(), conn.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = register_tools(&server, &conn).await {
+                        warn!(server = %server.config.name, error = %e, "MCP tool refresh failed");
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    conn.alive.store(false, Ordering::SeqCst);
+    if let Ok(mut pending) = conn.pending.lock() {
+        for (_, tx) in pending.drain() {
+            let _ = tx.send(Err("MCP server exited".into()));
+        }
+    }
+    warn!(server = %server.config.name, "MCP server exited");
+    schedule_restart(server);
+}
+
+impl Connection {
+    async fn send(&self, msg: &Value) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(msg)?;
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> anyhow::Result<()> {
+        self.send(&super::rpc_request(None, method, params)).await
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> anyhow::Result<Value> {
+        if !self.alive.load(Ordering::SeqCst) {
+            anyhow::bail!("MCP server is not running");
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id, tx);
+        }
+        if let Err(e) = self
+            .send(&super::rpc_request(Some(id), method, params))
+            .await
+        {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.remove(&id);
+            }
+            return Err(e).context("write to MCP server");
+        }
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Ok(v))) => Ok(v),
+            Ok(Ok(Err(msg))) => anyhow::bail!("{method} failed: {msg}"),
+            Ok(Err(_)) => anyhow::bail!("{method}: MCP connection closed"),
+            Err(_) => {
+                if let Ok(mut pending) = self.pending.lock() {
+                    pending.remove(&id);
+                }
+                anyhow::bail!("{method} timed out after {}s", timeout.as_secs())
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool registration + proxying
+// ---------------------------------------------------------------------------
+
+/// Namespace an MCP tool as `mcp__<server>__<tool>`, restricted to the
+/// characters and length that function-calling APIs accept.
+fn namespaced_tool_name(server: &str, tool: &str) -> String {
+    let sanitize = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let mut name = format!("mcp__{}__{}", sanitize(server), sanitize(tool));
+    name.truncate(MAX_TOOL_NAME_LEN);
+    name
+}
+
+async fn register_tools(server: &Arc<Server>, conn: &Arc<Connection>) -> anyhow::Result<()> {
+    let mut remote_tools = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = match cursor {
+            Some(ref c) => serde_json::json!({ "cursor": c }),
+            None => serde_json::json!({}),
+        };
+        let page = conn.request("tools/list", params, server.timeout()).await?;
+        if let Some(list) = page["tools"].as_array() {
+            remote_tools.extend(list.iter().cloned());
+        }
+        cursor = page["nextCursor"].as_str().map(String::from);
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    // Drop whatever this server registered before (restart / list_changed).
+    let previous = std::mem::take(&mut *server.tools.lock().expect("mcp tools poisoned"));
+    for name in &previous {
+        tools::unregister_tool(name);
+    }
+    if let Ok(mut index) = TOOL_INDEX.lock() {
+        index.retain(|_, s| *s != server.config.name);
+    }
+
+    let existing: HashSet<String> = tools::list_tools().into_iter().map(|m| m.name).collect();
+    let mut registered = Vec::new();
+    for tool in remote_tools {
+        let Some(remote_name) = tool["name"].as_str() else {
+            continue;
+        };
+        let name = namespaced_tool_name(&server.config.name, remote_name);
+        if tools::builtin_skill_names().contains(&name.as_str()) || existing.contains(&name) {
+            warn!(tool = %name, "MCP tool name collides with an existing tool — skipped");
+            continue;
+        }
+
+        let mut schema = tool
+            .get("inputSchema")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({ "type": "object", "properties": {} }));
+        if schema.get("properties").is_none() {
+            schema["properties"] = serde_json::json!({});
+        }
+        let meta = ToolMeta {
+            name: name.clone(),
+            description: format!(
+                "[MCP {}] {}",
+                server.config.name,
+                tool["description"].as_str().unwrap_or(remote_name)
+            ),
+            args_schema: schema,
+            // Remote side effects are unknown unless the server marks
+            // the tool read-only.
+            exclusive: !tool["annotations"]["readOnlyHint"]
+                .as_bool()
+                .unwrap_or(false),
+            // Each request already carries the server's own timeout.
+            timeout_secs: None,
+        };
+        if server.config.deferred {
+            tools::register_tool_deferred(meta);
+        } else {
+            tools::register_tool(meta);
+        }
+
+        let server_name = server.config.name.clone();
+        let remote_name = remote_name.to_string();
+        tools::register_handler(
+            &name,
+            Arc::new(move |args, _ws| {
+                let server_name = server_name.clone();
+                let remote_name = remote_name.clone();
+                Box::pin(async move { call_tool(&server_name, &remote_name, args).await })
+            }),
+        );
+        registered.push(name);
+    }
+
+    if let Ok(mut index) = TOOL_INDEX.lock() {
+        for name in &registered {
+            index.insert(name.clone(), server.config.name.clone());
+        }
+    }
+    info!(server = %server.config.name, tools = registered.len(), "MCP tools registered");
+    *server.tools.lock().expect("mcp tools poisoned") = registered;
+    Ok(())
+}
+
+/// Proxy a tool call to the server, reconnecting once if it is down.
+async fn call_tool(server_name: &str, remote_name: &str, args: Value) -> anyhow::Result<Value> {
+    let server = server(server_name)
+        .ok_or_else(|| anyhow::anyhow!("MCP server '{server_name}' is not configured"))?;
+
+    let live = server
+        .conn
+        .lock()
+        .await
+        .clone()
+        .filter(|c| c.alive.load(Ordering::SeqCst));
+    let conn = match live {
+        Some(c) => c,
+        None if !server.restarting.load(Ordering::SeqCst) => start(&server)
+            .await
+            .with_context(|| format!("MCP server '{server_name}' is down and failed to restart"))?,
+        None => anyhow::bail!("MCP server '{server_name}' is restarting — try again shortly"),
+    };
+
+    let args = if args.is_object() {
+        args
+    } else {
+        serde_json::json!({})
+    };
+    let result = conn
+        .request(
+            "tools/call",
+            serde_json::json!({ "name": remote_name, "arguments": args }),
+            server.timeout(),
+        )
+        .await?;
+
+    let text = content_text(&result);
+    if result["isError"].as_bool() == Some(true) {
+        anyhow::bail!("{text}");
+    }
+    let mut out = serde_json::json!({ "content": text });
+    if let Some(structured) = result.get("structuredContent") {
+        out["structured"] = structured.clone();
+    }
+    Ok(out)
+}
+
+/// Flatten an MCP `content` array into text.
+fn content_text(result: &Value) -> String {
+    let Some(items) = result["content"].as_array() else {
+        return String::new();
+    };
+    items
+        .iter()
+        .map(|item| match item["type"].as_str() {
+            Some("text") => item["text"].as_str().unwrap_or_default().to_string(),
+            Some("resource") => {
+                let res = &item["resource"];
+                res["text"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("[resource {}]", res["uri"].as_str().unwrap_or("?")))
+            }
+            Some(other) => format!(
+                "[{other} content{}]",
+                item["mimeType"]
+                    .as_str()
+                    .map(|m| format!(" ({m})"))
+                    .unwrap_or_default()
+            ),
+            None => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_names_are_namespaced_and_sanitized() {
+        assert_eq!(
+            namespaced_tool_name("github", "create_issue"),
+            "mcp__github__create_issue"
+        );
+        assert_eq!(
+            namespaced_tool_name("fs", "read.file"),
+            "mcp__fs__read_file"
+        );
+        assert!(namespaced_tool_name("s", &"x".repeat(100)).len() <= MAX_TOOL_NAME_LEN);
+    }
+
+    /// Minimal stdio MCP server in POSIX sh: one `echo` tool.
+    const FAKE_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"fake"}}}\n' "$id" ;;
+    *'"tools/list"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo","description":"Echo back","inputSchema":{"type":"object","properties":{"text":{"type":"string"}}}}]}}\n' "$id" ;;
+    *'"tools/call"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"pong"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    #[tokio::test]
+    async fn mounts_and_proxies_stdio_server_tools() {
+        let config = McpServerConfig {
+            name: "fake".into(),
+            command: "sh".into(),
+            args: vec!["-c".into(), FAKE_SERVER.into()],
+            env: Default::default(),
+            cwd: None,
+            deferred: false,
+            timeout_secs: Some(5),
+        };
+        let owners = HashSet::from(["owner".to_string()]);
+        let server = Arc::new(Server::new(config, Some(owners)));
+        SERVERS
+            .lock()
+            .unwrap()
+            .insert("fake".into(), server.clone());
+
+        start(&server).await.expect("fake server should start");
+
+        let meta = tools::list_tools()
+            .into_iter()
+            .find(|m| m.name == "mcp__fake__echo")
+            .expect("echo tool registered");
+        assert!(meta.description.contains("Echo back"));
+        assert!(tool_visible_to("mcp__fake__echo", "owner"));
+        assert!(!tool_visible_to("mcp__fake__echo", "someone-else"));
+
+        let out = tools::call_skill(
+            "mcp__fake__echo",
+            serde_json::json!({ "text": "ping" }),
+            std::path::Path::new("."),
+        )
+        .await
+        .unwrap();
+        assert_eq!(out["content"], "pong");
+    }
+
+    #[test]
+    fn content_text_flattens_items() {
+        let result = serde_json::json!({
+            "content": [
+                { "type": "text", "text": "hello" },
+                { "type": "image", "mimeType": "image/png", "data": "..." },
+                { "type": "resource", "resource": { "uri": "file:///a", "text": "body" } },
+            ]
+        });
+        assert_eq!(
+            content_text(&result),
+            "hello\n[image content (image/png)]\nbody"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Model Context Protocol (MCP) support.
+//!
+//! - [`client`] – launches stdio MCP servers from config and mounts their
+//!   tools in the unified tool registry.
+//! - [`server`] – `pinchy mcp serve`: exposes allowlisted tools, agents and
+//!   memories to MCP clients.
+//!
+//! MCP speaks JSON-RPC 2.0 over newline-delimited JSON on stdin/stdout.
+
+pub mod client;
+pub mod server;
+
+pub use client::{init, tool_visible_to};
+
+/// Protocol revision we advertise during `initialize`.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Build a JSON-RPC request (or notification when `id` is `None`).
+pub(crate) fn rpc_request(
+    id: Option<u64>,
+    method: &str,
+    params: serde_json::Value,
+) -> serde_json::Value {
+    let mut msg = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    });
+    if let Some(id) = id {
+        msg["id"] = serde_json::json!(id);
+    }
+    msg
+}
+
+/// Build a JSON-RPC success response.
+pub(crate) fn rpc_result(id: serde_json::Value, result: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Build a JSON-RPC error response.
+pub(crate) fn rpc_error(id: serde_json::Value, code: i64, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
@@ -0,0 +1,511 @@
+//! `pinchy mcp serve`: expose pinchy to MCP clients over stdio.
+//!
+//! Serves three things, all gated by the `mcp_serve` allowlist in
+//! `config.yaml`:
+//!
+//! - selected tools from the unified tool registry,
+//! - a `chat_with_agent` tool that runs a turn through the normal
+//!   dispatch path (message bus → agent dispatcher → connector reply),
+//! - agent memories as read-only resources (`pinchy://agents/<id>/memories/<key>`).
+//!
+//! Stdout carries protocol frames only; logs go to stderr.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::comm::{ChannelConnector, IncomingMessage};
+use crate::config::{Config, McpServeConfig};
+
+/// Default wait for a `chat_with_agent` reply.
+const DEFAULT_CHAT_TIMEOUT_SECS: u64 = 300;
+/// Channel prefix used for turns started by MCP clients.
+const CHANNEL_PREFIX: &str = "mcp:";
+/// Memories listed per agent in `resources/list`.
+const MAX_MEMORY_RESOURCES: usize = 500;
+
+/// Waiters for agent replies, keyed by the `mcp:<id>` channel.
+static REPLY_WAITERS: LazyLock<Mutex<HashMap<String, oneshot::Sender<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Connector that hands agent replies for `mcp:` channels back to the
+/// waiting `chat_with_agent` call.
+struct McpConnector;
+
+#[async_trait]
+impl ChannelConnector for McpConnector {
+    fn name(&self) -> &str {
+        "mcp"
+    }
+
+    fn matches(&self, channel: &str) -> bool {
+        channel.starts_with(CHANNEL_PREFIX)
+    }
+
+    async fn send(&self, channel: &str, text: &str) -> anyhow::Result<()> {
+        let waiter = REPLY_WAITERS
+            .lock()
+            .ok()
+            .and_then(|mut w| w.remove(channel));
+        match waiter {
+            Some(tx) => {
+                let _ = tx.send(text.to_string());
+                Ok(())
+            }
+            None => anyhow::bail!("no MCP caller waiting on {channel}"),
+        }
+    }
+}
+
+/// Resolved allowlist plus the agents it admits.
+struct ServeState {
+    allow: McpServeConfig,
+    /// Allowed agents: id → runtime workspace.
+    agents: Vec<(String, PathBuf)>,
+    /// Workspace exposed registry tools run in.
+    tool_workspace: PathBuf,
+}
+
+/// Run the stdio MCP server until stdin closes.
+pub async fn serve(config_path: &Path) -> anyhow::Result<()> {
+    let cfg = Config::load(config_path).await?;
+    let allow = cfg.mcp_serve.clone().unwrap_or_default();
+    if allow.tools.is_empty() && allow.agents.is_empty() {
+        warn!("mcp_serve allowlist is empty — no tools or resources will be exposed");
+    }
+
+    let agents: Vec<(String, PathBuf)> = cfg
+        .agents
+        .iter()
+        .filter(|a| {
+            allow
+                .agents
+                .iter()
+                .any(|p| crate::utils::glob_match(p, &a.id))
+        })
+        .map(|a| (a.id.clone(), PathBuf::from(&a.root).join("workspace")))
+        .collect();
+    let tool_workspace = allow
+        .workspace_agent
+        .as_deref()
+        .and_then(|id| cfg.agents.iter().find(|a| a.id == id))
+        .map(|a| PathBuf::from(&a.root).join("workspace"))
+        .or_else(|| agents.first().map(|(_, ws)| ws.clone()))
+        .unwrap_or_else(|| crate::utils::agent_workspace("default"));
+
+    let db =
+        crate::store::PinchyDb::open(&crate::pinchy_home()).context("failed to open pinchy.db")?;
+    crate::store::set_global_db(db);
+
+    let cancel = CancellationToken::new();
+    crate::tools::init();
+    crate::models::init();
+    crate::agent::init(&cfg, crate::comm::sender(), cancel.clone());
+    crate::comm::register_connector(Arc::new(McpConnector)).await;
+
+    let state = Arc::new(ServeState {
+        allow,
+        agents,
+        tool_workspace,
+    });
+    info!(
+        tools = state.allow.tools.len(),
+        agents = state.agents.len(),
+        "MCP server listening on stdio"
+    );
+
+    let stdout = Arc::new(tokio::sync::Mutex::new(tokio::io::stdout()));
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let msg: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(e) => {
+                let reply = super::rpc_error(Value::Null, -32700, &format!("parse error: {e}"));
+                write_frame(&stdout, &reply).await?;
+                continue;
+            }
+        };
+        // Handle each request on its own task so a long agent turn doesn't
+        // block pings or other calls.
+        let (state, stdout) = (state.clone(), stdout.clone());
+        tokio::spawn(async move {
+            if let Some(reply) = handle_message(&state, msg).await {
+                if let Err(e) = write_frame(&stdout, &reply).await {
+                    warn!(error = %e, "failed to write MCP response");
+                }
+            }
+        });
+    }
+
+    info!("MCP client disconnected — shutting down");
+    cancel.cancel();
+    Ok(())
+}
+
+async fn write_frame(
+    stdout: &tokio::sync::Mutex<tokio::io::Stdout>,
+    msg: &Value,
+) -> anyhow::Result<()> {
+    let mut line = serde_json::to_string(msg)?;
+    line.push('\n');
+    let mut out = stdout.lock().await;
+    out.write_all(line.as_bytes()).await?;
+    out.flush().await?;
+    Ok(())
+}
+
+/// Dispatch one JSON-RPC message.  Returns `None` for notifications.
+async fn handle_message(state: &ServeState, msg: Value) -> Option<Value> {
+    let id = msg.get("id").cloned()?;
+    let method = msg["method"].as_str().unwrap_or_default();
+    let params = msg.get("params").cloned().unwrap_or(Value::Null);
+    debug!(method, "MCP request");
+
+    let result = match method {
+        "initialize" => Ok(serde_json::json!({
+            "protocolVersion": super::PROTOCOL_VERSION,
+            "capabilities": { "tools": {}, "resources": {} },
+            "serverInfo": { "name": "pinchy", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(serde_json::json!({})),
+        "tools/list" => Ok(serde_json::json!({ "tools": list_tools(state) })),
+        "tools/call" => call_tool(state, &params).await,
+        "resources/list" => list_resources(state)
+            .await
+            .map(|r| serde_json::json!({ "resources": r })),
+        "resources/read" => read_resource(state, &params).await,
+        other => {
+            return Some(super::rpc_error(
+                id,
+                -32601,
+                &format!("method not found: {other}"),
+            ))
+        }
+    };
+
+    Some(match result {
+        Ok(v) => super::rpc_result(id, v),
+        Err(e) => super::rpc_error(id, -32602, &e.to_string()),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tools
+// ---------------------------------------------------------------------------
+
+fn tool_allowed(state: &ServeState, name: &str) -> bool {
+    state
+        .allow
+        .tools
+        .iter()
+        .any(|p| crate::utils::glob_match(p, name))
+}
+
+fn list_tools(state: &ServeState) -> Vec<Value> {
+    let mut tools: Vec<Value> = crate::tools::list_tools()
+        .into_iter()
+        .filter(|m| m.args_schema.is_object() && tool_allowed(state, &m.name))
+        .map(|m| {
+            serde_json::json!({
+                "name": m.name,
+                "description": m.description,
+                "inputSchema": m.args_schema,
+            })
+        })
+        .collect();
+
+    if !state.agents.is_empty() {
+        let ids: Vec<&str> = state.agents.iter().map(|(id, _)| id.as_str()).collect();
+        tools.push(serde_json::json!({
+            "name": "chat_with_agent",
+            "description": "Send a message to a pinchy agent and wait for its reply. \
+                The agent runs a full turn with its own tools, memory and session history.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "agent": { "type": "string", "enum": ids, "description": "Agent id" },
+                    "message": { "type": "string", "description": "Message to send" },
+                    "session_id": {
+                        "type": "string",
+                        "description": "Optional session to continue instead of the agent's current one"
+                    }
+                },
+                "required": ["agent", "message"]
+            }
+        }));
+    }
+    tools
+}
+
+async fn call_tool(state: &ServeState, params: &Value) -> anyhow::Result<Value> {
+    let name = params["name"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("tools/call requires a 'name'"))?;
+    let args = params
+        .get("arguments")
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    let outcome = if name == "chat_with_agent" && !state.agents.is_empty() {
+        chat_with_agent(state, &args).await
+    } else if tool_allowed(state, name) {
+        crate::tools::call_skill(name, args, &state.tool_workspace)
+            .await
+            .map(|v| match v {
+                Value::String(s) => s,
+                other => serde_json::to_string_pretty(&other).unwrap_or_default(),
+            })
+    } else {
+        anyhow::bail!("tool not exposed: {name}")
+    };
+
+    Ok(match outcome {
+        Ok(text) => serde_json::json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": false,
+        }),
+        Err(e) => serde_json::json!({
+            "content": [{ "type": "text", "text": e.to_string() }],
+            "isError": true,
+        }),
+    })
+}
+
+/// Run a turn on `agent` through the message bus and wait for its reply.
+async fn chat_with_agent(state: &ServeState, args: &Value) -> anyhow::Result<String> {
+    let agent = args["agent"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("chat_with_agent requires an 'agent' string"))?;
+    let message = args["message"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("chat_with_agent requires a 'message' string"))?;
+    if !state.agents.iter().any(|(id, _)| id == agent) {
+        anyhow::bail!("agent '{agent}' is not exposed over MCP");
+    }
+
+    let channel = format!("{CHANNEL_PREFIX}{}", uuid::Uuid::new_v4());
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut waiters) = REPLY_WAITERS.lock() {
+        waiters.insert(channel.clone(), tx);
+    }
+
+    let msg = IncomingMessage {
+        agent_id: Some(agent.to_string()),
+        channel: channel.clone(),
+        author: "mcp".to_string(),
+        author_id: None,
+        content: message.to_string(),
+        timestamp: chrono::Utc::now().timestamp(),
+        session_id: args["session_id"].as_str().map(String::from),
+        images: Vec::new(),
+    };
+    if crate::comm::sender().send(msg).is_err() {
+        REPLY_WAITERS
+            .lock()
+            .ok()
+            .and_then(|mut w| w.remove(&channel));
+        anyhow::bail!("agent dispatcher is not running");
+    }
+
+    let timeout = Duration::from_secs(
+        state
+            .allow
+            .chat_timeout_secs
+            .unwrap_or(DEFAULT_CHAT_TIMEOUT_SECS),
+    );
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(reply)) => Ok(reply),
+        Ok(Err(_)) => anyhow::bail!("agent '{agent}' dropped the request"),
+        Err(_) => {
+            REPLY_WAITERS
+                .lock()
+                .ok()
+                .and_then(|mut w| w.remove(&channel));
+            anyhow::bail!(
+                "agent '{agent}' did not reply within {}s",
+                timeout.as_secs()
+            )
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Resources
+// ---------------------------------------------------------------------------
+
+fn memory_uri(agent: &str, key: &str) -> String {
+    format!("pinchy://agents/{agent}/memories/{}", percent_encode(key))
+}
+
+/// Split `pinchy://agents/<id>/memories/<key>` into `(id, key)`.
+fn parse_memory_uri(uri: &str) -> Option<(String, String)> {
+    let rest = uri.strip_prefix("pinchy://agents/")?;
+    let (agent, key) = rest.split_once("/memories/")?;
+    Some((agent.to_string(), percent_decode(key)?))
+}
+
+fn agent_workspace<'a>(state: &'a ServeState, agent: &str) -> Option<&'a Path> {
+    state
+        .agents
+        .iter()
+        .find(|(id, _)| id == agent)
+        .map(|(_, ws)| ws.as_path())
+}
+
+async fn load_memories(
+    workspace: &Path,
+    limit: usize,
+) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
+    let workspace = workspace.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        crate::memory::MemoryStore::open(&workspace)?.search("", None, limit)
+    })
+    .await?
+}
+
+async fn list_resources(state: &ServeState) -> anyhow::Result<Vec<Value>> {
+    let mut resources = Vec::new();
+    for (agent, ws) in &state.agents {
+        let entries = match load_memories(ws, MAX_MEMORY_RESOURCES).await {
+            Ok(e) => e,
+            Err(e) => {
+                warn!(agent = %agent, error = %e, "failed to list memories for MCP");
+                continue;
+            }
+        };
+        for entry in entries {
+            resources.push(serde_json::json!({
+                "uri": memory_uri(agent, &entry.key),
+                "name": format!("{agent}: {}", entry.key),
+                "description": if entry.tags.is_empty() {
+                    format!("Memory saved {}", entry.timestamp)
+                } else {
+                    format!("Memory saved {} [{}]", entry.timestamp, entry.tags.join(", "))
+                },
+                "mimeType": "text/plain",
+            }));
+        }
+    }
+    Ok(resources)
+}
+
+async fn read_resource(state: &ServeState, params: &Value) -> anyhow::Result<Value> {
+    let uri = params["uri"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("resources/read requires a 'uri'"))?;
+    let (agent, key) =
+        parse_memory_uri(uri).ok_or_else(|| anyhow::anyhow!("unknown resource: {uri}"))?;
+    let ws = agent_workspace(state, &agent)
+        .ok_or_else(|| anyhow::anyhow!("agent '{agent}' is not exposed over MCP"))?;
+    let entry = load_memories(ws, usize::MAX >> 1)
+        .await?
+        .into_iter()
+        .find(|e| e.key == key)
+        .ok_or_else(|| anyhow::anyhow!("memory '{key}' not found"))?;
+    Ok(serde_json::json!({
+        "contents": [{ "uri": uri, "mimeType": "text/plain", "text": entry.value }]
+    }))
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(tools: &[&str]) -> ServeState {
+        ServeState {
+            allow: McpServeConfig {
+                tools: tools.iter().map(|s| s.to_string()).collect(),
+                agents: vec!["helper".into()],
+                workspace_agent: None,
+                chat_timeout_secs: None,
+            },
+            agents: vec![("helper".into(), PathBuf::from("/tmp/helper"))],
+            tool_workspace: PathBuf::from("/tmp/helper"),
+        }
+    }
+
+    #[test]
+    fn memory_uris_round_trip() {
+        let uri = memory_uri("helper", "project notes/v2");
+        assert_eq!(uri, "pinchy://agents/helper/memories/project%20notes%2Fv2");
+        assert_eq!(
+            parse_memory_uri(&uri),
+            Some(("helper".into(), "project notes/v2".into()))
+        );
+        assert_eq!(parse_memory_uri("file:///etc/passwd"), None);
+    }
+
+    #[tokio::test]
+    async fn unlisted_tools_are_refused() {
+        let st = state(&["recall_memory"]);
+        assert!(tool_allowed(&st, "recall_memory"));
+        assert!(!tool_allowed(&st, "exec_shell"));
+        let err = call_tool(
+            &st,
+            &serde_json::json!({ "name": "exec_shell", "arguments": {} }),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not exposed"));
+    }
+
+    #[tokio::test]
+    async fn initialize_advertises_tools_and_resources() {
+        let st = state(&[]);
+        let reply = handle_message(
+            &st,
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply["result"]["serverInfo"]["name"], "pinchy");
+        assert!(reply["result"]["capabilities"]["resources"].is_object());
+        assert!(handle_message(
+            &st,
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        )
+        .await
+        .is_none());
+    }
+}
@@ -1,12 +1,15 @@
 //! Persistent memory backend — SQLite with FTS5 full-text search.
 //!
-//! Storage: `agents/<id>/workspace/memory.db`
+//! Storage: `agents/<id>/workspace/memory.db`, plus shared scopes at
+//! `PINCHY_HOME/memory/<scope>.db` that several agents can read and write
+//! (see [`SharedMemoryConfig`](crate::config::SharedMemoryConfig)).
 //!
 //! Provides ranked keyword search via FTS5/BM25 instead of substring
 //! matching, plus efficient upsert and tag filtering.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
@@ -24,6 +27,35 @@ pub struct MemoryEntry {
     pub score: Option<f64>,
 }
 
+/// How long a write waits for another connection's lock before failing.
+/// Shared scopes are written by several agents at once.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Scope name for an agent's own memory.
+pub const PRIVATE_SCOPE: &str = "agent";
+
+/// Directory holding the shared memory databases.
+pub fn shared_dir() -> PathBuf {
+    crate::pinchy_home().join("memory")
+}
+
+/// Check a shared scope name: non-empty, at most 64 letters, digits, `-`
+/// or `_`, and not the private scope.
+pub fn validate_scope(scope: &str) -> anyhow::Result<()> {
+    if scope.is_empty()
+        || scope.len() > 64
+        || !scope
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("invalid memory scope '{scope}': use letters, digits, '-' or '_' (max 64)");
+    }
+    if scope == PRIVATE_SCOPE {
+        anyhow::bail!("memory scope '{PRIVATE_SCOPE}' is reserved for the agent's own memory");
+    }
+    Ok(())
+}
+
 /// SQLite-backed memory store with FTS5 search.
 pub struct MemoryStore {
     inner: Arc<Mutex<Connection>>,
@@ -36,12 +68,20 @@ impl MemoryStore {
         Self::open_path(&db_path)
     }
 
+    /// Open (or create) the shared memory database for `scope` at
+    /// `PINCHY_HOME/memory/<scope>.db`.
+    pub fn open_shared(scope: &str) -> anyhow::Result<Self> {
+        validate_scope(scope)?;
+        Self::open_path(&shared_dir().join(format!("{scope}.db")))
+    }
+
     /// Open a database at an explicit path (useful for tests).
     pub fn open_path(db_path: &Path) -> anyhow::Result<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         let conn = Connection::open(db_path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
 
         // Main table.
@@ -306,6 +346,17 @@ impl MemoryStore {
     /// Context-aware memory injection: selects the most relevant memories
     /// for the given query (last user message) using hybrid search.
     pub fn prompt_block_contextual(&self, query: &str, max_chars: usize) -> String {
+        self.prompt_block_for_scope(query, max_chars, None)
+    }
+
+    /// Like [`prompt_block_contextual`](Self::prompt_block_contextual), with
+    /// the block labelled as coming from a shared `scope`.
+    pub fn prompt_block_for_scope(
+        &self,
+        query: &str,
+        max_chars: usize,
+        scope: Option<&str>,
+    ) -> String {
         let entries = if query.is_empty() {
             self.search("", None, 50).unwrap_or_default()
         } else {
@@ -318,7 +369,10 @@ impl MemoryStore {
             return String::new();
         }
 
-        let mut block = String::from("<memory>\n");
+        let mut block = match scope {
+            Some(scope) => format!("<memory scope=\"{scope}\">\n"),
+            None => String::from("<memory>\n"),
+        };
         for entry in &entries {
             let line = format!("- **{}**: {}\n", entry.key, entry.value);
             if block.len() + line.len() > max_chars {
@@ -561,12 +615,12 @@ fn get_cached_query_embedding(query: &str) -> Option<Vec<f32>> {
 }
 
 /// Serialize an f32 slice to a compact little-endian byte blob.
-fn embedding_to_blob(vec: &[f32]) -> Vec<u8> {
+pub(crate) fn embedding_to_blob(vec: &[f32]) -> Vec<u8> {
     vec.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
 
 /// Deserialize a byte blob back to an f32 vector.
-fn blob_to_embedding(blob: &[u8], dim: usize) -> Vec<f32> {
+pub(crate) fn blob_to_embedding(blob: &[u8], dim: usize) -> Vec<f32> {
     let safe_dim = dim.min(blob.len() / 4);
     (0..safe_dim)
         .map(|i| {
@@ -578,7 +632,7 @@ fn blob_to_embedding(blob: &[u8], dim: usize) -> Vec<f32> {
 }
 
 /// Cosine similarity between two vectors.
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
     let mut dot = 0.0f64;
     let mut na = 0.0f64;
     let mut nb = 0.0f64;
@@ -619,6 +673,16 @@ mod tests {
         assert_eq!(results[0].value, "Alice");
     }
 
+    #[test]
+    fn shared_scope_names_are_validated() {
+        for ok in ["global", "family", "team_2", "ops-alerts"] {
+            assert!(validate_scope(ok).is_ok(), "{ok}");
+        }
+        for bad in ["", "agent", "../x", "a b", "x.db"] {
+            assert!(validate_scope(bad).is_err(), "{bad}");
+        }
+    }
+
     #[test]
     fn upsert_replaces_value() {
         let (_dir, store) = temp_store();
@@ -863,6 +927,23 @@ mod tests {
         assert_eq!(results[0].value, "val1");
     }
 
+    #[test]
+    fn writes_wait_for_another_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(dir.path()).unwrap();
+        let other = Connection::open(dir.path().join("memory.db")).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE").unwrap();
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            other.execute_batch("COMMIT").unwrap();
+        });
+
+        // Waits for the lock instead of failing with "database is locked".
+        store.save("key1", "val1", &[]).unwrap();
+        release.join().unwrap();
+        assert_eq!(store.count().unwrap(), 1);
+    }
+
     // ── Embedding / semantic search tests ────────────────────
 
     #[test]
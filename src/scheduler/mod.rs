@@ -109,6 +109,11 @@ pub struct PersistedCronJob {
     pub retry_count: u32,
     #[serde(default)]
     pub last_status: Option<String>,
+    /// Channel the job's turn runs in, so its reply reaches the user
+    /// there (set for reminders).  `None` runs the job in a dedicated
+    /// `cron:` session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deliver_to: Option<String>,
 }
 
 /// Resolve the effective timezone for an agent from config.
@@ -136,7 +141,6 @@ impl SchedulerHandle {
         let agent_id = entry.agent_id.clone();
         let name = entry.name.clone();
         let schedule = entry.schedule.clone();
-        let message = entry.message.clone();
         let job_key = format!("{}@{}", name, agent_id);
 
         // Remove any existing live job with the same key first.
@@ -167,59 +171,21 @@ impl SchedulerHandle {
 
         // Schedule -------------------------------------------------------
         if let Some(sched) = &self.cron_scheduler {
-            let aid = agent_id.to_string();
-            let jn = name.to_string();
-            let msg = message.unwrap_or_else(|| format!("[cron:{}]", jn));
-
             // Resolve timezone for this agent.
             let tz = {
                 let config_path = crate::pinchy_home().join("config.yaml");
                 crate::config::Config::load(&config_path)
                     .await
-                    .map(|cfg| resolve_agent_timezone(&cfg, &aid))
+                    .map(|cfg| resolve_agent_timezone(&cfg, &agent_id))
                     .unwrap_or(chrono_tz::UTC)
             };
 
+            let pj = entry;
             let job = Job::new_async_tz(schedule, tz, move |_uuid, _lock| {
-                let aid = aid.clone();
-                let jn = jn.clone();
-                let msg = msg.clone();
+                let pj = pj.clone();
                 Box::pin(async move {
-                    let now = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_secs();
-
-                    debug!(agent = %aid, job = %jn, "runtime cron job fired");
-
-                    // Create a dedicated session for this cron fire.
-                    let session_id = format!(
-                        "cron_{}_{}",
-                        jn.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_"),
-                        now,
-                    );
-
-                    let incoming = comm::IncomingMessage {
-                        agent_id: Some(aid.clone()),
-                        channel: format!("cron:{jn}"),
-                        author: format!("cron:{jn}"),
-                        content: msg.clone(),
-                        timestamp: now as i64,
-                        session_id: Some(session_id),
-                        images: Vec::new(),
-                    };
-
-                    if let Err(e) = comm::sender().send(incoming) {
-                        error!(agent = %aid, job = %jn, error = %e,
-                               "failed to dispatch runtime cron message");
-                    }
-
-                    gateway::publish_event_json(&serde_json::json!({
-                        "type": "cron",
-                        "agent": aid,
-                        "job": jn,
-                        "timestamp": now,
-                    }));
+                    debug!(agent = %pj.agent_id, job = %pj.name, "runtime cron job fired");
+                    run_persisted_job_tick(&pj).await;
                 })
             })
             .context("failed to create cron job")?;
@@ -333,6 +299,7 @@ pub async fn start(config: &Config) -> anyhow::Result<SchedulerHandle> {
                         condition: None,
                         retry_count: 0,
                         last_status: None,
+                        deliver_to: None,
                     };
                     if let Err(e) = db.upsert_cron_job(&entry) {
                         warn!(agent = %agent.id, job = %job_cfg.name,
@@ -355,7 +322,9 @@ pub async fn start(config: &Config) -> anyhow::Result<SchedulerHandle> {
 
     let persisted_jobs_list = Arc::new(Mutex::new(Vec::<PersistedCronJob>::new()));
 
-    let (cron_sched, cron_sched_for_handle, job_uuids_map) = if !all_jobs.is_empty() {
+    // The cron scheduler always runs so jobs created at runtime (cron
+    // tools, reminders) fire without a restart.
+    let (cron_sched, cron_sched_for_handle, job_uuids_map) = {
         let sched = JobScheduler::new()
             .await
             .context("failed to create cron scheduler")?;
@@ -395,11 +364,8 @@ pub async fn start(config: &Config) -> anyhow::Result<SchedulerHandle> {
             .await
             .context("failed to start cron scheduler")?;
 
-        debug!("scheduler: cron scheduler started");
+        debug!(jobs = all_jobs.len(), "scheduler: cron scheduler started");
         (Some(sched.clone()), Some(sched), job_uuids_map)
-    } else {
-        debug!("scheduler: no cron jobs configured");
-        (None, None, Arc::new(Mutex::new(HashMap::new())))
     };
 
     info!("scheduler: initialized");
@@ -516,6 +482,7 @@ async fn run_heartbeat(agent_id: &str, workspace: &Path, interval_secs: u64) {
             agent_id: Some(agent_id.to_string()),
             channel: "heartbeat".to_string(),
             author: "scheduler".to_string(),
+            author_id: None,
             content: content.clone(),
             timestamp: now as i64,
             session_id: None,
@@ -676,14 +643,32 @@ pub async fn run_persisted_job_tick(job: &PersistedCronJob) {
         now,
     );
 
-    let msg = comm::IncomingMessage {
-        agent_id: Some(agent_id.to_string()),
-        channel: format!("cron:{job_name}"),
-        author: format!("cron:{job_name}"),
-        content: message.clone(),
-        timestamp: now as i64,
-        session_id: Some(session_id),
-        images: Vec::new(),
+    let msg = match &job.deliver_to {
+        // Reminders run in the agent's current session on the channel
+        // they were set from, so the reply reaches the user there.
+        Some(channel) => comm::IncomingMessage {
+            agent_id: Some(agent_id.to_string()),
+            channel: channel.clone(),
+            author: format!("reminder:{job_name}"),
+            author_id: None,
+            content: format!(
+                "[reminder:{job_name}] A reminder you scheduled is due. \
+                 Deliver it to the user now: {message}"
+            ),
+            timestamp: now as i64,
+            session_id: None,
+            images: Vec::new(),
+        },
+        None => comm::IncomingMessage {
+            agent_id: Some(agent_id.to_string()),
+            channel: format!("cron:{job_name}"),
+            author: format!("cron:{job_name}"),
+            author_id: None,
+            content: message.clone(),
+            timestamp: now as i64,
+            session_id: Some(session_id),
+            images: Vec::new(),
+        },
     };
 
     let result = comm::sender()
@@ -140,6 +140,7 @@ impl SessionStore {
             .with_context(|| format!("open session file {}", path.display()))?;
 
         file.write_all(buf.as_bytes()).await?;
+        file.flush().await?;
         debug!(
             path = %path.display(),
             count = exchanges.len(),
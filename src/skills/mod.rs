@@ -9,6 +9,11 @@
 //! Follows the [Agent Skills](https://agentskills.io/specification) open format.
 //! Progressive disclosure: only name + description are injected at boot;
 //! full instructions are loaded on demand via `activate_skill`.
+//!
+//! A skill may also declare callable tools under `tools:` in its
+//! front-matter.  Each names an entrypoint script inside the skill
+//! directory; [`crate::tools::sync_skills`] registers them as real tools
+//! that run the script with the call's arguments as JSON on stdin.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -18,6 +23,10 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 pub mod defaults;
+pub mod package;
+pub mod requirements;
+
+pub use requirements::SkillRequirements;
 
 // ── Types ───────────────────────────────────────────────────
 
@@ -40,6 +49,100 @@ pub struct SkillMeta {
     /// inclusion via `SkillsConfig::operator_allowed`.
     #[serde(default)]
     pub operator_managed: Option<bool>,
+    /// Callable tools backed by scripts in the skill directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<SkillTool>,
+    /// Host requirements checked at load (see [`requirements`]).
+    /// `compatibility` stays free text for humans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires: Option<SkillRequirements>,
+}
+
+/// A tool declared in `SKILL.md` front-matter:
+///
+/// ```yaml
+/// tools:
+///   - name: weather_lookup
+///     description: Current weather for a city.
+///     entrypoint: scripts/weather.py
+///     args_schema:
+///       type: object
+///       properties:
+///         city: { type: string }
+///       required: [city]
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SkillTool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for the arguments.  Defaults to an empty object schema.
+    #[serde(default = "default_args_schema")]
+    pub args_schema: serde_json::Value,
+    /// Script path relative to the skill directory.
+    pub entrypoint: String,
+    /// Scripts may have side effects, so they run alone in a batch
+    /// unless the skill says otherwise.
+    #[serde(default = "default_exclusive")]
+    pub exclusive: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_args_schema() -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+fn default_exclusive() -> bool {
+    true
+}
+
+/// Resolve a tool's `entrypoint` inside `skill_dir`.  Fails when the
+/// script is missing or resolves (e.g. through `..` or a symlink) to a
+/// path outside the skill directory.
+pub fn resolve_entrypoint(skill_dir: &Path, entrypoint: &str) -> anyhow::Result<PathBuf> {
+    if Path::new(entrypoint).is_absolute() {
+        bail!("entrypoint '{entrypoint}' must be relative to the skill directory");
+    }
+    let dir = skill_dir
+        .canonicalize()
+        .with_context(|| format!("resolving {}", skill_dir.display()))?;
+    let script = dir
+        .join(entrypoint)
+        .canonicalize()
+        .with_context(|| format!("entrypoint '{entrypoint}' not found"))?;
+    if !script.starts_with(&dir) {
+        bail!("entrypoint '{entrypoint}' is outside the skill directory");
+    }
+    if !script.is_file() {
+        bail!("entrypoint '{entrypoint}' is not a file");
+    }
+    Ok(script)
+}
+
+/// Drop declared tools that are unusable, logging why.
+fn validate_tools(skill_dir: &Path, meta: &mut SkillMeta) {
+    let skill = meta.name.clone();
+    meta.tools.retain(|tool| {
+        let valid_name = !tool.name.is_empty()
+            && tool.name.len() <= 64
+            && tool
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            warn!(skill = %skill, tool = %tool.name, "skill tool name must be 1-64 of [A-Za-z0-9_-] — skipped");
+            return false;
+        }
+        if !tool.args_schema.is_object() {
+            warn!(skill = %skill, tool = %tool.name, "skill tool args_schema must be an object — skipped");
+            return false;
+        }
+        if let Err(e) = resolve_entrypoint(skill_dir, &tool.entrypoint) {
+            warn!(skill = %skill, tool = %tool.name, "skill tool skipped: {e:#}");
+            return false;
+        }
+        true
+    });
 }
 
 /// A loaded skill ready for resolution.
@@ -52,6 +155,8 @@ pub struct Skill {
     pub manifest: String,
     /// Markdown body from `SKILL.md` — injected into agent prompts on activation.
     pub instructions: String,
+    /// Why the skill's `requires` are not met; empty when it is usable.
+    pub unmet: Vec<String>,
 }
 
 // ── Registry ────────────────────────────────────────────────
@@ -117,9 +222,34 @@ impl SkillRegistry {
         if let Some(ref skills_cfg) = effective_cfg {
             Self::apply_skills_filter(&mut self.skills, skills_cfg);
         }
+
+        let secrets_dir = cfg
+            .and_then(|c| c.secrets.as_ref())
+            .and_then(|sc| sc.path.as_deref())
+            .map(Path::new);
+        self.check_requirements(secrets_dir);
         Ok(())
     }
 
+    /// Evaluate every skill's `requires` and record what is unmet.
+    pub fn check_requirements(&mut self, secrets_dir: Option<&Path>) {
+        for skill in self.skills.values_mut() {
+            skill.unmet = skill
+                .meta
+                .requires
+                .as_ref()
+                .map(|r| r.unmet(secrets_dir))
+                .unwrap_or_default();
+            if !skill.unmet.is_empty() {
+                warn!(
+                    name = %skill.meta.name,
+                    reasons = %skill.unmet.join("; "),
+                    "skill requirements not met — marked unavailable"
+                );
+            }
+        }
+    }
+
     /// Scan `<base>/*/SKILL.md`.
     fn load_skills_from(&mut self, base: &Path) -> anyhow::Result<()> {
         for entry in std::fs::read_dir(base)
@@ -142,7 +272,7 @@ impl SkillRegistry {
             let (raw, instructions) = parse_skill_md(&content)
                 .with_context(|| format!("parsing {}", skill_md.display()))?;
 
-            let meta: SkillMeta = serde_yaml_ng::from_str(&raw)
+            let mut meta: SkillMeta = serde_yaml_ng::from_str(&raw)
                 .with_context(|| format!("parsing front-matter in {}", skill_dir.display()))?;
 
             // Spec: name must match parent directory name.
@@ -155,9 +285,12 @@ impl SkillRegistry {
                 );
             }
 
+            validate_tools(&skill_dir, &mut meta);
+
             info!(
                 name = %meta.name,
                 path = %skill_dir.display(),
+                tools = meta.tools.len(),
                 "loaded skill"
             );
             if self.skills.contains_key(&meta.name) {
@@ -171,6 +304,7 @@ impl SkillRegistry {
                     path: skill_dir,
                     manifest: raw,
                     instructions,
+                    unmet: Vec::new(),
                 },
             );
         }
@@ -239,26 +373,23 @@ impl SkillRegistry {
     /// via `activate_skill`.
     pub fn prompt_metadata(&self, enabled_ids: Option<&[String]>) -> String {
         let mut parts: Vec<String> = Vec::new();
+        let mut any_unmet = false;
         for skill in self.skills.values() {
             if let Some(ids) = enabled_ids {
                 if !ids.iter().any(|id| id == &skill.meta.name) {
                     continue;
                 }
             }
-            parts.push(format!(
-                "  <skill>\n    <name>{}</name>\n    <description>{}</description>\n  </skill>",
-                skill.meta.name, skill.meta.description,
+            let tools: Vec<String> = skill.meta.tools.iter().map(|t| t.name.clone()).collect();
+            any_unmet |= !skill.unmet.is_empty();
+            parts.push(prompt_entry(
+                &skill.meta.name,
+                &skill.meta.description,
+                &tools,
+                &skill.unmet,
             ));
         }
-        if parts.is_empty() {
-            return String::new();
-        }
-        format!(
-            "<available_skills>\n{}\n</available_skills>\n\n\
-             To use a skill, call `activate_skill` with its name. \
-             This loads the full instructions into context.",
-            parts.join("\n")
-        )
+        wrap_prompt_entries(&parts, any_unmet)
     }
 
     /// Return the full instructions for a specific skill (activation).
@@ -273,6 +404,47 @@ impl SkillRegistry {
     }
 }
 
+// ── Prompt fragments ─────────────────────────────────────────
+
+/// One `<skill>` entry of the skills prompt.  Skills with unmet
+/// requirements carry an `<unavailable>` line with the reasons.
+pub fn prompt_entry(name: &str, description: &str, tools: &[String], unmet: &[String]) -> String {
+    let mut entry =
+        format!("  <skill>\n    <name>{name}</name>\n    <description>{description}</description>");
+    if !tools.is_empty() {
+        entry.push_str(&format!("\n    <tools>{}</tools>", tools.join(", ")));
+    }
+    if !unmet.is_empty() {
+        entry.push_str(&format!(
+            "\n    <unavailable>{}</unavailable>",
+            unmet.join("; ")
+        ));
+    }
+    entry.push_str("\n  </skill>");
+    entry
+}
+
+/// Wrap [`prompt_entry`] fragments into the `<available_skills>` block.
+pub fn wrap_prompt_entries(parts: &[String], any_unmet: bool) -> String {
+    if parts.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "<available_skills>\n{}\n</available_skills>\n\n\
+         To use a skill, call `activate_skill` with its name. \
+         This loads the full instructions into context.",
+        parts.join("\n")
+    );
+    if any_unmet {
+        out.push_str(
+            " Skills marked <unavailable> cannot work on this machine until the \
+             listed requirements are met — tell the user what is missing instead \
+             of trying them.",
+        );
+    }
+    out
+}
+
 // ── SKILL.md parser ──────────────────────────────────────────
 
 /// Parse a `SKILL.md` file into `(yaml_front_matter, markdown_body)`.
@@ -337,10 +509,13 @@ mod tests {
                 compatibility: None,
                 metadata: None,
                 operator_managed: None,
+                tools: Vec::new(),
+                requires: None,
             },
             path: PathBuf::from("/tmp"),
             manifest: String::new(),
             instructions: "do stuff".into(),
+            unmet: Vec::new(),
         };
         reg.skills.insert("a".into(), mk("a", "skill A"));
         reg.skills.insert("b".into(), mk("b", "skill B"));
@@ -355,6 +530,39 @@ mod tests {
         assert!(!filtered.contains("<name>b</name>"));
     }
 
+    #[test]
+    fn unmet_requirements_are_flagged_in_prompt() {
+        let mut reg = SkillRegistry::new(None);
+        reg.skills.insert(
+            "needs-tool".into(),
+            Skill {
+                meta: SkillMeta {
+                    name: "needs-tool".into(),
+                    description: "Needs a missing binary".into(),
+                    license: None,
+                    compatibility: None,
+                    metadata: None,
+                    operator_managed: None,
+                    tools: Vec::new(),
+                    requires: Some(SkillRequirements {
+                        bins: vec!["pinchy-test-no-such-bin".into()],
+                        ..Default::default()
+                    }),
+                },
+                path: PathBuf::from("/tmp"),
+                manifest: String::new(),
+                instructions: "do stuff".into(),
+                unmet: Vec::new(),
+            },
+        );
+        reg.check_requirements(None);
+        assert_eq!(reg.skills["needs-tool"].unmet.len(), 1);
+        let prompt = reg.prompt_metadata(None);
+        assert!(prompt
+            .contains("<unavailable>`pinchy-test-no-such-bin` not found on PATH</unavailable>"));
+        assert!(prompt.contains("tell the user what is missing"));
+    }
+
     #[test]
     fn get_skill_instructions_returns_full() {
         let mut reg = SkillRegistry::new(None);
@@ -368,10 +576,13 @@ mod tests {
                     compatibility: None,
                     metadata: None,
                     operator_managed: None,
+                    tools: Vec::new(),
+                    requires: None,
                 },
                 path: PathBuf::from("/tmp"),
                 manifest: String::new(),
                 instructions: "Navigate to URLs and click things.".into(),
+                unmet: Vec::new(),
             },
         );
 
@@ -394,14 +605,85 @@ mod tests {
                     compatibility: None,
                     metadata: None,
                     operator_managed: None,
+                    tools: Vec::new(),
+                    requires: None,
                 },
                 path: PathBuf::from("/tmp"),
                 manifest: String::new(),
                 instructions: "do stuff".into(),
+                unmet: Vec::new(),
             },
         );
         assert!(reg.skills.contains_key("old"));
         let _ = reg.reload(None);
         assert!(!reg.skills.contains_key("old"));
     }
+
+    #[tokio::test]
+    async fn declared_tools_run_their_scripts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skill_dir = tmp.path().join("skills/echoer");
+        std::fs::create_dir_all(skill_dir.join("scripts")).unwrap();
+        std::fs::write(tmp.path().join("skills/outside.sh"), "echo escaped\n").unwrap();
+        std::fs::write(skill_dir.join("scripts/echo.sh"), "cat\n").unwrap();
+        std::fs::write(
+            skill_dir.join("scripts/fail.sh"),
+            "echo 'no such city' >&2\nexit 3\n",
+        )
+        .unwrap();
+        std::fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: echoer\ndescription: Echo things\ntools:\n\
+             \x20 - name: skill_test_echo\n    description: Echo the args\n    entrypoint: scripts/echo.sh\n\
+             \x20   args_schema: { type: object, properties: { x: { type: integer } } }\n\
+             \x20 - name: skill_test_fail\n    description: Always fails\n    entrypoint: scripts/fail.sh\n\
+             \x20 - name: skill_test_escape\n    description: Escapes\n    entrypoint: ../outside.sh\n\
+             \x20 - name: bad name\n    description: Bad\n    entrypoint: scripts/echo.sh\n\
+             ---\nUse the tools.\n",
+        )
+        .unwrap();
+
+        let mut reg = SkillRegistry::new(None);
+        reg.load_skills_from(&tmp.path().join("skills")).unwrap();
+        let names: Vec<&str> = reg.skills["echoer"]
+            .meta
+            .tools
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, ["skill_test_echo", "skill_test_fail"]);
+
+        crate::tools::sync_skills(&reg);
+        let workspace = tmp.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let out =
+            crate::tools::call_skill("skill_test_echo", serde_json::json!({ "x": 1 }), &workspace)
+                .await
+                .unwrap();
+        assert_eq!(out, serde_json::json!({ "x": 1 }));
+        assert!(crate::tools::is_exclusive("skill_test_echo"));
+        let enabled = |ids: &[&str]| {
+            let ids: Vec<String> = ids.iter().map(|s| s.to_string()).collect();
+            crate::tools::skill_tool_enabled("skill_test_echo", Some(&ids))
+        };
+        assert!(enabled(&["echoer"]));
+        assert!(!enabled(&["other"]));
+        assert!(crate::tools::skill_tool_enabled("skill_test_echo", None));
+        assert!(crate::tools::skill_tool_enabled("read_file", Some(&[])));
+
+        let err = crate::tools::call_skill("skill_test_fail", serde_json::json!({}), &workspace)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exited with code 3"), "{err}");
+        assert!(err.contains("no such city"), "{err}");
+
+        let hint = crate::tools::call_skill("echoer", serde_json::json!({}), &workspace)
+            .await
+            .unwrap();
+        assert!(hint["instructions"]
+            .as_str()
+            .unwrap()
+            .contains("skill_test_echo"));
+    }
 }
@@ -0,0 +1,652 @@
+//! Skill packaging — install, export, upgrade and roll back skills.
+//!
+//! A package is a skill directory (a `SKILL.md` plus any scripts and
+//! resources), either as a plain directory, a git checkout (`.git` is
+//! left out) or a `.tar.gz` archive holding the directory.  Installing
+//! validates the manifest, then records `version` and `checksum` in its
+//! `metadata` map:
+//!
+//! - `version` comes from the package's own `metadata.version`
+//!   (`0.0.0` when absent);
+//! - `checksum` is a SHA-256 over every file in the skill, with
+//!   `SKILL.md` hashed without its `checksum` entry — so [`verify`] can
+//!   recompute it after install.
+//!
+//! Upgrading moves the installed copy to `agents/<id>/.skill-history/<name>/`
+//! first; [`rollback`] swaps the newest saved copy back in (and saves the
+//! current one, so a rollback can itself be undone).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use tracing::info;
+
+use super::{parse_skill_md, SkillMeta};
+
+/// Version recorded for packages that do not declare one.
+const DEFAULT_VERSION: &str = "0.0.0";
+/// Saved copies kept per skill.
+const MAX_HISTORY: usize = 5;
+/// Per-agent directory holding replaced skill versions.
+const HISTORY_DIR: &str = ".skill-history";
+/// Per-agent scratch directory for unpacking packages.
+const STAGING_DIR: &str = ".skill-staging";
+/// Most bytes a package archive may unpack to.
+const MAX_UNPACKED_BYTES: u64 = 64 * 1024 * 1024;
+/// Most entries a package archive may hold.
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// A package operation named something that does not exist.  Carried
+/// inside the `anyhow::Error` so the gateway can answer 404 without
+/// string matching.
+#[derive(Debug)]
+pub enum PackageError {
+    /// The package source path does not exist.
+    SourceNotFound(PathBuf),
+    /// The skill is not installed for the agent.
+    NotInstalled { agent: String, name: String },
+    /// No saved copy of the skill to roll back to.
+    NoEarlierVersion(String),
+}
+
+impl std::fmt::Display for PackageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SourceNotFound(path) => {
+                write!(f, "skill package not found: {}", path.display())
+            }
+            Self::NotInstalled { agent, name } => {
+                write!(f, "skill '{name}' is not installed for agent '{agent}'")
+            }
+            Self::NoEarlierVersion(name) => {
+                write!(f, "no earlier version of '{name}' to roll back to")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackageError {}
+
+/// Outcome of [`install`] or [`rollback`].
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallReport {
+    pub name: String,
+    pub version: String,
+    pub checksum: String,
+    /// Version that was replaced, if the skill was already installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    /// The package matched the installed copy, so nothing changed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unchanged: bool,
+    pub path: PathBuf,
+}
+
+/// A saved copy of a skill available to [`rollback`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedVersion {
+    pub version: String,
+    pub checksum: Option<String>,
+    /// Directory name under the history folder (sortable, newest last).
+    pub id: String,
+}
+
+fn skills_dir(agent_id: &str) -> PathBuf {
+    crate::utils::agent_root(agent_id).join("skills")
+}
+
+fn history_dir(agent_id: &str, name: &str) -> PathBuf {
+    crate::utils::agent_root(agent_id)
+        .join(HISTORY_DIR)
+        .join(name)
+}
+
+/// Agent Skills spec: 1-64 lowercase alphanumerics and single hyphens.
+fn check_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty()
+        || name.len() > 64
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        || name.starts_with('-')
+        || name.ends_with('-')
+        || name.contains("--")
+    {
+        bail!("invalid skill name '{name}': use 1-64 lowercase letters, digits and single hyphens");
+    }
+    Ok(())
+}
+
+// ── Manifest helpers ─────────────────────────────────────────
+
+/// Split `SKILL.md` into its front-matter mapping and body.
+fn read_manifest(skill_dir: &Path) -> anyhow::Result<(serde_yaml_ng::Mapping, String)> {
+    let path = skill_dir.join("SKILL.md");
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let (yaml, body) = parse_skill_md(&content)?;
+    let mapping: serde_yaml_ng::Mapping =
+        serde_yaml_ng::from_str(&yaml).context("parsing SKILL.md front-matter")?;
+    Ok((mapping, body))
+}
+
+fn write_manifest(
+    skill_dir: &Path,
+    mapping: &serde_yaml_ng::Mapping,
+    body: &str,
+) -> anyhow::Result<()> {
+    let yaml = serde_yaml_ng::to_string(mapping)?;
+    fs::write(
+        skill_dir.join("SKILL.md"),
+        format!("---\n{}---\n{body}", yaml),
+    )?;
+    Ok(())
+}
+
+fn metadata_value(mapping: &serde_yaml_ng::Mapping, key: &str) -> Option<String> {
+    mapping
+        .get("metadata")?
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+fn set_metadata(mapping: &mut serde_yaml_ng::Mapping, key: &str, value: Option<&str>) {
+    use serde_yaml_ng::Value;
+    let meta = mapping
+        .entry(Value::from("metadata"))
+        .or_insert_with(|| Value::Mapping(Default::default()));
+    if !meta.is_mapping() {
+        *meta = Value::Mapping(Default::default());
+    }
+    let meta = meta.as_mapping_mut().expect("metadata is a mapping");
+    match value {
+        Some(v) => {
+            meta.insert(Value::from(key), Value::from(v));
+        }
+        None => {
+            meta.remove(key);
+        }
+    }
+}
+
+/// Validate the manifest the same way the registry will load it.
+fn validate(skill_dir: &Path) -> anyhow::Result<SkillMeta> {
+    let (mapping, _) = read_manifest(skill_dir)?;
+    let meta: SkillMeta = serde_yaml_ng::from_value(serde_yaml_ng::Value::Mapping(mapping))
+        .context("SKILL.md front-matter does not match the skill format")?;
+    check_name(&meta.name)?;
+    if meta.description.trim().is_empty() {
+        bail!("skill '{}' has an empty description", meta.name);
+    }
+    for tool in &meta.tools {
+        super::resolve_entrypoint(skill_dir, &tool.entrypoint)
+            .with_context(|| format!("tool '{}'", tool.name))?;
+    }
+    Ok(meta)
+}
+
+// ── Checksums ────────────────────────────────────────────────
+
+/// SHA-256 over the skill's files, in path order.  `SKILL.md` is hashed
+/// without its `metadata.checksum` so the value can live inside it.
+pub fn checksum(skill_dir: &Path) -> anyhow::Result<String> {
+    let mut files = BTreeMap::new();
+    collect_files(skill_dir, skill_dir, &mut files)?;
+    let mut hasher = ring::digest::Context::new(&ring::digest::SHA256);
+    for (rel, abs) in &files {
+        let bytes = if rel == "SKILL.md" {
+            let (mut mapping, body) = read_manifest(skill_dir)?;
+            set_metadata(&mut mapping, "checksum", None);
+            format!("{}---\n{body}", serde_yaml_ng::to_string(&mapping)?).into_bytes()
+        } else {
+            fs::read(abs).with_context(|| format!("reading {}", abs.display()))?
+        };
+        hasher.update(rel.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(hasher
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Check an installed skill against its recorded checksum.  Returns
+/// `Ok(false)` when files changed since install, and an error when no
+/// checksum was recorded.
+pub fn verify(agent_id: &str, name: &str) -> anyhow::Result<bool> {
+    let dir = skills_dir(agent_id).join(name);
+    let (mapping, _) = read_manifest(&dir)?;
+    let recorded = metadata_value(&mapping, "checksum")
+        .ok_or_else(|| anyhow::anyhow!("skill '{name}' has no recorded checksum"))?;
+    Ok(checksum(&dir)? == recorded)
+}
+
+/// Regular files under `dir` keyed by `/`-separated relative path.
+/// `.git` directories and symlinks are skipped.
+fn collect_files(
+    dir: &Path,
+    base: &Path,
+    out: &mut BTreeMap<String, PathBuf>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let kind = entry.file_type()?;
+        if kind.is_dir() {
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            collect_files(&path, base, out)?;
+        } else if kind.is_file() {
+            let rel = path.strip_prefix(base)?;
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.insert(rel, path);
+        }
+    }
+    Ok(())
+}
+
+// ── Install ──────────────────────────────────────────────────
+
+/// Install a skill for `agent_id` from a directory, git checkout or
+/// `.tar.gz` archive, upgrading any installed copy.
+pub fn install(agent_id: &str, source: &Path) -> anyhow::Result<InstallReport> {
+    let staging = staging_path(agent_id, "");
+    fs::create_dir_all(&staging)?;
+    let result = install_staged(agent_id, source, &staging);
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+/// [`install`] from the bytes of an uploaded `.tar.gz`.
+pub fn install_archive(agent_id: &str, archive: &[u8]) -> anyhow::Result<InstallReport> {
+    let upload = staging_path(agent_id, ".tar.gz");
+    fs::create_dir_all(upload.parent().expect("staging path has a parent"))?;
+    fs::write(&upload, archive)?;
+    let result = install(agent_id, &upload);
+    let _ = fs::remove_file(&upload);
+    result
+}
+
+/// Unique scratch path under the agent's staging directory.
+fn staging_path(agent_id: &str, suffix: &str) -> PathBuf {
+    crate::utils::agent_root(agent_id)
+        .join(STAGING_DIR)
+        .join(format!(
+            "{}-{}{suffix}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+}
+
+fn install_staged(agent_id: &str, source: &Path, staging: &Path) -> anyhow::Result<InstallReport> {
+    if source.is_dir() {
+        let mut files = BTreeMap::new();
+        collect_files(source, source, &mut files)?;
+        for (rel, abs) in files {
+            let dest = staging.join(&rel);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&abs, &dest).with_context(|| format!("copying {rel}"))?;
+        }
+    } else if source.is_file() {
+        unpack(fs::File::open(source)?, staging)
+            .with_context(|| format!("unpacking {}", source.display()))?;
+    } else {
+        return Err(PackageError::SourceNotFound(source.to_path_buf()).into());
+    }
+
+    let root = package_root(staging)?;
+    let meta = validate(&root)?;
+    let name = meta.name.clone();
+
+    let (mut mapping, body) = read_manifest(&root)?;
+    let version = metadata_value(&mapping, "version").unwrap_or_else(|| DEFAULT_VERSION.into());
+    set_metadata(&mut mapping, "version", Some(&version));
+    set_metadata(&mut mapping, "checksum", None);
+    write_manifest(&root, &mapping, &body)?;
+    let sum = checksum(&root)?;
+    set_metadata(&mut mapping, "checksum", Some(&sum));
+    write_manifest(&root, &mapping, &body)?;
+
+    let target = skills_dir(agent_id).join(&name);
+    let mut previous_version = None;
+    if target.join("SKILL.md").is_file() {
+        let (installed, _) = read_manifest(&target)?;
+        if metadata_value(&installed, "checksum").as_deref() == Some(sum.as_str())
+            && checksum(&target)? == sum
+        {
+            return Ok(InstallReport {
+                name,
+                version,
+                checksum: sum,
+                previous_version: None,
+                unchanged: true,
+                path: target,
+            });
+        }
+        previous_version =
+            Some(metadata_value(&installed, "version").unwrap_or_else(|| DEFAULT_VERSION.into()));
+        save_to_history(agent_id, &name, &target)?;
+    } else if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::create_dir_all(skills_dir(agent_id))?;
+    fs::rename(&root, &target)
+        .with_context(|| format!("moving skill into {}", target.display()))?;
+
+    info!(agent = agent_id, skill = %name, version = %version, "installed skill");
+    Ok(InstallReport {
+        name,
+        version,
+        checksum: sum,
+        previous_version,
+        unchanged: false,
+        path: target,
+    })
+}
+
+/// Extract a gzip'd tarball into `dest`, keeping only regular files and
+/// directories (no links or devices) and refusing paths that escape it
+/// and archives over [`MAX_ARCHIVE_ENTRIES`] or [`MAX_UNPACKED_BYTES`].
+fn unpack(reader: impl Read, dest: &Path) -> anyhow::Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut unpacked = 0u64;
+    for (index, entry) in archive.entries()?.enumerate() {
+        if index >= MAX_ARCHIVE_ENTRIES {
+            bail!("package has more than {MAX_ARCHIVE_ENTRIES} entries");
+        }
+        let mut entry = entry?;
+        let kind = entry.header().entry_type();
+        if !(kind.is_file() || kind.is_dir()) {
+            continue;
+        }
+        unpacked = unpacked.saturating_add(entry.size());
+        if unpacked > MAX_UNPACKED_BYTES {
+            bail!("package unpacks to more than {MAX_UNPACKED_BYTES} bytes");
+        }
+        // `unpack_in` refuses `..` and absolute paths.
+        if !entry.unpack_in(dest)? {
+            bail!(
+                "archive entry escapes the package: {}",
+                entry.path()?.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The directory holding `SKILL.md`: the staging root itself, or its
+/// single top-level directory.
+fn package_root(staging: &Path) -> anyhow::Result<PathBuf> {
+    if staging.join("SKILL.md").is_file() {
+        return Ok(staging.to_path_buf());
+    }
+    let dirs: Vec<PathBuf> = fs::read_dir(staging)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    match dirs.as_slice() {
+        [only] if only.join("SKILL.md").is_file() => Ok(only.clone()),
+        _ => bail!("package has no SKILL.md at its root or in a single top-level directory"),
+    }
+}
+
+/// Move the installed copy of `name` into its history, pruning old copies.
+fn save_to_history(agent_id: &str, name: &str, installed: &Path) -> anyhow::Result<()> {
+    let (mapping, _) = read_manifest(installed)?;
+    let version = metadata_value(&mapping, "version").unwrap_or_else(|| DEFAULT_VERSION.into());
+    let dir = history_dir(agent_id, name);
+    fs::create_dir_all(&dir)?;
+    let id = format!(
+        "{}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+        version.replace(['/', '\\'], "_")
+    );
+    fs::rename(installed, dir.join(&id))
+        .with_context(|| format!("saving previous version of '{name}'"))?;
+
+    let mut saved = list_history(&dir)?;
+    while saved.len() > MAX_HISTORY {
+        let oldest = saved.remove(0);
+        let _ = fs::remove_dir_all(dir.join(oldest));
+    }
+    Ok(())
+}
+
+/// History entry names, oldest first.
+fn list_history(dir: &Path) -> anyhow::Result<Vec<String>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join("SKILL.md").is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Saved copies of `name` available for rollback, newest first.
+pub fn versions(agent_id: &str, name: &str) -> anyhow::Result<Vec<SavedVersion>> {
+    check_name(name)?;
+    let dir = history_dir(agent_id, name);
+    let mut out = Vec::new();
+    for id in list_history(&dir)?.into_iter().rev() {
+        let (mapping, _) = read_manifest(&dir.join(&id))?;
+        out.push(SavedVersion {
+            version: metadata_value(&mapping, "version").unwrap_or_else(|| DEFAULT_VERSION.into()),
+            checksum: metadata_value(&mapping, "checksum"),
+            id,
+        });
+    }
+    Ok(out)
+}
+
+/// Restore the most recently saved copy of `name`.  The copy being
+/// replaced is saved in turn.
+pub fn rollback(agent_id: &str, name: &str) -> anyhow::Result<InstallReport> {
+    check_name(name)?;
+    let dir = history_dir(agent_id, name);
+    let newest = list_history(&dir)?
+        .pop()
+        .ok_or_else(|| PackageError::NoEarlierVersion(name.to_string()))?;
+    let restored = dir.join(&newest);
+
+    let target = skills_dir(agent_id).join(name);
+    let mut previous_version = None;
+    if target.join("SKILL.md").is_file() {
+        let (installed, _) = read_manifest(&target)?;
+        previous_version =
+            Some(metadata_value(&installed, "version").unwrap_or_else(|| DEFAULT_VERSION.into()));
+        save_to_history(agent_id, name, &target)?;
+    }
+    fs::create_dir_all(skills_dir(agent_id))?;
+    fs::rename(&restored, &target).with_context(|| format!("restoring '{name}' from {newest}"))?;
+
+    let (mapping, _) = read_manifest(&target)?;
+    let version = metadata_value(&mapping, "version").unwrap_or_else(|| DEFAULT_VERSION.into());
+    info!(agent = agent_id, skill = name, version = %version, "rolled back skill");
+    Ok(InstallReport {
+        name: name.to_string(),
+        version,
+        checksum: metadata_value(&mapping, "checksum").unwrap_or_default(),
+        previous_version,
+        unchanged: false,
+        path: target,
+    })
+}
+
+// ── Export ───────────────────────────────────────────────────
+
+/// Write an installed skill as a `.tar.gz` holding `<name>/…` to `out`.
+/// Returns the recorded version.
+pub fn export(agent_id: &str, name: &str, out: impl Write) -> anyhow::Result<String> {
+    check_name(name)?;
+    let dir = skills_dir(agent_id).join(name);
+    if !dir.join("SKILL.md").is_file() {
+        return Err(PackageError::NotInstalled {
+            agent: agent_id.to_string(),
+            name: name.to_string(),
+        }
+        .into());
+    }
+    validate(&dir)?;
+    let (mapping, _) = read_manifest(&dir)?;
+    let version = metadata_value(&mapping, "version").unwrap_or_else(|| DEFAULT_VERSION.into());
+
+    let mut files = BTreeMap::new();
+    collect_files(&dir, &dir, &mut files)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+    for (rel, abs) in &files {
+        tar.append_path_with_name(abs, Path::new(name).join(rel))
+            .with_context(|| format!("adding {rel}"))?;
+    }
+    tar.into_inner()
+        .context("finalize gzip stream")?
+        .finish()
+        .context("flush gzip")?;
+    Ok(version)
+}
+
+/// File name for an exported package.
+pub fn archive_name(name: &str, version: &str) -> String {
+    format!("{name}-{version}.tar.gz")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_skill(dir: &Path, version: Option<&str>, body: &str) {
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        let meta = version
+            .map(|v| format!("metadata:\n  version: \"{v}\"\n"))
+            .unwrap_or_default();
+        fs::write(
+            dir.join("SKILL.md"),
+            format!("---\nname: greeter\ndescription: Says hello\n{meta}---\n{body}\n"),
+        )
+        .unwrap();
+        fs::write(dir.join("scripts/hello.sh"), "echo hello\n").unwrap();
+    }
+
+    #[test]
+    fn install_upgrade_export_and_rollback() {
+        let home = tempfile::tempdir().unwrap();
+        // agent_root() resolves under PINCHY_HOME; use a unique agent id
+        // below an absolute root instead of touching the environment.
+        let agent = home.path().join("agent").to_string_lossy().to_string();
+        let src = tempfile::tempdir().unwrap();
+        let pkg = src.path().join("greeter");
+
+        write_skill(&pkg, Some("1.0.0"), "v1");
+        fs::create_dir_all(pkg.join(".git")).unwrap();
+        fs::write(pkg.join(".git/HEAD"), "ref").unwrap();
+        let first = install(&agent, &pkg).unwrap();
+        assert_eq!(first.version, "1.0.0");
+        assert!(first.previous_version.is_none());
+        assert!(!first.path.join(".git").exists());
+        assert!(verify(&agent, "greeter").unwrap());
+        assert!(install(&agent, &pkg).unwrap().unchanged);
+
+        // Export v1, then upgrade from a directory.
+        let mut archive = Vec::new();
+        assert_eq!(export(&agent, "greeter", &mut archive).unwrap(), "1.0.0");
+        write_skill(&pkg, Some("2.0.0"), "v2");
+        let second = install(&agent, &pkg).unwrap();
+        assert_eq!(second.previous_version.as_deref(), Some("1.0.0"));
+        assert_ne!(second.checksum, first.checksum);
+        assert_eq!(versions(&agent, "greeter").unwrap()[0].version, "1.0.0");
+
+        // Roll back to v1 and forward again.
+        let back = rollback(&agent, "greeter").unwrap();
+        assert_eq!(back.version, "1.0.0");
+        assert_eq!(back.previous_version.as_deref(), Some("2.0.0"));
+        assert!(verify(&agent, "greeter").unwrap());
+        assert_eq!(rollback(&agent, "greeter").unwrap().version, "2.0.0");
+
+        // Reinstalling the exported archive restores v1 again.
+        let tgz = src.path().join("greeter-1.0.0.tar.gz");
+        fs::write(&tgz, &archive).unwrap();
+        let from_archive = install(&agent, &tgz).unwrap();
+        assert_eq!(from_archive.version, "1.0.0");
+        assert_eq!(from_archive.checksum, first.checksum);
+
+        // Local edits show up as a checksum mismatch.
+        fs::write(from_archive.path.join("scripts/hello.sh"), "echo hacked\n").unwrap();
+        assert!(!verify(&agent, "greeter").unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_packages() {
+        let home = tempfile::tempdir().unwrap();
+        let agent = home.path().join("agent").to_string_lossy().to_string();
+        let src = tempfile::tempdir().unwrap();
+
+        let bad = src.path().join("Bad");
+        fs::create_dir_all(&bad).unwrap();
+        fs::write(
+            bad.join("SKILL.md"),
+            "---\nname: Bad_Name\ndescription: x\n---\n",
+        )
+        .unwrap();
+        assert!(install(&agent, &bad).is_err());
+
+        let empty = src.path().join("empty");
+        fs::create_dir_all(&empty).unwrap();
+        assert!(install(&agent, &empty).is_err());
+        let err = rollback(&agent, "greeter").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PackageError>(),
+            Some(PackageError::NoEarlierVersion(_))
+        ));
+        assert!(!skills_dir(&agent).join("Bad_Name").exists());
+    }
+
+    #[test]
+    fn refuses_oversized_archives() {
+        let tar_gz = |entries: usize, size: usize| {
+            let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+            let data = vec![0u8; size];
+            for i in 0..entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(size as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, format!("s/f{i}"), data.as_slice())
+                    .unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap()
+        };
+        let dest = tempfile::tempdir().unwrap();
+
+        let err = unpack(tar_gz(MAX_ARCHIVE_ENTRIES + 1, 0).as_slice(), dest.path()).unwrap_err();
+        assert!(err.to_string().contains("entries"), "{err}");
+        let big = MAX_UNPACKED_BYTES as usize / 2 + 1;
+        let err = unpack(tar_gz(2, big).as_slice(), dest.path()).unwrap_err();
+        assert!(err.to_string().contains("bytes"), "{err}");
+        unpack(tar_gz(2, 16).as_slice(), dest.path()).unwrap();
+        assert!(dest.path().join("s/f1").is_file());
+    }
+}
@@ -0,0 +1,188 @@
+//! Skill requirements — what a skill needs from the host to work.
+//!
+//! Declared under `requires:` in `SKILL.md` front-matter and checked when
+//! the [`SkillRegistry`](super::SkillRegistry) loads.  Skills with unmet
+//! requirements stay listed but are flagged as unavailable, with the
+//! reasons, in the prompt and in `/api/skills`; their declared tools are
+//! not registered.
+//!
+//! ```yaml
+//! requires:
+//!   bins: [playwright-cli]          # all must be on PATH
+//!   any_bins: [chromium, $CHROME_PATH]  # at least one; `$VAR` = path in VAR
+//!   env: [GITHUB_TOKEN]             # set and non-empty
+//!   secrets: [OPENWEATHER_KEY]      # in the secrets store (or env)
+//!   pinchy: "0.1.10"                # minimum pinchy version
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Requirements declared under `requires:`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SkillRequirements {
+    /// Executables that must all be found on `PATH` (or be absolute paths).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bins: Vec<String>,
+    /// Executables of which at least one must be found.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any_bins: Vec<String>,
+    /// Environment variables that must be set and non-empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    /// Secrets that must exist in the secrets store (an environment
+    /// variable of the same name also counts).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    /// Minimum pinchy version, e.g. `"0.1.10"` (a leading `>=` is allowed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinchy: Option<String>,
+}
+
+impl SkillRequirements {
+    /// Human-readable reasons the requirements are not met; empty when
+    /// they all are.  `secrets_dir` is the configured secrets directory.
+    pub fn unmet(&self, secrets_dir: Option<&Path>) -> Vec<String> {
+        let mut reasons = Vec::new();
+        for bin in &self.bins {
+            if find_executable(bin).is_none() {
+                reasons.push(format!("`{bin}` not found on PATH"));
+            }
+        }
+        if !self.any_bins.is_empty() && !self.any_bins.iter().any(|b| find_executable(b).is_some())
+        {
+            reasons.push(format!(
+                "none of {} found on PATH",
+                self.any_bins
+                    .iter()
+                    .map(|b| format!("`{b}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        for var in &self.env {
+            if !env_set(var) {
+                reasons.push(format!("environment variable `{var}` is not set"));
+            }
+        }
+        for key in &self.secrets {
+            let stored = crate::secrets::get_secret_file(secrets_dir, key)
+                .ok()
+                .flatten()
+                .is_some_and(|v| !v.is_empty());
+            if !stored && !env_set(key) {
+                reasons.push(format!(
+                    "secret `{key}` is missing (store it with `pinchy secrets set {key}`)"
+                ));
+            }
+        }
+        if let Some(min) = &self.pinchy {
+            let min = min.trim().trim_start_matches(">=").trim();
+            let current = env!("CARGO_PKG_VERSION");
+            if !version_at_least(current, min) {
+                reasons.push(format!("needs pinchy {min} or newer (running {current})"));
+            }
+        }
+        reasons
+    }
+}
+
+fn env_set(var: &str) -> bool {
+    std::env::var(var).is_ok_and(|v| !v.is_empty())
+}
+
+/// Locate `bin` on `PATH`, or check it directly when it is a path.
+/// `$VAR` checks the path held in environment variable `VAR`.
+pub fn find_executable(bin: &str) -> Option<PathBuf> {
+    if let Some(var) = bin.strip_prefix('$') {
+        let value = std::env::var(var).ok().filter(|v| !v.is_empty())?;
+        return find_executable(&value);
+    }
+    let is_exec = |p: &Path| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            p.metadata()
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        }
+        #[cfg(not(unix))]
+        {
+            p.is_file()
+        }
+    };
+    if bin.contains('/') {
+        let p = PathBuf::from(bin);
+        return is_exec(&p).then_some(p);
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(bin))
+        .find(|p| is_exec(p))
+}
+
+/// Compare dotted numeric versions; pre-release suffixes are ignored.
+fn version_at_least(current: &str, min: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(['-', '+'])
+            .next()
+            .unwrap_or("")
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    let (cur, min) = (parts(current), parts(min));
+    for i in 0..cur.len().max(min.len()) {
+        let (a, b) = (
+            cur.get(i).copied().unwrap_or(0),
+            min.get(i).copied().unwrap_or(0),
+        );
+        if a != b {
+            return a > b;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_unmet_requirement() {
+        let secrets = tempfile::tempdir().unwrap();
+        let req = SkillRequirements {
+            bins: vec!["sh".into(), "pinchy-test-no-such-bin".into()],
+            any_bins: vec![
+                "pinchy-test-missing-a".into(),
+                "pinchy-test-missing-b".into(),
+            ],
+            env: vec!["PATH".into(), "PINCHY_TEST_UNSET_VAR".into()],
+            secrets: vec!["PINCHY_TEST_MISSING_SECRET".into()],
+            pinchy: Some(">= 999.0".into()),
+        };
+        let reasons = req.unmet(Some(secrets.path()));
+        assert_eq!(reasons.len(), 5, "{reasons:?}");
+        assert!(reasons[0].contains("pinchy-test-no-such-bin"));
+        assert!(reasons[1].starts_with("none of"));
+        assert!(reasons[2].contains("PINCHY_TEST_UNSET_VAR"));
+        assert!(reasons[3].contains("pinchy secrets set"));
+        assert!(reasons[4].contains("999.0"));
+
+        let met = SkillRequirements {
+            bins: vec!["sh".into()],
+            any_bins: vec!["$PINCHY_TEST_UNSET_VAR".into(), "sh".into()],
+            pinchy: Some("0.0.1".into()),
+            ..Default::default()
+        };
+        assert!(met.unmet(Some(secrets.path())).is_empty());
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(version_at_least("0.1.14", "0.1.9"));
+        assert!(version_at_least("0.1.14", "0.1.14"));
+        assert!(version_at_least("1.0.0-beta", "1.0"));
+        assert!(!version_at_least("0.1.14", "0.2"));
+    }
+}
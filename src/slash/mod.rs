@@ -2,8 +2,8 @@
 //!
 //! Provides a [`Registry`] that maps command names to async [`Handler`]s,
 //! plus [`register_builtin_commands`] which wires up the core set of
-//! slash commands (`/new`, `/end`, `/session`, `/list_sessions`,
-//! `/set-model`, `/status`, `/help`).
+//! slash commands (`/new`, `/end`, `/stop`, `/undo`, `/session`,
+//! `/list_sessions`, `/set-model`, `/status`, `/help`).
 
 use std::collections::HashMap;
 use std::future::Future;
@@ -248,6 +248,49 @@ pub fn register_builtin_commands(registry: &Registry) {
         }),
     );
 
+    // /stop — cancel the agent's running turn
+    registry.register(
+        cmd("stop", "Stop the agent's running turn", "/stop"),
+        Arc::new(|ctx, _args| {
+            Box::pin(async move {
+                if crate::agent::cancel::cancel(&ctx.agent_id) {
+                    debug!(agent = %ctx.agent_id, "turn cancelled via /stop");
+                    Ok(SlashResponse::Text("stopping the current turn".to_string()))
+                } else {
+                    Ok(SlashResponse::Text("nothing is running".to_string()))
+                }
+            })
+        }),
+    );
+
+    // /undo — revert the workspace changes of the last turn (or back to a turn)
+    registry.register(
+        cmd(
+            "undo",
+            "Revert the files changed by the last turn, or every turn since <turn-id>",
+            "/undo [turn-id]",
+        ),
+        Arc::new(|ctx, args| {
+            Box::pin(async move {
+                let turn = args.args.first().cloned();
+                let workspace = ctx.workspace.clone();
+                let reverted = tokio::task::spawn_blocking(move || match turn {
+                    Some(turn) => crate::journal::revert_to(&workspace, &turn),
+                    None => crate::journal::undo_last(&workspace),
+                })
+                .await
+                .map_err(|e| SlashError::Handler(format!("undo: {e}")))?;
+                match reverted {
+                    Ok(report) => {
+                        debug!(agent = %ctx.agent_id, turns = ?report.turns, "turn undone via /undo");
+                        Ok(SlashResponse::Text(report.summary()))
+                    }
+                    Err(e) => Ok(SlashResponse::Text(format!("cannot undo: {e}"))),
+                }
+            })
+        }),
+    );
+
     // /session — show current session id
     registry.register(
         cmd("session", "Show the current session id", "/session"),
@@ -13,6 +13,8 @@
 //!   cron_jobs       — persisted cron jobs (replaces cron_jobs.json)
 //!   cron_events     — job run records (replaces cron_events/*.json)
 //!   heartbeat_status — latest heartbeat per agent (replaces heartbeat_status.json)
+//!   bg_processes    — `exec_shell` background processes, re-attached on restart
+//!   tool_embeddings — cached embeddings of tool and skill descriptions
 
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -54,6 +56,30 @@ pub struct UsageBucket {
     pub estimated_cost_usd: f64,
 }
 
+/// A persisted `exec_shell` background process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BgProcessRecord {
+    /// The `process_id` handed to the agent (assigned on insert).
+    pub id: u64,
+    pub agent_id: Option<String>,
+    pub command: String,
+    /// OS pid, which is also the process group id.
+    pub os_pid: u32,
+    /// Kernel start time of `os_pid`, used to detect pid reuse.
+    pub os_start_time: Option<u64>,
+    pub interactive: bool,
+    /// Unix seconds.
+    pub started_at: i64,
+    /// Unix seconds after which the process is killed.
+    pub deadline: i64,
+    pub stdout_log: String,
+    /// `None` for interactive sessions, whose terminal output is one stream.
+    pub stderr_log: Option<String>,
+    pub done: bool,
+    pub exit_code: Option<i32>,
+    pub note: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // PinchyDb
 // ---------------------------------------------------------------------------
@@ -150,7 +176,11 @@ impl PinchyDb {
                 reply_summary      TEXT NOT NULL DEFAULT '',
                 model_id           TEXT NOT NULL DEFAULT '',
                 estimated_cost_usd REAL,
-                call_details_json  TEXT NOT NULL DEFAULT '[]'
+                call_details_json  TEXT NOT NULL DEFAULT '[]',
+                cancelled          INTEGER NOT NULL DEFAULT 0,
+                invalid_tool_calls INTEGER NOT NULL DEFAULT 0,
+                turn_id            TEXT NOT NULL DEFAULT '',
+                changed_files_json TEXT NOT NULL DEFAULT '[]'
             );
 
             CREATE INDEX IF NOT EXISTS idx_receipts_session
@@ -170,6 +200,7 @@ impl PinchyDb {
                 condition       TEXT,
                 retry_count     INTEGER NOT NULL DEFAULT 0,
                 last_status     TEXT,
+                deliver_to      TEXT,
                 PRIMARY KEY (agent_id, name)
             );
 
@@ -197,9 +228,49 @@ impl PinchyDb {
                 interval_secs    INTEGER,
                 message_preview  TEXT,
                 latest_session   TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS bg_processes (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                agent_id        TEXT,
+                command         TEXT NOT NULL,
+                os_pid          INTEGER NOT NULL,
+                os_start_time   INTEGER,
+                interactive     INTEGER NOT NULL DEFAULT 0,
+                started_at      INTEGER NOT NULL,
+                deadline        INTEGER NOT NULL,
+                stdout_log      TEXT NOT NULL,
+                stderr_log      TEXT,
+                done            INTEGER NOT NULL DEFAULT 0,
+                exit_code       INTEGER,
+                note            TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS tool_embeddings (
+                key         TEXT PRIMARY KEY,
+                hash        TEXT NOT NULL,
+                dim         INTEGER NOT NULL,
+                embedding   BLOB NOT NULL
             );",
         )
         .context("PinchyDb schema migration")?;
+
+        // Columns added after the first release.
+        add_column_if_missing(&conn, "receipts", "cancelled", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(
+            &conn,
+            "receipts",
+            "invalid_tool_calls",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(&conn, "receipts", "turn_id", "TEXT NOT NULL DEFAULT ''")?;
+        add_column_if_missing(
+            &conn,
+            "receipts",
+            "changed_files_json",
+            "TEXT NOT NULL DEFAULT '[]'",
+        )?;
+        add_column_if_missing(&conn, "cron_jobs", "deliver_to", "TEXT")?;
         Ok(())
     }
 
@@ -505,13 +576,16 @@ impl PinchyDb {
             serde_json::to_string(&receipt.tool_calls).unwrap_or_else(|_| "[]".into());
         let call_details_json =
             serde_json::to_string(&receipt.call_details).unwrap_or_else(|_| "[]".into());
+        let changed_files_json =
+            serde_json::to_string(&receipt.changed_files).unwrap_or_else(|_| "[]".into());
         conn.execute(
             "INSERT INTO receipts (
                 session_id, agent_id, started_at, duration_ms, user_prompt,
                 tool_calls_json, prompt_tokens, completion_tokens, total_tokens,
                 cached_tokens, reasoning_tokens, model_calls, reply_summary,
-                model_id, estimated_cost_usd, call_details_json
-             ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16)",
+                model_id, estimated_cost_usd, call_details_json, cancelled,
+                invalid_tool_calls, turn_id, changed_files_json
+             ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20)",
             params![
                 receipt.session,
                 receipt.agent,
@@ -529,6 +603,10 @@ impl PinchyDb {
                 receipt.model_id,
                 receipt.estimated_cost_usd,
                 call_details_json,
+                receipt.cancelled,
+                receipt.invalid_tool_calls,
+                receipt.turn_id,
+                changed_files_json,
             ],
         )?;
         debug!(agent = %receipt.agent, "receipt persisted");
@@ -542,7 +620,8 @@ impl PinchyDb {
             "SELECT session_id, agent_id, started_at, duration_ms, user_prompt,
                     tool_calls_json, prompt_tokens, completion_tokens, total_tokens,
                     cached_tokens, reasoning_tokens, model_calls, reply_summary,
-                    model_id, estimated_cost_usd, call_details_json
+                    model_id, estimated_cost_usd, call_details_json, cancelled,
+                    invalid_tool_calls, turn_id, changed_files_json
              FROM receipts WHERE session_id = ?1 ORDER BY id DESC",
         )?;
         let rows = stmt.query_map(params![session_id], Self::row_to_receipt)?;
@@ -556,6 +635,7 @@ impl PinchyDb {
     fn row_to_receipt(row: &rusqlite::Row<'_>) -> rusqlite::Result<TurnReceipt> {
         let tool_calls_json: String = row.get(5)?;
         let call_details_json: String = row.get(15)?;
+        let changed_files_json: String = row.get(19)?;
         Ok(TurnReceipt {
             session: row.get(0)?,
             agent: row.get(1)?,
@@ -575,6 +655,10 @@ impl PinchyDb {
             model_id: row.get(13)?,
             estimated_cost_usd: row.get(14)?,
             call_details: serde_json::from_str(&call_details_json).unwrap_or_default(),
+            cancelled: row.get(16)?,
+            invalid_tool_calls: row.get(17)?,
+            turn_id: row.get(18)?,
+            changed_files: serde_json::from_str(&changed_files_json).unwrap_or_default(),
         })
     }
 
@@ -687,13 +771,14 @@ impl PinchyDb {
         let kind_str = serde_json::to_string(&job.kind).unwrap_or_else(|_| "\"Message\"".into());
         let retry_delay_secs = job.retry_delay_secs.map(|v| v as i64);
         conn.execute(
-            "INSERT INTO cron_jobs (agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status)
-             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11)
+            "INSERT INTO cron_jobs (agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status, deliver_to)
+             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)
              ON CONFLICT(agent_id, name) DO UPDATE SET
                 schedule=excluded.schedule, message=excluded.message, kind=excluded.kind,
                 depends_on=excluded.depends_on, max_retries=excluded.max_retries,
                 retry_delay_secs=excluded.retry_delay_secs, condition=excluded.condition,
-                retry_count=excluded.retry_count, last_status=excluded.last_status",
+                retry_count=excluded.retry_count, last_status=excluded.last_status,
+                deliver_to=excluded.deliver_to",
             params![
                 job.agent_id,
                 job.name,
@@ -706,6 +791,7 @@ impl PinchyDb {
                 job.condition,
                 job.retry_count,
                 job.last_status,
+                job.deliver_to,
             ],
         )?;
         debug!(agent = %job.agent_id, name = %job.name, "cron job upserted");
@@ -726,7 +812,7 @@ impl PinchyDb {
     pub fn list_cron_jobs(&self, agent_id: &str) -> Result<Vec<PersistedCronJob>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status
+            "SELECT agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status, deliver_to
              FROM cron_jobs WHERE agent_id = ?1",
         )?;
         let rows = stmt.query_map(params![agent_id], |row| {
@@ -744,6 +830,7 @@ impl PinchyDb {
                 condition: row.get(8)?,
                 retry_count: row.get(9)?,
                 last_status: row.get(10)?,
+                deliver_to: row.get(11)?,
             })
         })?;
         let mut out = Vec::new();
@@ -757,7 +844,7 @@ impl PinchyDb {
     pub fn list_all_cron_jobs(&self) -> Result<Vec<PersistedCronJob>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status
+            "SELECT agent_id, name, schedule, message, kind, depends_on, max_retries, retry_delay_secs, condition, retry_count, last_status, deliver_to
              FROM cron_jobs",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -775,6 +862,7 @@ impl PinchyDb {
                 condition: row.get(8)?,
                 retry_count: row.get(9)?,
                 last_status: row.get(10)?,
+                deliver_to: row.get(11)?,
             })
         })?;
         let mut out = Vec::new();
@@ -966,6 +1054,160 @@ impl PinchyDb {
         .optional()
         .map_err(Into::into)
     }
+
+    // =====================================================================
+    // Background processes
+    // =====================================================================
+
+    /// Insert a background process, ignoring `record.id`.  Returns the
+    /// assigned id.
+    pub fn insert_bg_process(&self, record: &BgProcessRecord) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO bg_processes (agent_id, command, os_pid, os_start_time, interactive,
+                started_at, deadline, stdout_log, stderr_log, done, exit_code, note)
+             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
+            params![
+                record.agent_id,
+                record.command,
+                record.os_pid,
+                record.os_start_time.map(|v| v as i64),
+                record.interactive,
+                record.started_at,
+                record.deadline,
+                record.stdout_log,
+                record.stderr_log,
+                record.done,
+                record.exit_code,
+                record.note,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    /// Mark a background process as finished.
+    pub fn finish_bg_process(
+        &self,
+        id: u64,
+        exit_code: Option<i32>,
+        note: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE bg_processes SET done = 1, exit_code = ?2, note = ?3 WHERE id = ?1",
+            params![id as i64, exit_code, note],
+        )?;
+        Ok(())
+    }
+
+    /// All persisted background processes, oldest first.
+    pub fn list_bg_processes(&self) -> Result<Vec<BgProcessRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, command, os_pid, os_start_time, interactive, started_at,
+                    deadline, stdout_log, stderr_log, done, exit_code, note
+             FROM bg_processes ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(BgProcessRecord {
+                id: row.get::<_, i64>(0)? as u64,
+                agent_id: row.get(1)?,
+                command: row.get(2)?,
+                os_pid: row.get(3)?,
+                os_start_time: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                interactive: row.get(5)?,
+                started_at: row.get(6)?,
+                deadline: row.get(7)?,
+                stdout_log: row.get(8)?,
+                stderr_log: row.get(9)?,
+                done: row.get(10)?,
+                exit_code: row.get(11)?,
+                note: row.get(12)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Delete finished background processes started before `before`
+    /// (unix seconds).  Returns the deleted records so their logs can be
+    /// removed.
+    pub fn prune_bg_processes(&self, before: i64) -> Result<Vec<BgProcessRecord>> {
+        let stale: Vec<BgProcessRecord> = self
+            .list_bg_processes()?
+            .into_iter()
+            .filter(|r| r.done && r.started_at < before)
+            .collect();
+        let conn = self.conn.lock().unwrap();
+        for r in &stale {
+            conn.execute(
+                "DELETE FROM bg_processes WHERE id = ?1",
+                params![r.id as i64],
+            )?;
+        }
+        debug!(count = stale.len(), "pruned background processes");
+        Ok(stale)
+    }
+
+    // =====================================================================
+    // Tool embeddings
+    // =====================================================================
+
+    /// All cached tool/skill embeddings as `key → (hash, vector)`.
+    pub fn load_tool_embeddings(
+        &self,
+    ) -> Result<std::collections::HashMap<String, (String, Vec<f32>)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT key, hash, dim, embedding FROM tool_embeddings")?;
+        let rows = stmt.query_map([], |row| {
+            let dim: i64 = row.get(2)?;
+            let blob: Vec<u8> = row.get(3)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                (
+                    row.get::<_, String>(1)?,
+                    crate::memory::blob_to_embedding(&blob, dim as usize),
+                ),
+            ))
+        })?;
+        rows.collect::<std::result::Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Insert or replace cached embeddings, given as `(key, hash, vector)`.
+    pub fn save_tool_embeddings(&self, entries: &[(String, String, Vec<f32>)]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (key, hash, vector) in entries {
+            tx.execute(
+                "INSERT INTO tool_embeddings (key, hash, dim, embedding) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(key) DO UPDATE SET hash = ?2, dim = ?3, embedding = ?4",
+                params![
+                    key,
+                    hash,
+                    vector.len() as i64,
+                    crate::memory::embedding_to_blob(vector)
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// `ALTER TABLE … ADD COLUMN` unless `table` already has `column` —
+/// upgrades databases created before the column existed.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|c| c == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))
+            .with_context(|| format!("adding {table}.{column}"))?;
+    }
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -1043,12 +1285,58 @@ mod tests {
             model_id: "gpt-4o".into(),
             estimated_cost_usd: Some(0.001),
             call_details: vec![],
+            cancelled: true,
+            invalid_tool_calls: 2,
+            turn_id: "0000000000000001".into(),
+            changed_files: vec!["notes.txt".into()],
         };
         db.insert_receipt(&receipt).unwrap();
 
         let list = db.list_receipts_for_session("s1").unwrap();
         assert_eq!(list.len(), 1);
         assert_eq!(list[0].model_id, "gpt-4o");
+        assert!(list[0].cancelled);
+        assert_eq!(list[0].invalid_tool_calls, 2);
+        assert_eq!(list[0].turn_id, "0000000000000001");
+        assert_eq!(list[0].changed_files, ["notes.txt"]);
+    }
+
+    #[test]
+    fn migrate_adds_cancelled_column_to_old_receipts_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE receipts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id TEXT, agent_id TEXT NOT NULL,
+                    started_at INTEGER NOT NULL, duration_ms INTEGER NOT NULL,
+                    user_prompt TEXT NOT NULL,
+                    tool_calls_json TEXT NOT NULL DEFAULT '[]',
+                    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                    completion_tokens INTEGER NOT NULL DEFAULT 0,
+                    total_tokens INTEGER NOT NULL DEFAULT 0,
+                    cached_tokens INTEGER NOT NULL DEFAULT 0,
+                    reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+                    model_calls INTEGER NOT NULL DEFAULT 0,
+                    reply_summary TEXT NOT NULL DEFAULT '',
+                    model_id TEXT NOT NULL DEFAULT '',
+                    estimated_cost_usd REAL,
+                    call_details_json TEXT NOT NULL DEFAULT '[]'
+                );
+                INSERT INTO receipts (session_id, agent_id, started_at, duration_ms, user_prompt)
+                    VALUES ('s1', 'a', 1, 2, 'old');",
+            )
+            .unwrap();
+
+        let db = PinchyDb::open_path(&path).unwrap();
+        let list = db.list_receipts_for_session("s1").unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(!list[0].cancelled);
+        assert_eq!(list[0].invalid_tool_calls, 0);
+        assert!(list[0].turn_id.is_empty());
+        assert!(list[0].changed_files.is_empty());
     }
 
     #[test]
@@ -1066,6 +1354,7 @@ mod tests {
             condition: None,
             retry_count: 0,
             last_status: None,
+            deliver_to: None,
         };
         db.upsert_cron_job(&job).unwrap();
 
@@ -1095,4 +1384,41 @@ mod tests {
         let loaded = db.load_heartbeat_status("a").unwrap().unwrap();
         assert_eq!(loaded.last_tick, Some(500));
     }
+
+    #[test]
+    fn bg_process_roundtrip_and_prune() {
+        let db = PinchyDb::open_memory().unwrap();
+        let mut rec = BgProcessRecord {
+            id: 0,
+            agent_id: Some("agent-a".into()),
+            command: "cargo build".into(),
+            os_pid: 4242,
+            os_start_time: Some(99),
+            interactive: false,
+            started_at: 1000,
+            deadline: 1120,
+            stdout_log: "/tmp/1.stdout.log".into(),
+            stderr_log: Some("/tmp/1.stderr.log".into()),
+            done: false,
+            exit_code: None,
+            note: None,
+        };
+        let first = db.insert_bg_process(&rec).unwrap();
+        rec.started_at = 5000;
+        let second = db.insert_bg_process(&rec).unwrap();
+        assert!(second > first);
+
+        db.finish_bg_process(first, Some(0), Some("done")).unwrap();
+        let all = db.list_bg_processes().unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all[0].done);
+        assert_eq!(all[0].exit_code, Some(0));
+        assert_eq!(all[0].os_start_time, Some(99));
+
+        // Running processes are kept regardless of age.
+        let pruned = db.prune_bg_processes(10_000).unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].id, first);
+        assert_eq!(db.list_bg_processes().unwrap().len(), 1);
+    }
 }
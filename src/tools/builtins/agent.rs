@@ -119,6 +119,7 @@ pub async fn get_agent(_workspace: &Path, args: Value) -> anyhow::Result<Value>
             m.insert("model".into(), json!(ac.model));
             m.insert("heartbeat_secs".into(), json!(ac.heartbeat_secs));
             m.insert("max_tool_iterations".into(), json!(ac.max_tool_iterations));
+            m.insert("max_parallel_tools".into(), json!(ac.max_parallel_tools));
             m.insert("enabled_skills".into(), json!(ac.enabled_skills));
         }
     }
@@ -244,6 +245,8 @@ pub async fn create_agent(_workspace: &Path, args: Value) -> anyhow::Result<Valu
                 heartbeat_secs,
                 cron_jobs: Vec::new(),
                 max_tool_iterations,
+                max_parallel_tools: None,
+                tool_timeouts: Default::default(),
                 enabled_skills: enabled_skills.clone(),
                 fallback_models,
                 webhook_secret: None,
@@ -254,6 +257,15 @@ pub async fn create_agent(_workspace: &Path, args: Value) -> anyhow::Result<Valu
                 timezone: None,
                 watch_paths: Vec::new(),
                 reasoning_effort: None,
+                approvals: Vec::new(),
+                approvers: Vec::new(),
+                approval_timeout_secs: None,
+                mcp_servers: Vec::new(),
+                http_fetch: None,
+                tools: None,
+                sandbox: None,
+                journal_exec_shell: false,
+                shared_memory: None,
             });
             if let Err(e) = cfg.save(&config_path).await {
                 tracing::warn!(error = %e, "failed to save config after agent creation");
@@ -292,6 +304,8 @@ pub fn register() {
             "type": "object",
             "properties": {}
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -311,6 +325,8 @@ pub fn register() {
             },
             "required": ["id"]
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -342,5 +358,7 @@ pub fn register() {
             },
             "required": ["id"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
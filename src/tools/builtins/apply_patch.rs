@@ -1,75 +1,300 @@
 //! Apply unified-diff patches to workspace files.
-
-use crate::tools::{register_tool, ToolMeta};
-use serde_json::Value;
-use std::path::Path;
+//!
+//! Hunks are matched the way `patch(1)` does it, tolerating the slightly-off
+//! diffs models tend to write:
+//!
+//! * **offset** — a hunk is searched for outward from the line its header
+//!   names, so stale line numbers (or a bare `@@` header) still apply;
+//! * **whitespace** — when no exact match exists, lines are compared with
+//!   runs of whitespace collapsed, and the file's own context lines are kept;
+//! * **fuzz** — up to `fuzz` leading and trailing context lines (default 2)
+//!   may be ignored when the full context does not match.
+//!
+//! Hunks that still do not apply are written to `<file>.rej` in unified-diff
+//! form and returned in the result; the others are applied.  `dry_run`
+//! reports what would happen without touching the workspace.  Besides
+//! ordinary edits, patches may create files (`--- /dev/null`), delete them
+//! (`+++ /dev/null` or `deleted file mode`) and rename them (`rename from` /
+//! `rename to` in `diff --git` headers).
+
+use crate::tools::{canon_or_resolve, register_tool, ToolMeta};
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// Context lines that may be ignored at each end of a hunk by default.
+pub const DEFAULT_FUZZ: usize = 2;
+/// Largest accepted `fuzz`.
+const MAX_FUZZ: usize = 3;
 
 /// Apply a unified diff patch. Supports multi-file patches.
 pub async fn apply_patch(workspace: &Path, args: Value) -> anyhow::Result<Value> {
     let patch_text = args["patch"]
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("apply_patch requires a 'patch' string"))?;
+    let dry_run = args["dry_run"].as_bool().unwrap_or(false);
+    let fuzz = args["fuzz"]
+        .as_u64()
+        .map_or(DEFAULT_FUZZ, |f| (f as usize).min(MAX_FUZZ));
+
+    let patches = parse_patches(patch_text);
+    if patches.is_empty() {
+        anyhow::bail!(
+            "apply_patch: no file headers (`--- a/…` / `+++ b/…` or `diff --git`) found in patch"
+        );
+    }
 
     let mut applied = Vec::new();
     let mut errors = Vec::new();
-
-    for file_patch in parse_patches(patch_text) {
-        let target = if file_patch.path.starts_with('/') {
-            std::path::PathBuf::from(&file_patch.path)
-        } else {
-            workspace.join(&file_patch.path)
-        };
-
-        // Security: ensure the target is inside the workspace
-        let canonical_ws = workspace
-            .canonicalize()
-            .unwrap_or_else(|_| workspace.to_path_buf());
-        let canonical_target = if target.exists() {
-            target.canonicalize().unwrap_or_else(|_| target.clone())
-        } else {
-            // For new files, check the parent
-            let parent = target.parent().unwrap_or(workspace);
-            let parent_canon = parent
-                .canonicalize()
-                .unwrap_or_else(|_| parent.to_path_buf());
-            parent_canon.join(target.file_name().unwrap_or_default())
-        };
-
-        if !canonical_target.starts_with(&canonical_ws) {
-            errors.push(serde_json::json!({
-                "file": file_patch.path,
-                "error": "path escapes workspace"
-            }));
-            continue;
-        }
-
-        match apply_file_patch(&target, &file_patch).await {
-            Ok(()) => applied.push(file_patch.path.clone()),
-            Err(e) => errors.push(serde_json::json!({
-                "file": file_patch.path,
-                "error": e.to_string()
-            })),
+    let mut files = Vec::new();
+
+    for file_patch in &patches {
+        match patch_file(workspace, file_patch, fuzz, dry_run).await {
+            Ok(report) => {
+                let failed = report.failed();
+                if failed == 0 {
+                    applied.push(file_patch.path.clone());
+                } else {
+                    errors.push(json!({
+                        "file": file_patch.path,
+                        "error": report.failure_message(dry_run),
+                    }));
+                }
+                files.push(report.to_json());
+            }
+            Err(e) => {
+                errors.push(json!({
+                    "file": file_patch.path,
+                    "error": e.to_string()
+                }));
+                files.push(json!({
+                    "path": file_patch.path,
+                    "action": file_patch.action(),
+                    "error": e.to_string(),
+                }));
+            }
         }
     }
 
-    Ok(serde_json::json!({
+    Ok(json!({
+        "dry_run": dry_run,
         "applied": applied,
         "errors": errors,
+        "files": files,
     }))
 }
 
+#[derive(Debug)]
 struct FilePatch {
+    /// File the patch writes (the old name for deletions).
     path: String,
+    /// Original name when the file is renamed.
+    rename_from: Option<String>,
     hunks: Vec<Hunk>,
     is_new_file: bool,
+    is_delete: bool,
 }
 
+impl FilePatch {
+    fn action(&self) -> &'static str {
+        if self.is_new_file {
+            "created"
+        } else if self.is_delete {
+            "deleted"
+        } else if self.rename_from.is_some() {
+            "renamed"
+        } else {
+            "modified"
+        }
+    }
+}
+
+#[derive(Debug)]
 struct Hunk {
-    old_start: usize,
-    // Lines prefixed with ' ' (context) or '-' (remove)
-    old_lines: Vec<String>,
-    // Lines prefixed with ' ' (context) or '+' (add)
-    new_lines: Vec<String>,
+    /// The `@@ … @@` line, kept for reject output.
+    header: String,
+    /// 0-based index the hunk's old lines should start at, when the header
+    /// gives line numbers.
+    expected: Option<usize>,
+    lines: Vec<HunkLine>,
+}
+
+#[derive(Debug)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+impl HunkLine {
+    fn old_text(&self) -> Option<&str> {
+        match self {
+            HunkLine::Context(l) | HunkLine::Remove(l) => Some(l),
+            HunkLine::Add(_) => None,
+        }
+    }
+
+    fn new_text(&self) -> Option<&str> {
+        match self {
+            HunkLine::Context(l) | HunkLine::Add(l) => Some(l),
+            HunkLine::Remove(_) => None,
+        }
+    }
+}
+
+impl Hunk {
+    fn leading_context(&self) -> usize {
+        self.lines
+            .iter()
+            .take_while(|l| matches!(l, HunkLine::Context(_)))
+            .count()
+    }
+
+    fn trailing_context(&self) -> usize {
+        self.lines
+            .iter()
+            .rev()
+            .take_while(|l| matches!(l, HunkLine::Context(_)))
+            .count()
+    }
+
+    /// The hunk in unified-diff form, for `.rej` output.
+    fn render(&self) -> String {
+        let mut out = format!("{}\n", self.header);
+        for line in &self.lines {
+            let (prefix, text) = match line {
+                HunkLine::Context(l) => (' ', l),
+                HunkLine::Remove(l) => ('-', l),
+                HunkLine::Add(l) => ('+', l),
+            };
+            out.push(prefix);
+            out.push_str(text);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// How one hunk fared.
+#[derive(Debug)]
+struct HunkOutcome {
+    applied: bool,
+    /// 1-based line the hunk was applied at.
+    line: usize,
+    /// Lines between where the header said the hunk was and where it matched.
+    offset: Option<isize>,
+    /// Context lines ignored at each end to make it match.
+    fuzz: usize,
+    /// Whether whitespace differences were ignored.
+    whitespace: bool,
+}
+
+impl HunkOutcome {
+    fn failed() -> Self {
+        Self {
+            applied: false,
+            line: 0,
+            offset: None,
+            fuzz: 0,
+            whitespace: false,
+        }
+    }
+
+    fn to_json(&self, index: usize) -> Value {
+        if !self.applied {
+            return json!({ "hunk": index + 1, "status": "failed" });
+        }
+        let mut out = json!({ "hunk": index + 1, "status": "applied", "line": self.line });
+        if let Some(offset) = self.offset.filter(|o| *o != 0) {
+            out["offset"] = json!(offset);
+        }
+        if self.fuzz > 0 {
+            out["fuzz"] = json!(self.fuzz);
+        }
+        if self.whitespace {
+            out["whitespace"] = json!(true);
+        }
+        out
+    }
+}
+
+struct FileReport {
+    path: String,
+    rename_from: Option<String>,
+    action: &'static str,
+    hunks: Vec<HunkOutcome>,
+    /// Failed hunks in unified-diff form.
+    reject: Option<String>,
+    /// Workspace path of the `.rej` file, when one was written.
+    reject_file: Option<String>,
+}
+
+impl FileReport {
+    fn failed(&self) -> usize {
+        self.hunks.iter().filter(|h| !h.applied).count()
+    }
+
+    fn failure_message(&self, dry_run: bool) -> String {
+        let mut msg = format!(
+            "{} of {} hunks did not match file content",
+            self.failed(),
+            self.hunks.len()
+        );
+        if self.action == "deleted" {
+            msg.push_str("; file not deleted");
+        }
+        if let Some(rej) = &self.reject_file {
+            msg.push_str(&format!("; rejected hunks saved to {rej}"));
+        } else if dry_run {
+            msg.push_str(" (dry run)");
+        }
+        msg
+    }
+
+    fn to_json(&self) -> Value {
+        let mut out = json!({
+            "path": self.path,
+            "action": self.action,
+            "hunks": self
+                .hunks
+                .iter()
+                .enumerate()
+                .map(|(i, h)| h.to_json(i))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(from) = &self.rename_from {
+            out["old_path"] = json!(from);
+        }
+        if let Some(reject) = &self.reject {
+            out["reject"] = json!(reject);
+        }
+        if let Some(file) = &self.reject_file {
+            out["reject_file"] = json!(file);
+        }
+        out
+    }
+}
+
+/// Paths a patch would write, for policy checks before applying it.
+/// Renamed and deleted files are included under their old names.
+pub(crate) fn patched_paths(text: &str) -> Vec<String> {
+    parse_patches(text)
+        .into_iter()
+        .flat_map(|p| p.rename_from.into_iter().chain(std::iter::once(p.path)))
+        .collect()
+}
+
+/// Strip a `---` / `+++` header down to its path: drop the `a/` or `b/`
+/// prefix and any trailing timestamp.  `None` for `/dev/null`.
+fn header_path(raw: &str, prefix: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or(raw).trim();
+    if raw == "/dev/null" {
+        return None;
+    }
+    Some(raw.strip_prefix(prefix).unwrap_or(raw).to_string())
+}
+
+fn is_file_header(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ "))
 }
 
 fn parse_patches(text: &str) -> Vec<FilePatch> {
@@ -78,167 +303,442 @@ fn parse_patches(text: &str) -> Vec<FilePatch> {
     let mut i = 0;
 
     while i < lines.len() {
-        // Find next file header
-        if lines[i].starts_with("--- ") && i + 1 < lines.len() && lines[i + 1].starts_with("+++ ") {
-            let old_path = lines[i]
-                .trim_start_matches("--- ")
-                .trim_start_matches("a/")
-                .trim();
-            let new_path = lines[i + 1]
-                .trim_start_matches("+++ ")
-                .trim_start_matches("b/")
-                .trim();
-
-            let is_new_file = old_path == "/dev/null";
-            let path = new_path.to_string();
-
-            i += 2;
-            let mut hunks = Vec::new();
-
-            // Parse hunks
-            while i < lines.len() && !lines[i].starts_with("--- ") {
-                if lines[i].starts_with("@@ ") {
-                    if let Some(old_start) = parse_hunk_header(lines[i]) {
-                        let mut old_lines = Vec::new();
-                        let mut new_lines = Vec::new();
-                        i += 1;
-
-                        while i < lines.len()
-                            && !lines[i].starts_with("@@ ")
-                            && !lines[i].starts_with("--- ")
-                        {
-                            let line = lines[i];
-                            if let Some(stripped) = line.strip_prefix('-') {
-                                old_lines.push(stripped.to_string());
-                            } else if let Some(stripped) = line.strip_prefix('+') {
-                                new_lines.push(stripped.to_string());
-                            } else if let Some(stripped) = line.strip_prefix(' ') {
-                                old_lines.push(stripped.to_string());
-                                new_lines.push(stripped.to_string());
-                            } else if line == "\\ No newline at end of file" {
-                                // skip
-                            } else {
-                                // Treat as context line (some diffs omit the leading space)
-                                old_lines.push(line.to_string());
-                                new_lines.push(line.to_string());
-                            }
-                            i += 1;
-                        }
-
-                        hunks.push(Hunk {
-                            old_start,
-                            old_lines,
-                            new_lines,
-                        });
-                    } else {
-                        i += 1;
-                    }
-                } else {
-                    i += 1;
+        let mut old_path = None;
+        let mut new_path = None;
+        let mut rename_from = None;
+        let mut rename_to = None;
+        let mut is_new_file = false;
+        let mut is_delete = false;
+
+        if let Some(rest) = lines[i].strip_prefix("diff --git ") {
+            // `diff --git a/old b/new` followed by extended headers; pure
+            // renames and deletions of empty files have no `---` / `+++`.
+            if let Some((a, b)) = rest.strip_prefix("a/").and_then(|r| r.rsplit_once(" b/")) {
+                old_path = Some(a.to_string());
+                new_path = Some(b.to_string());
+            }
+            i += 1;
+            while i < lines.len()
+                && !lines[i].starts_with("diff --git ")
+                && !lines[i].starts_with("@@")
+                && !is_file_header(&lines, i)
+            {
+                let line = lines[i];
+                if let Some(from) = line.strip_prefix("rename from ") {
+                    rename_from = Some(from.trim().to_string());
+                } else if let Some(to) = line.strip_prefix("rename to ") {
+                    rename_to = Some(to.trim().to_string());
+                } else if line.starts_with("deleted file mode") {
+                    is_delete = true;
+                } else if line.starts_with("new file mode") {
+                    is_new_file = true;
                 }
+                i += 1;
             }
-
-            patches.push(FilePatch {
-                path,
-                hunks,
-                is_new_file,
-            });
+            if i < lines.len() && is_file_header(&lines, i) {
+                let old = header_path(&lines[i][4..], "a/");
+                let new = header_path(&lines[i + 1][4..], "b/");
+                is_new_file |= old.is_none();
+                is_delete |= new.is_none();
+                old_path = old.or(old_path);
+                new_path = new.or(new_path);
+                i += 2;
+            }
+        } else if is_file_header(&lines, i) {
+            old_path = header_path(&lines[i][4..], "a/");
+            new_path = header_path(&lines[i + 1][4..], "b/");
+            is_new_file = old_path.is_none();
+            is_delete = new_path.is_none();
+            i += 2;
         } else {
             i += 1;
+            continue;
         }
+
+        let hunks = parse_hunks(&lines, &mut i);
+
+        let old_path = rename_from.or(old_path);
+        let new_path = rename_to.or(new_path);
+        let Some(path) = (if is_delete {
+            old_path.clone()
+        } else {
+            new_path.clone()
+        }) else {
+            continue;
+        };
+        let rename_from = match (&old_path, &new_path) {
+            (Some(old), Some(new)) if !is_new_file && !is_delete && old != new => Some(old.clone()),
+            _ => None,
+        };
+
+        patches.push(FilePatch {
+            path,
+            rename_from,
+            hunks,
+            is_new_file,
+            is_delete,
+        });
     }
 
     patches
 }
 
-/// Parse "@@ -old_start,old_count +new_start,new_count @@"
+/// Parse the hunks following a file header, leaving `i` at the next header.
+fn parse_hunks(lines: &[&str], i: &mut usize) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+
+    while *i < lines.len() && !lines[*i].starts_with("diff --git ") && !is_file_header(lines, *i) {
+        if !lines[*i].starts_with("@@") {
+            *i += 1;
+            continue;
+        }
+        let header = lines[*i].to_string();
+        let expected = parse_hunk_header(&header);
+        let mut hunk_lines = Vec::new();
+        // Unprefixed blank lines at the end of a hunk separate it from the
+        // next file's diff; they are not context.
+        let mut trailing_blanks = 0;
+        *i += 1;
+
+        while *i < lines.len()
+            && !lines[*i].starts_with("@@")
+            && !lines[*i].starts_with("diff --git ")
+            && !is_file_header(lines, *i)
+        {
+            let line = lines[*i];
+            trailing_blanks = if line.is_empty() {
+                trailing_blanks + 1
+            } else {
+                0
+            };
+            if let Some(stripped) = line.strip_prefix('-') {
+                hunk_lines.push(HunkLine::Remove(stripped.to_string()));
+            } else if let Some(stripped) = line.strip_prefix('+') {
+                hunk_lines.push(HunkLine::Add(stripped.to_string()));
+            } else if let Some(stripped) = line.strip_prefix(' ') {
+                hunk_lines.push(HunkLine::Context(stripped.to_string()));
+            } else if line.starts_with('\\') {
+                // "\ No newline at end of file"
+            } else {
+                // Treat as context line (some diffs omit the leading space)
+                hunk_lines.push(HunkLine::Context(line.to_string()));
+            }
+            *i += 1;
+        }
+        hunk_lines.truncate(hunk_lines.len() - trailing_blanks);
+
+        hunks.push(Hunk {
+            header,
+            expected,
+            lines: hunk_lines,
+        });
+    }
+
+    hunks
+}
+
+/// Parse "@@ -old_start,old_count +new_start,new_count @@" into the 0-based
+/// index the old lines start at.  A hunk that removes nothing inserts
+/// *after* `old_start`.
 fn parse_hunk_header(line: &str) -> Option<usize> {
     let after_at = line.strip_prefix("@@ -")?;
-    let comma_or_space = after_at.find([',', ' '])?;
-    after_at[..comma_or_space].parse::<usize>().ok()
+    let range = &after_at[..after_at.find(' ')?];
+    let (start, count) = match range.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (range.parse::<usize>().ok()?, 1),
+    };
+    Some(if count == 0 {
+        start
+    } else {
+        start.saturating_sub(1)
+    })
 }
 
-async fn apply_file_patch(target: &std::path::Path, patch: &FilePatch) -> anyhow::Result<()> {
+/// Resolve a patch path and make sure it stays inside the workspace.
+fn resolve(workspace: &Path, raw: &str) -> anyhow::Result<PathBuf> {
+    let target = Path::new(raw);
+    if target.components().any(|c| c == Component::ParentDir) {
+        anyhow::bail!("path escapes workspace");
+    }
+    let target = if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        workspace.join(target)
+    };
+    let canonical_ws = workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf());
+    let canonical_target = canon_or_resolve(&target)?;
+    if !canonical_target.starts_with(&canonical_ws) || canonical_target == canonical_ws {
+        anyhow::bail!("path escapes workspace");
+    }
+    Ok(canonical_target)
+}
+
+async fn patch_file(
+    workspace: &Path,
+    patch: &FilePatch,
+    fuzz: usize,
+    dry_run: bool,
+) -> anyhow::Result<FileReport> {
+    let target = resolve(workspace, &patch.path)?;
+    let source = match &patch.rename_from {
+        Some(from) => resolve(workspace, from)?,
+        None => target.clone(),
+    };
+    let mut report = FileReport {
+        path: patch.path.clone(),
+        rename_from: patch.rename_from.clone(),
+        action: patch.action(),
+        hunks: Vec::new(),
+        reject: None,
+        reject_file: None,
+    };
+
     if patch.is_new_file {
-        if let Some(parent) = target.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+        if target.is_dir() {
+            anyhow::bail!("{} is a directory", patch.path);
         }
         let content: String = patch
             .hunks
             .iter()
-            .flat_map(|h| h.new_lines.iter())
+            .flat_map(|h| h.lines.iter().filter_map(HunkLine::new_text))
             .map(|l| format!("{l}\n"))
             .collect();
-        tokio::fs::write(target, content).await?;
-        return Ok(());
+        report.hunks = patch
+            .hunks
+            .iter()
+            .map(|_| HunkOutcome {
+                applied: true,
+                line: 1,
+                offset: None,
+                fuzz: 0,
+                whitespace: false,
+            })
+            .collect();
+        if !dry_run {
+            crate::journal::record(workspace, &target, "apply_patch").await;
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&target, content).await?;
+        }
+        return Ok(report);
     }
 
-    let original = tokio::fs::read_to_string(target)
+    let original = tokio::fs::read_to_string(&source)
         .await
-        .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", target.display()))?;
-
-    let mut lines: Vec<String> = original.lines().map(String::from).collect();
+        .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", source.display()))?;
+    if source != target && target.exists() {
+        anyhow::bail!("cannot rename to {}: it already exists", patch.path);
+    }
 
-    // Apply hunks in reverse order so line numbers remain valid
-    let mut hunks: Vec<&Hunk> = patch.hunks.iter().collect();
-    hunks.sort_by(|a, b| b.old_start.cmp(&a.old_start));
+    let (patched, outcomes) = apply_hunks(&original, &patch.hunks, fuzz);
+    report.hunks = outcomes;
+    let failed: Vec<&Hunk> = patch
+        .hunks
+        .iter()
+        .zip(&report.hunks)
+        .filter(|(_, o)| !o.applied)
+        .map(|(h, _)| h)
+        .collect();
+
+    if patch.is_delete && failed.is_empty() && !patched.trim().is_empty() {
+        anyhow::bail!(
+            "{} has content the patch does not remove; file not deleted",
+            patch.path
+        );
+    }
 
-    for hunk in hunks {
-        let start = if hunk.old_start == 0 {
-            0
+    if !failed.is_empty() {
+        let old_name = patch.rename_from.as_deref().unwrap_or(&patch.path);
+        let new_name = if patch.is_delete {
+            "/dev/null".to_string()
         } else {
-            hunk.old_start - 1
+            format!("b/{}", patch.path)
         };
-        let old_count = hunk.old_lines.len();
+        let mut reject = format!("--- a/{old_name}\n+++ {new_name}\n");
+        for hunk in &failed {
+            reject.push_str(&hunk.render());
+        }
+        if !dry_run {
+            let rej_path = PathBuf::from(format!("{}.rej", target.display()));
+            crate::journal::record(workspace, &rej_path, "apply_patch").await;
+            tokio::fs::write(&rej_path, &reject).await?;
+            report.reject_file = Some(format!("{}.rej", patch.path));
+        }
+        report.reject = Some(reject);
+    }
 
-        let pos = find_hunk_position(&lines, &hunk.old_lines, start);
+    if dry_run {
+        return Ok(report);
+    }
 
-        match pos {
-            Some(actual_start) => {
-                let end = actual_start + old_count;
-                let clamped_end = end.min(lines.len());
-                lines.splice(actual_start..clamped_end, hunk.new_lines.iter().cloned());
-            }
-            None => {
-                return Err(anyhow::anyhow!(
-                    "hunk at line {} did not match file content",
-                    hunk.old_start
-                ));
+    if patch.is_delete {
+        if failed.is_empty() {
+            crate::journal::record(workspace, &source, "apply_patch").await;
+            tokio::fs::remove_file(&source).await?;
+        }
+        return Ok(report);
+    }
+
+    if source != target {
+        crate::journal::record(workspace, &source, "apply_patch").await;
+        crate::journal::record(workspace, &target, "apply_patch").await;
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(&source, &target).await?;
+    } else {
+        crate::journal::record(workspace, &target, "apply_patch").await;
+    }
+    if patched != original {
+        tokio::fs::write(&target, patched).await?;
+    }
+    Ok(report)
+}
+
+/// Where, and how loosely, a hunk matched.
+struct Match {
+    /// Index of the first file line matched.
+    start: usize,
+    /// Context lines ignored at the start and end of the hunk.
+    lead: usize,
+    trail: usize,
+    whitespace: bool,
+}
+
+/// Apply `hunks` to `original` in order, returning the new text and how
+/// each hunk fared.  Hunks that do not match are skipped.
+fn apply_hunks(original: &str, hunks: &[Hunk], max_fuzz: usize) -> (String, Vec<HunkOutcome>) {
+    let newline = if original.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = original.lines().map(String::from).collect();
+    let mut outcomes = Vec::with_capacity(hunks.len());
+    // Lines added minus lines removed by the hunks applied so far.
+    let mut delta: isize = 0;
+    // End of the last applied hunk, where a hunk without line numbers is
+    // looked for first.
+    let mut cursor = 0;
+
+    for hunk in hunks {
+        let expected = match hunk.expected {
+            Some(start) => (start as isize + delta).max(0) as usize,
+            None => cursor,
+        };
+        let Some(m) = locate(&lines, hunk, expected, max_fuzz) else {
+            outcomes.push(HunkOutcome::failed());
+            continue;
+        };
+
+        let body = &hunk.lines[m.lead..hunk.lines.len() - m.trail];
+        let mut replacement = Vec::new();
+        let mut k = m.start;
+        for line in body {
+            match line {
+                // Keep the file's own version of context lines.
+                HunkLine::Context(_) => {
+                    replacement.push(lines[k].clone());
+                    k += 1;
+                }
+                HunkLine::Remove(_) => k += 1,
+                HunkLine::Add(l) => replacement.push(l.clone()),
             }
         }
+        let removed = k - m.start;
+        let added = replacement.len();
+        lines.splice(m.start..k, replacement);
+
+        outcomes.push(HunkOutcome {
+            applied: true,
+            line: m.start + 1,
+            offset: hunk
+                .expected
+                .map(|_| m.start as isize - (expected + m.lead) as isize),
+            fuzz: m.lead.max(m.trail),
+            whitespace: m.whitespace,
+        });
+        delta += added as isize - removed as isize;
+        cursor = m.start + added;
     }
 
-    let mut result = lines.join("\n");
-    if original.ends_with('\n') && !result.ends_with('\n') {
-        result.push('\n');
+    let mut result = lines.join(newline);
+    if (original.ends_with('\n') || original.is_empty()) && !result.is_empty() {
+        result.push_str(newline);
     }
+    (result, outcomes)
+}
 
-    tokio::fs::write(target, result).await?;
-    Ok(())
+/// Find the best place for `hunk`: full context before fuzz, exact text
+/// before whitespace-insensitive, nearest to `expected` first.
+fn locate(file_lines: &[String], hunk: &Hunk, expected: usize, max_fuzz: usize) -> Option<Match> {
+    let has_old = hunk.lines.iter().any(|l| l.old_text().is_some());
+    let (leading, trailing) = (hunk.leading_context(), hunk.trailing_context());
+    let mut tried = None;
+
+    for fuzz in 0..=max_fuzz {
+        let lead = leading.min(fuzz);
+        let trail = trailing.min(fuzz);
+        if lead + trail >= hunk.lines.len() && fuzz > 0 {
+            break;
+        }
+        if tried == Some((lead, trail)) {
+            continue;
+        }
+        tried = Some((lead, trail));
+
+        let old: Vec<&str> = hunk.lines[lead..hunk.lines.len() - trail]
+            .iter()
+            .filter_map(HunkLine::old_text)
+            .collect();
+        if has_old && old.is_empty() {
+            break;
+        }
+        for whitespace in [false, true] {
+            if let Some(start) = find_hunk_position(file_lines, &old, expected + lead, whitespace) {
+                return Some(Match {
+                    start,
+                    lead,
+                    trail,
+                    whitespace,
+                });
+            }
+        }
+    }
+
+    None
 }
 
-/// Find where a hunk's old lines actually appear in the file.
-/// Tries the expected position first, then searches ±50 lines.
+/// Find where a hunk's old lines actually appear in the file, trying the
+/// expected position first and then moving outward.
 fn find_hunk_position(
     file_lines: &[String],
-    old_lines: &[String],
+    old_lines: &[&str],
     expected: usize,
+    whitespace: bool,
 ) -> Option<usize> {
     if old_lines.is_empty() {
         return Some(expected.min(file_lines.len()));
     }
-
-    if matches_at(file_lines, old_lines, expected) {
-        return Some(expected);
+    if old_lines.len() > file_lines.len() {
+        return None;
     }
 
-    for offset in 1..=50 {
-        if expected + offset < file_lines.len()
-            && matches_at(file_lines, old_lines, expected + offset)
+    let last = file_lines.len() - old_lines.len();
+    let expected = expected.min(last);
+    for offset in 0..=last {
+        if expected + offset <= last
+            && matches_at(file_lines, old_lines, expected + offset, whitespace)
         {
             return Some(expected + offset);
         }
-        if offset <= expected && matches_at(file_lines, old_lines, expected - offset) {
+        if offset > 0
+            && offset <= expected
+            && matches_at(file_lines, old_lines, expected - offset, whitespace)
+        {
             return Some(expected - offset);
         }
     }
@@ -246,31 +746,206 @@ fn find_hunk_position(
     None
 }
 
-fn matches_at(file_lines: &[String], old_lines: &[String], start: usize) -> bool {
-    if start + old_lines.len() > file_lines.len() {
-        return false;
-    }
-    for (i, old) in old_lines.iter().enumerate() {
-        if file_lines[start + i].trim_end() != old.trim_end() {
-            return false;
+fn matches_at(file_lines: &[String], old_lines: &[&str], start: usize, whitespace: bool) -> bool {
+    old_lines.iter().enumerate().all(|(i, old)| {
+        let line = &file_lines[start + i];
+        if whitespace {
+            line.split_whitespace().eq(old.split_whitespace())
+        } else {
+            line.trim_end() == old.trim_end()
         }
-    }
-    true
+    })
 }
 
 pub fn register() {
     register_tool(ToolMeta {
         name: "apply_patch".into(),
-        description: "Apply a unified diff patch to one or more files. Useful for bulk multi-file edits. Provide a standard unified diff (as produced by `diff -u` or `git diff`).".into(),
+        description: "Apply a unified diff patch to one or more files. Useful for bulk multi-file edits. Provide a standard unified diff (as produced by `diff -u` or `git diff`); creating, deleting (`+++ /dev/null`) and renaming (`rename from`/`rename to`) files is supported. Hunks are matched even when line numbers are off, whitespace differs or a little context is wrong; hunks that still fail are returned (and saved to <file>.rej) while the rest are applied. Use `dry_run` to check a patch first.".into(),
         args_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "patch": {
                     "type": "string",
                     "description": "Unified diff patch text (supports multi-file patches)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Report how each hunk would apply without changing any files (default false)."
+                },
+                "fuzz": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": MAX_FUZZ,
+                    "description": "Context lines that may be ignored at each end of a hunk when it does not match exactly (default 2; 0 requires all context)."
                 }
             },
             "required": ["patch"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(original: &str, patch: &str, fuzz: usize) -> (String, Vec<HunkOutcome>) {
+        let patches = parse_patches(patch);
+        apply_hunks(original, &patches[0].hunks, fuzz)
+    }
+
+    #[test]
+    fn hunks_apply_despite_offsets_whitespace_and_bad_context() {
+        let original = "header\n\nfn main() {\n    let x = 1;\n    println!(\"{x}\");\n}\n";
+
+        // Line numbers off by two, no line numbers at all, and tabs vs spaces.
+        for header in ["@@ -1,3 +1,3 @@", "@@"] {
+            let patch = format!(
+                "--- a/main.rs\n+++ b/main.rs\n{header}\n fn main() {{\n-    let x = 1;\n+    let x = 2;\n     println!(\"{{x}}\");\n"
+            );
+            let (out, outcomes) = apply(original, &patch, 0);
+            assert!(out.contains("let x = 2;"), "{header}: {out}");
+            assert_eq!(outcomes[0].line, 3);
+        }
+        let (_, outcomes) = apply(
+            original,
+            "--- a/m\n+++ b/m\n@@ -1,3 +1,3 @@\n fn main() {\n-    let x = 1;\n+    let x = 2;\n",
+            0,
+        );
+        assert_eq!(outcomes[0].offset, Some(2));
+
+        let (out, outcomes) = apply(
+            original,
+            "--- a/m\n+++ b/m\n@@ -3,3 +3,3 @@\n fn main() {\n-\tlet x = 1;\n+\tlet x = 2;\n \tprintln!(\"{x}\");\n",
+            0,
+        );
+        assert!(outcomes[0].whitespace);
+        // The file's own indentation is kept for context lines.
+        assert!(out.contains("    println!"), "{out}");
+        assert!(out.contains("\tlet x = 2;"), "{out}");
+
+        // Wrong trailing context only applies with fuzz.
+        let patch = "--- a/m\n+++ b/m\n@@ -3,3 +3,3 @@\n fn main() {\n-    let x = 1;\n+    let x = 2;\n     eprintln!(\"{x}\");\n";
+        assert!(!apply(original, patch, 0).1[0].applied);
+        let (out, outcomes) = apply(original, patch, 2);
+        assert_eq!(outcomes[0].fuzz, 1);
+        assert!(out.contains("let x = 2;\n    println!"), "{out}");
+    }
+
+    #[test]
+    fn parses_rename_delete_and_new_file_headers() {
+        let patch = "\
+diff --git a/old.txt b/new.txt
+similarity index 90%
+rename from old.txt
+rename to new.txt
+--- a/old.txt
++++ b/new.txt
+@@ -1 +1 @@
+-one
++uno
+
+diff --git a/moved.txt b/dir/moved.txt
+similarity index 100%
+rename from moved.txt
+rename to dir/moved.txt
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-a
+-b
+--- /dev/null
++++ b/fresh.txt\t2024-01-01 00:00:00
+@@ -0,0 +1 @@
++hello
+";
+        let patches = parse_patches(patch);
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0].action(), "renamed");
+        assert_eq!(patches[0].rename_from.as_deref(), Some("old.txt"));
+        assert_eq!(patches[0].hunks[0].lines.len(), 2);
+        assert_eq!(patches[1].path, "dir/moved.txt");
+        assert!(patches[1].hunks.is_empty());
+        assert_eq!(patches[2].action(), "deleted");
+        assert_eq!(patches[2].path, "gone.txt");
+        assert_eq!(patches[3].action(), "created");
+        assert_eq!(patches[3].path, "fresh.txt");
+
+        assert_eq!(
+            patched_paths(patch),
+            [
+                "old.txt",
+                "new.txt",
+                "moved.txt",
+                "dir/moved.txt",
+                "gone.txt",
+                "fresh.txt"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_and_failures_are_rejected() {
+        let ws = tempfile::tempdir().unwrap();
+        std::fs::write(ws.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(ws.path().join("old.txt"), "keep\n").unwrap();
+        std::fs::write(ws.path().join("gone.txt"), "bye\n").unwrap();
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+-one
++ONE
+ two
+@@ -3 +3 @@
+-missing
++nope
+diff --git a/old.txt b/new.txt
+rename from old.txt
+rename to new.txt
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let result = apply_patch(ws.path(), json!({ "patch": patch, "dry_run": true }))
+            .await
+            .unwrap();
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(result["applied"], json!(["new.txt", "gone.txt"]));
+        assert_eq!(result["files"][0]["hunks"][0]["status"], "applied");
+        assert_eq!(result["files"][0]["hunks"][1]["status"], "failed");
+        assert!(result["files"][0]["reject"]
+            .as_str()
+            .unwrap()
+            .contains("-missing\n+nope"));
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+        assert!(ws.path().join("gone.txt").exists());
+        assert!(!ws.path().join("a.txt.rej").exists());
+
+        let result = apply_patch(ws.path(), json!({ "patch": patch }))
+            .await
+            .unwrap();
+        assert_eq!(result["errors"][0]["file"], "a.txt");
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("a.txt")).unwrap(),
+            "ONE\ntwo\nthree\n"
+        );
+        let rej = std::fs::read_to_string(ws.path().join("a.txt.rej")).unwrap();
+        assert!(rej.starts_with("--- a/a.txt\n+++ b/a.txt\n@@ -3 +3 @@\n-missing"));
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("new.txt")).unwrap(),
+            "keep\n"
+        );
+        assert!(!ws.path().join("old.txt").exists());
+        assert!(!ws.path().join("gone.txt").exists());
+    }
+}
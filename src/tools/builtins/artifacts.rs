@@ -0,0 +1,467 @@
+//! Artifact store for oversized tool results, plus the `read_artifact`
+//! tool that pages through and searches them.
+//!
+//! Tool output larger than
+//! [`MAX_TOOL_RESULT_BYTES`](crate::agent::types::MAX_TOOL_RESULT_BYTES)
+//! is written to
+//! `<workspace>/.artifacts/<id>.txt`, where `id` is a prefix of the
+//! content's SHA-256, so identical outputs share one file.  The model
+//! receives a head/tail summary with the artifact id instead of a
+//! truncated blob, and the id is recorded on the turn's `ToolCallRecord`
+//! so the web UI can fetch the full output.
+
+use regex::RegexBuilder;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use crate::tools::{register_tool, ToolMeta};
+use crate::utils::truncate_str;
+
+/// Workspace-relative directory holding artifacts (hidden from
+/// `list_files` / `search_files`).
+pub const ARTIFACT_DIR: &str = ".artifacts";
+/// Artifacts untouched for this long are pruned when a new one is stored.
+const RETENTION_SECS: u64 = 7 * 24 * 3600;
+/// Bytes of the original output shown at the start of the summary.
+const SUMMARY_HEAD_BYTES: usize = 4000;
+/// Bytes of the original output shown at the end of the summary.
+const SUMMARY_TAIL_BYTES: usize = 1500;
+/// Largest page `read_artifact` returns, kept well under the result cap so
+/// reading an artifact never produces another one.
+const MAX_PAGE_BYTES: usize = 12_000;
+const DEFAULT_LINES: usize = 200;
+const DEFAULT_MATCHES: usize = 50;
+const MAX_MATCHES: usize = 200;
+const MAX_LINE_CHARS: usize = 400;
+
+/// Why an artifact could not be loaded.  Carried inside the
+/// `anyhow::Error` so the gateway can map it to a status without string
+/// matching.
+#[derive(Debug)]
+pub enum ArtifactError {
+    /// The id is not 16 hex characters.
+    InvalidId(String),
+    /// No artifact with this id is stored (or it has been pruned).
+    NotFound(String),
+}
+
+impl std::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidId(id) => {
+                write!(f, "invalid artifact id '{id}' (expected 16 hex characters)")
+            }
+            Self::NotFound(id) => write!(f, "artifact '{id}' not found"),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+/// Content-addressed id: the first 16 hex chars of the SHA-256 digest.
+pub fn artifact_id(content: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, content.as_bytes());
+    digest.as_ref()[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn artifact_path(workspace: &Path, id: &str) -> anyhow::Result<PathBuf> {
+    if id.len() != 16 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ArtifactError::InvalidId(id.to_string()).into());
+    }
+    Ok(workspace
+        .join(ARTIFACT_DIR)
+        .join(format!("{}.txt", id.to_ascii_lowercase())))
+}
+
+/// Persist `content` and return its id.  Storing the same content twice
+/// is a no-op apart from refreshing its timestamp.
+pub fn store(workspace: &Path, content: &str) -> anyhow::Result<String> {
+    let id = artifact_id(content);
+    let path = artifact_path(workspace, &id)?;
+    let dir = workspace.join(ARTIFACT_DIR);
+    std::fs::create_dir_all(&dir)?;
+    prune(&dir);
+    if path.exists() {
+        // Touch so retention counts from the latest use.
+        let _ = std::fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(std::time::SystemTime::now()));
+    } else {
+        let tmp = dir.join(format!("{id}.tmp"));
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)?;
+    }
+    Ok(id)
+}
+
+/// Load an artifact's full content.
+pub fn load(workspace: &Path, id: &str) -> anyhow::Result<String> {
+    let path = artifact_path(workspace, id)?;
+    std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ArtifactError::NotFound(id.to_string()).into(),
+        _ => anyhow::anyhow!("cannot read artifact '{id}': {e}"),
+    })
+}
+
+fn prune(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = std::time::SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| now.duration_since(t).ok())
+            .is_some_and(|age| age.as_secs() > RETENTION_SECS);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Render a tool result as text for storage.  String fields are written
+/// out verbatim under a `## key` heading so multi-line output (shell
+/// stdout, file contents, page text) stays line-addressable; anything
+/// else is pretty-printed JSON.
+pub fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Object(map) if map.values().any(Value::is_string) => {
+            let mut out = String::new();
+            for (key, v) in map {
+                match v {
+                    Value::String(s) => {
+                        out.push_str(&format!("## {key}\n{s}"));
+                        if !s.ends_with('\n') {
+                            out.push('\n');
+                        }
+                    }
+                    other => out.push_str(&format!(
+                        "## {key}\n{}\n",
+                        serde_json::to_string_pretty(other).unwrap_or_default()
+                    )),
+                }
+            }
+            out
+        }
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+/// Build the tool result the model sees in place of an oversized output.
+pub fn summary(id: &str, tool: &str, content: &str) -> Value {
+    let total_lines = content.lines().count();
+    let head = prefix(content, SUMMARY_HEAD_BYTES);
+    let tail = suffix(content, SUMMARY_TAIL_BYTES);
+    json!({
+        "artifact_id": id,
+        "bytes": content.len(),
+        "lines": total_lines,
+        "head": head,
+        "tail": tail,
+        "note": format!(
+            "The `{tool}` result was {} bytes, too large to include. Only the head and tail are \
+             shown; the full output is stored as artifact '{id}'. Use read_artifact with \
+             `start_line`/`end_line`, `offset`/`limit` or `pattern` to read the parts you need.",
+            content.len()
+        ),
+    })
+}
+
+fn prefix(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn suffix(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return "";
+    }
+    let mut start = s.len() - max;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    &s[start..]
+}
+
+/// Read part of a stored artifact.
+///
+/// Args: `{ "id": "…", "start_line?": N, "end_line?": N }`,
+///       `{ "id": "…", "offset": N, "limit?": N }` or
+///       `{ "id": "…", "pattern": "…", "literal?": bool, "ignore_case?": bool,
+///          "context?": N, "max_matches?": N }`
+pub async fn read_artifact(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let id = args
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("read_artifact: missing `id` argument"))?;
+    let content = load(workspace, id).map_err(|e| anyhow::anyhow!("read_artifact: {e}"))?;
+
+    if let Some(pattern) = args.get("pattern").and_then(Value::as_str) {
+        return search(id, &content, pattern, &args);
+    }
+    if let Some(offset) = args.get("offset").and_then(Value::as_u64) {
+        let limit = arg_usize(&args, "limit", MAX_PAGE_BYTES).clamp(1, MAX_PAGE_BYTES);
+        return Ok(byte_page(id, &content, offset as usize, limit));
+    }
+    line_page(id, &content, &args)
+}
+
+fn line_page(id: &str, content: &str, args: &Value) -> anyhow::Result<Value> {
+    let lines: Vec<&str> = content.lines().collect();
+    let total = lines.len();
+    let start = arg_usize(args, "start_line", 1).max(1);
+    if start > total.max(1) {
+        anyhow::bail!("read_artifact: start_line {start} out of bounds ({total} lines)");
+    }
+    let end = arg_usize(args, "end_line", start + DEFAULT_LINES - 1).clamp(start, total.max(1));
+
+    let mut out = String::new();
+    let mut last = start - 1;
+    for line in lines.iter().take(end).skip(start - 1) {
+        if out.len() + line.len() + 1 > MAX_PAGE_BYTES {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+        last += 1;
+    }
+    if last < start && start <= total {
+        // A single line longer than the page: return its first slice.
+        out = prefix(lines[start - 1], MAX_PAGE_BYTES).to_string();
+        last = start;
+    }
+
+    let mut result = json!({
+        "artifact_id": id,
+        "content": out,
+        "start_line": start,
+        "end_line": last,
+        "total_lines": total,
+        "bytes": content.len(),
+    });
+    if last < total {
+        result["next_start_line"] = json!(last + 1);
+    }
+    Ok(result)
+}
+
+fn byte_page(id: &str, content: &str, offset: usize, limit: usize) -> Value {
+    let mut start = offset.min(content.len());
+    while !content.is_char_boundary(start) {
+        start += 1;
+    }
+    let page = prefix(&content[start..], limit);
+    let end = start + page.len();
+    let mut result = json!({
+        "artifact_id": id,
+        "content": page,
+        "offset": start,
+        "end_offset": end,
+        "bytes": content.len(),
+    });
+    if end < content.len() {
+        result["next_offset"] = json!(end);
+    }
+    result
+}
+
+fn search(id: &str, content: &str, pattern: &str, args: &Value) -> anyhow::Result<Value> {
+    let literal = args
+        .get("literal")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let source = if literal {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    let re = RegexBuilder::new(&source)
+        .case_insensitive(
+            args.get("ignore_case")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        )
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| anyhow::anyhow!("read_artifact: invalid regex: {e}"))?;
+    let context = arg_usize(args, "context", 0).min(10);
+    let max = arg_usize(args, "max_matches", DEFAULT_MATCHES).clamp(1, MAX_MATCHES);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut matches = Vec::new();
+    let mut total = 0usize;
+    let mut used = 0usize;
+    for (idx, line) in lines.iter().enumerate() {
+        if !re.is_match(line) {
+            continue;
+        }
+        total += 1;
+        if matches.len() >= max || used > MAX_PAGE_BYTES {
+            continue;
+        }
+        let mut hit = json!({ "line": idx + 1, "text": truncate_str(line, MAX_LINE_CHARS) });
+        if context > 0 {
+            let before: Vec<String> = lines[idx.saturating_sub(context)..idx]
+                .iter()
+                .map(|l| truncate_str(l, MAX_LINE_CHARS))
+                .collect();
+            let after: Vec<String> = lines[idx + 1..(idx + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| truncate_str(l, MAX_LINE_CHARS))
+                .collect();
+            hit["before"] = json!(before);
+            hit["after"] = json!(after);
+        }
+        used += hit.to_string().len();
+        matches.push(hit);
+    }
+    let mut result = json!({
+        "artifact_id": id,
+        "matches": matches,
+        "total_matches": total,
+        "total_lines": lines.len(),
+    });
+    if total > matches.len() {
+        result["truncated"] = json!(true);
+    }
+    Ok(result)
+}
+
+fn arg_usize(args: &Value, key: &str, default: usize) -> usize {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(default)
+}
+
+/// Register the `read_artifact` tool metadata.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "read_artifact".into(),
+        description: "Read a stored artifact — the full output of an earlier tool call that was too large to return inline. Page by line range or byte offset, or search it with a regex.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Artifact id from the oversized tool result."
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "1-based first line to return (default 1)."
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "1-based last line to return (default start_line + 199). Pages are capped at ~12 KB."
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Byte offset to read from instead of a line range."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Bytes to read from `offset` (max 12000)."
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex (or plain text with literal=true) to search for; returns matching lines with line numbers."
+                },
+                "literal": {
+                    "type": "boolean",
+                    "description": "Treat `pattern` as plain text. Default: false."
+                },
+                "ignore_case": {
+                    "type": "boolean",
+                    "description": "Case-insensitive search. Default: false."
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context around each match (max 10)."
+                },
+                "max_matches": {
+                    "type": "integer",
+                    "description": "Maximum matches returned (max 200). Default: 50."
+                }
+            },
+            "required": ["id"],
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::types::MAX_TOOL_RESULT_BYTES;
+
+    #[tokio::test]
+    async fn store_summarize_and_page() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        let content: String = (1..=3000).map(|i| format!("line {i}\n")).collect();
+        assert!(content.len() > MAX_TOOL_RESULT_BYTES);
+
+        let id = store(ws, &content).unwrap();
+        assert_eq!(store(ws, &content).unwrap(), id);
+        assert!(artifact_path(ws, &id).unwrap().exists());
+
+        let s = summary(&id, "exec_shell", &content);
+        assert_eq!(s["lines"], 3000);
+        assert!(s["head"].as_str().unwrap().starts_with("line 1\n"));
+        assert!(s["tail"].as_str().unwrap().ends_with("line 3000\n"));
+        assert!(s.to_string().len() < MAX_TOOL_RESULT_BYTES);
+
+        let page = read_artifact(ws, json!({"id": id, "start_line": 10, "end_line": 12}))
+            .await
+            .unwrap();
+        assert_eq!(page["content"], "line 10\nline 11\nline 12\n");
+        assert_eq!(page["next_start_line"], 13);
+
+        let bytes = read_artifact(ws, json!({"id": id, "offset": 0, "limit": 7}))
+            .await
+            .unwrap();
+        assert_eq!(bytes["content"], "line 1\n");
+        assert_eq!(bytes["next_offset"], 7);
+
+        let found = read_artifact(ws, json!({"id": id, "pattern": "^line 29\\d\\d$"}))
+            .await
+            .unwrap();
+        assert_eq!(found["total_matches"], 100);
+        assert_eq!(found["matches"].as_array().unwrap().len(), DEFAULT_MATCHES);
+        assert_eq!(found["matches"][0]["line"], 2900);
+    }
+
+    #[test]
+    fn render_keeps_lines() {
+        let text = render(&json!({"exit_code": 0, "stdout": "a\nb\n"}));
+        assert_eq!(text, "## exit_code\n0\n## stdout\na\nb\n");
+        assert_eq!(render(&json!([1])), "[\n  1\n]");
+    }
+
+    #[test]
+    fn rejects_bad_ids() {
+        let ws = Path::new("/tmp");
+        assert!(artifact_path(ws, "../../etc/passwd").is_err());
+        assert!(artifact_path(ws, "0123456789abcdeg").is_err());
+        let err = load(ws, "0123456789abcdef").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArtifactError>(),
+            Some(ArtifactError::NotFound(_))
+        ));
+    }
+}
@@ -117,6 +117,7 @@ pub async fn create_cron_job(_workspace: &Path, args: Value) -> anyhow::Result<V
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
 
     // Try to register via the global scheduler handle.
@@ -272,6 +273,7 @@ pub async fn run_cron_job(_workspace: &Path, args: Value) -> anyhow::Result<Valu
         agent_id: Some(agent_id.to_string()),
         channel: "cron-manual".to_string(),
         author: format!("cron:{name}"),
+        author_id: None,
         content: message.clone(),
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -422,6 +424,8 @@ pub fn register() {
                 }
             }
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -453,6 +457,8 @@ pub fn register() {
             },
             "required": ["agent_id", "schedule", "message"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -484,6 +490,8 @@ pub fn register() {
             },
             "required": ["agent_id", "name"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -503,6 +511,8 @@ pub fn register() {
             },
             "required": ["agent_id", "name"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -522,6 +532,8 @@ pub fn register() {
             },
             "required": ["agent_id", "name"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -544,5 +556,7 @@ pub fn register() {
                 }
             }
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 }
@@ -48,6 +48,7 @@ pub async fn delegate(_workspace: &std::path::Path, args: Value) -> anyhow::Resu
     let msg = crate::comm::IncomingMessage {
         channel: format!("delegate:{}", target_agent_id),
         author: "delegate".to_string(),
+        author_id: None,
         content: delegated_task,
         agent_id: Some(target_agent_id.clone()),
         timestamp: chrono::Utc::now().timestamp(),
@@ -167,5 +168,7 @@ pub fn register() {
             },
             "required": ["agent", "task"]
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 }
@@ -39,6 +39,7 @@ pub async fn edit_file(workspace: &Path, args: Value) -> anyhow::Result<Value> {
     };
 
     let bytes = output.len();
+    crate::journal::record(workspace, &path, "edit_file").await;
 
     // Ensure parent directories exist.
     if let Some(parent) = path.parent() {
@@ -213,5 +214,7 @@ pub fn register() {
             "required": ["path"],
             "additionalProperties": false
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
@@ -0,0 +1,1097 @@
+//! Background and interactive process registry for `exec_shell`.
+//!
+//! Background commands write stdout/stderr straight to log files under
+//! `<pinchy_home>/bg_logs/`; interactive ones run on a pseudo-terminal
+//! whose output is copied to a log.  A supervisor task tails the logs
+//! into the process entry and publishes each complete line to the
+//! gateway as an `exec_output` event, so the agent can poll incrementally
+//! with a cursor and the UI can show live progress.
+//!
+//! Process metadata is persisted in `PinchyDb`.  Because output goes to
+//! files rather than pipes, processes outlive a daemon restart (e.g.
+//! `self_update`), and [`restore`] re-attaches to them on startup.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::sync::{oneshot, Notify};
+
+use super::{pty, sandbox, shell_command, spawn_sandboxed, AgentContext, MAX_OUTPUT};
+use crate::config::SandboxConfig;
+use crate::store::{BgProcessRecord, PinchyDb};
+
+/// Default lifetime of a piped background process.
+pub(super) const DEFAULT_BG_TIMEOUT_SECS: u64 = 120;
+/// Default lifetime of an interactive session.
+pub(super) const DEFAULT_INTERACTIVE_TIMEOUT_SECS: u64 = 30 * 60;
+/// Upper bound for a caller-supplied `timeout_secs`.
+pub(super) const MAX_TIMEOUT_SECS: u64 = 4 * 60 * 60;
+
+/// Combined output retained per process for cursor reads.  Older output
+/// is dropped; cursors pointing into it skip ahead.
+const MAX_BUFFERED_OUTPUT: usize = 1024 * 1024;
+/// Longest line published in an `exec_output` event.
+const MAX_EVENT_LINE: usize = 1000;
+/// Longest `wait_ms` accepted by `action: "read"`.
+const MAX_READ_WAIT_MS: u64 = 30_000;
+/// How long the `output` action waits for the process to finish.
+const OUTPUT_WAIT: Duration = Duration::from_secs(30);
+/// How often the supervisor tails the log files.
+const TAIL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long to let the PTY reader drain after the process exits.  A
+/// daemonised grandchild can hold the terminal open indefinitely.
+const READER_DRAIN: Duration = Duration::from_secs(2);
+/// Finished processes (and their logs) are forgotten after this long.
+const RETENTION_SECS: i64 = 3 * 24 * 60 * 60;
+
+const NOTE_KILLED: &str = "killed by user";
+const NOTE_EXITED_DETACHED: &str = "exited while pinchy was restarting; exit status unknown";
+const NOTE_TERMINAL_LOST: &str =
+    "terminal detached by a pinchy restart; input and further output are unavailable";
+
+/// A tracked background process.
+struct BgProcess {
+    /// Display label (the original command string).
+    command: String,
+    /// Agent that started the process.  Tags gateway events, and only
+    /// this agent may manage the process.
+    agent: Option<String>,
+    /// True for PTY-backed sessions started with `interactive: true`.
+    interactive: bool,
+    /// True when re-attached after a restart rather than spawned by this
+    /// daemon (no exit status is available).
+    detached: bool,
+    /// OS process id (also the process group id).
+    os_pid: u32,
+    /// Kernel start time of `os_pid`, to guard against pid reuse.
+    os_start_time: Option<u64>,
+    /// Unix seconds.
+    started_at: i64,
+    /// PTY master used to write input (interactive sessions only).
+    input: Option<std::fs::File>,
+    /// Asks the supervisor to kill the process.
+    kill_tx: Option<oneshot::Sender<()>>,
+    /// Interleaved stdout/stderr (or terminal output), capped at
+    /// [`MAX_BUFFERED_OUTPUT`].
+    output: String,
+    /// Stream offset of the first byte still held in `output`.
+    output_start: usize,
+    /// First [`MAX_OUTPUT`] bytes of stdout (terminal output when
+    /// interactive).
+    stdout: String,
+    /// First [`MAX_OUTPUT`] bytes of stderr.
+    stderr: String,
+    /// Exit code once finished, if known.
+    exit_code: Option<i32>,
+    /// True once the process has been collected.
+    done: bool,
+    /// Why the process was stopped early (timeout or kill), if it was.
+    note: Option<String>,
+    /// Sandbox settings the process ran under, if any.
+    sandbox: Option<SandboxConfig>,
+    /// Sandbox violations detected after collection.
+    violations: Vec<String>,
+    /// Woken whenever output arrives or the process finishes.
+    changed: Arc<Notify>,
+}
+
+impl BgProcess {
+    fn new(record: &BgProcessRecord, kill_tx: Option<oneshot::Sender<()>>) -> Self {
+        Self {
+            command: record.command.clone(),
+            agent: record.agent_id.clone(),
+            interactive: record.interactive,
+            detached: false,
+            os_pid: record.os_pid,
+            os_start_time: record.os_start_time,
+            started_at: record.started_at,
+            input: None,
+            kill_tx,
+            output: String::new(),
+            output_start: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: record.exit_code,
+            done: record.done,
+            note: record.note.clone(),
+            sandbox: None,
+            violations: Vec::new(),
+            changed: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Stream offset one past the last byte of output received so far.
+    fn output_end(&self) -> usize {
+        self.output_start + self.output.len()
+    }
+
+    fn summary(&self, pid: u64) -> Value {
+        let mut out = json!({
+            "process_id": pid,
+            "command": self.command,
+            "done": self.done,
+            "exit_code": self.exit_code,
+        });
+        if let Some(started) = chrono::DateTime::from_timestamp(self.started_at, 0) {
+            out["started_at"] = json!(started.to_rfc3339());
+        }
+        if self.interactive {
+            out["interactive"] = json!(true);
+        }
+        if self.detached {
+            out["reattached"] = json!(true);
+        }
+        if let Some(ref note) = self.note {
+            out["note"] = json!(note);
+        }
+        out
+    }
+}
+
+static BG_PROCS: LazyLock<Mutex<HashMap<u64, BgProcess>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Process ids handed out when no database is available.
+static BG_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Distinguishes log files created within the same second.
+static LOG_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Which output stream a chunk came from.
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+    Pty,
+}
+
+impl Stream {
+    fn as_str(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+            Stream::Pty => "pty",
+        }
+    }
+}
+
+/// Fresh log file paths for a new process.
+fn new_log_paths(interactive: bool) -> anyhow::Result<(PathBuf, Option<PathBuf>)> {
+    let dir = crate::pinchy_home().join("bg_logs");
+    std::fs::create_dir_all(&dir)?;
+    let stem = format!(
+        "{}-{}-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S"),
+        std::process::id(),
+        LOG_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    );
+    if interactive {
+        Ok((dir.join(format!("{stem}.pty.log")), None))
+    } else {
+        Ok((
+            dir.join(format!("{stem}.stdout.log")),
+            Some(dir.join(format!("{stem}.stderr.log"))),
+        ))
+    }
+}
+
+/// Persist `record` and return its process id.
+fn register(db: Option<&PinchyDb>, record: &BgProcessRecord) -> u64 {
+    if let Some(db) = db {
+        match db.insert_bg_process(record) {
+            Ok(id) => return id,
+            Err(e) => {
+                tracing::warn!(error = %e, "exec_shell: failed to persist background process")
+            }
+        }
+    }
+    BG_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Spawn `command` in the background and start supervising it.
+/// Returns the `process_id` used by the management actions.
+pub(super) fn start(
+    command: &str,
+    workspace: &Path,
+    ctx: AgentContext,
+    interactive: bool,
+    timeout: Duration,
+) -> anyhow::Result<Value> {
+    let (stdout_log, stderr_log) = new_log_paths(interactive)
+        .map_err(|e| anyhow::anyhow!("exec_shell: cannot create log files: {e}"))?;
+
+    let mut cmd = shell_command(command, workspace);
+    // Survive a daemon restart; the supervisor enforces the timeout.
+    cmd.kill_on_drop(false);
+    let master = if interactive {
+        Some(pty::attach(&mut cmd).map_err(|e| anyhow::anyhow!("exec_shell: {e}"))?)
+    } else {
+        let stderr_log = stderr_log
+            .as_deref()
+            .expect("piped process has a stderr log");
+        cmd.stdout(std::fs::File::create(&stdout_log)?)
+            .stderr(std::fs::File::create(stderr_log)?)
+            .process_group(0);
+        None
+    };
+    let child = spawn_sandboxed(cmd, workspace, ctx.sandbox.as_ref())?;
+    let os_pid = child
+        .id()
+        .ok_or_else(|| anyhow::anyhow!("exec_shell: process exited before it could be tracked"))?;
+
+    let started_at = chrono::Utc::now().timestamp();
+    let record = BgProcessRecord {
+        id: 0,
+        agent_id: ctx.agent_id,
+        command: command.to_string(),
+        os_pid,
+        os_start_time: pty::start_time(os_pid),
+        interactive,
+        started_at,
+        deadline: started_at + timeout.as_secs() as i64,
+        stdout_log: stdout_log.display().to_string(),
+        stderr_log: stderr_log.as_ref().map(|p| p.display().to_string()),
+        done: false,
+        exit_code: None,
+        note: None,
+    };
+    let db = crate::store::global_db().cloned();
+    let pid = register(db.as_ref(), &record);
+
+    let (kill_tx, kill_rx) = oneshot::channel();
+    let mut entry = BgProcess::new(&record, Some(kill_tx));
+    entry.sandbox = ctx.sandbox;
+    if let Some(ref m) = master {
+        entry.input = Some(m.try_clone()?);
+    }
+    BG_PROCS
+        .lock()
+        .expect("bg proc registry poisoned")
+        .insert(pid, entry);
+
+    let (tails, pty_reader) = match master {
+        Some(master) => {
+            let log = std::fs::File::create(&stdout_log)?;
+            // A plain thread rather than `spawn_blocking`: the read can
+            // block for as long as anything holds the terminal open, and
+            // the runtime waits for blocking tasks when it shuts down.
+            let (done_tx, done_rx) = oneshot::channel();
+            std::thread::Builder::new()
+                .name(format!("pty-reader-{pid}"))
+                .spawn(move || {
+                    read_pty(pid, master, log);
+                    let _ = done_tx.send(());
+                })?;
+            (Vec::new(), Some(done_rx))
+        }
+        None => (log_tails(&record, true), None),
+    };
+
+    tokio::spawn(supervise(Supervised {
+        pid,
+        child: Some(child),
+        tails,
+        pty_reader,
+        kill_rx,
+        started_at: record.started_at,
+        deadline: record.deadline,
+        os_pid,
+        os_start_time: record.os_start_time,
+        db,
+    }));
+
+    let mut result = json!({ "process_id": pid });
+    if interactive {
+        result["interactive"] = json!(true);
+    }
+    Ok(result)
+}
+
+/// Re-attach to background processes recorded in the global database:
+/// still-running ones are supervised again, finished ones have their
+/// logs loaded so `status`/`output`/`read` keep working.  Returns the
+/// number of running processes re-attached.
+pub(super) fn restore() -> usize {
+    match crate::store::global_db() {
+        Some(db) => restore_from(db),
+        None => 0,
+    }
+}
+
+fn restore_from(db: &PinchyDb) -> usize {
+    let now = chrono::Utc::now().timestamp();
+    match db.prune_bg_processes(now - RETENTION_SECS) {
+        Ok(stale) => {
+            for r in stale {
+                let _ = std::fs::remove_file(&r.stdout_log);
+                if let Some(ref p) = r.stderr_log {
+                    let _ = std::fs::remove_file(p);
+                }
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "exec_shell: failed to prune background processes"),
+    }
+    let records = match db.list_bg_processes() {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!(error = %e, "exec_shell: failed to load background processes");
+            return 0;
+        }
+    };
+
+    let mut reattached = 0;
+    for mut record in records {
+        let pid = record.id;
+        BG_COUNTER.fetch_max(pid + 1, std::sync::atomic::Ordering::Relaxed);
+        if BG_PROCS
+            .lock()
+            .expect("bg proc registry poisoned")
+            .contains_key(&pid)
+        {
+            continue;
+        }
+
+        let alive = !record.done && pty::is_alive(record.os_pid, record.os_start_time);
+        if !record.done && !alive {
+            record.done = true;
+            record.note = Some(NOTE_EXITED_DETACHED.into());
+            if let Err(e) = db.finish_bg_process(pid, None, record.note.as_deref()) {
+                tracing::warn!(error = %e, "exec_shell: failed to update background process");
+            }
+        }
+
+        let (kill_tx, kill_rx) = oneshot::channel();
+        let mut entry = BgProcess::new(&record, alive.then_some(kill_tx));
+        entry.detached = true;
+        if alive && record.interactive {
+            entry.note = Some(NOTE_TERMINAL_LOST.into());
+        }
+        BG_PROCS
+            .lock()
+            .expect("bg proc registry poisoned")
+            .insert(pid, entry);
+
+        // Catch up on existing output without replaying it as events.
+        let mut tails = log_tails(&record, false);
+        if !alive {
+            for tail in &mut tails {
+                tail.finish(pid);
+            }
+            continue;
+        }
+        for tail in &mut tails {
+            tail.poll(pid);
+            tail.live = true;
+        }
+        tokio::spawn(supervise(Supervised {
+            pid,
+            child: None,
+            tails,
+            pty_reader: None,
+            kill_rx,
+            started_at: record.started_at,
+            deadline: record.deadline,
+            os_pid: record.os_pid,
+            os_start_time: record.os_start_time,
+            db: Some(db.clone()),
+        }));
+        reattached += 1;
+    }
+    reattached
+}
+
+/// Everything the supervisor task needs to follow one process.
+struct Supervised {
+    pid: u64,
+    /// The child handle, when this daemon spawned the process.
+    child: Option<Child>,
+    tails: Vec<LogTail>,
+    /// Fires when the PTY reader thread has drained the terminal.
+    pty_reader: Option<oneshot::Receiver<()>>,
+    kill_rx: oneshot::Receiver<()>,
+    /// Unix seconds.
+    started_at: i64,
+    /// Unix seconds after which the process is killed.
+    deadline: i64,
+    os_pid: u32,
+    os_start_time: Option<u64>,
+    db: Option<PinchyDb>,
+}
+
+/// Tail the logs until the process exits (or is killed / times out),
+/// then record the outcome.
+async fn supervise(mut s: Supervised) {
+    enum Wake {
+        Exited(Option<std::process::ExitStatus>),
+        Timeout,
+        Kill,
+        Tick,
+    }
+
+    let remaining = (s.deadline - chrono::Utc::now().timestamp()).max(0) as u64;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(remaining);
+    let (status, note) = loop {
+        for tail in &mut s.tails {
+            tail.poll(s.pid);
+        }
+        let exited = async {
+            match s.child.as_mut() {
+                Some(child) => child.wait().await.ok(),
+                None => std::future::pending().await,
+            }
+        };
+        let wake = tokio::select! {
+            status = exited => Wake::Exited(status),
+            _ = tokio::time::sleep_until(deadline) => Wake::Timeout,
+            Ok(()) = &mut s.kill_rx => Wake::Kill,
+            _ = tokio::time::sleep(TAIL_INTERVAL) => Wake::Tick,
+        };
+        match wake {
+            Wake::Exited(status) => break (status, None),
+            Wake::Timeout => {
+                let note = format!("timed out (killed after {}s)", s.deadline - s.started_at);
+                break (kill(&mut s).await, Some(note));
+            }
+            Wake::Kill => break (kill(&mut s).await, Some(NOTE_KILLED.to_string())),
+            Wake::Tick => {
+                if s.child.is_none() && !pty::is_alive(s.os_pid, s.os_start_time) {
+                    break (None, Some(NOTE_EXITED_DETACHED.to_string()));
+                }
+            }
+        }
+    };
+
+    for tail in &mut s.tails {
+        tail.finish(s.pid);
+    }
+    if let Some(reader) = s.pty_reader.take() {
+        let _ = tokio::time::timeout(READER_DRAIN, reader).await;
+    }
+
+    // Re-attached processes are not our children, so their status is
+    // unknown; a process we spawned that died by signal reports -1.
+    let exit_code = match (&s.child, status) {
+        (None, _) => None,
+        (Some(_), status) => Some(status.and_then(|st| st.code()).unwrap_or(-1)),
+    };
+    let agent = {
+        let mut reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+        let Some(proc) = reg.get_mut(&s.pid) else {
+            return;
+        };
+        if let Some(ref cfg) = proc.sandbox {
+            let diagnostics = if proc.interactive {
+                &proc.stdout
+            } else {
+                &proc.stderr
+            };
+            proc.violations = sandbox::violations(cfg, status, diagnostics);
+        }
+        proc.done = true;
+        proc.exit_code = exit_code;
+        proc.note = note.clone();
+        proc.input = None;
+        proc.kill_tx = None;
+        proc.changed.notify_waiters();
+        proc.agent.clone()
+    };
+
+    if let Some(ref db) = s.db {
+        if let Err(e) = db.finish_bg_process(s.pid, exit_code, note.as_deref()) {
+            tracing::warn!(error = %e, "exec_shell: failed to update background process");
+        }
+    }
+
+    crate::gateway::publish_event_json(&json!({
+        "type": "exec_exit",
+        "agent": agent,
+        "process_id": s.pid,
+        "exit_code": exit_code,
+    }));
+}
+
+/// Kill the whole process group, then reap (or, for a re-attached
+/// process, wait out) the shell.
+async fn kill(s: &mut Supervised) -> Option<std::process::ExitStatus> {
+    if s.child.is_some() || pty::is_alive(s.os_pid, s.os_start_time) {
+        let _ = pty::signal_group(s.os_pid, "KILL");
+    }
+    match s.child.as_mut() {
+        Some(child) => {
+            let _ = child.start_kill();
+            child.wait().await.ok()
+        }
+        None => {
+            for _ in 0..25 {
+                if !pty::is_alive(s.os_pid, s.os_start_time) {
+                    break;
+                }
+                tokio::time::sleep(TAIL_INTERVAL).await;
+            }
+            None
+        }
+    }
+}
+
+/// Log readers for a persisted process.  `live` controls whether new
+/// lines are published as events.
+fn log_tails(record: &BgProcessRecord, live: bool) -> Vec<LogTail> {
+    let mut tails = Vec::new();
+    if record.interactive {
+        tails.push(LogTail::new(&record.stdout_log, Stream::Pty, live));
+    } else {
+        tails.push(LogTail::new(&record.stdout_log, Stream::Stdout, live));
+        if let Some(ref p) = record.stderr_log {
+            tails.push(LogTail::new(p, Stream::Stderr, live));
+        }
+    }
+    tails
+}
+
+/// Follows a log file as the process appends to it.
+struct LogTail {
+    path: PathBuf,
+    file: Option<std::fs::File>,
+    stream: Stream,
+    decoder: Decoder,
+    /// Publish completed lines as gateway events.
+    live: bool,
+}
+
+impl LogTail {
+    fn new(path: impl Into<PathBuf>, stream: Stream, live: bool) -> Self {
+        Self {
+            path: path.into(),
+            file: None,
+            stream,
+            decoder: Decoder::default(),
+            live,
+        }
+    }
+
+    /// Read everything appended since the last call.
+    fn poll(&mut self, pid: u64) {
+        if self.file.is_none() {
+            self.file = std::fs::File::open(&self.path).ok();
+        }
+        let Some(ref mut file) = self.file else {
+            return;
+        };
+        let mut buf = [0u8; 8192];
+        loop {
+            match file.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let (text, lines) = self.decoder.push(&buf[..n]);
+                    append(
+                        pid,
+                        self.stream,
+                        &text,
+                        if self.live { lines } else { Vec::new() },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Read the remainder and flush any unterminated last line.
+    fn finish(&mut self, pid: u64) {
+        self.poll(pid);
+        let (text, lines) = self.decoder.finish();
+        append(
+            pid,
+            self.stream,
+            &text,
+            if self.live { lines } else { Vec::new() },
+        );
+    }
+}
+
+/// Blocking read loop over the PTY master, copying output to `log`.
+/// Ends with `EIO` once every holder of the slave side has exited.
+fn read_pty(pid: u64, mut master: std::fs::File, mut log: std::fs::File) {
+    let mut decoder = Decoder::default();
+    let mut buf = [0u8; 8192];
+    loop {
+        match master.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                let _ = log.write_all(&buf[..n]);
+                let (text, lines) = decoder.push(&buf[..n]);
+                append(pid, Stream::Pty, &text, lines);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+    let (text, lines) = decoder.finish();
+    append(pid, Stream::Pty, &text, lines);
+}
+
+/// Record a chunk of output and publish its completed lines.
+fn append(pid: u64, stream: Stream, text: &str, lines: Vec<String>) {
+    if text.is_empty() && lines.is_empty() {
+        return;
+    }
+    let agent = {
+        let mut reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+        let Some(proc) = reg.get_mut(&pid) else {
+            return;
+        };
+        let capped = match stream {
+            Stream::Stderr => &mut proc.stderr,
+            Stream::Stdout | Stream::Pty => &mut proc.stdout,
+        };
+        if capped.len() < MAX_OUTPUT {
+            let room = floor_char_boundary(text, MAX_OUTPUT - capped.len());
+            capped.push_str(&text[..room]);
+        }
+        proc.output.push_str(text);
+        if proc.output.len() > MAX_BUFFERED_OUTPUT {
+            let cut = ceil_char_boundary(&proc.output, proc.output.len() - MAX_BUFFERED_OUTPUT);
+            proc.output.drain(..cut);
+            proc.output_start += cut;
+        }
+        proc.changed.notify_waiters();
+        proc.agent.clone()
+    };
+
+    for line in lines {
+        crate::gateway::publish_event_json(&json!({
+            "type": "exec_output",
+            "agent": agent,
+            "process_id": pid,
+            "stream": stream.as_str(),
+            "line": line,
+        }));
+    }
+}
+
+/// Incremental UTF-8 decoder that also splits output into lines.
+#[derive(Default)]
+struct Decoder {
+    /// Trailing bytes of an incomplete UTF-8 sequence.
+    pending: Vec<u8>,
+    /// The current, unterminated line.
+    line: String,
+}
+
+impl Decoder {
+    /// Decode `bytes`, returning the new text and any lines it completed.
+    fn push(&mut self, bytes: &[u8]) -> (String, Vec<String>) {
+        self.pending.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.pending) {
+            // Incomplete sequence at the end — keep it for the next chunk.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let text = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+        self.pending.drain(..valid);
+
+        let mut lines = Vec::new();
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(event_line(&std::mem::take(&mut self.line)));
+            }
+            if self.line.len() < MAX_EVENT_LINE * 16 {
+                self.line.push_str(part);
+            }
+        }
+        (text, lines)
+    }
+
+    /// Flush whatever is left once the stream has closed.
+    fn finish(&mut self) -> (String, Vec<String>) {
+        let text = String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned();
+        self.line.push_str(&text);
+        let line = std::mem::take(&mut self.line);
+        let lines = if line.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![event_line(&line)]
+        };
+        (text, lines)
+    }
+}
+
+/// Render a raw line for an event: carriage returns overwrite (as in a
+/// progress bar), and the result is capped at [`MAX_EVENT_LINE`] bytes.
+fn event_line(raw: &str) -> String {
+    let raw = raw.strip_suffix('\r').unwrap_or(raw);
+    let shown = raw.rsplit('\r').next().unwrap_or(raw);
+    shown[..floor_char_boundary(shown, MAX_EVENT_LINE)].to_string()
+}
+
+fn floor_char_boundary(s: &str, mut i: usize) -> usize {
+    if i >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn ceil_char_boundary(s: &str, mut i: usize) -> usize {
+    while i < s.len() && !s.is_char_boundary(i) {
+        i += 1;
+    }
+    i.min(s.len())
+}
+
+// ── Management actions ───────────────────────────────────────
+
+fn require_pid(args: &Value, action: &str) -> anyhow::Result<u64> {
+    args.get("process_id")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| anyhow::anyhow!("exec_shell: action='{action}' requires `process_id`"))
+}
+
+fn no_such_process(pid: u64) -> anyhow::Error {
+    anyhow::anyhow!("exec_shell: no background process with id {pid}")
+}
+
+/// The `process_id` argument, if it names a process started by `agent`.
+/// Another agent's process is reported as missing: managing it would
+/// get around that agent's command allowlist and sandbox.
+fn require_owned_pid(args: &Value, action: &str, agent: Option<&str>) -> anyhow::Result<u64> {
+    let pid = require_pid(args, action)?;
+    let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+    match reg.get(&pid) {
+        Some(p) if p.agent.as_deref() == agent => Ok(pid),
+        _ => Err(no_such_process(pid)),
+    }
+}
+
+/// Handle background process management actions for the calling
+/// `agent`, which only sees the processes it started.
+pub(super) async fn handle_action(
+    action: &str,
+    args: &Value,
+    agent: Option<&str>,
+) -> anyhow::Result<Value> {
+    match action {
+        "list" => {
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            let mut procs: Vec<(&u64, &BgProcess)> = reg
+                .iter()
+                .filter(|(_, p)| p.agent.as_deref() == agent)
+                .collect();
+            procs.sort_by_key(|(pid, _)| **pid);
+            let procs: Vec<Value> = procs.into_iter().map(|(pid, p)| p.summary(*pid)).collect();
+            Ok(json!({ "processes": procs }))
+        }
+        "status" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            reg.get(&pid)
+                .map(|p| p.summary(pid))
+                .ok_or_else(|| no_such_process(pid))
+        }
+        "output" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let done = wait_for(pid, OUTPUT_WAIT, |p| p.done).await?;
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            let p = reg.get(&pid).ok_or_else(|| no_such_process(pid))?;
+            let mut out = p.summary(pid);
+            out["stdout"] = json!(p.stdout.trim());
+            out["stderr"] = json!(p.stderr.trim());
+            if !done {
+                out["note"] = json!(
+                    "process still running after 30s; output so far shown. \
+                     Use action='read' with a cursor to follow it."
+                );
+            }
+            if !p.violations.is_empty() {
+                out["sandbox_violations"] = json!(p.violations);
+            }
+            Ok(out)
+        }
+        "read" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let cursor = args.get("cursor").and_then(Value::as_u64).unwrap_or(0) as usize;
+            let wait_ms = args
+                .get("wait_ms")
+                .and_then(Value::as_u64)
+                .unwrap_or(0)
+                .min(MAX_READ_WAIT_MS);
+            wait_for(pid, Duration::from_millis(wait_ms), |p| {
+                p.done || p.output_end() > cursor
+            })
+            .await?;
+
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            let p = reg.get(&pid).ok_or_else(|| no_such_process(pid))?;
+            let from = cursor.clamp(p.output_start, p.output_end()) - p.output_start;
+            let from = ceil_char_boundary(&p.output, from);
+            let to = floor_char_boundary(&p.output, from + MAX_OUTPUT);
+            let next = p.output_start + to;
+            let mut out = json!({
+                "process_id": pid,
+                "output": &p.output[from..to],
+                "cursor": next,
+                "done": p.done,
+                "exit_code": p.exit_code,
+            });
+            if cursor < p.output_start {
+                out["skipped_bytes"] = json!(p.output_start - cursor);
+            }
+            if next < p.output_end() {
+                out["more"] = json!(true);
+            }
+            Ok(out)
+        }
+        "write" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let input = args
+                .get("input")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("exec_shell: action='write' requires `input`"))?;
+            let mut file = {
+                let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+                let p = reg.get(&pid).ok_or_else(|| no_such_process(pid))?;
+                if !p.interactive {
+                    anyhow::bail!(
+                        "exec_shell: process {pid} is not interactive; start it with interactive=true to send input"
+                    );
+                }
+                match (p.done, &p.input) {
+                    (false, Some(f)) => f.try_clone()?,
+                    (false, None) => anyhow::bail!(
+                        "exec_shell: process {pid} has no terminal attached (pinchy restarted since it started)"
+                    ),
+                    (true, _) => anyhow::bail!("exec_shell: process {pid} has exited"),
+                }
+            };
+            let bytes = input.as_bytes().to_vec();
+            let written = bytes.len();
+            tokio::task::spawn_blocking(move || file.write_all(&bytes))
+                .await?
+                .map_err(|e| anyhow::anyhow!("exec_shell: write failed: {e}"))?;
+            Ok(json!({ "process_id": pid, "written": written }))
+        }
+        "signal" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let signal = args
+                .get("signal")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("exec_shell: action='signal' requires `signal`"))?;
+            let os_pid = {
+                let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+                let p = reg.get(&pid).ok_or_else(|| no_such_process(pid))?;
+                // A re-attached pid may have been recycled since the last
+                // supervisor tick.
+                if p.done || (p.detached && !pty::is_alive(p.os_pid, p.os_start_time)) {
+                    anyhow::bail!("exec_shell: process {pid} has exited");
+                }
+                p.os_pid
+            };
+            pty::signal_group(os_pid, signal).map_err(|e| anyhow::anyhow!("exec_shell: {e}"))?;
+            Ok(json!({ "process_id": pid, "signal": signal }))
+        }
+        "kill" => {
+            let pid = require_owned_pid(args, action, agent)?;
+            let kill_tx = {
+                let mut reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+                let p = reg.get_mut(&pid).ok_or_else(|| no_such_process(pid))?;
+                p.kill_tx.take()
+            };
+            if let Some(tx) = kill_tx {
+                let _ = tx.send(());
+                wait_for(pid, Duration::from_secs(5), |p| p.done).await?;
+            }
+            Ok(json!({
+                "process_id": pid,
+                "killed": true,
+            }))
+        }
+        other => {
+            anyhow::bail!(
+                "exec_shell: unknown action '{other}'. Valid: list, status, output, read, write, signal, kill"
+            )
+        }
+    }
+}
+
+/// Wait until `ready` holds for the process or `timeout` elapses.
+/// Returns whether it became ready.
+async fn wait_for(
+    pid: u64,
+    timeout: Duration,
+    ready: impl Fn(&BgProcess) -> bool,
+) -> anyhow::Result<bool> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let changed = {
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            let p = reg.get(&pid).ok_or_else(|| no_such_process(pid))?;
+            if ready(p) {
+                return Ok(true);
+            }
+            p.changed.clone()
+        };
+        let notified = changed.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        // Re-check: the state may have changed before we registered.
+        {
+            let reg = BG_PROCS.lock().expect("bg proc registry poisoned");
+            if reg.get(&pid).is_some_and(&ready) {
+                return Ok(true);
+            }
+        }
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            return Ok(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoder_splits_lines_across_chunks() {
+        let mut d = Decoder::default();
+        let (text, lines) = d.push(b"hel");
+        assert_eq!(text, "hel");
+        assert!(lines.is_empty());
+        let (_, lines) = d.push(b"lo\nwor");
+        assert_eq!(lines, vec!["hello"]);
+        // A multi-byte character split across reads is held back.
+        let (text, _) = d.push(&"ld é".as_bytes()[..4]);
+        assert_eq!(text, "ld ");
+        let (text, lines) = d.push(&"é\n".as_bytes()[1..]);
+        assert_eq!(text, "é\n");
+        assert_eq!(lines, vec!["world é"]);
+        let (_, lines) = d.push(b"10%\r50%\r100%\r\ntail");
+        assert_eq!(lines, vec!["100%"]);
+        let (_, lines) = d.finish();
+        assert_eq!(lines, vec!["tail"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn restore_reattaches_live_processes() {
+        use std::os::unix::process::CommandExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = PinchyDb::open_path(&dir.path().join("pinchy.db")).unwrap();
+        let log = dir.path().join("out.log");
+        std::fs::write(&log, "before restart\n").unwrap();
+
+        // Stands in for a process that outlived the previous daemon.
+        let mut sleeper = std::process::Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let record = |os_start_time| BgProcessRecord {
+            id: 0,
+            agent_id: Some("agent-a".into()),
+            command: "sleep 30".into(),
+            os_pid: sleeper.id(),
+            os_start_time,
+            interactive: false,
+            started_at: chrono::Utc::now().timestamp(),
+            deadline: chrono::Utc::now().timestamp() + 60,
+            stdout_log: log.display().to_string(),
+            stderr_log: None,
+            done: false,
+            exit_code: None,
+            note: None,
+        };
+        let live = db
+            .insert_bg_process(&record(pty::start_time(sleeper.id())))
+            .unwrap();
+        // Same pid, different start time: the pid was recycled.
+        let recycled = db.insert_bg_process(&record(Some(1))).unwrap();
+
+        assert_eq!(restore_from(&db), 1);
+
+        let status = handle_action("status", &json!({ "process_id": live }), Some("agent-a"))
+            .await
+            .unwrap();
+        assert_eq!(status["done"], false);
+        assert_eq!(status["reattached"], true);
+        let read = handle_action("read", &json!({ "process_id": live }), Some("agent-a"))
+            .await
+            .unwrap();
+        assert_eq!(read["output"], "before restart\n");
+        let gone = handle_action(
+            "status",
+            &json!({ "process_id": recycled }),
+            Some("agent-a"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(gone["done"], true);
+        assert_eq!(gone["note"], NOTE_EXITED_DETACHED);
+
+        handle_action("kill", &json!({ "process_id": live }), Some("agent-a"))
+            .await
+            .unwrap();
+        assert!(!sleeper.wait().unwrap().success());
+        wait_for(live, Duration::from_secs(5), |p| p.done)
+            .await
+            .unwrap();
+        assert!(db.list_bg_processes().unwrap().iter().all(|r| r.done));
+    }
+
+    #[tokio::test]
+    async fn agents_only_manage_their_own_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        // Stay clear of the ids `restore_reattaches_live_processes` restores.
+        BG_COUNTER.fetch_max(1_000_000, std::sync::atomic::Ordering::Relaxed);
+        let ctx = |id: &str| AgentContext {
+            agent_id: Some(id.into()),
+            ..AgentContext::default()
+        };
+        let started = start(
+            "sleep 30",
+            dir.path(),
+            ctx("owner"),
+            false,
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        let pid = started["process_id"].as_u64().unwrap();
+        let args = json!({ "process_id": pid, "signal": "TERM", "input": "x" });
+
+        for action in ["status", "read", "output", "write", "signal", "kill"] {
+            let err = handle_action(action, &args, Some("intruder"))
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("no background process"),
+                "{action}: {err}"
+            );
+        }
+        let listed = |v: Value| {
+            v["processes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|p| p["process_id"] == pid)
+        };
+        assert!(!listed(
+            handle_action("list", &args, Some("intruder"))
+                .await
+                .unwrap()
+        ));
+        assert!(!listed(handle_action("list", &args, None).await.unwrap()));
+        assert!(listed(
+            handle_action("list", &args, Some("owner")).await.unwrap()
+        ));
+
+        let status = handle_action("status", &args, Some("owner")).await.unwrap();
+        assert_eq!(status["done"], false);
+        handle_action("kill", &args, Some("owner")).await.unwrap();
+        let status = handle_action("status", &args, Some("owner")).await.unwrap();
+        assert_eq!(status["done"], true);
+    }
+}
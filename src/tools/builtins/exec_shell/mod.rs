@@ -0,0 +1,557 @@
+//! Built-in `exec_shell` tool — runs a sandboxed shell command in the agent workspace.
+//!
+//! Supports an optional `background: true` flag that spawns the command
+//! detached and returns immediately with a `process_id`, and
+//! `interactive: true` for PTY-backed sessions that accept input.  Use
+//! `exec_shell` with `action: "status"` / `"read"` / `"write"` /
+//! `"signal"` / `"kill"` / `"output"` to manage background processes.
+//! Background processes are persisted and survive a daemon restart.
+
+mod background;
+mod pty;
+
+use serde_json::{json, Value};
+use std::path::Path;
+use tokio::process::Child;
+
+use super::sandbox::{self, Sandbox};
+use crate::config::SandboxConfig;
+use crate::tools::{register_tool, truncate_utf8_owned, ToolMeta};
+
+/// Return the PATH from the parent process environment, falling back to a
+/// minimal default if unset.  This ensures the agent can use the same
+/// tools (cargo, node, pnpm, etc.) as the user who launched pinchy.
+fn inherited_path() -> String {
+    std::env::var("PATH").unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string())
+}
+
+/// Per-stream cap on returned stdout/stderr.
+const MAX_OUTPUT: usize = 32 * 1024; // 32 KB
+
+/// Default limit for foreground commands (see [`crate::tools::timeout_for`]).
+const DEFAULT_FOREGROUND_TIMEOUT_SECS: u64 = 60;
+
+/// Check if a command is blocked by the blacklist.
+fn is_command_blocked(cmd_name: &str, _workspace: &Path) -> bool {
+    EXEC_BLOCKLIST.contains(&cmd_name)
+}
+
+/// Blocklist of commands that are never allowed for `exec_shell`.
+///
+/// Only blocks privilege-escalation vectors, kernel/disk destructors,
+/// and namespace escapes.  Interpreters (python, node, etc.) are
+/// intentionally allowed — `exec_shell` already runs via `sh -c`, so
+/// blocking interpreters provides no real security benefit while
+/// preventing the agent from running scripts it creates.  This is a
+/// personal daemon, not a hostile multi-tenant sandbox.
+const EXEC_BLOCKLIST: &[&str] = &[
+    // Privilege escalation
+    "sudo", "su", "doas", "pkexec", // Dangerous disk / partition tools
+    "dd", "mkfs", "fdisk", "parted", "losetup", // Kernel / module manipulation
+    "insmod", "rmmod", "modprobe", // Namespace / chroot escapes
+    "nsenter", "unshare", "chroot",
+];
+
+/// Patterns that are always rejected regardless of the command, to
+/// prevent sandbox escapes via subshells or process substitution.
+///
+/// NOTE: Since `exec_shell` already runs via `sh -c`, blocking most
+/// shell syntax provides no real security benefit — the agent is already
+/// in a shell.  We only block `eval` which can reassemble blocked
+/// command names from strings to bypass the blocklist.
+const SHELL_ESCAPE_PATTERNS: &[&str] = &["eval ", "eval\t"];
+
+/// Extract individual command names from a shell command string.
+///
+/// Splits on `|`, `&&`, `||`, and `;` operators, then takes the first
+/// whitespace-delimited token of each resulting segment.  Path prefixes
+/// (e.g. `/usr/bin/cat`) are stripped to their basename.
+pub fn extract_command_names(cmd: &str) -> Vec<String> {
+    let normalized = cmd.replace("&&", "\x00").replace("||", "\x00");
+    let mut result = Vec::new();
+    for segment in normalized.split(['|', ';', '\x00']) {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            continue;
+        }
+        if let Some(first) = segment.split_whitespace().next() {
+            let name = first.rsplit('/').next().unwrap_or(first);
+            result.push(name.to_string());
+        }
+    }
+    result
+}
+
+/// Validate the full command for sandbox escapes beyond the simple
+/// blocklist check.  Returns an error message if the command is unsafe.
+fn validate_command_safety(command: &str) -> Result<(), String> {
+    // Reject shell escape patterns.
+    let lower = command.to_lowercase();
+    for pat in SHELL_ESCAPE_PATTERNS {
+        if lower.contains(pat) {
+            return Err(format!("shell escape pattern '{pat}' is not allowed"));
+        }
+    }
+
+    // Block `sed` with the '/e' flag which executes pattern space as shell.
+    // This is the only per-command check worth keeping — it's a genuine
+    // sandbox escape that the blocklist can't catch.
+    let normalized = command.replace("&&", "\x00").replace("||", "\x00");
+    for segment in normalized.split(['|', ';', '\x00']) {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            continue;
+        }
+        let tokens: Vec<&str> = segment.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        let cmd_name = tokens[0].rsplit('/').next().unwrap_or(tokens[0]);
+        if cmd_name == "sed" {
+            for token in &tokens[1..] {
+                if token.ends_with("/e") || token.contains("/e;") || token.contains("/e}") {
+                    return Err(
+                        "sed with '/e' flag is not allowed (executes shell commands)".into(),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute a shell command inside the workspace directory.
+///
+/// **Foreground mode** (default):
+///   Args: `{ "command": "ls -la" }`
+///   Returns: `{ "exit_code": 0, "stdout": "…", "stderr": "…" }`
+///
+/// **Background mode** (`"background": true`):
+///   Spawns the command and returns immediately with a `process_id`.
+///   Output is collected as it arrives and streamed to the gateway as
+///   `exec_output` events.
+///
+/// **Interactive mode** (`"interactive": true`):
+///   Like background mode, but the command runs on a pseudo-terminal and
+///   accepts input via `"action": "write"`.
+///
+/// **Management actions** (no `command` needed):
+///   - `{ "action": "status", "process_id": 1 }`
+///   - `{ "action": "read", "process_id": 1, "cursor": 0, "wait_ms": 2000 }` — output since `cursor`
+///   - `{ "action": "write", "process_id": 1, "input": "y\n" }` — interactive sessions only
+///   - `{ "action": "signal", "process_id": 1, "signal": "INT" }`
+///   - `{ "action": "output", "process_id": 1 }` — collect output (blocks until done, 30 s timeout)
+///   - `{ "action": "kill", "process_id": 1 }`
+///   - `{ "action": "list" }` — list this agent's background processes
+///
+///   An agent can only see and manage the processes it started.
+///
+/// **Sandboxing:**
+/// - Commands in [`EXEC_BLOCKLIST`] (shells, sudo, curl, etc.) are rejected.
+/// - Foreground commands killed after 60 seconds (`tool_timeouts.exec_shell`
+///   overrides it); background after 120 s
+///   and interactive sessions after 30 min unless `timeout_secs` is given.
+/// - `stdout` and `stderr` are truncated to 32 KB each.
+pub async fn exec_shell(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    // ── Management actions (no command needed) ────────────────────
+    if let Some(action) = args.get("action").and_then(Value::as_str) {
+        let ctx = agent_context(workspace, "exec_shell").await?;
+        return background::handle_action(action, &args, ctx.agent_id.as_deref()).await;
+    }
+
+    let command = args
+        .get("command")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("exec_shell: missing `command` argument"))?;
+
+    // ── Command blocklist enforcement ─────────────────────────────
+    let cmd_names = extract_command_names(command);
+    for name in &cmd_names {
+        if is_command_blocked(name, workspace) {
+            anyhow::bail!(
+                "exec_shell: command '{name}' is blocked. \
+                 Blocked commands: {EXEC_BLOCKLIST:?}"
+            );
+        }
+    }
+
+    // ── Deep argument/pattern safety check ────────────────────────
+    if let Err(reason) = validate_command_safety(command) {
+        anyhow::bail!("exec_shell: blocked — {reason}");
+    }
+
+    let ctx = agent_context(workspace, "exec_shell").await?;
+
+    let background = args
+        .get("background")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let interactive = args
+        .get("interactive")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    // ── Background / interactive: spawn and return immediately ────
+    if background || interactive {
+        let default_timeout = if interactive {
+            background::DEFAULT_INTERACTIVE_TIMEOUT_SECS
+        } else {
+            background::DEFAULT_BG_TIMEOUT_SECS
+        };
+        let timeout_secs = args
+            .get("timeout_secs")
+            .and_then(Value::as_u64)
+            .unwrap_or(default_timeout)
+            .clamp(1, background::MAX_TIMEOUT_SECS);
+        return background::start(
+            command,
+            workspace,
+            ctx,
+            interactive,
+            std::time::Duration::from_secs(timeout_secs),
+        );
+    }
+
+    // ── Foreground mode (original behaviour) ──────────────────────
+    let timeout_dur = ctx.foreground_timeout;
+
+    let sandbox_cfg = ctx.sandbox;
+    let snapshot = if ctx.journal {
+        let ws = workspace.to_path_buf();
+        tokio::task::spawn_blocking(move || crate::journal::Snapshot::take(&ws))
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let mut cmd = shell_command(command, workspace);
+    cmd.stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    let child = spawn_sandboxed(cmd, workspace, sandbox_cfg.as_ref())?;
+
+    let waited = tokio::time::timeout(timeout_dur, child.wait_with_output()).await;
+    if let Some(snapshot) = snapshot {
+        let _ = tokio::task::spawn_blocking(move || snapshot.record_changes("exec_shell")).await;
+    }
+    let output = match waited {
+        Ok(result) => result.map_err(|e| anyhow::anyhow!("exec_shell: {e}"))?,
+        Err(_elapsed) => {
+            let mut result = json!({
+                "exit_code": -1,
+                "stdout": "",
+                "stderr": format!(
+                    "timed out after {}s (child killed). Use background=true for long-running commands.",
+                    timeout_dur.as_secs()
+                ),
+            });
+            if sandbox_cfg.is_some() {
+                result["sandboxed"] = json!(true);
+            }
+            return Ok(result);
+        }
+    };
+
+    let code = output.status.code().unwrap_or(-1);
+    let full_stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let full_stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let violations = sandbox_cfg
+        .as_ref()
+        .map(|cfg| sandbox::violations(cfg, Some(output.status), &full_stderr))
+        .unwrap_or_default();
+
+    let truncated_stdout = full_stdout.len() > MAX_OUTPUT;
+    let truncated_stderr = full_stderr.len() > MAX_OUTPUT;
+
+    let stdout = truncate_utf8_owned(full_stdout, MAX_OUTPUT);
+    let stderr = truncate_utf8_owned(full_stderr, MAX_OUTPUT);
+
+    let mut result = json!({ "stdout": stdout });
+
+    if code != 0 {
+        result["exit_code"] = json!(code);
+    }
+    if !stderr.is_empty() {
+        result["stderr"] = json!(stderr);
+    }
+    if truncated_stdout {
+        result["truncated_stdout"] = json!(true);
+    }
+    if truncated_stderr {
+        result["truncated_stderr"] = json!(true);
+    }
+    if sandbox_cfg.is_some() {
+        result["sandboxed"] = json!(true);
+    }
+    if !violations.is_empty() {
+        result["sandbox_violations"] = json!(violations);
+    }
+
+    Ok(result)
+}
+
+/// Run a skill tool's entrypoint `script` with `input` as JSON on stdin.
+///
+/// Follows the rules for foreground `exec_shell` commands: the workspace
+/// is the working directory, the environment is scrubbed, the agent's
+/// sandbox applies, and the script is killed at the tool's time limit.
+/// JSON printed on stdout is returned as-is; other output comes back as
+/// `{ "stdout": … }`.  A non-zero exit is an error carrying stderr.
+pub async fn run_script(
+    workspace: &Path,
+    tool: &str,
+    script: &Path,
+    input: &Value,
+) -> anyhow::Result<Value> {
+    use tokio::io::AsyncWriteExt;
+
+    let ctx = agent_context(workspace, tool).await?;
+    let timeout_dur = ctx.foreground_timeout;
+    let sandbox_cfg = ctx.sandbox;
+
+    let mut cmd = script_command(script);
+    cmd.current_dir(workspace)
+        .env_clear()
+        .env("PATH", inherited_path())
+        .env("HOME", workspace.to_string_lossy().to_string())
+        .env("PINCHY_TOOL", tool)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = spawn_sandboxed(cmd, workspace, sandbox_cfg.as_ref())?;
+
+    // Written from a task so a script that prints before reading its
+    // input cannot deadlock against us; scripts may also ignore stdin.
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = input.to_string().into_bytes();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&payload).await;
+        });
+    }
+
+    let output = match tokio::time::timeout(timeout_dur, child.wait_with_output()).await {
+        Ok(result) => result.map_err(|e| anyhow::anyhow!("{tool}: {e}"))?,
+        Err(_elapsed) => anyhow::bail!(
+            "{tool}: script timed out after {}s (killed)",
+            timeout_dur.as_secs()
+        ),
+    };
+
+    let full_stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let full_stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let violations = sandbox_cfg
+        .as_ref()
+        .map(|cfg| sandbox::violations(cfg, Some(output.status), &full_stderr))
+        .unwrap_or_default();
+    let stderr = truncate_utf8_owned(full_stderr, MAX_OUTPUT);
+
+    if !output.status.success() {
+        let code = output.status.code().unwrap_or(-1);
+        let mut msg = format!("{tool}: script exited with code {code}");
+        if !stderr.is_empty() {
+            msg.push_str(&format!(": {stderr}"));
+        }
+        if !violations.is_empty() {
+            msg.push_str(&format!(" (sandbox: {})", violations.join("; ")));
+        }
+        anyhow::bail!(msg);
+    }
+
+    if full_stdout.len() <= MAX_OUTPUT {
+        if let Ok(value) = serde_json::from_str::<Value>(&full_stdout) {
+            return Ok(value);
+        }
+    }
+    let truncated = full_stdout.len() > MAX_OUTPUT;
+    let mut result = json!({ "stdout": truncate_utf8_owned(full_stdout, MAX_OUTPUT) });
+    if truncated {
+        result["truncated_stdout"] = json!(true);
+    }
+    if !stderr.is_empty() {
+        result["stderr"] = json!(stderr);
+    }
+    if !violations.is_empty() {
+        result["sandbox_violations"] = json!(violations);
+    }
+    Ok(result)
+}
+
+/// Command running `script`, through an interpreter picked by its
+/// extension so scripts need not be executable.
+fn script_command(script: &Path) -> tokio::process::Command {
+    let interpreter = match script.extension().and_then(|e| e.to_str()) {
+        Some("py") => Some("python3"),
+        Some("js" | "mjs" | "cjs") => Some("node"),
+        Some("sh") => Some("sh"),
+        Some("bash") => Some("bash"),
+        Some("rb") => Some("ruby"),
+        _ => None,
+    };
+    match interpreter {
+        Some(bin) => {
+            let mut cmd = tokio::process::Command::new(bin);
+            cmd.arg(script);
+            cmd
+        }
+        None => tokio::process::Command::new(script),
+    }
+}
+
+/// Re-attach to background processes that survived a daemon restart.
+/// Call once at startup, after the global database is set.  Returns the
+/// number of still-running processes picked up again.
+pub fn restore_background_processes() -> usize {
+    background::restore()
+}
+
+/// What `exec_shell` needs to know about the calling agent.
+struct AgentContext {
+    /// Agent id, used to tag gateway events and to scope management
+    /// actions to the agent's own background processes.
+    agent_id: Option<String>,
+    /// Sandbox settings, when the sandbox is enabled.
+    sandbox: Option<SandboxConfig>,
+    /// Limit for foreground commands.
+    foreground_timeout: std::time::Duration,
+    /// Journal foreground commands' workspace changes (`journal_exec_shell`).
+    journal: bool,
+}
+
+impl Default for AgentContext {
+    fn default() -> Self {
+        Self {
+            agent_id: None,
+            sandbox: None,
+            foreground_timeout: std::time::Duration::from_secs(DEFAULT_FOREGROUND_TIMEOUT_SECS),
+            journal: false,
+        }
+    }
+}
+
+/// Look up the calling agent in the config by its workspace.  `tool`
+/// selects the `tool_timeouts` entry used for the foreground limit.
+async fn agent_context(workspace: &Path, tool: &str) -> anyhow::Result<AgentContext> {
+    let limit = |agent| {
+        crate::tools::timeout_for(tool, agent).unwrap_or(std::time::Duration::from_secs(
+            DEFAULT_FOREGROUND_TIMEOUT_SECS,
+        ))
+    };
+    let unknown = || AgentContext {
+        foreground_timeout: limit(None),
+        ..AgentContext::default()
+    };
+    let Some(cfg) = crate::config::Config::current().await? else {
+        return Ok(unknown());
+    };
+    let Some(agent) = cfg.agent_for_workspace(workspace) else {
+        return Ok(unknown());
+    };
+    Ok(AgentContext {
+        agent_id: Some(agent.id.clone()),
+        sandbox: agent.sandbox.clone().filter(|s| s.enabled),
+        foreground_timeout: limit(Some(agent)),
+        journal: agent.journal_exec_shell,
+    })
+}
+
+/// Build `sh -c command` in the workspace with a scrubbed environment.
+/// Callers set up stdio.
+fn shell_command(command: &str, workspace: &Path) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(workspace)
+        .env_clear()
+        .env("PATH", inherited_path())
+        .env("HOME", workspace.to_string_lossy().to_string())
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    cmd
+}
+
+/// Spawn `cmd`, sandboxed when configured.
+fn spawn_sandboxed(
+    mut cmd: tokio::process::Command,
+    workspace: &Path,
+    sandbox_cfg: Option<&SandboxConfig>,
+) -> anyhow::Result<Child> {
+    // Held until after spawn: owns the fds the child inherits.
+    let sandbox = match sandbox_cfg {
+        Some(cfg) => {
+            let sb = Sandbox::prepare(cfg, workspace)
+                .map_err(|e| anyhow::anyhow!("exec_shell: sandbox setup failed: {e}"))?;
+            sb.apply(&mut cmd);
+            Some(sb)
+        }
+        None => None,
+    };
+    let child = cmd.spawn().map_err(|e| {
+        if sandbox.is_some() {
+            anyhow::anyhow!("exec_shell: sandboxed spawn failed: {e}")
+        } else {
+            anyhow::anyhow!("exec_shell: spawn failed: {e}")
+        }
+    })?;
+    drop(sandbox);
+    Ok(child)
+}
+
+/// Register the `exec_shell` tool metadata in the global registry.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "exec_shell".into(),
+        description: "Execute a shell command in the agent workspace. Most commands allowed; sudo, privilege escalation, and destructive disk/kernel tools are blocked. Interpreters (python, node, etc.) and scripts are allowed. Supports background mode (background=true) returning a process_id, and interactive PTY sessions (interactive=true) that accept input. Manage them with action='read' (incremental output since a cursor), 'write' (send input), 'signal', 'status', 'output', 'kill' or 'list'.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to execute (via `sh -c`). Not needed for management actions."
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "If true, spawn in background and return a process_id immediately. Default: false."
+                },
+                "interactive": {
+                    "type": "boolean",
+                    "description": "If true, run on a pseudo-terminal in the background so the process can be driven with action='write'/'read'. Use for REPLs, prompts and TUIs. Default: false."
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Kill a background/interactive process after this many seconds. Default: 120 (background), 1800 (interactive); max 14400."
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "read", "write", "signal", "output", "kill", "list"],
+                    "description": "Manage background processes: 'status' checks if done, 'read' returns output since `cursor` (pass back the returned cursor next time), 'write' sends `input` to an interactive session, 'signal' sends `signal`, 'output' waits and returns stdout/stderr, 'kill' terminates, 'list' shows this agent's processes."
+                },
+                "process_id": {
+                    "type": "integer",
+                    "description": "Background process ID (returned from background=true or interactive=true). Required for all actions except list."
+                },
+                "cursor": {
+                    "type": "integer",
+                    "description": "For action='read': output offset to read from. Default: 0."
+                },
+                "wait_ms": {
+                    "type": "integer",
+                    "description": "For action='read': wait up to this long (max 30000) for new output. Default: 0."
+                },
+                "input": {
+                    "type": "string",
+                    "description": "For action='write': text to send to the terminal. Include \\n to press Enter."
+                },
+                "signal": {
+                    "type": "string",
+                    "enum": pty::SIGNAL_NAMES,
+                    "description": "For action='signal': signal to send to the process group."
+                }
+            },
+            "additionalProperties": false
+        }),
+        exclusive: true,
+        timeout_secs: Some(DEFAULT_FOREGROUND_TIMEOUT_SECS),
+    });
+}
@@ -0,0 +1,160 @@
+//! Pseudo-terminal, signal and liveness plumbing for background
+//! `exec_shell` processes.
+//!
+//! Only implemented on Linux; elsewhere interactive sessions and
+//! signals other than `kill` are reported as unsupported, and processes
+//! cannot be re-attached after a restart.
+
+use std::fs::File;
+
+/// Signals the agent may send to a background process group.
+pub(super) const SIGNAL_NAMES: &[&str] = &[
+    "INT", "TERM", "KILL", "HUP", "QUIT", "STOP", "CONT", "USR1", "USR2",
+];
+
+/// Allocate a pseudo-terminal and wire its slave side up as the
+/// command's stdin/stdout/stderr.  The child becomes a session leader
+/// with the terminal as its controlling tty, so Ctrl-C style signals
+/// and job control behave as in a real shell.
+///
+/// Returns the master side; read it for output and write it for input.
+/// Must be called before any other `pre_exec` hooks are installed.
+#[cfg(target_os = "linux")]
+pub(super) fn attach(cmd: &mut tokio::process::Command) -> anyhow::Result<File> {
+    use std::ffi::CStr;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::process::Stdio;
+
+    let os_err = |what: &str| anyhow::anyhow!("{what}: {}", std::io::Error::last_os_error());
+
+    // SAFETY: plain libc calls on a descriptor we own; `name` is sized
+    // for any /dev/pts path and ptsname_r NUL-terminates on success.
+    let (master, slave_path) = unsafe {
+        let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(os_err("posix_openpt"));
+        }
+        let master = OwnedFd::from_raw_fd(fd);
+        if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+            return Err(os_err("unlockpt"));
+        }
+        let mut name = [0 as libc::c_char; 128];
+        if libc::ptsname_r(fd, name.as_mut_ptr(), name.len()) != 0 {
+            return Err(os_err("ptsname_r"));
+        }
+        let path = CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+        (master, path)
+    };
+
+    let slave = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(&slave_path)?;
+
+    // SAFETY: ioctl/tcgetattr on the valid descriptors opened above.
+    unsafe {
+        let size = libc::winsize {
+            ws_row: 40,
+            ws_col: 120,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size);
+
+        // Keep bare `\n` line endings so output reads like piped output.
+        let mut tio: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(slave.as_raw_fd(), &mut tio) == 0 {
+            tio.c_oflag &= !libc::ONLCR;
+            libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &tio);
+        }
+    }
+
+    cmd.stdin(Stdio::from(slave.try_clone()?))
+        .stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave))
+        .env("TERM", "dumb");
+
+    // SAFETY: setsid and ioctl are async-signal-safe.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    Ok(File::from(master))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn attach(_cmd: &mut tokio::process::Command) -> anyhow::Result<File> {
+    anyhow::bail!("interactive sessions are only supported on Linux")
+}
+
+/// Send `signal` (e.g. `"INT"` or `"SIGTERM"`) to the process group led
+/// by `pid`, so children of the shell receive it too.
+#[cfg(target_os = "linux")]
+pub(super) fn signal_group(pid: u32, signal: &str) -> anyhow::Result<()> {
+    let name = signal.trim().to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    let sig = match name {
+        "INT" => libc::SIGINT,
+        "TERM" => libc::SIGTERM,
+        "KILL" => libc::SIGKILL,
+        "HUP" => libc::SIGHUP,
+        "QUIT" => libc::SIGQUIT,
+        "STOP" => libc::SIGSTOP,
+        "CONT" => libc::SIGCONT,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        other => anyhow::bail!(
+            "unknown signal '{other}'. Valid: {}",
+            SIGNAL_NAMES.join(", ")
+        ),
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    if unsafe { libc::kill(-(pid as libc::pid_t), sig) } != 0 {
+        anyhow::bail!("kill: {}", std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn signal_group(_pid: u32, _signal: &str) -> anyhow::Result<()> {
+    anyhow::bail!("signals are only supported on Linux; use action='kill'")
+}
+
+/// Kernel start time of `pid` (clock ticks since boot), or `None` if the
+/// process does not exist or is a zombie.  Stored alongside the pid so a
+/// recycled pid is not mistaken for the original process.
+#[cfg(target_os = "linux")]
+pub(super) fn start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name may contain spaces and parens; fields after it
+    // start at field 3 (state), so starttime (field 22) is index 19.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    if fields.first() == Some(&"Z") {
+        return None;
+    }
+    fields.get(19)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn start_time(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Whether `pid` is still the process that was started at `started`.
+pub(super) fn is_alive(pid: u32, started: Option<u64>) -> bool {
+    match (start_time(pid), started) {
+        (Some(now), Some(then)) => now == then,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
@@ -0,0 +1,1337 @@
+//! Built-in `git` tool — structured git operations on repositories inside
+//! the agent workspace.
+//!
+//! Each action runs plain `git` subprocesses (no shell) and parses their
+//! machine-readable output into JSON, so the model gets compact results
+//! instead of pager-formatted text.  Nothing that rewrites history is
+//! exposed: there is no amend, reset, rebase or force push, `branch` only
+//! deletes merged branches and `push` sends a single branch
+//! fast-forward-only (optionally limited by the agent's `tools.git_push`
+//! policy).
+//!
+//! git runs outside the `exec_shell` sandbox, so everything a checked-out
+//! repo's config or `.gitattributes` could use to start a program is
+//! switched off: hooks, fsmonitor, filter and merge drivers, textconv and
+//! external diff, pagers, editors, askpass, credential helpers, signing,
+//! custom SSH commands and transports other than file, http(s) and ssh.
+//! The environment is scrubbed (the ssh-agent socket is kept) and system
+//! config ignored; with credential helpers off, push over SSH.
+//!
+//! `checkout` and the stash operations that touch the worktree run under
+//! a journal [`Snapshot`](crate::journal::Snapshot), so `/undo` can put
+//! the files back.
+
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::tools::{register_tool, sandbox_path, ToolMeta};
+
+const ACTIONS: &[&str] = &[
+    "status", "diff", "log", "show", "add", "commit", "branch", "checkout", "stash", "push",
+];
+/// Arguments that would ask for a history rewrite.  Refused outright so
+/// the model gets a clear answer instead of a silently ignored flag.
+const REWRITE_ARGS: &[&str] = &["force", "force_with_lease", "amend", "hard", "rebase"];
+/// Actions the model may reach for that rewrite history.
+const REWRITE_ACTIONS: &[&str] = &[
+    "reset",
+    "rebase",
+    "amend",
+    "filter-branch",
+    "filter_branch",
+    "force_push",
+];
+const DEFAULT_PAGE_LINES: usize = 400;
+const MAX_PAGE_LINES: usize = 2000;
+const DEFAULT_LOG_COUNT: usize = 20;
+const MAX_LOG_COUNT: usize = 200;
+/// Entries reported per status list.
+const MAX_STATUS_ENTRIES: usize = 500;
+/// Config applied to every invocation.  Command-line config wins over
+/// the repo's own, which is what keeps a hostile `.git/config` from
+/// naming programs for git to run.
+const SAFE_CONFIG: &[&str] = &[
+    "-c",
+    "core.hooksPath=/dev/null",
+    "-c",
+    "core.fsmonitor=false",
+    "-c",
+    "core.alternateRefsCommand=",
+    "-c",
+    "credential.helper=",
+    "-c",
+    "commit.gpgSign=false",
+    "-c",
+    "tag.gpgSign=false",
+    "-c",
+    "log.showSignature=false",
+    "-c",
+    "diff.external=",
+    "-c",
+    "core.quotepath=off",
+    "-c",
+    "color.ui=false",
+    "-c",
+    "advice.detachedHead=false",
+];
+
+/// Run a structured git action.
+///
+/// Args: `{ "action": "status" | "diff" | "log" | "show" | "add" | "commit"
+///          | "branch" | "checkout" | "stash" | "push", "path": "repo", … }`
+/// — see the tool schema for per-action arguments.
+pub async fn git(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let action = args
+        .get("action")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("git: missing `action` argument"))?;
+    check_history_safe(action, &args)?;
+    let raw = args.get("path").and_then(Value::as_str).unwrap_or(".");
+    let repo = sandbox_path(workspace, raw)?;
+    if !repo.is_dir() {
+        anyhow::bail!("git: '{raw}' is not a directory");
+    }
+
+    match action {
+        "status" => status(&repo).await,
+        "diff" => diff(&repo, &args).await,
+        "log" => log(&repo, &args).await,
+        "show" => show(&repo, &args).await,
+        "add" => add(&repo, &args).await,
+        "commit" => commit(workspace, &repo, &args).await,
+        "branch" => branch(&repo, &args).await,
+        "checkout" => journalled(workspace, checkout(&repo, &args)).await,
+        "stash" => match args.get("op").and_then(Value::as_str) {
+            Some("list" | "show") => stash(&repo, &args).await,
+            _ => journalled(workspace, stash(&repo, &args)).await,
+        },
+        "push" => push(workspace, &repo, &args).await,
+        other => anyhow::bail!(
+            "git: unknown action '{other}' — expected one of {}",
+            ACTIONS.join(", ")
+        ),
+    }
+}
+
+/// Run an action that rewrites worktree files, journalling what it
+/// changed for the running turn.
+async fn journalled(
+    workspace: &Path,
+    action: impl std::future::Future<Output = anyhow::Result<Value>>,
+) -> anyhow::Result<Value> {
+    let ws = workspace.to_path_buf();
+    let snapshot = tokio::task::spawn_blocking(move || crate::journal::Snapshot::take(&ws))
+        .await
+        .ok()
+        .flatten();
+    let result = action.await;
+    if let Some(snapshot) = snapshot {
+        let _ = tokio::task::spawn_blocking(move || snapshot.record_changes("git")).await;
+    }
+    result
+}
+
+/// Refuse requests that would rewrite history.
+fn check_history_safe(action: &str, args: &Value) -> anyhow::Result<()> {
+    if REWRITE_ACTIONS.contains(&action) {
+        anyhow::bail!("git: '{action}' rewrites history and is not available");
+    }
+    if let Some(key) = REWRITE_ARGS.iter().find(|k| {
+        args.get(**k)
+            .is_some_and(|v| v != &Value::Bool(false) && !v.is_null())
+    }) {
+        anyhow::bail!("git: `{key}` is not allowed — this tool never rewrites history");
+    }
+    Ok(())
+}
+
+// ── Process helpers ──────────────────────────────────────────
+
+struct GitOutput {
+    ok: bool,
+    stdout: String,
+    stderr: String,
+}
+
+impl GitOutput {
+    /// stderr, else stdout — git reports some failures (e.g. "nothing to
+    /// commit") on stdout.
+    fn message(&self) -> String {
+        let err = self.stderr.trim();
+        if err.is_empty() {
+            self.stdout.trim().to_string()
+        } else {
+            err.to_string()
+        }
+    }
+}
+
+/// Environment variables passed through to git; everything else is
+/// dropped.
+const KEPT_ENV: &[&str] = &["PATH", "HOME", "XDG_CONFIG_HOME", "SSH_AUTH_SOCK"];
+
+/// `git` with the safe config and a scrubbed environment.  Environment
+/// settings override the matching repo config (`core.sshCommand`,
+/// `core.pager`, `core.editor`, `core.askPass`).
+fn git_command(repo: &Path) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.arg("--no-pager")
+        .args(SAFE_CONFIG)
+        .current_dir(repo)
+        .env_clear()
+        .envs(
+            KEPT_ENV
+                .iter()
+                .filter_map(|k| Some((k, std::env::var_os(k)?))),
+        )
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .env("GIT_SSH_COMMAND", "ssh")
+        .env("GIT_PAGER", "cat")
+        .env("GIT_EDITOR", "true")
+        .env("GIT_ALLOW_PROTOCOL", "file:http:https:ssh")
+        .env("LC_ALL", "C")
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    cmd
+}
+
+/// `-c` overrides blanking every filter and merge driver the repo's
+/// config defines: `.gitattributes` selects drivers by name, so each
+/// one has to be switched off by name.
+async fn driver_overrides(repo: &Path) -> anyhow::Result<Vec<String>> {
+    let output = git_command(repo)
+        .args([
+            "config",
+            "--name-only",
+            "--get-regexp",
+            r"^(filter|merge)\.",
+        ])
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("git: failed to run git: {e}"))?;
+    let names = String::from_utf8_lossy(&output.stdout);
+    let mut drivers: Vec<(&str, &str)> = names
+        .lines()
+        .filter_map(|key| {
+            let (kind, rest) = key.split_once('.')?;
+            let (name, _) = rest.rsplit_once('.')?;
+            Some((kind, name))
+        })
+        .collect();
+    drivers.sort_unstable();
+    drivers.dedup();
+    let mut args = Vec::new();
+    for (kind, name) in drivers {
+        let keys: &[&str] = if kind == "filter" {
+            &["clean=", "smudge=", "process=", "required=false"]
+        } else {
+            &["driver="]
+        };
+        for key in keys {
+            args.push("-c".to_string());
+            args.push(format!("{kind}.{name}.{key}"));
+        }
+    }
+    Ok(args)
+}
+
+async fn run(repo: &Path, args: &[&str]) -> anyhow::Result<GitOutput> {
+    let overrides = driver_overrides(repo).await?;
+    let output = git_command(repo)
+        .args(overrides)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("git: failed to run git: {e}"))?;
+    Ok(GitOutput {
+        ok: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Like [`run`], failing with git's message on a non-zero exit.
+async fn run_ok(repo: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let out = run(repo, args).await?;
+    if !out.ok {
+        anyhow::bail!("git {}: {}", args[0], out.message());
+    }
+    Ok(out.stdout)
+}
+
+/// Validate a revision, branch or remote name from the model: no option
+/// injection, no whitespace or control characters.
+fn check_rev<'a>(what: &str, rev: &'a str) -> anyhow::Result<&'a str> {
+    if rev.is_empty()
+        || rev.starts_with('-')
+        || rev.len() > 256
+        || rev.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        anyhow::bail!("git: invalid {what} '{rev}'");
+    }
+    Ok(rev)
+}
+
+fn opt_rev<'a>(args: &'a Value, key: &str) -> anyhow::Result<Option<&'a str>> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(|s| check_rev(key, s))
+        .transpose()
+}
+
+/// `paths` argument: repo-relative pathspecs, each confined to the repo.
+fn pathspecs(repo: &Path, args: &Value) -> anyhow::Result<Vec<String>> {
+    let Some(list) = args.get("paths") else {
+        return Ok(Vec::new());
+    };
+    let list = list
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("git: `paths` must be an array of strings"))?;
+    list.iter()
+        .map(|p| {
+            let p = p
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("git: `paths` must be an array of strings"))?;
+            sandbox_path(repo, p)?;
+            Ok(p.to_string())
+        })
+        .collect()
+}
+
+fn flag(args: &Value, key: &str) -> bool {
+    args.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Return one page of `text` by line (`start_line` is 1-based).
+fn page(text: &str, args: &Value) -> Value {
+    let lines: Vec<&str> = text.lines().collect();
+    let total = lines.len();
+    let start = args
+        .get("start_line")
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+        .max(1) as usize;
+    let max = args
+        .get("max_lines")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_PAGE_LINES)
+        .clamp(1, MAX_PAGE_LINES);
+    let from = (start - 1).min(total);
+    let to = (from + max).min(total);
+    let mut out = json!({
+        "patch": lines[from..to].join("\n"),
+        "start_line": from + 1,
+        "end_line": to,
+        "total_lines": total,
+    });
+    if to < total {
+        out["next_start_line"] = json!(to + 1);
+    }
+    out
+}
+
+// ── Parsers ──────────────────────────────────────────────────
+
+fn status_word(c: char) -> &'static str {
+    match c {
+        'M' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'T' => "typechange",
+        'U' => "unmerged",
+        _ => "changed",
+    }
+}
+
+/// Parse `git status --porcelain=v2 --branch -z`.
+fn parse_status(raw: &str) -> Value {
+    let mut branch: Option<String> = None;
+    let mut upstream: Option<String> = None;
+    let (mut ahead, mut behind) = (0i64, 0i64);
+    let mut staged = Vec::new();
+    let mut unstaged = Vec::new();
+    let mut untracked = Vec::new();
+    let mut conflicted = Vec::new();
+
+    let mut fields = raw.split('\0').filter(|f| !f.is_empty());
+    while let Some(entry) = fields.next() {
+        if let Some(header) = entry.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value != "(detached)" => branch = Some(value.to_string()),
+                "branch.upstream" => upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split(' ') {
+                        if let Some(n) = part.strip_prefix('+') {
+                            ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let kind = entry.chars().next().unwrap_or(' ');
+        match kind {
+            '1' | '2' => {
+                // 1 XY sub mH mI mW hH hI path
+                // 2 XY sub mH mI mW hH hI Xscore path  (+ origPath field)
+                let n = if kind == '1' { 9 } else { 10 };
+                let parts: Vec<&str> = entry.splitn(n, ' ').collect();
+                let xy: Vec<char> = parts.get(1).unwrap_or(&"..").chars().collect();
+                let path = parts.last().copied().unwrap_or("").to_string();
+                let from = if kind == '2' {
+                    fields.next().map(str::to_string)
+                } else {
+                    None
+                };
+                if xy[0] != '.' {
+                    let mut e = json!({ "path": path, "status": status_word(xy[0]) });
+                    if let Some(ref from) = from {
+                        e["from"] = json!(from);
+                    }
+                    staged.push(e);
+                }
+                if xy.get(1).is_some_and(|c| *c != '.') {
+                    unstaged.push(json!({ "path": path, "status": status_word(xy[1]) }));
+                }
+            }
+            'u' => {
+                let path = entry.splitn(11, ' ').last().unwrap_or("");
+                conflicted.push(json!(path));
+            }
+            '?' => untracked.push(json!(&entry[2..])),
+            _ => {}
+        }
+    }
+
+    let clean =
+        staged.is_empty() && unstaged.is_empty() && untracked.is_empty() && conflicted.is_empty();
+    let mut out = json!({
+        "branch": branch,
+        "detached": branch.is_none(),
+        "upstream": upstream,
+        "ahead": ahead,
+        "behind": behind,
+        "clean": clean,
+    });
+    for (key, mut list) in [
+        ("staged", staged),
+        ("unstaged", unstaged),
+        ("untracked", untracked),
+        ("conflicted", conflicted),
+    ] {
+        if list.len() > MAX_STATUS_ENTRIES {
+            out[format!("{key}_total")] = json!(list.len());
+            list.truncate(MAX_STATUS_ENTRIES);
+        }
+        out[key] = Value::Array(list);
+    }
+    out
+}
+
+/// Parse `--numstat -z` output into `[{path, added, deleted, from?}]`.
+/// Binary files report `null` counts.
+fn parse_numstat(raw: &str) -> Vec<Value> {
+    let mut files = Vec::new();
+    let mut fields = raw.split('\0');
+    while let Some(entry) = fields.next() {
+        let entry = entry.trim_start_matches('\n');
+        let mut parts = entry.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let count = |s: &str| s.parse::<u64>().ok();
+        let mut file = json!({ "added": count(added), "deleted": count(deleted) });
+        if path.is_empty() {
+            // Rename/copy: the old and new paths follow as separate fields.
+            let from = fields.next().unwrap_or("");
+            let to = fields.next().unwrap_or("");
+            file["path"] = json!(to);
+            file["from"] = json!(from);
+        } else {
+            file["path"] = json!(path);
+        }
+        files.push(file);
+    }
+    files
+}
+
+const COMMIT_FORMAT: &str = "--format=%H%x1f%h%x1f%an%x1f%ae%x1f%aI%x1f%s";
+
+/// Parse one record of [`COMMIT_FORMAT`].
+fn parse_commit(record: &str) -> Option<Value> {
+    let f: Vec<&str> = record.trim_matches('\n').split('\x1f').collect();
+    if f.len() < 6 {
+        return None;
+    }
+    Some(json!({
+        "commit": f[0],
+        "short": f[1],
+        "author": f[2],
+        "email": f[3],
+        "date": f[4],
+        "subject": f[5],
+    }))
+}
+
+async fn current_branch(repo: &Path) -> Option<String> {
+    let out = run(repo, &["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .await
+        .ok()?;
+    out.ok.then(|| out.stdout.trim().to_string())
+}
+
+// ── Actions ──────────────────────────────────────────────────
+
+async fn status(repo: &Path) -> anyhow::Result<Value> {
+    let raw = run_ok(
+        repo,
+        &[
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "-z",
+            "--untracked-files=all",
+        ],
+    )
+    .await?;
+    Ok(parse_status(&raw))
+}
+
+async fn diff(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let staged = flag(args, "staged");
+    let rev = opt_rev(args, "ref")?;
+    let paths = pathspecs(repo, args)?;
+
+    let build = |extra: &[&'static str]| {
+        let mut argv: Vec<String> = vec!["diff".into(), "--no-ext-diff".into()];
+        argv.extend(extra.iter().map(|s| s.to_string()));
+        if staged {
+            argv.push("--cached".into());
+        }
+        if let Some(rev) = rev {
+            argv.push(rev.to_string());
+        }
+        argv.push("--".into());
+        argv.extend(paths.iter().cloned());
+        argv
+    };
+    let numstat = build(&["--numstat", "-z"]);
+    let patch = build(&["--no-textconv", "--patch"]);
+
+    let files = parse_numstat(&run_ok(repo, &as_strs(&numstat)).await?);
+    let text = run_ok(repo, &as_strs(&patch)).await?;
+    let mut out = page(&text, args);
+    out["files"] = json!(files);
+    out["staged"] = json!(staged);
+    Ok(out)
+}
+
+async fn log(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let count = args
+        .get("max_count")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_LOG_COUNT)
+        .clamp(1, MAX_LOG_COUNT);
+    let skip = args.get("skip").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let rev = opt_rev(args, "ref")?;
+    let paths = pathspecs(repo, args)?;
+
+    let mut argv: Vec<String> = vec![
+        "log".into(),
+        "-z".into(),
+        COMMIT_FORMAT.into(),
+        format!("--max-count={}", count + 1),
+        format!("--skip={skip}"),
+    ];
+    if let Some(rev) = rev {
+        argv.push(rev.to_string());
+    }
+    argv.push("--".into());
+    argv.extend(paths);
+
+    let out = run(repo, &as_strs(&argv)).await?;
+    if !out.ok {
+        // A fresh repository has no commits yet.
+        if out.stderr.contains("does not have any commits") {
+            return Ok(json!({ "commits": [], "has_more": false }));
+        }
+        anyhow::bail!("git log: {}", out.message());
+    }
+    let mut commits: Vec<Value> = out.stdout.split('\0').filter_map(parse_commit).collect();
+    let has_more = commits.len() > count;
+    commits.truncate(count);
+    let mut result = json!({ "commits": commits, "has_more": has_more });
+    if has_more {
+        result["next_skip"] = json!(skip + count);
+    }
+    Ok(result)
+}
+
+async fn show(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let rev = opt_rev(args, "ref")?.unwrap_or("HEAD");
+    let paths = pathspecs(repo, args)?;
+
+    let meta = run_ok(
+        repo,
+        &[
+            "show",
+            "-s",
+            "--format=%H%x1f%h%x1f%an%x1f%ae%x1f%aI%x1f%P%x1f%B",
+            rev,
+            "--",
+        ],
+    )
+    .await?;
+    let f: Vec<&str> = meta.splitn(7, '\x1f').collect();
+    if f.len() < 7 {
+        anyhow::bail!("git show: unexpected output for '{rev}'");
+    }
+    let numstat = run_ok(repo, &["show", "--format=", "--numstat", "-z", rev, "--"]).await?;
+
+    let mut argv: Vec<String> = [
+        "show",
+        "--format=",
+        "--no-ext-diff",
+        "--no-textconv",
+        "--patch",
+        rev,
+        "--",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    argv.extend(paths);
+    let text = run_ok(repo, &as_strs(&argv)).await?;
+
+    let mut out = page(&text, args);
+    out["commit"] = json!(f[0]);
+    out["short"] = json!(f[1]);
+    out["author"] = json!(f[2]);
+    out["email"] = json!(f[3]);
+    out["date"] = json!(f[4]);
+    out["parents"] = json!(f[5].split_whitespace().collect::<Vec<_>>());
+    out["message"] = json!(f[6].trim_end());
+    out["files"] = json!(parse_numstat(&numstat));
+    Ok(out)
+}
+
+async fn add(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let paths = pathspecs(repo, args)?;
+    if flag(args, "all") {
+        run_ok(repo, &["add", "--all"]).await?;
+    } else if paths.is_empty() {
+        anyhow::bail!("git add: pass `paths` (or `all: true` to stage everything)");
+    } else {
+        let mut argv = vec!["add".to_string(), "--".to_string()];
+        argv.extend(paths);
+        run_ok(repo, &as_strs(&argv)).await?;
+    }
+    let status = status(repo).await?;
+    Ok(json!({ "staged": status["staged"], "unstaged": status["unstaged"] }))
+}
+
+async fn commit(workspace: &Path, repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let message = args
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("git commit: missing `message` argument"))?;
+
+    // Fall back to an identity named after the agent when the repo and
+    // user config have none.
+    let mut argv: Vec<String> = Vec::new();
+    let has_identity = run(repo, &["config", "user.email"]).await?.ok;
+    if !has_identity {
+        let name = workspace
+            .parent()
+            .and_then(Path::file_name)
+            .and_then(|n| n.to_str())
+            .unwrap_or("pinchy");
+        argv.extend([
+            "-c".into(),
+            format!("user.name={name}"),
+            "-c".into(),
+            "user.email=pinchy@localhost".into(),
+        ]);
+    }
+    argv.extend([
+        "commit".into(),
+        "--quiet".into(),
+        "-m".into(),
+        message.into(),
+    ]);
+    if flag(args, "all") {
+        argv.push("--all".into());
+    }
+    let out = run(repo, &as_strs(&argv)).await?;
+    if !out.ok {
+        anyhow::bail!("git commit: {}", out.message());
+    }
+
+    let head = run_ok(repo, &["show", "-s", COMMIT_FORMAT, "HEAD"]).await?;
+    let mut result = parse_commit(&head).unwrap_or_else(|| json!({}));
+    let numstat = run_ok(repo, &["show", "--format=", "--numstat", "-z", "HEAD"]).await?;
+    result["files"] = json!(parse_numstat(&numstat));
+    result["branch"] = json!(current_branch(repo).await);
+    Ok(result)
+}
+
+async fn branch(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let op = args.get("op").and_then(Value::as_str).unwrap_or("list");
+    match op {
+        "list" => {
+            let mut refs = vec!["refs/heads"];
+            if flag(args, "remotes") {
+                refs.push("refs/remotes");
+            }
+            let mut argv = vec![
+                "for-each-ref",
+                "--format=%(refname:short)%1f%(objectname:short)%1f%(upstream:short)%1f%(HEAD)",
+            ];
+            argv.extend(refs);
+            let raw = run_ok(repo, &argv).await?;
+            let branches: Vec<Value> = raw
+                .lines()
+                .filter_map(|l| {
+                    let f: Vec<&str> = l.split('\x1f').collect();
+                    (f.len() == 4).then(|| {
+                        json!({
+                            "name": f[0],
+                            "commit": f[1],
+                            "upstream": (!f[2].is_empty()).then_some(f[2]),
+                            "current": f[3] == "*",
+                        })
+                    })
+                })
+                .collect();
+            Ok(json!({ "branches": branches }))
+        }
+        "create" => {
+            let name = opt_rev(args, "name")?
+                .ok_or_else(|| anyhow::anyhow!("git branch: missing `name` argument"))?;
+            let mut argv = vec!["branch", "--", name];
+            if let Some(start) = opt_rev(args, "start_point")? {
+                argv.push(start);
+            }
+            run_ok(repo, &argv).await?;
+            Ok(json!({ "created": name }))
+        }
+        "delete" => {
+            let name = opt_rev(args, "name")?
+                .ok_or_else(|| anyhow::anyhow!("git branch: missing `name` argument"))?;
+            // `-d` refuses branches that are not fully merged.
+            run_ok(repo, &["branch", "-d", "--", name]).await?;
+            Ok(json!({ "deleted": name }))
+        }
+        other => {
+            anyhow::bail!("git branch: unknown op '{other}' — expected list, create or delete")
+        }
+    }
+}
+
+async fn checkout(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let target = opt_rev(args, "branch")?
+        .or(opt_rev(args, "ref")?)
+        .ok_or_else(|| anyhow::anyhow!("git checkout: missing `branch` argument"))?;
+    let mut argv = vec!["switch"];
+    if flag(args, "create") {
+        argv.extend(["--create", target]);
+        if let Some(start) = opt_rev(args, "start_point")? {
+            argv.push(start);
+        }
+    } else if flag(args, "detach") {
+        argv.extend(["--detach", target]);
+    } else {
+        argv.push(target);
+    }
+    // `switch` refuses to overwrite uncommitted changes.
+    run_ok(repo, &argv).await?;
+    let status = status(repo).await?;
+    Ok(json!({
+        "branch": status["branch"],
+        "detached": status["detached"],
+        "clean": status["clean"],
+    }))
+}
+
+async fn stash(repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let op = args.get("op").and_then(Value::as_str).unwrap_or("push");
+    let index = args.get("index").and_then(Value::as_u64).unwrap_or(0);
+    let entry = format!("stash@{{{index}}}");
+    match op {
+        "push" => {
+            let mut argv = vec!["stash".to_string(), "push".to_string()];
+            if flag(args, "include_untracked") {
+                argv.push("--include-untracked".into());
+            }
+            if let Some(msg) = args.get("message").and_then(Value::as_str) {
+                argv.extend(["--message".into(), msg.to_string()]);
+            }
+            let out = run_ok(repo, &as_strs(&argv)).await?;
+            Ok(json!({ "result": out.trim() }))
+        }
+        "list" => {
+            let raw = run_ok(repo, &["stash", "list", "--format=%gd%x1f%gs"]).await?;
+            let entries: Vec<Value> = raw
+                .lines()
+                .filter_map(|l| l.split_once('\x1f'))
+                .map(|(r, m)| json!({ "ref": r, "message": m }))
+                .collect();
+            Ok(json!({ "stashes": entries }))
+        }
+        "pop" | "apply" => {
+            let out = run(repo, &["stash", op, &entry]).await?;
+            if !out.ok {
+                anyhow::bail!("git stash {op}: {}", out.message());
+            }
+            let status = status(repo).await?;
+            Ok(json!({ "applied": entry, "dropped": op == "pop", "status": status }))
+        }
+        "show" => {
+            let text = run_ok(
+                repo,
+                &[
+                    "stash",
+                    "show",
+                    "--no-ext-diff",
+                    "--no-textconv",
+                    "--patch",
+                    &entry,
+                ],
+            )
+            .await?;
+            let mut out = page(&text, args);
+            out["stash"] = json!(entry);
+            Ok(out)
+        }
+        other => anyhow::bail!(
+            "git stash: unknown op '{other}' — expected push, list, pop, apply or show"
+        ),
+    }
+}
+
+async fn push(workspace: &Path, repo: &Path, args: &Value) -> anyhow::Result<Value> {
+    let branch = match opt_rev(args, "branch")? {
+        Some(b) => b.to_string(),
+        None => current_branch(repo)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("git push: HEAD is detached — pass `branch`"))?,
+    };
+    check_rev("branch", &branch)?;
+    if !run(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{branch}"),
+        ],
+    )
+    .await?
+    .ok
+    {
+        anyhow::bail!("git push: no local branch '{branch}'");
+    }
+
+    let remote = match opt_rev(args, "remote")? {
+        Some(r) => r.to_string(),
+        None => run(repo, &["config", &format!("branch.{branch}.remote")])
+            .await?
+            .stdout
+            .trim()
+            .to_string(),
+    };
+    let remote = if remote.is_empty() {
+        "origin".to_string()
+    } else {
+        remote
+    };
+    let remotes = run_ok(repo, &["remote"]).await?;
+    if !remotes.lines().any(|r| r == remote) {
+        anyhow::bail!("git push: no remote named '{remote}'");
+    }
+
+    let policy = agent_tool_policy(workspace).await.map_err(|e| {
+        anyhow::anyhow!("git push: not pushed — this agent's push rules could not be checked ({e})")
+    })?;
+    if let Some(policy) = policy {
+        crate::tools::policy::check_git_push(&policy, &remote, &branch)
+            .map_err(|e| anyhow::anyhow!(e))?;
+    }
+
+    let refspec = format!("refs/heads/{branch}:refs/heads/{branch}");
+    // Named explicitly: `remote.<name>.receivepack` would otherwise pick
+    // the program a local (file) push runs.
+    let mut argv = vec!["push", "--porcelain", "--receive-pack=git-receive-pack"];
+    if flag(args, "set_upstream") {
+        argv.push("--set-upstream");
+    }
+    argv.extend(["--", remote.as_str(), refspec.as_str()]);
+    let out = run(repo, &argv).await?;
+
+    // Porcelain lines: "<flag>\t<from>:<to>\t<summary>".
+    let line = out
+        .stdout
+        .lines()
+        .find(|l| l.contains(&format!(":refs/heads/{branch}")));
+    let (state, summary) = match line {
+        Some(l) => {
+            let mut parts = l.split('\t');
+            let flag = parts.next().unwrap_or("");
+            let summary = parts.nth(1).unwrap_or("").to_string();
+            let state = match flag {
+                " " => "pushed",
+                "*" => "new_branch",
+                "=" => "up_to_date",
+                "!" => "rejected",
+                _ => "unknown",
+            };
+            (state, summary)
+        }
+        None => ("failed", String::new()),
+    };
+    if !out.ok || state == "rejected" {
+        anyhow::bail!(
+            "git push to {remote}/{branch} {state}: {} — force pushes are not allowed; \
+             integrate the remote changes first",
+            if summary.is_empty() {
+                out.message()
+            } else {
+                summary
+            }
+        );
+    }
+    Ok(json!({
+        "remote": remote,
+        "branch": branch,
+        "result": state,
+        "summary": summary,
+    }))
+}
+
+/// The calling agent's `tools:` policy, if it has one.  A config that
+/// fails to load is an error, so a broken file cannot lift the agent's
+/// push restrictions.
+async fn agent_tool_policy(workspace: &Path) -> anyhow::Result<Option<crate::config::ToolPolicy>> {
+    let Some(cfg) = crate::config::Config::current().await? else {
+        return Ok(None);
+    };
+    Ok(cfg
+        .agent_for_workspace(workspace)
+        .and_then(|a| a.tools.clone()))
+}
+
+fn as_strs(v: &[String]) -> Vec<&str> {
+    v.iter().map(String::as_str).collect()
+}
+
+/// Register the `git` tool.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "git".into(),
+        description: "Structured git for repositories in the workspace: status, diff (paged), log, show, add, commit, branch, checkout, stash and push. Returns JSON. History rewriting (amend, reset, rebase, force push) is not available. Prefer this over exec_shell git.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ACTIONS,
+                    "description": "Operation to run."
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative repository directory (default: '.')."
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "diff/log/show: limit to these repo-relative paths. add: paths to stage."
+                },
+                "ref": {
+                    "type": "string",
+                    "description": "diff: revision or range to compare (e.g. 'HEAD~1', 'main..feature'). log: revision/range to list. show: commit (default HEAD)."
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "diff: show staged changes instead of unstaged ones."
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "diff/show/stash show: first patch line to return (1-based). Default: 1."
+                },
+                "max_lines": {
+                    "type": "integer",
+                    "description": "diff/show/stash show: patch lines per page (max 2000). Default: 400."
+                },
+                "max_count": {
+                    "type": "integer",
+                    "description": "log: commits to return (max 200). Default: 20."
+                },
+                "skip": {
+                    "type": "integer",
+                    "description": "log: commits to skip (use next_skip to page)."
+                },
+                "all": {
+                    "type": "boolean",
+                    "description": "add: stage every change including untracked files. commit: also commit modified tracked files."
+                },
+                "message": {
+                    "type": "string",
+                    "description": "commit: commit message (required). stash push: stash description."
+                },
+                "op": {
+                    "type": "string",
+                    "enum": ["list", "create", "delete", "push", "pop", "apply", "show"],
+                    "description": "branch: list (default), create or delete (merged branches only). stash: push (default), list, pop, apply or show."
+                },
+                "name": {
+                    "type": "string",
+                    "description": "branch create/delete: branch name."
+                },
+                "start_point": {
+                    "type": "string",
+                    "description": "branch create / checkout create: commit to start from (default HEAD)."
+                },
+                "remotes": {
+                    "type": "boolean",
+                    "description": "branch list: include remote-tracking branches."
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "checkout: branch to switch to (or commit with detach=true). push: branch to push (default: current)."
+                },
+                "create": {
+                    "type": "boolean",
+                    "description": "checkout: create the branch and switch to it."
+                },
+                "detach": {
+                    "type": "boolean",
+                    "description": "checkout: check out a commit with a detached HEAD."
+                },
+                "index": {
+                    "type": "integer",
+                    "description": "stash pop/apply/show: stash entry number (default 0)."
+                },
+                "include_untracked": {
+                    "type": "boolean",
+                    "description": "stash push: also stash untracked files."
+                },
+                "remote": {
+                    "type": "string",
+                    "description": "push: remote name (default: the branch's upstream remote, else 'origin')."
+                },
+                "set_upstream": {
+                    "type": "boolean",
+                    "description": "push: record the remote branch as upstream."
+                }
+            },
+            "required": ["action"],
+            "additionalProperties": false
+        }),
+        exclusive: true,
+        timeout_secs: Some(120),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_porcelain_v2_status() {
+        let raw = "# branch.oid abc\0# branch.head main\0# branch.upstream origin/main\0\
+                   # branch.ab +2 -1\0\
+                   1 M. N... 100644 100644 100644 aaa bbb src/lib.rs\0\
+                   1 .M N... 100644 100644 100644 aaa bbb README.md\0\
+                   2 R. N... 100644 100644 100644 aaa bbb R100 new name.rs\0old.rs\0\
+                   u UU N... 100644 100644 100644 100644 a b c conflict.txt\0\
+                   ? notes/todo.md\0";
+        let s = parse_status(raw);
+        assert_eq!(s["branch"], "main");
+        assert_eq!(s["upstream"], "origin/main");
+        assert_eq!(s["ahead"], 2);
+        assert_eq!(s["behind"], 1);
+        assert_eq!(s["clean"], false);
+        assert_eq!(
+            s["staged"][0],
+            json!({"path": "src/lib.rs", "status": "modified"})
+        );
+        assert_eq!(
+            s["staged"][1],
+            json!({"path": "new name.rs", "status": "renamed", "from": "old.rs"})
+        );
+        assert_eq!(s["unstaged"][0]["path"], "README.md");
+        assert_eq!(s["conflicted"], json!(["conflict.txt"]));
+        assert_eq!(s["untracked"], json!(["notes/todo.md"]));
+    }
+
+    #[test]
+    fn parses_numstat_with_renames_and_binaries() {
+        let raw = "3\t1\tsrc/a.rs\0-\t-\timg.png\0\
+                   0\t0\t\0old.rs\0new.rs\0";
+        let files = parse_numstat(raw);
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[0],
+            json!({"path": "src/a.rs", "added": 3, "deleted": 1})
+        );
+        assert_eq!(files[1]["added"], Value::Null);
+        assert_eq!(files[2]["path"], "new.rs");
+        assert_eq!(files[2]["from"], "old.rs");
+    }
+
+    #[test]
+    fn refuses_history_rewrites_and_option_injection() {
+        assert!(check_history_safe("reset", &json!({})).is_err());
+        assert!(check_history_safe("push", &json!({"force": true})).is_err());
+        assert!(check_history_safe("commit", &json!({"amend": true})).is_err());
+        assert!(check_history_safe("push", &json!({"force": false})).is_ok());
+        assert!(check_rev("ref", "--output=/etc/passwd").is_err());
+        assert!(check_rev("ref", "main feature").is_err());
+        assert!(check_rev("ref", "main..feature").is_ok());
+    }
+
+    async fn git_in(dir: &Path, args: &[&str]) {
+        let out = run(dir, args).await.unwrap();
+        assert!(out.ok, "git {args:?}: {}", out.message());
+    }
+
+    #[tokio::test]
+    async fn commit_branch_and_log_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        git_in(ws, &["init", "--quiet", "--initial-branch=main"]).await;
+        std::fs::write(ws.join("a.txt"), "one\n").unwrap();
+
+        let st = git(ws, json!({"action": "status"})).await.unwrap();
+        assert_eq!(st["untracked"], json!(["a.txt"]));
+
+        git(ws, json!({"action": "add", "paths": ["a.txt"]}))
+            .await
+            .unwrap();
+        let c = git(ws, json!({"action": "commit", "message": "first"}))
+            .await
+            .unwrap();
+        assert_eq!(c["subject"], "first");
+        assert_eq!(c["branch"], "main");
+        assert_eq!(c["files"][0]["path"], "a.txt");
+
+        std::fs::write(ws.join("a.txt"), "one\ntwo\n").unwrap();
+        let d = git(ws, json!({"action": "diff"})).await.unwrap();
+        assert_eq!(d["files"][0]["added"], 1);
+        assert!(d["patch"].as_str().unwrap().contains("+two"));
+
+        git(ws, json!({"action": "stash"})).await.unwrap();
+        let st = git(ws, json!({"action": "status"})).await.unwrap();
+        assert_eq!(st["clean"], true);
+
+        git(
+            ws,
+            json!({"action": "checkout", "branch": "feature", "create": true}),
+        )
+        .await
+        .unwrap();
+        git(ws, json!({"action": "stash", "op": "pop"}))
+            .await
+            .unwrap();
+        git(
+            ws,
+            json!({"action": "commit", "message": "second", "all": true}),
+        )
+        .await
+        .unwrap();
+
+        let l = git(ws, json!({"action": "log", "max_count": 1}))
+            .await
+            .unwrap();
+        assert_eq!(l["commits"][0]["subject"], "second");
+        assert_eq!(l["has_more"], true);
+        assert_eq!(l["next_skip"], 1);
+
+        let b = git(ws, json!({"action": "branch"})).await.unwrap();
+        let names: Vec<&str> = b["branches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["feature", "main"]);
+
+        // Unmerged branches are not deleted.
+        git(ws, json!({"action": "checkout", "branch": "main"}))
+            .await
+            .unwrap();
+        let err = git(
+            ws,
+            json!({"action": "branch", "op": "delete", "name": "feature"}),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not fully merged"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn checkout_and_stash_are_journalled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        git_in(ws, &["init", "--quiet", "--initial-branch=main"]).await;
+        std::fs::write(ws.join("a.txt"), "one\n").unwrap();
+        git(ws, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(ws, json!({"action": "commit", "message": "first"}))
+            .await
+            .unwrap();
+        git_in(ws, &["switch", "--quiet", "--create", "feature"]).await;
+        std::fs::write(ws.join("b.txt"), "feature\n").unwrap();
+        git(ws, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(ws, json!({"action": "commit", "message": "second"}))
+            .await
+            .unwrap();
+        std::fs::write(ws.join("a.txt"), "edited\n").unwrap();
+
+        let guard = crate::journal::begin(ws, "turn-git");
+        git(ws, json!({"action": "stash"})).await.unwrap();
+        git(ws, json!({"action": "checkout", "branch": "main"}))
+            .await
+            .unwrap();
+        drop(guard);
+        assert_eq!(std::fs::read_to_string(ws.join("a.txt")).unwrap(), "one\n");
+        assert!(!ws.join("b.txt").exists());
+
+        let changed = crate::journal::changed_files(ws, "turn-git");
+        assert!(changed.contains(&"a.txt".to_string()), "{changed:?}");
+        assert!(changed.contains(&"b.txt".to_string()), "{changed:?}");
+        crate::journal::undo_last(ws).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(ws.join("a.txt")).unwrap(),
+            "edited\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(ws.join("b.txt")).unwrap(),
+            "feature\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn repo_config_cannot_run_programs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path().join("ws");
+        std::fs::create_dir_all(&ws).unwrap();
+        git_in(&ws, &["init", "--quiet", "--initial-branch=main"]).await;
+        let marker = tmp.path().join("ran");
+        let touch = format!("touch {}; cat", marker.display());
+        for (key, value) in [
+            ("filter.evil.clean", touch.as_str()),
+            ("filter.evil.smudge", touch.as_str()),
+            ("filter.evil.required", "true"),
+            ("diff.evil.textconv", touch.as_str()),
+            ("merge.evil.driver", touch.as_str()),
+            ("core.pager", touch.as_str()),
+            ("commit.gpgSign", "true"),
+            ("gpg.program", touch.as_str()),
+        ] {
+            git_in(&ws, &["config", key, value]).await;
+        }
+        std::fs::write(
+            ws.join(".gitattributes"),
+            "*.txt filter=evil diff=evil merge=evil\n",
+        )
+        .unwrap();
+        std::fs::write(ws.join("a.txt"), "one\n").unwrap();
+
+        git(&ws, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(&ws, json!({"action": "commit", "message": "first"}))
+            .await
+            .unwrap();
+        std::fs::write(ws.join("a.txt"), "one\ntwo\n").unwrap();
+        git(&ws, json!({"action": "status"})).await.unwrap();
+        git(&ws, json!({"action": "diff"})).await.unwrap();
+        git(&ws, json!({"action": "stash"})).await.unwrap();
+        git(&ws, json!({"action": "stash", "op": "show"}))
+            .await
+            .unwrap();
+        git(&ws, json!({"action": "stash", "op": "pop"}))
+            .await
+            .unwrap();
+        git(&ws, json!({"action": "log"})).await.unwrap();
+        assert!(!marker.exists(), "repo config ran a program");
+    }
+
+    #[tokio::test]
+    async fn push_is_fast_forward_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let remote = tmp.path().join("remote.git");
+        let ws = tmp.path().join("ws");
+        let other = tmp.path().join("other");
+        std::fs::create_dir_all(&ws).unwrap();
+        git_in(
+            tmp.path(),
+            &[
+                "init",
+                "--quiet",
+                "--bare",
+                "--initial-branch=main",
+                "remote.git",
+            ],
+        )
+        .await;
+        git_in(&ws, &["init", "--quiet", "--initial-branch=main"]).await;
+        git_in(&ws, &["remote", "add", "origin", remote.to_str().unwrap()]).await;
+        std::fs::write(ws.join("a.txt"), "one\n").unwrap();
+        git(&ws, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(&ws, json!({"action": "commit", "message": "first"}))
+            .await
+            .unwrap();
+
+        let p = git(&ws, json!({"action": "push", "set_upstream": true}))
+            .await
+            .unwrap();
+        assert_eq!(p["result"], "new_branch");
+        assert_eq!(p["remote"], "origin");
+
+        // Someone else pushes; our diverged branch is then rejected.
+        git_in(
+            tmp.path(),
+            &["clone", "--quiet", remote.to_str().unwrap(), "other"],
+        )
+        .await;
+        std::fs::write(other.join("b.txt"), "theirs\n").unwrap();
+        git(&other, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(&other, json!({"action": "commit", "message": "theirs"}))
+            .await
+            .unwrap();
+        git(&other, json!({"action": "push"})).await.unwrap();
+
+        std::fs::write(ws.join("c.txt"), "ours\n").unwrap();
+        git(&ws, json!({"action": "add", "all": true}))
+            .await
+            .unwrap();
+        git(&ws, json!({"action": "commit", "message": "ours"}))
+            .await
+            .unwrap();
+        let err = git(&ws, json!({"action": "push"})).await.unwrap_err();
+        assert!(err.to_string().contains("rejected"), "{err}");
+    }
+}
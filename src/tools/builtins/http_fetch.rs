@@ -0,0 +1,894 @@
+//! `http_fetch` tool — fetch a URL without a browser.
+//!
+//! Uses the shared reqwest client.  HTML responses are reduced to their
+//! main content (a small readability-style scorer) and rendered as
+//! Markdown; JSON is pretty-printed; other text is returned as-is.
+//! Hosts are checked against the agent's `http_fetch` domain policy
+//! before the request and on every redirect hop.  Loopback, private and
+//! link-local addresses are refused — whether written as an IP or
+//! reached through DNS — unless the host is in the agent's
+//! `allow_domains`, so a fetch cannot reach the gateway or other local
+//! services.  System proxies are not used.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::{json, Value};
+
+use crate::config::HttpFetchConfig;
+use crate::tools::{register_tool, ToolMeta};
+
+/// Default response body cap (2 MiB).
+const DEFAULT_MAX_BYTES: usize = 2 * 1024 * 1024;
+/// Hard upper bound for `max_bytes` (10 MiB — keeps a Pi comfortable).
+const MAX_MAX_BYTES: usize = 10 * 1024 * 1024;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 120;
+/// Default user agent; some sites refuse requests without one.
+const USER_AGENT: &str = concat!("pinchy/", env!("CARGO_PKG_VERSION"), " (+http_fetch)");
+
+/// Execute the `http_fetch` tool.
+pub async fn http_fetch(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let raw_url = args["url"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("http_fetch requires a 'url' string"))?;
+    let url = Url::parse(raw_url).map_err(|e| anyhow::anyhow!("invalid url '{raw_url}': {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("only http and https URLs are supported");
+    }
+
+    let policy = load_policy(workspace).await?;
+    check_domain(&policy, &url)?;
+    check_ip_host(&policy, &url)?;
+
+    let method = args["method"].as_str().unwrap_or("GET").to_uppercase();
+    let max_bytes = args["max_bytes"]
+        .as_u64()
+        .map(|n| (n as usize).min(MAX_MAX_BYTES))
+        .unwrap_or(DEFAULT_MAX_BYTES);
+    let timeout = Duration::from_secs(
+        args["timeout_secs"]
+            .as_u64()
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .clamp(1, MAX_TIMEOUT_SECS),
+    );
+    let raw = args["raw"].as_bool().unwrap_or(false);
+
+    let client = fetch_client(&policy)?;
+    let mut req = match method.as_str() {
+        "GET" => client.get(url.clone()),
+        "POST" => client.post(url.clone()),
+        other => anyhow::bail!("unsupported method '{other}' (use GET or POST)"),
+    }
+    .timeout(timeout)
+    .header(reqwest::header::USER_AGENT, USER_AGENT);
+
+    if let Some(headers) = args["headers"].as_object() {
+        for (name, value) in headers {
+            let value = value
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("header '{name}' must be a string"))?;
+            req = req.header(name.as_str(), value);
+        }
+    }
+    if method == "POST" {
+        if let Some(body) = args.get("json").filter(|v| !v.is_null()) {
+            req = req.json(body);
+        } else if let Some(body) = args["body"].as_str() {
+            req = req.body(body.to_string());
+        }
+    }
+
+    let mut resp = req
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("request to {url} failed: {:#}", anyhow::Error::from(e)))?;
+
+    let final_url = resp.url().clone();
+    let status = resp.status();
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+
+    // Stream the body so oversized responses are cut off early.
+    let mut body: Vec<u8> = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed reading response body: {e}"))?
+    {
+        let room = max_bytes - body.len();
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            truncated = true;
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut result = json!({
+        "url": final_url.as_str(),
+        "status": status.as_u16(),
+        "content_type": content_type,
+        "bytes": body.len(),
+    });
+    if truncated {
+        result["truncated"] = json!(true);
+    }
+
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let text = String::from_utf8_lossy(&body);
+
+    if raw {
+        result["content"] = json!(text);
+    } else if mime.contains("html") || (mime.is_empty() && looks_like_html(&text)) {
+        let page = html_to_markdown(&text, &final_url);
+        if let Some(title) = page.title {
+            result["title"] = json!(title);
+        }
+        result["content"] = json!(page.markdown);
+    } else if mime.contains("json") {
+        result["content"] = match serde_json::from_str::<Value>(&text) {
+            Ok(v) => json!(serde_json::to_string_pretty(&v)?),
+            Err(_) => json!(text),
+        };
+    } else if mime.is_empty() || is_textual(&mime) {
+        result["content"] = json!(text);
+    } else {
+        result["content"] = json!(format!(
+            "[binary content ({mime}, {} bytes) not shown]",
+            body.len()
+        ));
+    }
+
+    Ok(result)
+}
+
+// ── Domain policy ───────────────────────────────────────────
+
+/// Load the calling agent's domain policy.  A missing config file means
+/// no restrictions; an unreadable one is an error rather than a silent
+/// fail-open.
+async fn load_policy(workspace: &Path) -> anyhow::Result<HttpFetchConfig> {
+    let Some(cfg) = crate::config::Config::current().await? else {
+        return Ok(HttpFetchConfig::default());
+    };
+    Ok(cfg
+        .agent_for_workspace(workspace)
+        .and_then(|a| a.http_fetch.clone())
+        .unwrap_or_default())
+}
+
+/// Most redirects followed before giving up (reqwest's default).
+const MAX_REDIRECTS: usize = 10;
+
+/// The client for a fetch.  Its redirect policy re-checks the domain
+/// policy and IP hosts on every hop, and its resolver drops non-public
+/// addresses, so neither a redirect nor a DNS answer can reach a denied
+/// or local host (not even to send it the request).
+fn fetch_client(policy: &HttpFetchConfig) -> anyhow::Result<reqwest::Client> {
+    let redirect_policy = policy.clone();
+    let redirect = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        let checked = check_domain(&redirect_policy, attempt.url())
+            .and_then(|()| check_ip_host(&redirect_policy, attempt.url()));
+        match checked {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e.to_string()),
+        }
+    });
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .redirect(redirect)
+        .dns_resolver(Arc::new(PublicResolver {
+            policy: policy.clone(),
+        }))
+        .no_proxy()
+        .build()
+        .map_err(|e| anyhow::anyhow!("failed to build HTTP client: {e}"))
+}
+
+// ── Local address guard ─────────────────────────────────────
+
+/// Whether `host` is listed in the agent's `allow_domains`, which lifts
+/// the local address guard for it.
+fn explicitly_allowed(policy: &HttpFetchConfig, host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    policy
+        .allow_domains
+        .iter()
+        .any(|p| domain_matches(p, &host))
+}
+
+/// Whether `ip` is reachable on the public internet — not loopback,
+/// private, link-local, carrier-grade NAT, unique-local or unspecified.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Refuse a URL whose host is a non-public IP literal.  Hostnames are
+/// checked when they resolve (see [`PublicResolver`]).
+fn check_ip_host(policy: &HttpFetchConfig, url: &Url) -> anyhow::Result<()> {
+    let host = url.host_str().unwrap_or_default();
+    let Ok(ip) = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    else {
+        return Ok(());
+    };
+    if !is_public(ip) && !explicitly_allowed(policy, host) {
+        anyhow::bail!(
+            "'{host}' is a loopback, private or link-local address; \
+             add it to this agent's http_fetch.allow_domains to fetch it"
+        );
+    }
+    Ok(())
+}
+
+/// DNS resolver that keeps only public addresses, unless the name is
+/// explicitly allowed.  Checking the resolved addresses (rather than
+/// resolving once up front) means a rebinding DNS answer cannot slip a
+/// local address in between the check and the connect.
+struct PublicResolver {
+    policy: HttpFetchConfig,
+}
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        let allowed = explicitly_allowed(&self.policy, &host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|a| allowed || is_public(a.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!(
+                    "'{host}' resolves only to loopback, private or link-local addresses; \
+                     add it to this agent's http_fetch.allow_domains to fetch it"
+                )
+                .into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+fn domain_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+    if pattern.contains(['*', '?']) {
+        return crate::utils::glob_match(&pattern, host);
+    }
+    host == pattern || host.ends_with(&format!(".{pattern}"))
+}
+
+fn check_domain(policy: &HttpFetchConfig, url: &Url) -> anyhow::Result<()> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow::anyhow!("url has no host: {url}"))?
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+    if policy.deny_domains.iter().any(|p| domain_matches(p, &host)) {
+        anyhow::bail!("domain '{host}' is denied for this agent");
+    }
+    if !policy.allow_domains.is_empty()
+        && !policy
+            .allow_domains
+            .iter()
+            .any(|p| domain_matches(p, &host))
+    {
+        anyhow::bail!("domain '{host}' is not in this agent's allow list");
+    }
+    Ok(())
+}
+
+fn is_textual(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime,
+            "application/xml" | "application/javascript" | "application/x-yaml"
+        )
+}
+
+fn looks_like_html(text: &str) -> bool {
+    let head = text.trim_start().get(..256).unwrap_or(text).to_lowercase();
+    head.starts_with("<!doctype html") || head.contains("<html")
+}
+
+// ── HTML → Markdown ─────────────────────────────────────────
+
+/// Result of main-content extraction.
+struct Page {
+    title: Option<String>,
+    markdown: String,
+}
+
+/// Elements whose content is never part of the readable page.
+const SKIP_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "form", "button",
+    "input", "select", "textarea", "nav", "header", "footer", "aside", "head",
+];
+
+/// class/id fragments that mark page chrome rather than content.
+const UNLIKELY_HINTS: &[&str] = &[
+    "sidebar",
+    "comment",
+    "footer",
+    "header",
+    "menu",
+    "nav",
+    "share",
+    "social",
+    "advert",
+    "promo",
+    "cookie",
+    "banner",
+    "popup",
+    "related",
+    "breadcrumb",
+    "subscribe",
+];
+
+fn html_to_markdown(html: &str, base: &Url) -> Page {
+    let doc = Html::parse_document(html);
+    let title = Selector::parse("title")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next())
+        .map(|t| collapse_ws(&t.text().collect::<String>()))
+        .filter(|t| !t.is_empty());
+
+    let root = main_content(&doc).unwrap_or_else(|| doc.root_element());
+    let mut md = Markdown::new(base);
+    md.element(root, true);
+    Page {
+        title,
+        markdown: md.finish(),
+    }
+}
+
+/// Render a local HTML document (a saved page, an EPUB chapter) as
+/// Markdown.  Returns the `<title>` and the main content.
+pub(crate) fn render_html(html: &str) -> (Option<String>, String) {
+    let base = Url::parse("file:///").expect("static URL parses");
+    let page = html_to_markdown(html, &base);
+    (page.title, page.markdown)
+}
+
+/// Pick the element most likely to hold the page's main content.
+///
+/// Semantic containers win outright; otherwise paragraphs vote for their
+/// parent (full score) and grandparent (half), and each candidate is
+/// discounted by its link density.
+fn main_content(doc: &Html) -> Option<ElementRef<'_>> {
+    for sel in ["article", "main", "[role=main]"] {
+        let Ok(sel) = Selector::parse(sel) else {
+            continue;
+        };
+        let best = doc
+            .select(&sel)
+            .max_by_key(|el| text_len(*el))
+            .filter(|el| text_len(*el) > 200);
+        if best.is_some() {
+            return best;
+        }
+    }
+
+    let para = Selector::parse("p, pre, td").ok()?;
+    let mut scores = HashMap::new();
+    for p in doc.select(&para) {
+        let len = text_len(p);
+        if len < 25 {
+            continue;
+        }
+        let commas = p.text().map(|t| t.matches(',').count()).sum::<usize>();
+        let score = 1.0 + commas as f64 + (len as f64 / 100.0).min(3.0);
+        let parent = p.parent().and_then(ElementRef::wrap);
+        if let Some(parent) = parent {
+            *scores.entry(parent.id()).or_insert(0.0) += score;
+            if let Some(grand) = parent.parent().and_then(ElementRef::wrap) {
+                *scores.entry(grand.id()).or_insert(0.0) += score / 2.0;
+            }
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = doc.tree.get(id).and_then(ElementRef::wrap)?;
+            Some((el, score * (1.0 - link_density(el))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+}
+
+fn text_len(el: ElementRef<'_>) -> usize {
+    el.text().map(|t| t.trim().len()).sum()
+}
+
+fn link_density(el: ElementRef<'_>) -> f64 {
+    let total = text_len(el);
+    if total == 0 {
+        return 1.0;
+    }
+    let Ok(sel) = Selector::parse("a") else {
+        return 0.0;
+    };
+    let linked: usize = el.select(&sel).map(text_len).sum();
+    linked as f64 / total as f64
+}
+
+fn is_unlikely(el: ElementRef<'_>) -> bool {
+    let v = el.value();
+    let hints = format!(
+        "{} {}",
+        v.attr("class").unwrap_or(""),
+        v.attr("id").unwrap_or("")
+    )
+    .to_ascii_lowercase();
+    !hints.trim().is_empty() && UNLIKELY_HINTS.iter().any(|h| hints.contains(h))
+}
+
+fn collapse_ws(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Minimal Markdown writer over a scraper element tree.
+struct Markdown<'u> {
+    base: &'u Url,
+    out: String,
+    /// Open lists: `Some(n)` for ordered (next number), `None` for bullets.
+    lists: Vec<Option<usize>>,
+}
+
+impl<'u> Markdown<'u> {
+    fn new(base: &'u Url) -> Self {
+        Self {
+            base,
+            out: String::new(),
+            lists: Vec::new(),
+        }
+    }
+
+    /// Render `el`'s children into a fresh writer and return the text.
+    fn render_inline(&self, el: ElementRef<'_>) -> String {
+        let mut sub = Markdown::new(self.base);
+        sub.children(el);
+        collapse_ws(&sub.out)
+    }
+
+    fn finish(self) -> String {
+        let mut result = String::with_capacity(self.out.len());
+        let mut blank_run = 0;
+        for line in self.out.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                blank_run += 1;
+                if blank_run > 1 {
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim().to_string()
+    }
+
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push_str(if self.out.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+    }
+
+    fn newline(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, t: &str) {
+        let starts_ws = t.starts_with(char::is_whitespace);
+        let ends_ws = t.ends_with(char::is_whitespace);
+        let body = collapse_ws(t);
+        if body.is_empty() {
+            if (starts_ws || ends_ws) && !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if starts_ws && !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+        self.out.push_str(&body);
+        if ends_ws {
+            self.out.push(' ');
+        }
+    }
+
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        self.base.join(href).ok().map(|u| u.to_string())
+    }
+
+    fn children(&mut self, el: ElementRef<'_>) {
+        for child in el.children() {
+            if let Some(t) = child.value().as_text() {
+                self.text(t);
+            } else if let Some(c) = ElementRef::wrap(child) {
+                self.element(c, false);
+            }
+        }
+    }
+
+    fn element(&mut self, el: ElementRef<'_>, is_root: bool) {
+        let tag = el.value().name();
+        if !is_root && (SKIP_TAGS.contains(&tag) || is_unlikely(el)) {
+            return;
+        }
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag[1..].parse::<usize>().unwrap_or(1);
+                let text = self.render_inline(el);
+                if !text.is_empty() {
+                    self.blank_line();
+                    self.out.push_str(&"#".repeat(level));
+                    self.out.push(' ');
+                    self.out.push_str(&text);
+                    self.blank_line();
+                }
+            }
+            "p" | "div" | "section" | "article" | "main" | "figure" | "dl" => {
+                if self.lists.is_empty() {
+                    self.blank_line();
+                    self.children(el);
+                    self.blank_line();
+                } else {
+                    self.children(el);
+                }
+            }
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.blank_line();
+                self.out.push_str("---");
+                self.blank_line();
+            }
+            "strong" | "b" => self.wrap_inline(el, "**"),
+            "em" | "i" => self.wrap_inline(el, "*"),
+            "code" => {
+                let code = el.text().collect::<String>();
+                if !code.trim().is_empty() {
+                    self.out.push('`');
+                    self.out.push_str(code.trim());
+                    self.out.push('`');
+                }
+            }
+            "pre" => {
+                let code = el.text().collect::<String>();
+                let lang = std::iter::once(el)
+                    .chain(el.children().filter_map(ElementRef::wrap))
+                    .flat_map(|e| e.value().classes())
+                    .find_map(|c| c.strip_prefix("language-"))
+                    .unwrap_or("")
+                    .to_string();
+                self.blank_line();
+                self.out
+                    .push_str(&format!("```{lang}\n{}\n```", code.trim_end_matches('\n')));
+                self.blank_line();
+            }
+            "a" => {
+                let text = self.render_inline(el);
+                match el.value().attr("href").and_then(|h| self.resolve(h)) {
+                    Some(href) if !text.is_empty() => {
+                        self.text(&format!(" [{text}]({href}) "));
+                    }
+                    _ => self.text(&format!(" {text} ")),
+                }
+            }
+            "img" => {
+                if let Some(src) = el.value().attr("src").and_then(|s| self.resolve(s)) {
+                    let alt = collapse_ws(el.value().attr("alt").unwrap_or(""));
+                    self.out.push_str(&format!("![{alt}]({src})"));
+                }
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+                self.lists.push((tag == "ol").then_some(1));
+                self.children(el);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            "li" => {
+                self.newline();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        let m = format!("{n}. ");
+                        *n += 1;
+                        m
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&marker);
+                self.children(el);
+                self.newline();
+            }
+            "blockquote" => {
+                let mut sub = Markdown::new(self.base);
+                sub.children(el);
+                let quoted = sub
+                    .finish()
+                    .lines()
+                    .map(|l| format!("> {l}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.blank_line();
+                self.out.push_str(&quoted);
+                self.blank_line();
+            }
+            "table" => self.table(el),
+            _ => self.children(el),
+        }
+    }
+
+    fn wrap_inline(&mut self, el: ElementRef<'_>, marker: &str) {
+        let text = self.render_inline(el);
+        if !text.is_empty() {
+            self.text(&format!(" {marker}{text}{marker} "));
+        }
+    }
+
+    fn table(&mut self, el: ElementRef<'_>) {
+        let (Ok(row_sel), Ok(cell_sel)) = (Selector::parse("tr"), Selector::parse("th, td")) else {
+            return;
+        };
+        let rows: Vec<Vec<String>> = el
+            .select(&row_sel)
+            .map(|tr| {
+                tr.select(&cell_sel)
+                    .map(|c| self.render_inline(c).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|r| !r.is_empty())
+            .collect();
+        let Some(width) = rows.iter().map(Vec::len).max() else {
+            return;
+        };
+        self.blank_line();
+        for (i, row) in rows.iter().enumerate() {
+            let mut cells = row.clone();
+            cells.resize(width, String::new());
+            self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            if i == 0 {
+                self.out.push_str(&format!("|{}\n", " --- |".repeat(width)));
+            }
+        }
+        self.blank_line();
+    }
+}
+
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "http_fetch".into(),
+        description: "Fetch a URL over HTTP(S) without a browser. HTML pages are reduced to their main content and returned as Markdown; JSON is pretty-printed. Supports GET/POST, custom headers, and size/time limits. Use for reading articles, docs and APIs; use the browser skill only for pages that need JavaScript.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "http:// or https:// URL to fetch" },
+                "method": { "type": "string", "enum": ["GET", "POST"], "description": "HTTP method (default GET)" },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra request headers"
+                },
+                "body": { "type": "string", "description": "Raw request body for POST" },
+                "json": { "description": "JSON request body for POST (sets Content-Type)" },
+                "max_bytes": { "type": "integer", "description": "Maximum response bytes to read (default 2 MiB, max 10 MiB)" },
+                "timeout_secs": { "type": "integer", "description": "Request timeout in seconds (default 30, max 120)" },
+                "raw": { "type": "boolean", "description": "Return the body as-is instead of extracting Markdown (default false)" }
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/blog/post").unwrap()
+    }
+
+    #[test]
+    fn extracts_article_and_drops_chrome() {
+        let html = r#"<html><head><title> My Post </title><script>var x=1;</script></head>
+            <body>
+              <nav><a href="/">Home</a> <a href="/about">About</a></nav>
+              <div class="sidebar"><p>Subscribe to our newsletter, it is great, really.</p></div>
+              <article>
+                <h1>Hello world</h1>
+                <p>This is the <strong>first</strong> paragraph, with a <a href="/docs">link</a>.</p>
+                <ul><li>one</li><li>two</li></ul>
+                <pre><code class="language-rust">fn main() {}</code></pre>
+                <p>Second paragraph has enough words in it to make the article long enough to be picked up by the extractor as main content.</p>
+              </article>
+              <footer>© 2024</footer>
+            </body></html>"#;
+        let page = html_to_markdown(html, &base());
+        assert_eq!(page.title.as_deref(), Some("My Post"));
+        let md = page.markdown;
+        assert!(md.starts_with("# Hello world"), "{md}");
+        assert!(md.contains("**first**"), "{md}");
+        assert!(md.contains("[link](https://example.com/docs)"), "{md}");
+        assert!(md.contains("- one\n- two"), "{md}");
+        assert!(md.contains("```rust\nfn main() {}\n```"), "{md}");
+        assert!(!md.contains("newsletter"), "{md}");
+        assert!(!md.contains("About"), "{md}");
+        assert!(!md.contains("var x"), "{md}");
+    }
+
+    #[test]
+    fn scores_paragraph_container_without_semantic_tags() {
+        let body = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod.";
+        let html = format!(
+            r#"<body><div id="links"><a href="/a">A</a><a href="/b">B</a></div>
+               <div id="content"><p>{body}</p><p>{body}</p><p>{body}</p></div></body>"#
+        );
+        let md = html_to_markdown(&html, &base()).markdown;
+        assert_eq!(md.matches("Lorem ipsum").count(), 3, "{md}");
+        assert!(!md.contains("[A]"), "{md}");
+    }
+
+    #[test]
+    fn renders_tables() {
+        let html = "<article><table><tr><th>Name</th><th>Qty</th></tr>\
+                    <tr><td>apple</td><td>3</td></tr></table></article>";
+        let md = html_to_markdown(html, &base()).markdown;
+        assert_eq!(md, "| Name | Qty |\n| --- | --- |\n| apple | 3 |");
+    }
+
+    #[test]
+    fn domain_policy() {
+        let policy = HttpFetchConfig {
+            allow_domains: vec!["example.com".into(), "*.docs.rs".into()],
+            deny_domains: vec!["private.example.com".into()],
+        };
+        let check = |u: &str| check_domain(&policy, &Url::parse(u).unwrap()).is_ok();
+        assert!(check("https://example.com/x"));
+        assert!(check("https://www.example.com/x"));
+        assert!(check("https://tokio.docs.rs/"));
+        assert!(!check("https://docs.rs/"));
+        assert!(!check("https://private.example.com/"));
+        assert!(!check("https://api.private.example.com/"));
+        assert!(!check("https://notexample.com/"));
+        assert!(check_domain(&HttpFetchConfig::default(), &base()).is_ok());
+    }
+
+    #[test]
+    fn local_addresses_need_an_explicit_allow() {
+        let open = HttpFetchConfig::default();
+        let check = |p: &HttpFetchConfig, u: &str| check_ip_host(p, &Url::parse(u).unwrap());
+        for url in [
+            "http://127.0.0.1:3131/api/approvals",
+            "http://10.1.2.3/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/",
+            "http://100.64.0.1/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:127.0.0.1]/",
+        ] {
+            assert!(check(&open, url).is_err(), "{url}");
+        }
+        assert!(check(&open, "http://93.184.216.34/").is_ok());
+        assert!(check(&open, "http://[2606:4700::1111]/").is_ok());
+        assert!(check(&open, "https://example.com/").is_ok());
+        let lan = HttpFetchConfig {
+            allow_domains: vec!["192.168.1.1".into()],
+            deny_domains: vec![],
+        };
+        assert!(check(&lan, "http://192.168.1.1/").is_ok());
+        assert!(check(&lan, "http://192.168.1.2/").is_err());
+    }
+
+    #[tokio::test]
+    async fn redirects_are_checked_on_every_hop() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/out"))
+            .respond_with(
+                ResponseTemplate::new(302).insert_header("location", "http://denied.invalid/"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(path("/in"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/ok"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/ok"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fine"))
+            .mount(&server)
+            .await;
+
+        let policy = HttpFetchConfig {
+            allow_domains: vec!["127.0.0.1".into()],
+            deny_domains: vec![],
+        };
+        let client = fetch_client(&policy).unwrap();
+        let err = client
+            .get(format!("{}/out", server.uri()))
+            .send()
+            .await
+            .unwrap_err();
+        let err = format!("{:#}", anyhow::Error::from(err));
+        assert!(
+            err.contains("'denied.invalid' is not in this agent's allow list"),
+            "{err}"
+        );
+
+        let resp = client
+            .get(format!("{}/in", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.text().await.unwrap(), "fine");
+    }
+}
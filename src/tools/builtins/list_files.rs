@@ -2,18 +2,20 @@
 //! workspace with optional glob filtering and recursive traversal.
 
 use serde_json::{json, Value};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::tools::{register_tool, sandbox_path, ToolMeta};
+use crate::utils::gitignore::GitIgnore;
 
 /// Lightweight entry used for collecting & sorting before converting to
 /// JSON, avoiding heap-allocated `serde_json::Value` during the sort (#9).
-struct DirEntry {
-    name: String,
-    rel_path: String,
-    is_dir: bool,
-    size_bytes: Option<u64>,
-    modified_secs: Option<u64>,
+pub(super) struct DirEntry {
+    pub name: String,
+    pub rel_path: String,
+    pub is_dir: bool,
+    pub size_bytes: Option<u64>,
+    pub modified_secs: Option<u64>,
 }
 
 /// List files in a directory, optionally with glob pattern and recursion.
@@ -47,9 +49,10 @@ pub async fn list_files(workspace: &Path, args: Value) -> anyhow::Result<Value>
         include_metadata,
         max: max_entries,
         max_depth,
+        gitignore: false,
     };
 
-    collect_entries(&dir, &opts, &mut entries, 0).await?;
+    collect_entries(&dir, &opts, &mut entries).await?;
 
     // Sort: directories first, then alphabetical (#9 — sort on typed fields).
     entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
@@ -115,23 +118,68 @@ const SKIP_DIRS: &[&str] = &[
 ];
 
 /// Options for recursive directory traversal.
-struct CollectOpts<'a> {
-    workspace: &'a Path,
-    pattern: Option<&'a str>,
-    recursive: bool,
-    include_metadata: bool,
-    max: usize,
-    max_depth: usize,
+pub(super) struct CollectOpts<'a> {
+    pub workspace: &'a Path,
+    pub pattern: Option<&'a str>,
+    pub recursive: bool,
+    pub include_metadata: bool,
+    pub max: usize,
+    pub max_depth: usize,
+    /// Skip entries matched by `.gitignore` files between the workspace
+    /// root and each directory.
+    pub gitignore: bool,
+}
+
+/// `.gitignore` rules in effect, each with the directory it applies from.
+type IgnoreStack = Vec<(PathBuf, Arc<GitIgnore>)>;
+
+/// Collect entries under `dir` (shared by `list_files` and
+/// `search_files`).
+pub(super) async fn collect_entries(
+    dir: &Path,
+    opts: &CollectOpts<'_>,
+    entries: &mut Vec<DirEntry>,
+) -> anyhow::Result<()> {
+    let mut ignores = IgnoreStack::new();
+    if opts.gitignore {
+        // Rules from ancestors inside the workspace apply too.
+        let mut ancestors: Vec<&Path> = dir
+            .ancestors()
+            .take_while(|a| a.starts_with(opts.workspace))
+            .collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            if let Some(ig) = GitIgnore::load(ancestor) {
+                ignores.push((ancestor.to_path_buf(), Arc::new(ig)));
+            }
+        }
+    }
+    walk(dir, opts, entries, &ignores, 0).await
+}
+
+/// Whether the innermost applicable `.gitignore` rule ignores `path`.
+fn is_ignored(ignores: &IgnoreStack, path: &Path, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for (base, ig) in ignores {
+        let Ok(rel) = path.strip_prefix(base) else {
+            continue;
+        };
+        if let Some(decision) = ig.matches(&rel.to_string_lossy(), is_dir) {
+            ignored = decision;
+        }
+    }
+    ignored
 }
 
 /// Recursively collect directory entries using breadth-first traversal so
 /// that sibling files are discovered before recursing into subdirectories.
 ///
 /// `depth` / `max_depth` guard against symlink loops (#8).
-async fn collect_entries(
+async fn walk(
     dir: &Path,
     opts: &CollectOpts<'_>,
     entries: &mut Vec<DirEntry>,
+    ignores: &IgnoreStack,
     depth: usize,
 ) -> anyhow::Result<()> {
     if depth > opts.max_depth {
@@ -158,8 +206,12 @@ async fn collect_entries(
         let ft = entry.file_type().await.ok();
         let is_dir = ft.as_ref().map(|f| f.is_dir()).unwrap_or(false);
 
+        if opts.gitignore && is_ignored(ignores, &entry.path(), is_dir) {
+            continue;
+        }
+
         if let Some(pat) = opts.pattern {
-            if !is_dir && !glob_match(pat, &name) {
+            if !is_dir && !crate::utils::glob_match(pat, &name) {
                 continue;
             }
         }
@@ -215,47 +267,21 @@ async fn collect_entries(
         if entries.len() >= opts.max {
             break;
         }
-        Box::pin(collect_entries(&subdir, opts, entries, depth + 1)).await?;
+        let mut nested;
+        let ignores = match GitIgnore::load(&subdir).filter(|_| opts.gitignore) {
+            Some(ig) => {
+                nested = ignores.clone();
+                nested.push((subdir.clone(), Arc::new(ig)));
+                &nested
+            }
+            None => ignores,
+        };
+        Box::pin(walk(&subdir, opts, entries, ignores, depth + 1)).await?;
     }
 
     Ok(())
 }
 
-/// Simple glob matching supporting `*` (any chars) and `?` (single char).
-///
-/// Operates on byte indices into `&str` to avoid allocating `Vec<char>` (#16).
-fn glob_match(pattern: &str, name: &str) -> bool {
-    let pat = pattern.as_bytes();
-    let txt = name.as_bytes();
-    let mut pi = 0usize;
-    let mut ti = 0usize;
-    let mut star_pi: Option<usize> = None;
-    let mut star_ti = 0usize;
-
-    while ti < txt.len() {
-        if pi < pat.len() && (pat[pi] == b'?' || pat[pi] == txt[ti]) {
-            pi += 1;
-            ti += 1;
-        } else if pi < pat.len() && pat[pi] == b'*' {
-            star_pi = Some(pi);
-            star_ti = ti;
-            pi += 1;
-        } else if let Some(sp) = star_pi {
-            pi = sp + 1;
-            star_ti += 1;
-            ti = star_ti;
-        } else {
-            return false;
-        }
-    }
-
-    while pi < pat.len() && pat[pi] == b'*' {
-        pi += 1;
-    }
-
-    pi == pat.len()
-}
-
 /// Register the `list_files` tool metadata.
 pub fn register() {
     register_tool(ToolMeta {
@@ -283,5 +309,7 @@ pub fn register() {
             },
             "additionalProperties": false
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 }
@@ -1,20 +1,100 @@
 //! Persistent memory tool — cross-session knowledge store.
 //!
-//! Storage: `agents/<id>/workspace/memory.db` (SQLite + FTS5)
+//! Storage: `agents/<id>/workspace/memory.db` (SQLite + FTS5), plus the
+//! shared scopes at `PINCHY_HOME/memory/<scope>.db` the agent is granted in
+//! its `shared_memory` config.
 //!
 //! Tools exposed to the agent:
-//! - `save_memory { key, value, tags? }` — upsert a memory entry
-//! - `recall_memory { query?, tag?, limit? }` — ranked full-text search
-//! - `forget_memory { key }` — delete a memory entry
+//! - `save_memory { key, value, tags?, scope? }` — upsert a memory entry
+//! - `recall_memory { query?, tag?, limit?, scope? }` — ranked search over
+//!   the agent's memory and every shared scope it can read
+//! - `forget_memory { key, scope? }` — delete a memory entry
 
 use std::path::Path;
 use std::sync::Arc;
 
 use serde_json::Value;
 
+use crate::config::SharedMemoryConfig;
+use crate::memory::{MemoryEntry, MemoryStore, PRIVATE_SCOPE};
 use crate::tools::register_tool;
 use crate::tools::ToolMeta;
 
+/// Load the calling agent's shared memory permissions.  A missing config
+/// file grants no shared scopes.
+async fn load_shared_access(workspace: &Path) -> anyhow::Result<SharedMemoryConfig> {
+    let Some(cfg) = crate::config::Config::current().await? else {
+        return Ok(SharedMemoryConfig::default());
+    };
+    Ok(cfg
+        .agent_for_workspace(workspace)
+        .and_then(|a| a.shared_memory.clone())
+        .unwrap_or_default())
+}
+
+fn scope_list(scopes: &[String]) -> String {
+    if scopes.is_empty() {
+        "none".into()
+    } else {
+        scopes.join(", ")
+    }
+}
+
+/// Open the store for `scope`, checking the agent may write to it.
+async fn open_writable(workspace: &Path, scope: &str) -> anyhow::Result<MemoryStore> {
+    if scope == PRIVATE_SCOPE {
+        return MemoryStore::open(workspace);
+    }
+    let access = load_shared_access(workspace).await?;
+    if !access.can_write(scope) {
+        anyhow::bail!(
+            "memory scope '{scope}' is not writable by this agent (writable shared scopes: {})",
+            scope_list(&access.write)
+        );
+    }
+    MemoryStore::open_shared(scope)
+}
+
+/// The stores `recall_memory` searches: just `scope` when given, otherwise
+/// the agent's own memory followed by every shared scope it can read.
+async fn open_readable(
+    workspace: &Path,
+    scope: Option<&str>,
+) -> anyhow::Result<Vec<(String, Arc<MemoryStore>)>> {
+    if scope == Some(PRIVATE_SCOPE) {
+        return Ok(vec![(
+            PRIVATE_SCOPE.to_string(),
+            Arc::new(MemoryStore::open(workspace)?),
+        )]);
+    }
+    let access = load_shared_access(workspace).await?;
+    let readable = access.readable();
+    if let Some(scope) = scope {
+        if !access.can_read(scope) {
+            anyhow::bail!(
+                "memory scope '{scope}' is not readable by this agent (readable shared scopes: {})",
+                scope_list(&readable)
+            );
+        }
+        return Ok(vec![(
+            scope.to_string(),
+            Arc::new(MemoryStore::open_shared(scope)?),
+        )]);
+    }
+
+    let mut stores = vec![(
+        PRIVATE_SCOPE.to_string(),
+        Arc::new(MemoryStore::open(workspace)?),
+    )];
+    for scope in readable {
+        match MemoryStore::open_shared(&scope) {
+            Ok(store) => stores.push((scope, Arc::new(store))),
+            Err(e) => tracing::warn!(scope = %scope, error = %e, "cannot open shared memory"),
+        }
+    }
+    Ok(stores)
+}
+
 /// `save_memory` tool — upsert a memory entry by key.
 pub async fn save_memory(workspace: &Path, args: Value) -> anyhow::Result<Value> {
     let key = args["key"]
@@ -33,8 +113,9 @@ pub async fn save_memory(workspace: &Path, args: Value) -> anyhow::Result<Value>
                 .collect()
         })
         .unwrap_or_default();
+    let scope = args["scope"].as_str().unwrap_or(PRIVATE_SCOPE).to_string();
 
-    let store = Arc::new(crate::memory::MemoryStore::open(workspace)?);
+    let store = Arc::new(open_writable(workspace, &scope).await?);
     let store2 = Arc::clone(&store);
     let key2 = key.clone();
     let value2 = value.clone();
@@ -46,23 +127,30 @@ pub async fn save_memory(workspace: &Path, args: Value) -> anyhow::Result<Value>
     })
     .await??;
 
-    Ok(serde_json::json!({
+    let mut out = serde_json::json!({
         "status": "saved",
         "key": key,
-    }))
+    });
+    if scope != PRIVATE_SCOPE {
+        out["scope"] = serde_json::json!(scope);
+    }
+    Ok(out)
 }
 
 /// `recall_memory` tool — search memories with FTS5 ranked search.
 ///
 /// When mode is unspecified (the default), this auto-detects whether an
 /// embedding provider is available and prefers semantic search if so.
+/// Without a `scope`, the agent's own memory and every shared scope it can
+/// read are searched and the rankings fused.
 pub async fn recall_memory(workspace: &Path, args: Value) -> anyhow::Result<Value> {
     let query = args["query"].as_str().unwrap_or("").to_string();
     let tag = args["tag"].as_str().map(String::from);
     let limit = args["limit"].as_u64().unwrap_or(10) as usize;
     let explicit_mode = args["mode"].as_str().map(String::from);
+    let scope = args["scope"].as_str();
 
-    let store = Arc::new(crate::memory::MemoryStore::open(workspace)?);
+    let stores = open_readable(workspace, scope).await?;
 
     // Determine effective mode: if the caller didn't specify, auto-detect
     // embedding availability and prefer hybrid search when possible.
@@ -82,53 +170,115 @@ pub async fn recall_memory(workspace: &Path, args: Value) -> anyhow::Result<Valu
         }
     };
 
+    let mut ranked = Vec::with_capacity(stores.len());
+    for (scope, store) in &stores {
+        let results = search_store(store, mode, &query, tag.as_deref(), limit).await?;
+        ranked.push((scope.clone(), results));
+    }
+    let show_scope = stores.len() > 1 || stores[0].0 != PRIVATE_SCOPE;
+    let results = merge_scopes(ranked, query.is_empty(), limit);
+
+    let items: Vec<Value> = results
+        .iter()
+        .map(|(scope, e)| {
+            let mut obj = serde_json::json!({
+                "key": e.key,
+                "value": e.value,
+                "tags": e.tags,
+                "timestamp": e.timestamp,
+            });
+            if let Some(score) = e.score {
+                obj["relevance"] = serde_json::json!(score);
+            }
+            if show_scope {
+                obj["scope"] = serde_json::json!(scope);
+            }
+            obj
+        })
+        .collect();
+
+    Ok(serde_json::json!({ "memories": items }))
+}
+
+/// Search one store in the given mode.
+async fn search_store(
+    store: &Arc<MemoryStore>,
+    mode: &str,
+    query: &str,
+    tag: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<Vec<MemoryEntry>> {
+    let query = query.to_string();
+    let tag = tag.map(String::from);
     let results = match mode {
         "hybrid" => {
             // Try hybrid (BM25 + vector RRF), fall back gracefully.
             // First ensure embeddings exist (backfill if needed).
-            if let Err(e) = backfill_embeddings(&store, &query).await {
+            if let Err(e) = backfill_embeddings(store, &query).await {
                 tracing::debug!(error = %e, "embedding backfill failed, hybrid will degrade to BM25");
             }
-            let s = Arc::clone(&store);
-            let q = query.clone();
-            let t = tag.clone();
-            tokio::task::spawn_blocking(move || s.search_hybrid(&q, t.as_deref(), limit)).await??
+            let s = Arc::clone(store);
+            tokio::task::spawn_blocking(move || s.search_hybrid(&query, tag.as_deref(), limit))
+                .await??
         }
-        "semantic" => match recall_semantic(&store, &query, tag.as_deref(), limit).await {
+        "semantic" => match recall_semantic(store, &query, tag.as_deref(), limit).await {
             Ok(r) => r,
             Err(e) => {
                 tracing::debug!(error = %e, "semantic recall failed, falling back to text search");
-                let s = Arc::clone(&store);
-                let q = query.clone();
-                let t = tag.clone();
-                tokio::task::spawn_blocking(move || s.search(&q, t.as_deref(), limit)).await??
+                let s = Arc::clone(store);
+                tokio::task::spawn_blocking(move || s.search(&query, tag.as_deref(), limit))
+                    .await??
             }
         },
         _ => {
-            let s = Arc::clone(&store);
-            let q = query.clone();
-            let t = tag.clone();
-            tokio::task::spawn_blocking(move || s.search(&q, t.as_deref(), limit)).await??
+            let s = Arc::clone(store);
+            tokio::task::spawn_blocking(move || s.search(&query, tag.as_deref(), limit)).await??
         }
     };
+    Ok(results)
+}
 
-    let items: Vec<Value> = results
-        .iter()
-        .map(|e| {
-            let mut obj = serde_json::json!({
-                "key": e.key,
-                "value": e.value,
-                "tags": e.tags,
-                "timestamp": e.timestamp,
-            });
-            if let Some(score) = e.score {
-                obj["relevance"] = serde_json::json!(score);
-            }
-            obj
+/// Combine per-scope results.  Relevance scores from different databases
+/// are not comparable, so ranked results are fused with Reciprocal Rank
+/// Fusion (k=60, as in hybrid search); unranked listings are ordered by
+/// recency.  A single scope's results pass through unchanged.
+fn merge_scopes(
+    ranked: Vec<(String, Vec<MemoryEntry>)>,
+    unranked: bool,
+    limit: usize,
+) -> Vec<(String, MemoryEntry)> {
+    if ranked.len() == 1 {
+        let (scope, results) = ranked.into_iter().next().unwrap_or_default();
+        return results
+            .into_iter()
+            .take(limit)
+            .map(|e| (scope.clone(), e))
+            .collect();
+    }
+
+    let k = 60.0f64;
+    let mut merged: Vec<(String, MemoryEntry)> = ranked
+        .into_iter()
+        .flat_map(|(scope, results)| {
+            results.into_iter().enumerate().map(move |(rank, mut e)| {
+                if !unranked {
+                    e.score = Some(1.0 / (k + rank as f64 + 1.0));
+                }
+                (scope.clone(), e)
+            })
         })
         .collect();
-
-    Ok(serde_json::json!({ "memories": items }))
+    if unranked {
+        merged.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp));
+    } else {
+        merged.sort_by(|a, b| {
+            b.1.score
+                .partial_cmp(&a.1.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+    merged.truncate(limit);
+    merged
 }
 
 /// Check whether an embedding provider is currently available.
@@ -141,11 +291,11 @@ fn has_embedding_provider() -> bool {
 
 /// Helper: semantic recall via embedding provider.
 async fn recall_semantic(
-    store: &Arc<crate::memory::MemoryStore>,
+    store: &Arc<MemoryStore>,
     query: &str,
     tag: Option<&str>,
     limit: usize,
-) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
+) -> anyhow::Result<Vec<MemoryEntry>> {
     if query.is_empty() {
         anyhow::bail!("semantic recall requires a non-empty query");
     }
@@ -183,7 +333,7 @@ async fn recall_semantic(
                 let pairs: Vec<(String, Vec<f32>)> = texts_to_embed
                     .iter()
                     .map(|(k, _)| k.clone())
-                    .zip(vecs.into_iter())
+                    .zip(vecs)
                     .collect();
                 tokio::task::spawn_blocking(move || {
                     for (key, vec) in &pairs {
@@ -203,10 +353,7 @@ async fn recall_semantic(
 
 /// Backfill missing embeddings for memories that don't have them yet.
 /// Called before hybrid search to ensure vector results are available.
-async fn backfill_embeddings(
-    store: &Arc<crate::memory::MemoryStore>,
-    _query: &str,
-) -> anyhow::Result<()> {
+async fn backfill_embeddings(store: &Arc<MemoryStore>, _query: &str) -> anyhow::Result<()> {
     let pm =
         crate::models::get_global_providers().ok_or_else(|| anyhow::anyhow!("no providers"))?;
 
@@ -233,11 +380,8 @@ async fn backfill_embeddings(
         let text_refs: Vec<&str> = chunk.iter().map(|(_, v)| v.as_str()).collect();
         if let Ok(Some(vecs)) = pm.embed(&text_refs).await {
             let s = Arc::clone(store);
-            let pairs: Vec<(String, Vec<f32>)> = chunk
-                .iter()
-                .map(|(k, _)| k.clone())
-                .zip(vecs.into_iter())
-                .collect();
+            let pairs: Vec<(String, Vec<f32>)> =
+                chunk.iter().map(|(k, _)| k.clone()).zip(vecs).collect();
             tokio::task::spawn_blocking(move || {
                 for (key, vec) in &pairs {
                     let _ = s.save_embedding(key, vec);
@@ -255,8 +399,9 @@ pub async fn forget_memory(workspace: &Path, args: Value) -> anyhow::Result<Valu
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("forget_memory requires a 'key' string"))?
         .to_string();
+    let scope = args["scope"].as_str().unwrap_or(PRIVATE_SCOPE);
 
-    let store = Arc::new(crate::memory::MemoryStore::open(workspace)?);
+    let store = open_writable(workspace, scope).await?;
     let key2 = key.clone();
     let deleted = tokio::task::spawn_blocking(move || {
         let deleted = store.forget(&key2)?;
@@ -275,7 +420,7 @@ pub async fn forget_memory(workspace: &Path, args: Value) -> anyhow::Result<Valu
 pub fn register() {
     register_tool(ToolMeta {
         name: "save_memory".into(),
-        description: "Save a piece of information to persistent memory. Survives across sessions."
+        description: "Save a piece of information to persistent memory. Survives across sessions. Use a shared scope for facts other agents need too."
             .into(),
         args_schema: serde_json::json!({
             "type": "object",
@@ -292,10 +437,16 @@ pub fn register() {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "Optional tags for categorisation"
+                },
+                "scope": {
+                    "type": "string",
+                    "description": "Where to save: 'agent' (default, your own memory) or a shared scope you may write, such as 'global'"
                 }
             },
             "required": ["key", "value"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -320,9 +471,15 @@ pub fn register() {
                     "type": "string",
                     "enum": ["text", "semantic", "hybrid"],
                     "description": "Search mode: 'hybrid' (default, BM25 + vector fusion), 'semantic' (embedding only), or 'text' (keyword only)"
+                },
+                "scope": {
+                    "type": "string",
+                    "description": "Search only this scope: 'agent' (your own memory) or a shared scope. Default: your memory plus every shared scope you can read"
                 }
             }
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -334,9 +491,15 @@ pub fn register() {
                 "key": {
                     "type": "string",
                     "description": "The key of the memory entry to delete"
+                },
+                "scope": {
+                    "type": "string",
+                    "description": "Scope the entry is in: 'agent' (default) or a shared scope you may write"
                 }
             },
             "required": ["key"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
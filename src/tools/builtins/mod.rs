@@ -6,15 +6,23 @@
 
 pub mod agent;
 pub mod apply_patch;
+pub mod artifacts;
 pub mod cron;
 pub mod delegate;
 pub mod edit_file;
 pub mod exec_shell;
+pub mod git;
+pub mod http_fetch;
 pub mod list_files;
 pub mod memory;
 pub mod read_file;
+pub mod remind;
+pub mod sandbox;
+pub mod search_files;
 pub mod self_update;
 pub mod send_message;
 pub mod session;
 pub mod skill_author;
+pub mod sql_query;
+pub mod view_image;
 pub mod write_file;
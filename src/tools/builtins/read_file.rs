@@ -3,6 +3,11 @@
 //! Supports optional `start_line` / `end_line` for partial reads (1-based
 //! inclusive) and optional `include_info: true` to return metadata
 //! (total lines, byte size).
+//!
+//! PDF, DOCX, ODT and EPUB files are returned as extracted text (see
+//! [`crate::documents`]), as are HTML files with `extract: true` (they
+//! are read raw otherwise); `start_page` / `end_page` pick a page range
+//! (chapters for EPUB) before any line range is applied.
 
 use serde_json::{json, Value};
 use std::path::Path;
@@ -11,8 +16,9 @@ use crate::tools::{register_tool, sandbox_path, ToolMeta};
 
 /// Read a file inside the workspace.
 ///
-/// Args: `{ "path": "…", "start_line?": N, "end_line?": N, "include_info?": bool }`
-/// Returns: `{ "content": "…", "start_line": N, "end_line": N, "total_lines?": N, "size_bytes?": N }`
+/// Args: `{ "path": "…", "start_line?": N, "end_line?": N, "start_page?": N, "end_page?": N, "extract?": bool, "include_info?": bool }`
+/// Returns: `{ "content": "…", "start_line": N, "end_line": N, "total_lines?": N, "size_bytes?": N }`,
+/// plus `format`, `total_pages`, `start_page` and `end_page` for documents.
 pub async fn read_file(workspace: &Path, args: Value) -> anyhow::Result<Value> {
     let raw = args
         .get("path")
@@ -21,9 +27,45 @@ pub async fn read_file(workspace: &Path, args: Value) -> anyhow::Result<Value> {
 
     let path = sandbox_path(workspace, raw)?;
 
-    let full_content = tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| anyhow::anyhow!("read_file: cannot read {}: {e}", path.display()))?;
+    let page_arg = |key: &str| args.get(key).and_then(Value::as_u64).map(|v| v as usize);
+    let (start_page, end_page) = (page_arg("start_page"), page_arg("end_page"));
+    let extract = args
+        .get("extract")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let document =
+        crate::documents::DocumentKind::from_path(&path).filter(|kind| extract || kind.is_binary());
+
+    let mut document_info = None;
+    let full_content = if let Some(kind) = document {
+        let doc_path = path.clone();
+        let doc = tokio::task::spawn_blocking(move || crate::documents::extract(&doc_path))
+            .await?
+            .map_err(|e| anyhow::anyhow!("read_file: {e:#}"))?;
+        let total_pages = doc.pages.len();
+        let first = start_page.unwrap_or(1);
+        let last = end_page.unwrap_or(total_pages).min(total_pages);
+        let text = doc
+            .page_range(first, last)
+            .map_err(|e| anyhow::anyhow!("read_file: {e}"))?;
+        document_info = Some(json!({
+            "format": kind.name(),
+            "total_pages": total_pages,
+            "start_page": first,
+            "end_page": last,
+        }));
+        text
+    } else {
+        if start_page.is_some() || end_page.is_some() {
+            anyhow::bail!(
+                "read_file: start_page/end_page only apply to PDF, DOCX, ODT and EPUB files, \
+                 or HTML read with extract: true"
+            );
+        }
+        tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| anyhow::anyhow!("read_file: cannot read {}: {e}", path.display()))?
+    };
 
     let all_lines: Vec<&str> = full_content.split('\n').collect();
     let total_lines = all_lines.len();
@@ -81,6 +123,11 @@ pub async fn read_file(workspace: &Path, args: Value) -> anyhow::Result<Value> {
         result["total_lines"] = json!(total_lines);
         result["size_bytes"] = json!(size_bytes);
     }
+    if let Some(Value::Object(info)) = document_info {
+        for (key, value) in info {
+            result[key] = value;
+        }
+    }
 
     Ok(result)
 }
@@ -89,7 +136,7 @@ pub async fn read_file(workspace: &Path, args: Value) -> anyhow::Result<Value> {
 pub fn register() {
     register_tool(ToolMeta {
         name: "read_file".into(),
-        description: "Read files inside the agent workspace. Supports optional line range for partial reads and include_info for metadata.".into(),
+        description: "Read files inside the agent workspace. Supports optional line range for partial reads and include_info for metadata. PDF, DOCX, ODT and EPUB files are returned as extracted text (HTML too with extract: true); use start_page/end_page to read part of a long document.".into(),
         args_schema: json!({
             "type": "object",
             "properties": {
@@ -105,6 +152,18 @@ pub fn register() {
                     "type": "integer",
                     "description": "Optional 1-based end line (inclusive). Defaults to end of file if omitted."
                 },
+                "start_page": {
+                    "type": "integer",
+                    "description": "Documents only: optional 1-based first page (EPUB: chapter) to read."
+                },
+                "end_page": {
+                    "type": "integer",
+                    "description": "Documents only: optional 1-based last page (inclusive). Defaults to the last page."
+                },
+                "extract": {
+                    "type": "boolean",
+                    "description": "HTML only: return the page's main content as Markdown instead of the raw markup."
+                },
                 "include_info": {
                     "type": "boolean",
                     "description": "If true, include total_lines and size_bytes in the response."
@@ -113,5 +172,7 @@ pub fn register() {
             "required": ["path"],
             "additionalProperties": false
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 }
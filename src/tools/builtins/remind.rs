@@ -0,0 +1,738 @@
+//! Reminders — one-off or recurring nudges written in natural language and
+//! delivered back to the channel they were asked for in.
+//!
+//! A reminder is a cron job with `deliver_to` set.  One-off reminders are
+//! [`JobKind::OneShot`] jobs pinned to a date (a 7-field schedule ending in
+//! the year); recurring ones are ordinary 6-field schedules.  Times are read
+//! in the agent's timezone (see [`Config::resolve_timezone`]).
+//!
+//! Tool exposed:
+//! - `remind { action?, when?, message?, name?, channel? }` — `create`
+//!   (default), `list` or `cancel` reminders
+//!
+//! `when` understands, for example:
+//! - `in 20 minutes`, `in 1 hour 30 mins`, `in half an hour`, `in 2 days`
+//! - `at 5pm`, `17:30`, `noon`, `tonight`, `tomorrow at 9am`, `tomorrow morning`
+//! - `friday at 3pm`, `next monday`, `2026-11-02 09:30`
+//! - `every 15 minutes`, `every hour`, `every day at 8am`, `every weekday at 9`,
+//!   `every monday and thursday at 18:00`, `daily`, `hourly`
+//!
+//! [`Config::resolve_timezone`]: crate::config::Config::resolve_timezone
+
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Weekday};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+
+use crate::scheduler::{JobKind, PersistedCronJob};
+use crate::tools::{register_tool, ToolMeta};
+
+/// When a reminder fires.
+#[derive(Debug, Clone, PartialEq)]
+pub enum When {
+    /// Once, at this local time.
+    Once(DateTime<Tz>),
+    /// On a 6-field cron schedule, in the agent's timezone.
+    Every(String),
+}
+
+impl When {
+    /// Cron expression for the scheduler; a one-off time is pinned to its year.
+    pub fn schedule(&self) -> String {
+        match self {
+            When::Once(at) => format!(
+                "{} {} {} {} {} * {}",
+                at.second(),
+                at.minute(),
+                at.hour(),
+                at.day(),
+                at.month(),
+                at.year()
+            ),
+            When::Every(expr) => expr.clone(),
+        }
+    }
+}
+
+/// Parse a natural-language time relative to `now` (in the agent's
+/// timezone).
+pub fn parse_when(text: &str, now: DateTime<Tz>) -> anyhow::Result<When> {
+    let normalized = normalize(text);
+    let recurring = if let Some(rest) = normalized.strip_prefix("every ") {
+        Some(rest.to_string())
+    } else if let Some(rest) = normalized.strip_prefix("daily") {
+        Some(format!("day{rest}"))
+    } else if let Some(rest) = normalized.strip_prefix("weekdays ") {
+        Some(format!("weekday {rest}"))
+    } else if normalized == "hourly" {
+        Some("hour".to_string())
+    } else {
+        None
+    };
+    let parsed = match recurring {
+        Some(rest) => parse_every(&rest).map(When::Every),
+        None => parse_once(&normalized, now).map(When::Once),
+    };
+    parsed.ok_or_else(|| {
+        anyhow::anyhow!(
+            "could not understand '{text}' as a time; try e.g. 'in 20 minutes', \
+             'tomorrow at 9am', 'friday 17:00' or 'every weekday at 8:30' \
+             (repeats must fit a cron schedule: every N minutes where N divides 60, \
+             every N hours where N divides 24, or days of the week at a time)"
+        )
+    })
+}
+
+/// Lower-case, drop punctuation, and glue `9 am` into `9am`.
+fn normalize(text: &str) -> String {
+    let text = text
+        .to_lowercase()
+        .replace("a.m.", "am")
+        .replace("p.m.", "pm")
+        .replace("o'clock", "")
+        .replace(',', " ");
+    let mut words: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let word = word.trim_end_matches(['.', '!', '?']);
+        if word.is_empty() {
+            continue;
+        }
+        let glue = matches!(word, "am" | "pm")
+            && words
+                .last()
+                .is_some_and(|w| w.ends_with(|c: char| c.is_ascii_digit()));
+        if glue {
+            if let Some(last) = words.last_mut() {
+                last.push_str(word);
+            }
+            continue;
+        }
+        // ISO `2026-11-02t09:30` → date and time as separate words.
+        if word.len() > 11 && word.as_bytes()[10] == b't' && parse_date(&word[..10]).is_some() {
+            words.push(word[..10].to_string());
+            words.push(word[11..].to_string());
+            continue;
+        }
+        words.push(word.to_string());
+    }
+    words.join(" ")
+}
+
+fn parse_once(text: &str, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+    if let Some(rest) = text.strip_prefix("in ") {
+        return now.checked_add_signed(parse_duration(rest)?);
+    }
+    let tz = now.timezone();
+    let at = |date: NaiveDate, time: NaiveTime| local(&tz, date, time);
+    let today = now.date_naive();
+    let morning = NaiveTime::from_hms_opt(9, 0, 0)?;
+
+    let (day, time) = split_time(text)?;
+    let day = day.strip_prefix("on ").unwrap_or(&day);
+    let when = match day {
+        "" => {
+            let time = time?;
+            let today_at = at(today, time)?;
+            if today_at > now {
+                today_at
+            } else {
+                at(today.succ_opt()?, time)?
+            }
+        }
+        "today" => at(today, time?)?,
+        "tonight" => {
+            let time = time.unwrap_or(NaiveTime::from_hms_opt(20, 0, 0)?);
+            // `tonight at 11` means 23:00.
+            let time = if time.hour() < 12 {
+                time + Duration::hours(12)
+            } else {
+                time
+            };
+            at(today, time)?
+        }
+        "tomorrow" => at(today.succ_opt()?, time.unwrap_or(morning))?,
+        "day after tomorrow" => at(today + Duration::days(2), time.unwrap_or(morning))?,
+        _ => {
+            if let Some(date) = parse_date(day) {
+                return at(date, time.unwrap_or(morning));
+            }
+            let (next, name) = match day.strip_prefix("next ") {
+                Some(name) => (true, name),
+                None => (false, day.strip_prefix("this ").unwrap_or(day)),
+            };
+            let weekday = parse_weekday(name)?;
+            let time = time.unwrap_or(morning);
+            let mut ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            if ahead == 0 && (next || at(today, time)? <= now) {
+                ahead = 7;
+            }
+            at(today + Duration::days(ahead.into()), time)?
+        }
+    };
+    Some(when)
+}
+
+fn parse_every(rest: &str) -> Option<String> {
+    match rest {
+        "minute" => return Some("0 * * * * *".to_string()),
+        "hour" => return Some("0 0 * * * *".to_string()),
+        _ => {}
+    }
+    if let Some(interval) = parse_duration(rest) {
+        let secs = interval.num_seconds();
+        // `*/N` restarts at the top of each hour (or day), so only
+        // intervals dividing it evenly keep a steady spacing.
+        return match secs {
+            60 => Some("0 * * * * *".to_string()),
+            3600 => Some("0 0 * * * *".to_string()),
+            s if s > 0 && s % 60 == 0 && 60 % (s / 60) == 0 => {
+                Some(format!("0 */{} * * * *", s / 60))
+            }
+            s if s > 0 && s % 3600 == 0 && 24 % (s / 3600) == 0 => {
+                Some(format!("0 0 */{} * * *", s / 3600))
+            }
+            _ => None,
+        };
+    }
+
+    let (days, time) = split_time(rest)?;
+    let time = time.unwrap_or(NaiveTime::from_hms_opt(9, 0, 0)?);
+    let dow = match days.as_str() {
+        "" | "day" => "*".to_string(),
+        "weekday" => "MON-FRI".to_string(),
+        "weekend" => "SAT,SUN".to_string(),
+        list => {
+            let days: Vec<Weekday> = list
+                .split(' ')
+                .filter(|w| !matches!(*w, "and" | "&" | "on"))
+                .map(parse_weekday)
+                .collect::<Option<_>>()?;
+            days.iter()
+                .map(|d| cron_day(*d))
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+    };
+    Some(format!("0 {} {} * * {dow}", time.minute(), time.hour()))
+}
+
+/// Split a time of day (`at 5pm`, a leading or trailing `17:30`,
+/// `morning`, …) off `text`.  `None` when `at` is followed by something
+/// that is not a time.
+fn split_time(text: &str) -> Option<(String, Option<NaiveTime>)> {
+    let text = text.strip_prefix("at ").unwrap_or(text);
+    if let Some((rest, time)) = text.rsplit_once(" at ") {
+        return Some((rest.trim().to_string(), Some(parse_time(time)?)));
+    }
+    let words: Vec<&str> = text.split(' ').collect();
+    if let Some(time) = words.last().and_then(|w| parse_time(w)) {
+        return Some((words[..words.len() - 1].join(" "), Some(time)));
+    }
+    if let Some(time) = words.first().and_then(|w| parse_time(w)) {
+        return Some((words[1..].join(" "), Some(time)));
+    }
+    Some((text.to_string(), None))
+}
+
+/// `9`, `9am`, `9:30pm`, `17:30`, `7.15pm`, `noon`, `midnight`, `morning`, …
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let hm = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+    match text {
+        "noon" | "midday" => return hm(12, 0),
+        "midnight" => return hm(0, 0),
+        "morning" => return hm(9, 0),
+        "afternoon" => return hm(15, 0),
+        "evening" => return hm(18, 0),
+        "night" => return hm(21, 0),
+        _ => {}
+    }
+    let (digits, pm) = if let Some(d) = text.strip_suffix("am") {
+        (d, Some(false))
+    } else if let Some(d) = text.strip_suffix("pm") {
+        (d, Some(true))
+    } else {
+        (text, None)
+    };
+    let mut parts = digits.split([':', '.']);
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second: u32 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let hour = match pm {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// `20 minutes`, `1 hour and 30 mins`, `2h`, `an hour`, `half an hour`, `1.5 days`.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.replace("half an hour", "30 minutes");
+    let mut words = text.split(' ').filter(|w| !w.is_empty() && *w != "and");
+    let mut total = 0.0;
+    let mut any = false;
+    while let Some(word) = words.next() {
+        // `20m` / `2h`, or an amount followed by its unit.
+        let (amount, unit) = match word.find(|c: char| c.is_ascii_alphabetic()) {
+            Some(i) if i > 0 => (&word[..i], &word[i..]),
+            _ => (word, words.next()?),
+        };
+        let amount: f64 = match amount {
+            "a" | "an" | "one" => 1.0,
+            n => n.parse().ok()?,
+        };
+        if !amount.is_finite() || amount < 0.0 {
+            return None;
+        }
+        total += amount * unit_secs(unit)?;
+        any = true;
+    }
+    if !any || total < 1.0 {
+        return None;
+    }
+    Duration::try_seconds(total.round() as i64)
+}
+
+fn unit_secs(unit: &str) -> Option<f64> {
+    let unit = unit
+        .strip_suffix('s')
+        .filter(|u| !u.is_empty())
+        .unwrap_or(unit);
+    Some(match unit {
+        "s" | "sec" | "second" => 1.0,
+        "m" | "min" | "minute" => 60.0,
+        "h" | "hr" | "hour" => 3600.0,
+        "d" | "day" => 86_400.0,
+        "w" | "wk" | "week" => 604_800.0,
+        _ => return None,
+    })
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+/// Weekday names, abbreviations and plurals (`mondays`).
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let text = text
+        .strip_suffix('s')
+        .filter(|t| t.ends_with("day"))
+        .unwrap_or(text);
+    Some(match text {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Cron day names, which both cron implementations agree on (numeric
+/// days of the week differ between them).
+fn cron_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MON",
+        Weekday::Tue => "TUE",
+        Weekday::Wed => "WED",
+        Weekday::Thu => "THU",
+        Weekday::Fri => "FRI",
+        Weekday::Sat => "SAT",
+        Weekday::Sun => "SUN",
+    }
+}
+
+/// `date` at `time` in `tz`; a time skipped by a DST change moves an hour later.
+fn local(tz: &Tz, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Tz>> {
+    let naive = date.and_time(time);
+    tz.from_local_datetime(&naive).earliest().or_else(|| {
+        tz.from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+    })
+}
+
+/// Next time `schedule` fires after `now`.
+pub fn next_run(schedule: &str, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+    cron::Schedule::from_str(schedule).ok()?.after(&now).next()
+}
+
+fn format_time(at: &DateTime<Tz>) -> String {
+    at.format("%a %Y-%m-%d %H:%M %Z").to_string()
+}
+
+/// Internal channels have no user on the other end to remind.
+fn is_internal_channel(channel: &str) -> bool {
+    channel.is_empty()
+        || channel.starts_with("cron:")
+        || channel == "heartbeat"
+        || channel.starts_with("delegate:")
+        || channel == "inter-agent"
+}
+
+/// Where a reminder is delivered: an explicit `channel`, else the channel
+/// the request came from, else the configured default channel.
+fn delivery_channel(
+    explicit: Option<&str>,
+    origin: Option<String>,
+    default: Option<String>,
+) -> anyhow::Result<String> {
+    if let Some(channel) = explicit.map(str::trim).filter(|c| !c.is_empty()) {
+        return Ok(channel.to_string());
+    }
+    origin
+        .filter(|c| !is_internal_channel(c))
+        .or(default)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "no channel to deliver the reminder to; pass `channel` or configure channels.default_channel"
+            )
+        })
+}
+
+/// `remind` — create, list or cancel reminders for the calling agent.
+pub async fn remind(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let config_path = crate::pinchy_home().join("config.yaml");
+    let cfg = crate::config::Config::load(&config_path).await?;
+    let agent_id = cfg
+        .agent_for_workspace(workspace)
+        .map(|a| a.id.clone())
+        .ok_or_else(|| anyhow::anyhow!("remind: no configured agent owns this workspace"))?;
+    let tz = cfg.resolve_timezone(&agent_id);
+    let now = chrono::Utc::now().with_timezone(&tz);
+
+    match args["action"].as_str().unwrap_or("create") {
+        "create" => {
+            let default = cfg
+                .channels
+                .default_channel
+                .as_ref()
+                .map(|dc| dc.to_channel_string());
+            create(&agent_id, now, &args, default).await
+        }
+        "list" => list(&agent_id, now).await,
+        "cancel" => cancel(&agent_id, &args).await,
+        other => anyhow::bail!("unknown action '{other}' (expected create, list or cancel)"),
+    }
+}
+
+async fn create(
+    agent_id: &str,
+    now: DateTime<Tz>,
+    args: &Value,
+    default_channel: Option<String>,
+) -> anyhow::Result<Value> {
+    let when_text = args["when"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("remind requires a 'when' string to create a reminder"))?;
+    let message = args["message"]
+        .as_str()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!("remind requires a 'message' string to create a reminder")
+        })?;
+
+    let when = parse_when(when_text, now)?;
+    if let When::Once(at) = &when {
+        if *at <= now {
+            anyhow::bail!("'{when_text}' ({}) is in the past", format_time(at));
+        }
+    }
+    let schedule = when.schedule();
+    let next = next_run(&schedule, now)
+        .ok_or_else(|| anyhow::anyhow!("'{when_text}' does not fire at any future time"))?;
+    let channel = delivery_channel(
+        args["channel"].as_str(),
+        crate::tools::call_channel(),
+        default_channel,
+    )?;
+
+    let existing = crate::scheduler::load_persisted_cron_jobs(agent_id).await;
+    let name = match args["name"]
+        .as_str()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+    {
+        Some(name) => {
+            if existing
+                .iter()
+                .any(|j| j.name == name && j.deliver_to.is_none())
+            {
+                anyhow::bail!("'{name}' is already a cron job; pick another reminder name");
+            }
+            name.to_string()
+        }
+        None => {
+            let base = format!("reminder_{}", crate::scheduler::now_secs());
+            let mut name = base.clone();
+            let mut n = 1;
+            while existing.iter().any(|j| j.name == name) {
+                n += 1;
+                name = format!("{base}_{n}");
+            }
+            name
+        }
+    };
+
+    let kind = match when {
+        When::Once(_) => JobKind::OneShot,
+        When::Every(_) => JobKind::Recurring,
+    };
+    let entry = PersistedCronJob {
+        agent_id: agent_id.to_string(),
+        name: name.clone(),
+        schedule: schedule.clone(),
+        message: Some(message.to_string()),
+        kind: kind.clone(),
+        depends_on: None,
+        max_retries: None,
+        retry_delay_secs: None,
+        condition: None,
+        retry_count: 0,
+        last_status: None,
+        deliver_to: Some(channel.clone()),
+    };
+
+    let status = match crate::scheduler::scheduler_handle_ref() {
+        Some(handle) => {
+            handle.register_job(entry).await?;
+            "scheduled"
+        }
+        None => {
+            // Scheduler not running — persist so it picks up on next restart.
+            if let Some(db) = crate::store::global_db() {
+                db.upsert_cron_job(&entry)
+                    .map_err(|e| anyhow::anyhow!("{e}"))?;
+            } else {
+                tracing::warn!("no database available — skipping remind persistence");
+            }
+            "persisted_offline"
+        }
+    };
+
+    Ok(json!({
+        "status": status,
+        "name": name,
+        "kind": kind,
+        "schedule": schedule,
+        "next": format_time(&next),
+        "timezone": now.timezone().name(),
+        "channel": channel,
+    }))
+}
+
+async fn list(agent_id: &str, now: DateTime<Tz>) -> anyhow::Result<Value> {
+    let jobs = crate::scheduler::load_persisted_cron_jobs(agent_id).await;
+    let mut reminders: Vec<(Option<DateTime<Tz>>, Value)> = jobs
+        .iter()
+        .filter_map(|j| {
+            let channel = j.deliver_to.as_ref()?;
+            let next = next_run(&j.schedule, now);
+            Some((
+                next,
+                json!({
+                    "name": j.name,
+                    "message": j.message,
+                    "kind": j.kind,
+                    "schedule": j.schedule,
+                    "next": next.as_ref().map(format_time),
+                    "channel": channel,
+                }),
+            ))
+        })
+        .collect();
+    // Soonest first; reminders with no upcoming run last.
+    reminders.sort_by_key(|(next, _)| (next.is_none(), *next));
+    let reminders: Vec<Value> = reminders.into_iter().map(|(_, v)| v).collect();
+    Ok(json!({
+        "timezone": now.timezone().name(),
+        "count": reminders.len(),
+        "reminders": reminders,
+    }))
+}
+
+async fn cancel(agent_id: &str, args: &Value) -> anyhow::Result<Value> {
+    let name = args["name"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("remind requires a 'name' string to cancel a reminder"))?;
+    let jobs = crate::scheduler::load_persisted_cron_jobs(agent_id).await;
+    match jobs.iter().find(|j| j.name == name) {
+        None => anyhow::bail!("reminder '{name}' not found"),
+        Some(job) if job.deliver_to.is_none() => {
+            anyhow::bail!("'{name}' is a cron job, not a reminder; use delete_cron_job")
+        }
+        Some(_) => {}
+    }
+    crate::scheduler::remove_persisted_job(name, agent_id).await;
+    Ok(json!({ "status": "cancelled", "name": name }))
+}
+
+/// Register the `remind` tool.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "remind".into(),
+        description: "Set, list or cancel reminders. `when` is natural language in the agent's \
+            timezone: 'in 20 minutes', 'tomorrow at 9am', 'friday 17:00', '2026-11-02 09:30', \
+            'every weekday at 8:30', 'every monday and thursday at 18:00'. When a reminder is \
+            due you are prompted, in the channel it was set from, to deliver its message."
+            .into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "cancel"],
+                    "description": "create (default): schedule a reminder. list: show pending reminders. cancel: remove one by `name`."
+                },
+                "when": {
+                    "type": "string",
+                    "description": "create: when to remind, one-off or recurring (starting with 'every')."
+                },
+                "message": {
+                    "type": "string",
+                    "description": "create: what to remind the user about."
+                },
+                "name": {
+                    "type": "string",
+                    "description": "create: optional name (generated if omitted). cancel: the reminder to remove."
+                },
+                "channel": {
+                    "type": "string",
+                    "description": "create: channel to deliver to. Defaults to the current conversation's channel."
+                }
+            },
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday 2026-10-16, 14:20 in Berlin.
+    fn now() -> DateTime<Tz> {
+        chrono_tz::Europe::Berlin
+            .with_ymd_and_hms(2026, 10, 16, 14, 20, 0)
+            .unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> When {
+        When::Once(
+            chrono_tz::Europe::Berlin
+                .with_ymd_and_hms(2026, month, day, hour, minute, 0)
+                .unwrap(),
+        )
+    }
+
+    fn every(schedule: &str) -> When {
+        When::Every(schedule.to_string())
+    }
+
+    #[test]
+    fn parses_one_off_times() {
+        let cases = [
+            ("in 20 minutes", at(10, 16, 14, 40)),
+            ("In 1 hour and 30 mins.", at(10, 16, 15, 50)),
+            ("in half an hour", at(10, 16, 14, 50)),
+            ("in 2h", at(10, 16, 16, 20)),
+            ("at 5pm", at(10, 16, 17, 0)),
+            ("9:30", at(10, 17, 9, 30)),
+            ("noon", at(10, 17, 12, 0)),
+            ("tonight", at(10, 16, 20, 0)),
+            ("tomorrow", at(10, 17, 9, 0)),
+            ("tomorrow at 7.15 p.m.", at(10, 17, 19, 15)),
+            ("5pm tomorrow", at(10, 17, 17, 0)),
+            ("tomorrow evening", at(10, 17, 18, 0)),
+            ("monday 8am", at(10, 19, 8, 0)),
+            ("friday", at(10, 23, 9, 0)),
+            ("on friday at 6pm", at(10, 16, 18, 0)),
+            ("next friday 6pm", at(10, 23, 18, 0)),
+            ("2026-12-24 18:00", at(12, 24, 18, 0)),
+            ("2026-12-24T07:45", at(12, 24, 7, 45)),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_when(text, now()).unwrap(), expected, "{text}");
+        }
+        assert_eq!(at(10, 16, 14, 40).schedule(), "0 40 14 16 10 * 2026");
+    }
+
+    #[test]
+    fn parses_recurring_times() {
+        let cases = [
+            ("every 15 minutes", every("0 */15 * * * *")),
+            ("every hour", every("0 0 * * * *")),
+            ("every 2 hours", every("0 0 */2 * * *")),
+            ("hourly", every("0 0 * * * *")),
+            ("daily", every("0 0 9 * * *")),
+            ("daily at 7am", every("0 0 7 * * *")),
+            ("every evening", every("0 0 18 * * *")),
+            ("every weekday at 8:30 am", every("0 30 8 * * MON-FRI")),
+            ("every weekend at noon", every("0 0 12 * * SAT,SUN")),
+            (
+                "every monday and thursday at 18:00",
+                every("0 0 18 * * MON,THU"),
+            ),
+            ("every tuesdays", every("0 0 9 * * TUE")),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_when(text, now()).unwrap(), expected, "{text}");
+        }
+        let next = next_run("0 30 8 * * MON-FRI", now()).unwrap();
+        assert_eq!(
+            next,
+            chrono_tz::Europe::Berlin
+                .with_ymd_and_hms(2026, 10, 19, 8, 30, 0)
+                .unwrap()
+        );
+        let once = next_run(&at(12, 24, 18, 0).schedule(), now()).unwrap();
+        assert_eq!(When::Once(once), at(12, 24, 18, 0));
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_schedule() {
+        for text in [
+            "whenever",
+            "in a bit",
+            "at 25:00",
+            "13pm",
+            "every 90 minutes",
+            "every 45 minutes",
+            "every 7 minutes",
+            "every 5 hours",
+            "every 0 minutes",
+            "every 2 days",
+            "every blue moon",
+        ] {
+            assert!(parse_when(text, now()).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn delivers_to_the_requesting_channel_unless_internal() {
+        let default = Some("dm:42".to_string());
+        assert_eq!(
+            delivery_channel(Some("123"), Some("456".into()), default.clone()).unwrap(),
+            "123"
+        );
+        assert_eq!(
+            delivery_channel(None, Some("456".into()), default.clone()).unwrap(),
+            "456"
+        );
+        assert_eq!(
+            delivery_channel(None, Some("cron:daily".into()), default.clone()).unwrap(),
+            "dm:42"
+        );
+        assert!(delivery_channel(None, Some("heartbeat".into()), None).is_err());
+    }
+}
@@ -0,0 +1,638 @@
+//! OS-level sandbox for `exec_shell` (Linux only).
+//!
+//! Layers, applied in the child between `fork` and `exec`:
+//!
+//! 1. **rlimits** — CPU seconds, address space, process count, file size.
+//! 2. **user + network namespace** — when the agent has no network
+//!    access the command gets an empty network namespace (loopback only,
+//!    down).  The user namespace maps the caller's own uid/gid so file
+//!    ownership looks unchanged.
+//! 3. **landlock** — writes are confined to the workspace, any configured
+//!    `writable_paths`, and a few harmless device files.  Reads are not
+//!    restricted.
+//! 4. **seccomp** — privileged or escape-prone syscalls (mount, ptrace,
+//!    module loading, namespace creation, ...) fail with `EPERM`.
+//!
+//! Setup failures refuse the command instead of running it unsandboxed.
+//! After the command finishes, [`violations`] turns tell-tale exit
+//! statuses and stderr lines into a short report for the agent.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use crate::config::SandboxConfig;
+
+const DEFAULT_CPU_SECS: u64 = 60;
+const DEFAULT_MEMORY_MB: u64 = 2048;
+const DEFAULT_MAX_PROCESSES: u64 = 512;
+const DEFAULT_MAX_FILE_MB: u64 = 256;
+
+/// Cap on reported violations so a noisy command can't flood the result.
+const MAX_VIOLATIONS: usize = 10;
+
+/// Parent-side sandbox state, prepared before spawning a command.
+///
+/// Must outlive the `spawn()` call: it owns the landlock ruleset fd the
+/// child inherits.
+pub struct Sandbox {
+    config: SandboxConfig,
+    tmp_dir: PathBuf,
+    #[cfg(target_os = "linux")]
+    ruleset: std::os::fd::OwnedFd,
+}
+
+impl Sandbox {
+    /// Prepare the sandbox for commands running in `workspace`.
+    #[cfg(target_os = "linux")]
+    pub fn prepare(config: &SandboxConfig, workspace: &Path) -> anyhow::Result<Self> {
+        let tmp_dir = workspace.join(".tmp");
+        std::fs::create_dir_all(&tmp_dir)?;
+
+        let mut writable = vec![workspace.to_path_buf()];
+        writable.extend(config.writable_paths.iter().map(PathBuf::from));
+        let ruleset = linux::landlock_ruleset(&writable)?;
+
+        if config.seccomp && linux::seccomp_filter().is_none() {
+            anyhow::bail!(
+                "seccomp filtering is not supported on this architecture — set sandbox.seccomp: false"
+            );
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            tmp_dir,
+            ruleset,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn prepare(_config: &SandboxConfig, _workspace: &Path) -> anyhow::Result<Self> {
+        anyhow::bail!("the exec_shell sandbox is only supported on Linux")
+    }
+
+    /// Install the sandbox on `cmd`.  Call before `spawn()`.
+    pub fn apply(&self, cmd: &mut tokio::process::Command) {
+        cmd.env("TMPDIR", &self.tmp_dir);
+        #[cfg(target_os = "linux")]
+        linux::install(cmd, &self.config, &self.ruleset);
+    }
+}
+
+/// Summarise likely sandbox violations from a finished command.
+pub fn violations(config: &SandboxConfig, status: Option<ExitStatus>, stderr: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    let mut push = |v: String| {
+        if found.len() < MAX_VIOLATIONS && !found.contains(&v) {
+            found.push(v);
+        }
+    };
+
+    // Killed by a limit signal — directly, or reported by `sh` as 128+N.
+    let signal = status.and_then(|s| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            s.signal()
+                .or_else(|| s.code().filter(|c| *c > 128).map(|c| c - 128))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = s;
+            None
+        }
+    });
+    match signal {
+        Some(24) => push(format!(
+            "cpu: CPU time limit of {}s exceeded",
+            config.cpu_secs.unwrap_or(DEFAULT_CPU_SECS)
+        )),
+        Some(25) => push(format!(
+            "file size: file size limit of {} MiB exceeded",
+            config.max_file_mb.unwrap_or(DEFAULT_MAX_FILE_MB)
+        )),
+        Some(31) => push("seccomp: process killed by the syscall filter".into()),
+        _ => {}
+    }
+
+    for line in stderr.lines() {
+        let line = line.trim();
+        let lower = line.to_lowercase();
+        let short = crate::utils::truncate_str(line, 200);
+        if lower.contains("permission denied") || lower.contains("read-only file system") {
+            push(format!(
+                "filesystem: write outside the workspace was denied ({short})"
+            ));
+        } else if lower.contains("operation not permitted") {
+            push(format!("syscall: privileged operation blocked ({short})"));
+        } else if !config.network
+            && [
+                "network is unreachable",
+                "temporary failure in name resolution",
+                "could not resolve host",
+                "name or service not known",
+                "no address associated with hostname",
+            ]
+            .iter()
+            .any(|p| lower.contains(p))
+        {
+            push(format!("network: network access is disabled ({short})"));
+        } else if lower.contains("resource temporarily unavailable")
+            || lower.contains("fork: retry")
+            || lower.contains("cannot fork")
+        {
+            push(format!(
+                "processes: process limit of {} reached ({short})",
+                config.max_processes.unwrap_or(DEFAULT_MAX_PROCESSES)
+            ));
+        } else if lower.contains("cannot allocate memory")
+            || lower.contains("memoryerror")
+            || lower.contains("out of memory")
+        {
+            push(format!(
+                "memory: memory limit of {} MiB reached ({short})",
+                config.memory_mb.unwrap_or(DEFAULT_MEMORY_MB)
+            ));
+        } else if lower.contains("cpu time limit exceeded") {
+            push(format!(
+                "cpu: CPU time limit of {}s exceeded",
+                config.cpu_secs.unwrap_or(DEFAULT_CPU_SECS)
+            ));
+        } else if lower.contains("file size limit exceeded") {
+            push(format!(
+                "file size: file size limit of {} MiB exceeded",
+                config.max_file_mb.unwrap_or(DEFAULT_MAX_FILE_MB)
+            ));
+        }
+    }
+    found
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    // ── landlock uapi (not exposed by the libc crate) ───────────
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+    const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// ABI 2.
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    /// ABI 3.
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Every write-type right in landlock ABI 1.
+    const WRITE_ACCESS_V1: u64 = ACCESS_FS_WRITE_FILE
+        | ACCESS_FS_REMOVE_DIR
+        | ACCESS_FS_REMOVE_FILE
+        | ACCESS_FS_MAKE_CHAR
+        | ACCESS_FS_MAKE_DIR
+        | ACCESS_FS_MAKE_REG
+        | ACCESS_FS_MAKE_SOCK
+        | ACCESS_FS_MAKE_FIFO
+        | ACCESS_FS_MAKE_BLOCK
+        | ACCESS_FS_MAKE_SYM;
+
+    /// Rights that may be granted on a non-directory.
+    const FILE_ACCESS: u64 = ACCESS_FS_WRITE_FILE | ACCESS_FS_TRUNCATE;
+
+    /// Device files commands routinely write to.
+    const WRITABLE_DEVICES: &[&str] = &["/dev/null", "/dev/zero", "/dev/full", "/dev/tty"];
+
+    /// Build a landlock ruleset that handles every write right and grants
+    /// them beneath `writable`.  Reads and execution stay unrestricted.
+    pub(super) fn landlock_ruleset(writable: &[PathBuf]) -> anyhow::Result<OwnedFd> {
+        // SAFETY: version query — NULL attr with size 0 is the documented form.
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            anyhow::bail!(
+                "landlock is unavailable on this kernel ({}) — the exec_shell sandbox needs Linux 5.13+ with landlock enabled",
+                io::Error::last_os_error()
+            );
+        }
+
+        let mut handled = WRITE_ACCESS_V1;
+        if abi >= 2 {
+            handled |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled |= ACCESS_FS_TRUNCATE;
+        }
+
+        let attr = RulesetAttr {
+            handled_access_fs: handled,
+        };
+        // SAFETY: attr is a valid, correctly sized landlock_ruleset_attr prefix.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                std::mem::size_of::<RulesetAttr>(),
+                0u32,
+            )
+        };
+        if fd < 0 {
+            anyhow::bail!(
+                "landlock ruleset creation failed: {}",
+                io::Error::last_os_error()
+            );
+        }
+        // SAFETY: the kernel just returned this fd (opened O_CLOEXEC); we own it.
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        let devices = WRITABLE_DEVICES.iter().map(PathBuf::from);
+        for path in writable.iter().cloned().chain(devices) {
+            let Ok(file) = std::fs::OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+                .open(&path)
+            else {
+                tracing::debug!(path = %path.display(), "sandbox: skipping missing writable path");
+                continue;
+            };
+            let is_dir = file.metadata().map(|m| m.is_dir()).unwrap_or(false);
+            let rule = PathBeneathAttr {
+                allowed_access: if is_dir {
+                    handled
+                } else {
+                    handled & FILE_ACCESS
+                },
+                parent_fd: file.as_raw_fd(),
+            };
+            // SAFETY: ruleset and rule.parent_fd are open fds; rule is a
+            // valid landlock_path_beneath_attr.
+            let rc = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    ruleset.as_raw_fd(),
+                    LANDLOCK_RULE_PATH_BENEATH,
+                    &rule as *const PathBeneathAttr,
+                    0u32,
+                )
+            };
+            if rc != 0 {
+                anyhow::bail!(
+                    "landlock rule for {} failed: {}",
+                    path.display(),
+                    io::Error::last_os_error()
+                );
+            }
+        }
+        Ok(ruleset)
+    }
+
+    // ── seccomp ─────────────────────────────────────────────────
+
+    // Classic BPF opcodes: BPF_LD|BPF_W|BPF_ABS, BPF_JMP|<op>|BPF_K, BPF_RET|BPF_K.
+    const BPF_LD_W_ABS: u16 = 0x20;
+    const BPF_JMP_JEQ_K: u16 = 0x15;
+    const BPF_JMP_JGE_K: u16 = 0x35;
+    const BPF_JMP_JSET_K: u16 = 0x45;
+    const BPF_RET_K: u16 = 0x06;
+
+    const SECCOMP_DATA_NR: u32 = 0;
+    const SECCOMP_DATA_ARCH: u32 = 4;
+    /// Low 32 bits of `args[0]` (little-endian).
+    const SECCOMP_DATA_ARG0_LO: u32 = 16;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    /// Namespace-creating `clone` flags.
+    const CLONE_NS_FLAGS: u32 = (libc::CLONE_NEWNS
+        | libc::CLONE_NEWCGROUP
+        | libc::CLONE_NEWUTS
+        | libc::CLONE_NEWIPC
+        | libc::CLONE_NEWUSER
+        | libc::CLONE_NEWPID
+        | libc::CLONE_NEWNET) as u32;
+
+    fn stmt(code: u16, k: u32) -> libc::sock_filter {
+        libc::sock_filter {
+            code,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+        libc::sock_filter { code, jt, jf, k }
+    }
+
+    /// Syscalls the sandboxed command may never make.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn denied_syscalls() -> Vec<libc::c_long> {
+        #[allow(unused_mut)]
+        let mut list = vec![
+            libc::SYS_ptrace,
+            libc::SYS_mount,
+            libc::SYS_umount2,
+            libc::SYS_pivot_root,
+            libc::SYS_chroot,
+            libc::SYS_swapon,
+            libc::SYS_swapoff,
+            libc::SYS_reboot,
+            libc::SYS_kexec_load,
+            libc::SYS_kexec_file_load,
+            libc::SYS_init_module,
+            libc::SYS_finit_module,
+            libc::SYS_delete_module,
+            libc::SYS_bpf,
+            libc::SYS_perf_event_open,
+            libc::SYS_keyctl,
+            libc::SYS_add_key,
+            libc::SYS_request_key,
+            libc::SYS_unshare,
+            libc::SYS_setns,
+            libc::SYS_open_by_handle_at,
+            libc::SYS_name_to_handle_at,
+            libc::SYS_process_vm_readv,
+            libc::SYS_process_vm_writev,
+            libc::SYS_userfaultfd,
+            libc::SYS_acct,
+            libc::SYS_settimeofday,
+            libc::SYS_clock_settime,
+            libc::SYS_adjtimex,
+            libc::SYS_sethostname,
+            libc::SYS_setdomainname,
+            libc::SYS_fsopen,
+            libc::SYS_fsmount,
+            libc::SYS_move_mount,
+            libc::SYS_open_tree,
+        ];
+        #[cfg(target_arch = "x86_64")]
+        list.extend([libc::SYS_iopl, libc::SYS_ioperm]);
+        list
+    }
+
+    /// Build the seccomp BPF program, or `None` on unsupported arches.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    pub(super) fn seccomp_filter() -> Option<Vec<libc::sock_filter>> {
+        let errno = |e: i32| libc::SECCOMP_RET_ERRNO | (e as u32 & libc::SECCOMP_RET_DATA);
+        let mut prog = vec![
+            stmt(BPF_LD_W_ABS, SECCOMP_DATA_ARCH),
+            jump(BPF_JMP_JEQ_K, AUDIT_ARCH, 1, 0),
+            stmt(BPF_RET_K, libc::SECCOMP_RET_KILL_PROCESS),
+            stmt(BPF_LD_W_ABS, SECCOMP_DATA_NR),
+        ];
+        // x32 syscalls share the x86_64 audit arch; refuse them outright.
+        #[cfg(target_arch = "x86_64")]
+        prog.extend([
+            jump(BPF_JMP_JGE_K, 0x4000_0000, 0, 1),
+            stmt(BPF_RET_K, errno(libc::EPERM)),
+        ]);
+        for nr in denied_syscalls() {
+            prog.push(jump(BPF_JMP_JEQ_K, nr as u32, 0, 1));
+            prog.push(stmt(BPF_RET_K, errno(libc::EPERM)));
+        }
+        // clone3 takes its flags by pointer, which BPF can't inspect;
+        // ENOSYS makes libc fall back to clone, whose flags we can check.
+        prog.push(jump(BPF_JMP_JEQ_K, libc::SYS_clone3 as u32, 0, 1));
+        prog.push(stmt(BPF_RET_K, errno(libc::ENOSYS)));
+        prog.extend([
+            jump(BPF_JMP_JEQ_K, libc::SYS_clone as u32, 0, 3),
+            stmt(BPF_LD_W_ABS, SECCOMP_DATA_ARG0_LO),
+            jump(BPF_JMP_JSET_K, CLONE_NS_FLAGS, 0, 1),
+            stmt(BPF_RET_K, errno(libc::EPERM)),
+            stmt(BPF_RET_K, libc::SECCOMP_RET_ALLOW),
+        ]);
+        Some(prog)
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    pub(super) fn seccomp_filter() -> Option<Vec<libc::sock_filter>> {
+        None
+    }
+
+    // ── child-side installation ─────────────────────────────────
+
+    /// Write `data` to `path` using only async-signal-safe calls.
+    fn write_proc(path: &CString, data: &[u8]) -> io::Result<()> {
+        // SAFETY: plain open/write/close on a NUL-terminated path.
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let n = libc::write(fd, data.as_ptr().cast(), data.len());
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            if n < 0 {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn check(rc: libc::c_long) -> io::Result<()> {
+        if rc < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(super) fn install(
+        cmd: &mut tokio::process::Command,
+        config: &SandboxConfig,
+        ruleset: &OwnedFd,
+    ) {
+        let mib = 1024 * 1024;
+        let limit = |soft: u64, hard: u64| libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        };
+        let cpu = config.cpu_secs.unwrap_or(DEFAULT_CPU_SECS);
+        let memory = config.memory_mb.unwrap_or(DEFAULT_MEMORY_MB) * mib;
+        let procs = config.max_processes.unwrap_or(DEFAULT_MAX_PROCESSES);
+        let fsize = config.max_file_mb.unwrap_or(DEFAULT_MAX_FILE_MB) * mib;
+        // CPU gets a one-second grace between SIGXCPU (soft) and SIGKILL (hard).
+        let rlimits = [
+            (libc::RLIMIT_CPU, limit(cpu, cpu + 1)),
+            (libc::RLIMIT_AS, limit(memory, memory)),
+            (libc::RLIMIT_NPROC, limit(procs, procs)),
+            (libc::RLIMIT_FSIZE, limit(fsize, fsize)),
+        ];
+
+        // Everything the child needs is allocated here, before fork.
+        let netns = (!config.network).then(|| {
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            (
+                CString::new("/proc/self/setgroups").unwrap_or_default(),
+                CString::new("/proc/self/uid_map").unwrap_or_default(),
+                format!("{uid} {uid} 1").into_bytes(),
+                CString::new("/proc/self/gid_map").unwrap_or_default(),
+                format!("{gid} {gid} 1").into_bytes(),
+            )
+        });
+        let ruleset_fd = ruleset.as_raw_fd();
+        let filter = if config.seccomp {
+            seccomp_filter()
+        } else {
+            None
+        };
+
+        // SAFETY: the closure runs in the forked child before exec and only
+        // makes raw syscalls on data prepared above — no allocation, no locks.
+        unsafe {
+            cmd.pre_exec(move || {
+                for (resource, lim) in &rlimits {
+                    if libc::setrlimit(*resource, lim) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                if let Some((setgroups, uid_map, uid_line, gid_map, gid_line)) = &netns {
+                    if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    write_proc(setgroups, b"deny")?;
+                    write_proc(uid_map, uid_line)?;
+                    write_proc(gid_map, gid_line)?;
+                }
+                check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) as libc::c_long)?;
+                check(libc::syscall(
+                    libc::SYS_landlock_restrict_self,
+                    ruleset_fd,
+                    0u32,
+                ))?;
+                if let Some(filter) = &filter {
+                    let prog = libc::sock_fprog {
+                        len: filter.len() as u16,
+                        filter: filter.as_ptr() as *mut libc::sock_filter,
+                    };
+                    check(libc::prctl(
+                        libc::PR_SET_SECCOMP,
+                        libc::SECCOMP_MODE_FILTER,
+                        &prog as *const libc::sock_fprog,
+                    ) as libc::c_long)?;
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SandboxConfig {
+        SandboxConfig {
+            enabled: true,
+            network: false,
+            writable_paths: Vec::new(),
+            cpu_secs: Some(2),
+            memory_mb: None,
+            max_processes: None,
+            max_file_mb: Some(1),
+            seccomp: true,
+        }
+    }
+
+    #[test]
+    fn classifies_stderr_lines() {
+        let stderr = "touch: cannot touch '/etc/x': Permission denied\n\
+                      curl: (6) Could not resolve host: example.com\n\
+                      touch: cannot touch '/etc/x': Permission denied";
+        let v = violations(&config(), None, stderr);
+        assert_eq!(v.len(), 2, "{v:?}");
+        assert!(v[0].starts_with("filesystem:"));
+        assert!(v[1].starts_with("network:"));
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn run(ws: &Path, command: &str) -> (std::process::Output, Vec<String>) {
+        let cfg = config();
+        let sandbox = Sandbox::prepare(&cfg, ws).expect("sandbox setup");
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(command).current_dir(ws);
+        sandbox.apply(&mut cmd);
+        let out = cmd.output().await.expect("spawn");
+        let v = violations(
+            &cfg,
+            Some(out.status),
+            &String::from_utf8_lossy(&out.stderr),
+        );
+        (out, v)
+    }
+
+    /// Exercises the real kernel features; skipped where landlock or
+    /// unprivileged user namespaces are unavailable.
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn confines_writes_network_and_limits() {
+        let ws = tempfile::tempdir().unwrap();
+        if Sandbox::prepare(&config(), ws.path()).is_err() {
+            eprintln!("landlock unavailable — skipping");
+            return;
+        }
+        let (probe, _) = run(ws.path(), "true").await;
+        if !probe.status.success() {
+            eprintln!("user namespaces unavailable — skipping");
+            return;
+        }
+
+        let (out, v) = run(ws.path(), "echo hi > inside.txt && cat inside.txt").await;
+        assert!(out.status.success(), "{v:?}");
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "hi");
+
+        let outside = ws
+            .path()
+            .parent()
+            .unwrap()
+            .join(format!("pinchy-sandbox-escape-{}", std::process::id()));
+        let (out, v) = run(ws.path(), &format!("touch {}", outside.display())).await;
+        assert!(!out.status.success());
+        assert!(!outside.exists());
+        assert!(v.iter().any(|v| v.starts_with("filesystem:")), "{v:?}");
+
+        let (out, v) = run(ws.path(), "head -c 2000000 /dev/zero > big.bin").await;
+        assert!(!out.status.success());
+        assert!(v.iter().any(|v| v.starts_with("file size:")), "{v:?}");
+
+        // Empty network namespace: only the loopback device exists.
+        let (out, _) = run(ws.path(), "tail -n +3 /proc/net/dev | cut -d: -f1").await;
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "lo");
+
+        let (_, v) = run(ws.path(), "unshare -r true").await;
+        assert!(v.iter().any(|v| v.starts_with("syscall:")), "{v:?}");
+    }
+}
@@ -0,0 +1,330 @@
+//! Built-in `search_files` tool — regex/literal content search across
+//! the agent workspace with context lines and bounded, structured output.
+//!
+//! Walks the tree with the same traversal as `list_files` (skipping
+//! hidden entries, dependency directories and `.gitignore`d paths), skips
+//! binary and oversized files, and caps matches per file and overall.
+
+use regex::{Regex, RegexBuilder};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use super::list_files::{collect_entries, CollectOpts, DirEntry};
+use crate::tools::{register_tool, sandbox_path, ToolMeta};
+use crate::utils::gitignore::path_glob_match;
+use crate::utils::{glob_match, truncate_str};
+
+/// Files larger than this are skipped.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Upper bound on files visited per search.
+const MAX_FILES: usize = 20_000;
+/// Matched and context lines are shortened to this many characters.
+const MAX_LINE_CHARS: usize = 400;
+const DEFAULT_PER_FILE: usize = 20;
+const MAX_PER_FILE: usize = 200;
+const DEFAULT_MAX_RESULTS: usize = 200;
+const MAX_RESULTS: usize = 1000;
+const MAX_CONTEXT: usize = 10;
+
+/// Search file contents in the workspace.
+///
+/// Args: `{ "pattern": "fn main", "path": "src", "literal": false,
+///          "ignore_case": false, "include": ["*.rs"], "exclude": [],
+///          "context": 2, "max_per_file": 20, "max_results": 200,
+///          "gitignore": true }`
+pub async fn search_files(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let pattern = args
+        .get("pattern")
+        .and_then(Value::as_str)
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("search_files: missing `pattern` argument"))?;
+    let raw = args.get("path").and_then(Value::as_str).unwrap_or(".");
+    let root = sandbox_path(workspace, raw)?;
+
+    let literal = args
+        .get("literal")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let ignore_case = args
+        .get("ignore_case")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let source = if literal {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    let re = RegexBuilder::new(&source)
+        .case_insensitive(ignore_case)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| anyhow::anyhow!("search_files: invalid regex: {e}"))?;
+
+    let include = string_list(&args, "include");
+    let exclude = string_list(&args, "exclude");
+    let context = arg_usize(&args, "context", 0).min(MAX_CONTEXT);
+    let per_file = arg_usize(&args, "max_per_file", DEFAULT_PER_FILE).clamp(1, MAX_PER_FILE);
+    let max_results = arg_usize(&args, "max_results", DEFAULT_MAX_RESULTS).clamp(1, MAX_RESULTS);
+    let gitignore = args
+        .get("gitignore")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    let files: Vec<String> = if root.is_file() {
+        vec![relative(workspace, &root)]
+    } else if root.is_dir() {
+        let opts = CollectOpts {
+            workspace,
+            pattern: None,
+            recursive: true,
+            include_metadata: false,
+            max: MAX_FILES,
+            max_depth: 20,
+            gitignore,
+        };
+        let mut entries: Vec<DirEntry> = Vec::new();
+        collect_entries(&root, &opts, &mut entries).await?;
+        let mut files: Vec<String> = entries
+            .into_iter()
+            .filter(|e| !e.is_dir)
+            .map(|e| e.rel_path)
+            .filter(|p| {
+                (include.is_empty() || include.iter().any(|g| glob_hit(g, p)))
+                    && !exclude.iter().any(|g| glob_hit(g, p))
+            })
+            .collect();
+        files.sort();
+        files
+    } else {
+        anyhow::bail!("search_files: '{raw}' does not exist");
+    };
+
+    let mut results = Vec::new();
+    let mut total = 0usize;
+    let mut searched = 0usize;
+    let mut skipped_binary = 0usize;
+    let mut skipped_large = 0usize;
+    let mut truncated = false;
+
+    for rel in &files {
+        if total >= max_results {
+            truncated = true;
+            break;
+        }
+        let path = workspace.join(rel);
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) if meta.len() > MAX_FILE_BYTES => {
+                skipped_large += 1;
+                continue;
+            }
+            Ok(_) => {}
+            Err(_) => continue,
+        }
+        let Ok(bytes) = tokio::fs::read(&path).await else {
+            continue;
+        };
+        if is_binary(&bytes) {
+            skipped_binary += 1;
+            continue;
+        }
+        searched += 1;
+
+        let text = String::from_utf8_lossy(&bytes);
+        let limit = per_file.min(max_results - total);
+        let (matches, more) = search_text(&re, &text, context, limit);
+        if matches.is_empty() {
+            continue;
+        }
+        total += matches.len();
+        let mut file = json!({ "path": rel, "matches": matches });
+        if more {
+            file["truncated"] = json!(true);
+        }
+        results.push(file);
+    }
+
+    let mut result = json!({
+        "results": results,
+        "total_matches": total,
+        "files_searched": searched,
+    });
+    if truncated {
+        result["truncated"] = json!(true);
+    }
+    if skipped_binary > 0 {
+        result["skipped_binary"] = json!(skipped_binary);
+    }
+    if skipped_large > 0 {
+        result["skipped_large"] = json!(skipped_large);
+    }
+    if files.len() >= MAX_FILES {
+        result["note"] = json!(format!(
+            "stopped after {MAX_FILES} files; narrow `path` or `include` to search the rest"
+        ));
+    }
+    Ok(result)
+}
+
+/// Find up to `limit` matching lines.  Returns the matches and whether
+/// more were left unreported.
+fn search_text(re: &Regex, text: &str, context: usize, limit: usize) -> (Vec<Value>, bool) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut matches = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(m) = re.find(line) else {
+            continue;
+        };
+        if matches.len() >= limit {
+            return (matches, true);
+        }
+        let mut hit = json!({
+            "line": idx + 1,
+            "column": line[..m.start()].chars().count() + 1,
+            "text": truncate_str(line, MAX_LINE_CHARS),
+        });
+        if context > 0 {
+            let before: Vec<String> = lines[idx.saturating_sub(context)..idx]
+                .iter()
+                .map(|l| truncate_str(l, MAX_LINE_CHARS))
+                .collect();
+            let after: Vec<String> = lines[idx + 1..(idx + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| truncate_str(l, MAX_LINE_CHARS))
+                .collect();
+            hit["before"] = json!(before);
+            hit["after"] = json!(after);
+        }
+        matches.push(hit);
+    }
+    (matches, false)
+}
+
+/// Heuristic: a NUL byte in the first 8 KB means binary.
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|&b| b == 0)
+}
+
+/// Globs containing `/` match the workspace-relative path; others match
+/// the file name.
+fn glob_hit(glob: &str, rel: &str) -> bool {
+    if glob.contains('/') {
+        path_glob_match(glob, rel)
+    } else {
+        glob_match(glob, rel.rsplit('/').next().unwrap_or(rel))
+    }
+}
+
+fn relative(workspace: &Path, path: &Path) -> String {
+    let canonical_ws = workspace.canonicalize().ok();
+    path.strip_prefix(workspace)
+        .ok()
+        .or_else(|| path.strip_prefix(canonical_ws.as_deref()?).ok())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Accept either a single string or an array of strings.
+fn string_list(args: &Value, key: &str) -> Vec<String> {
+    match args.get(key) {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn arg_usize(args: &Value, key: &str, default: usize) -> usize {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(default)
+}
+
+/// Register the `search_files` tool metadata.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "search_files".into(),
+        description: "Search file contents in the agent workspace (like grep -rn, but bounded). Regex or literal patterns, include/exclude globs, .gitignore aware, optional context lines. Skips binary files. Prefer this over exec_shell grep.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression (Rust syntax) to search for, or plain text when literal=true."
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative file or directory to search (default: '.')."
+                },
+                "literal": {
+                    "type": "boolean",
+                    "description": "Treat `pattern` as plain text rather than a regex. Default: false."
+                },
+                "ignore_case": {
+                    "type": "boolean",
+                    "description": "Case-insensitive matching. Default: false."
+                },
+                "include": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only search files matching any of these globs (e.g. '*.rs', 'src/**/*.ts'). Globs without '/' match the file name."
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Skip files matching any of these globs."
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context before and after each match (max 10). Default: 0."
+                },
+                "max_per_file": {
+                    "type": "integer",
+                    "description": "Maximum matches reported per file (max 200). Default: 20."
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum matches reported overall (max 1000). Default: 200."
+                },
+                "gitignore": {
+                    "type": "boolean",
+                    "description": "Skip paths ignored by .gitignore files. Default: true."
+                }
+            },
+            "required": ["pattern"],
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_and_per_file_cap() {
+        let re = Regex::new("hit").unwrap();
+        let text = "a\nhit one\nb\nc\nhit two\nhit three\n";
+        let (matches, more) = search_text(&re, text, 1, 2);
+        assert!(more);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0]["line"], 2);
+        assert_eq!(matches[0]["column"], 1);
+        assert_eq!(matches[0]["before"], json!(["a"]));
+        assert_eq!(matches[0]["after"], json!(["b"]));
+        assert_eq!(matches[1]["line"], 5);
+    }
+
+    #[test]
+    fn glob_scope() {
+        assert!(glob_hit("*.rs", "src/deep/main.rs"));
+        assert!(glob_hit("src/**/*.rs", "src/deep/main.rs"));
+        assert!(!glob_hit("tests/*.rs", "src/main.rs"));
+    }
+}
@@ -214,5 +214,7 @@ pub fn register() {
                 }
             }
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
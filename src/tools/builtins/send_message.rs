@@ -194,5 +194,7 @@ pub fn register() {
             },
             "required": []
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
@@ -174,6 +174,7 @@ pub async fn session_send(_workspace: &Path, args: Value) -> anyhow::Result<Valu
         agent_id: Some(agent_id.to_string()),
         channel: channel.to_string(),
         author: sender_id.to_string(),
+        author_id: None,
         content: message.to_string(),
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -329,6 +330,7 @@ pub async fn session_spawn(_workspace: &Path, args: Value) -> anyhow::Result<Val
             agent_id: Some(agent_id.to_string()),
             channel: channel.to_string(),
             author: "agent".to_string(),
+            author_id: None,
             content: message.to_string(),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -369,6 +371,8 @@ pub fn register() {
                 }
             }
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -383,6 +387,8 @@ pub fn register() {
                 }
             }
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -422,6 +428,8 @@ pub fn register() {
             },
             "required": ["agent_id", "message"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -453,5 +461,7 @@ pub fn register() {
             },
             "required": ["agent_id"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
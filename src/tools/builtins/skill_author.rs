@@ -210,6 +210,8 @@ pub fn register() {
             },
             "required": ["name"]
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -234,6 +236,8 @@ pub fn register() {
             },
             "required": ["name", "description", "instructions"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -243,6 +247,8 @@ pub fn register() {
             "type": "object",
             "properties": {}
         }),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -258,6 +264,8 @@ pub fn register() {
             },
             "required": ["name"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 
     register_tool(ToolMeta {
@@ -281,5 +289,7 @@ pub fn register() {
             },
             "required": ["name"]
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
@@ -0,0 +1,476 @@
+//! Built-in `sql_query` tool — read-only SQL over SQLite databases in the
+//! agent workspace.
+//!
+//! Databases are opened read-only (plus `PRAGMA query_only`), and every
+//! statement must be a single query: it has to start with `SELECT`,
+//! `WITH`, `VALUES`, `EXPLAIN` or `PRAGMA`, and an authorizer rejects
+//! anything beyond reads, functions and introspection pragmas — so
+//! `ATTACH` cannot reach files outside the workspace and `VACUUM INTO`
+//! cannot write any.  Results are capped by rows and bytes, and a
+//! progress handler interrupts queries that run past their time limit.
+
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::tools::{register_tool, sandbox_path, ToolMeta};
+
+const DB_EXTENSIONS: &[&str] = &["db", "sqlite", "sqlite3"];
+const DEFAULT_MAX_ROWS: usize = 200;
+const MAX_ROWS: usize = 5000;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+const MAX_BYTES: usize = 512 * 1024;
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+const MAX_TIMEOUT_MS: u64 = 30_000;
+/// Text values longer than this are shortened.
+const MAX_TEXT_CHARS: usize = 4_000;
+/// Statement keywords a query may start with.
+const QUERY_KEYWORDS: &[&str] = &["select", "with", "values", "explain", "pragma"];
+/// Pragmas that only describe the schema or database.
+const READ_PRAGMAS: &[&str] = &[
+    "table_info",
+    "table_xinfo",
+    "table_list",
+    "index_list",
+    "index_info",
+    "index_xinfo",
+    "foreign_key_list",
+    "collation_list",
+    "function_list",
+    "user_version",
+    "application_id",
+    "schema_version",
+    "page_count",
+    "page_size",
+    "freelist_count",
+    "encoding",
+    "integrity_check",
+    "quick_check",
+];
+
+/// Run a read-only query, or describe the schema.
+///
+/// Args: `{ "path": "memory.db", "action": "query" | "schema",
+///          "sql": "SELECT …", "params": [..], "max_rows": 200,
+///          "max_bytes": 65536, "timeout_ms": 5000, "table": "…" }`
+pub async fn sql_query(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let raw = args
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("sql_query: missing `path` argument"))?;
+    let path = sandbox_path(workspace, raw)?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if !DB_EXTENSIONS.contains(&ext.as_str()) {
+        anyhow::bail!(
+            "sql_query: '{raw}' is not a database file (expected .{})",
+            DB_EXTENSIONS.join(", .")
+        );
+    }
+    if !path.is_file() {
+        anyhow::bail!("sql_query: '{raw}' does not exist");
+    }
+
+    let action = args
+        .get("action")
+        .and_then(Value::as_str)
+        .unwrap_or("query")
+        .to_string();
+    let timeout = Duration::from_millis(
+        args.get("timeout_ms")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .clamp(1, MAX_TIMEOUT_MS),
+    );
+    tokio::task::spawn_blocking(move || match action.as_str() {
+        "query" => run_query(&path, &args, timeout),
+        "schema" => describe_schema(&path, args.get("table").and_then(Value::as_str)),
+        other => anyhow::bail!("sql_query: unknown action '{other}' — expected query or schema"),
+    })
+    .await?
+}
+
+fn open_read_only(path: &Path) -> anyhow::Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| anyhow::anyhow!("sql_query: cannot open database: {e}"))?;
+    conn.execute_batch("PRAGMA query_only = ON;")?;
+    Ok(conn)
+}
+
+/// Authorizer for model-supplied SQL: reads and introspection only.
+fn authorize(ctx: AuthContext<'_>) -> Authorization {
+    match ctx.action {
+        AuthAction::Select | AuthAction::Read { .. } | AuthAction::Recursive => {
+            Authorization::Allow
+        }
+        AuthAction::Function { function_name } if function_name != "load_extension" => {
+            Authorization::Allow
+        }
+        AuthAction::Pragma { pragma_name, .. }
+            if READ_PRAGMAS.contains(&pragma_name.to_ascii_lowercase().as_str()) =>
+        {
+            Authorization::Allow
+        }
+        _ => Authorization::Deny,
+    }
+}
+
+/// First keyword of `sql`, skipping leading whitespace and comments.
+fn leading_keyword(sql: &str) -> String {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map_or("", |(_, r)| r);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map_or("", |(_, r)| r);
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn bind_value(v: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as Sql;
+    match v {
+        Value::Null => Sql::Null,
+        Value::Bool(b) => Sql::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Sql::Integer(i),
+            None => Sql::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => Sql::Text(s.clone()),
+        other => Sql::Text(other.to_string()),
+    }
+}
+
+/// JSON for one column value and its approximate size in bytes.
+fn cell(v: ValueRef<'_>) -> (Value, usize) {
+    match v {
+        ValueRef::Null => (Value::Null, 4),
+        ValueRef::Integer(i) => (json!(i), 8),
+        ValueRef::Real(f) => (json!(f), 8),
+        ValueRef::Text(t) => {
+            let s = String::from_utf8_lossy(t);
+            if s.chars().count() > MAX_TEXT_CHARS {
+                let short: String = s.chars().take(MAX_TEXT_CHARS).collect();
+                let len = short.len();
+                (json!(format!("{short}… [{} bytes total]", t.len())), len)
+            } else {
+                (json!(s), t.len())
+            }
+        }
+        ValueRef::Blob(b) => (json!({ "blob_bytes": b.len() }), 24),
+    }
+}
+
+fn run_query(path: &Path, args: &Value, timeout: Duration) -> anyhow::Result<Value> {
+    let sql = args
+        .get("sql")
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("sql_query: missing `sql` argument"))?;
+    let keyword = leading_keyword(sql);
+    if !QUERY_KEYWORDS.contains(&keyword.as_str()) {
+        anyhow::bail!(
+            "sql_query: only read-only queries are allowed (statements starting with {}); got '{keyword}'",
+            QUERY_KEYWORDS.join(", ").to_uppercase()
+        );
+    }
+    let max_rows = args
+        .get("max_rows")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_MAX_ROWS)
+        .clamp(1, MAX_ROWS);
+    let max_bytes = args
+        .get("max_bytes")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_MAX_BYTES)
+        .clamp(1024, MAX_BYTES);
+    let params: Vec<rusqlite::types::Value> = args
+        .get("params")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().map(bind_value).collect())
+        .unwrap_or_default();
+
+    let conn = open_read_only(path)?;
+    conn.busy_timeout(timeout)?;
+    conn.authorizer(Some(authorize))?;
+    let deadline = Instant::now() + timeout;
+    conn.progress_handler(1_000, Some(move || Instant::now() > deadline))?;
+
+    let started = Instant::now();
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| anyhow::anyhow!("sql_query: {e}"))?;
+    if !stmt.readonly() {
+        anyhow::bail!("sql_query: only read-only queries are allowed");
+    }
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows_iter = stmt
+        .query(rusqlite::params_from_iter(params.iter()))
+        .map_err(|e| anyhow::anyhow!("sql_query: {e}"))?;
+
+    let mut rows: Vec<Value> = Vec::new();
+    let mut bytes = 0usize;
+    let mut truncated = None;
+    loop {
+        let row = match rows_iter.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(_) if Instant::now() > deadline => {
+                anyhow::bail!("sql_query: query timed out after {}ms", timeout.as_millis());
+            }
+            Err(e) => anyhow::bail!("sql_query: {e}"),
+        };
+        if rows.len() == max_rows {
+            truncated = Some("max_rows");
+            break;
+        }
+        let mut cells = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            let (v, size) = cell(row.get_ref(i)?);
+            bytes += size;
+            cells.push(v);
+        }
+        if bytes > max_bytes && !rows.is_empty() {
+            truncated = Some("max_bytes");
+            break;
+        }
+        rows.push(Value::Array(cells));
+    }
+
+    let mut out = json!({
+        "columns": columns,
+        "rows": rows,
+        "row_count": rows.len(),
+        "elapsed_ms": started.elapsed().as_millis() as u64,
+    });
+    if let Some(reason) = truncated {
+        out["truncated"] = json!(reason);
+        out["note"] = json!("More rows are available — add LIMIT/OFFSET or narrow the query.");
+    }
+    Ok(out)
+}
+
+fn describe_schema(path: &Path, only: Option<&str>) -> anyhow::Result<Value> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn.prepare(
+        "SELECT type, name, tbl_name, sql FROM sqlite_schema
+         WHERE name NOT LIKE 'sqlite_%' AND (?1 IS NULL OR tbl_name = ?1)
+         ORDER BY type = 'index', name",
+    )?;
+    let entries: Vec<(String, String, String, Option<String>)> = stmt
+        .query_map([only], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut tables = Vec::new();
+    let mut indexes = Vec::new();
+    for (kind, name, table, sql) in entries {
+        match kind.as_str() {
+            "table" | "view" => {
+                let mut cols = conn.prepare(
+                    "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)",
+                )?;
+                let columns: Vec<Value> = cols
+                    .query_map([&name], |r| {
+                        Ok(json!({
+                            "name": r.get::<_, String>(0)?,
+                            "type": r.get::<_, String>(1)?,
+                            "not_null": r.get::<_, i64>(2)? != 0,
+                            "default": r.get::<_, Option<String>>(3)?,
+                            "primary_key": r.get::<_, i64>(4)? != 0,
+                        }))
+                    })?
+                    .collect::<Result<_, _>>()?;
+                let mut entry = json!({
+                    "name": name,
+                    "type": kind,
+                    "columns": columns,
+                    "sql": sql,
+                });
+                if kind == "table" {
+                    // Row counts are cheap enough for workspace databases.
+                    let count: i64 = conn
+                        .query_row(
+                            &format!("SELECT count(*) FROM \"{}\"", name.replace('"', "\"\"")),
+                            [],
+                            |r| r.get(0),
+                        )
+                        .unwrap_or(-1);
+                    entry["rows"] = json!(count);
+                }
+                tables.push(entry);
+            }
+            "index" => indexes.push(json!({ "name": name, "table": table, "sql": sql })),
+            _ => {}
+        }
+    }
+    if let Some(t) = only {
+        if tables.is_empty() {
+            anyhow::bail!("sql_query: no table or view named '{t}'");
+        }
+    }
+    Ok(json!({ "tables": tables, "indexes": indexes }))
+}
+
+/// Register the `sql_query` tool.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "sql_query".into(),
+        description: "Run a read-only SQL query against a SQLite database file in the workspace (.db/.sqlite), or describe its schema. Returns columns and rows as JSON, capped by rows/bytes with a timeout. Use instead of the sqlite3 CLI.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative path of the database file (e.g. 'memory.db', 'data/app.sqlite')."
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["query", "schema"],
+                    "description": "query (default): run `sql`. schema: list tables, views, columns and indexes."
+                },
+                "sql": {
+                    "type": "string",
+                    "description": "A single read-only statement (SELECT, WITH, VALUES, EXPLAIN or an introspection PRAGMA). Use ?1, ?2… placeholders with `params`."
+                },
+                "params": {
+                    "type": "array",
+                    "description": "Values bound to the statement's placeholders."
+                },
+                "table": {
+                    "type": "string",
+                    "description": "schema: describe only this table or view."
+                },
+                "max_rows": {
+                    "type": "integer",
+                    "description": "Maximum rows returned (max 5000). Default: 200."
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": "Approximate cap on returned data in bytes (max 524288). Default: 65536."
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Abort the query after this many milliseconds (max 30000). Default: 5000."
+                }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: Some(60),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let conn = Connection::open(tmp.path().join("app.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, title TEXT NOT NULL, body BLOB);
+             CREATE INDEX notes_title ON notes(title);
+             INSERT INTO notes (title, body) VALUES ('a', x'0102'), ('b', NULL), ('c', NULL);",
+        )
+        .unwrap();
+        tmp
+    }
+
+    #[tokio::test]
+    async fn query_returns_columns_rows_and_caps() {
+        let tmp = fixture();
+        let out = sql_query(
+            tmp.path(),
+            json!({ "path": "app.db", "sql": "SELECT id, title, body FROM notes WHERE id >= ?1 ORDER BY id", "params": [1], "max_rows": 2 }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(out["columns"], json!(["id", "title", "body"]));
+        assert_eq!(out["rows"][0], json!([1, "a", { "blob_bytes": 2 }]));
+        assert_eq!(out["row_count"], 2);
+        assert_eq!(out["truncated"], "max_rows");
+    }
+
+    #[tokio::test]
+    async fn rejects_writes_attach_and_vacuum() {
+        let tmp = fixture();
+        let outside = tmp.path().join("copy.db");
+        for sql in [
+            "INSERT INTO notes (title) VALUES ('x')".to_string(),
+            "/* sneaky */ DELETE FROM notes".to_string(),
+            format!("ATTACH '{}' AS other", outside.display()),
+            format!("VACUUM INTO '{}'", outside.display()),
+            "SELECT 1; DROP TABLE notes".to_string(),
+            "PRAGMA journal_mode = DELETE".to_string(),
+        ] {
+            let res = sql_query(tmp.path(), json!({ "path": "app.db", "sql": sql })).await;
+            assert!(res.is_err(), "{sql} should be rejected");
+        }
+        assert!(!outside.exists());
+        let ok = sql_query(
+            tmp.path(),
+            json!({ "path": "app.db", "sql": "PRAGMA table_info(notes)" }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(ok["row_count"], 3);
+    }
+
+    #[tokio::test]
+    async fn long_queries_time_out() {
+        let tmp = fixture();
+        let err = sql_query(
+            tmp.path(),
+            json!({
+                "path": "app.db",
+                "sql": "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n",
+                "timeout_ms": 200,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn schema_lists_tables_and_indexes() {
+        let tmp = fixture();
+        let out = sql_query(tmp.path(), json!({ "path": "app.db", "action": "schema" }))
+            .await
+            .unwrap();
+        assert_eq!(out["tables"][0]["name"], "notes");
+        assert_eq!(out["tables"][0]["rows"], 3);
+        assert_eq!(out["tables"][0]["columns"][1]["name"], "title");
+        assert_eq!(out["tables"][0]["columns"][1]["not_null"], true);
+        assert_eq!(out["indexes"][0]["name"], "notes_title");
+
+        assert!(sql_query(tmp.path(), json!({ "path": "notes.txt" }))
+            .await
+            .is_err());
+        assert!(sql_query(
+            tmp.path(),
+            json!({ "path": "../app.db", "sql": "SELECT 1" })
+        )
+        .await
+        .is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Built-in `view_image` tool — shows the model an image from the agent
+//! workspace.
+//!
+//! The image is downscaled so its longer side is at most `max_dimension`
+//! pixels (1024 by default), re-encoded as JPEG (PNG when it has
+//! transparency) and attached to the next model call via
+//! [`attach_image`].  Only models that accept image inputs can use it:
+//! see [`ModelConfig::supports_vision`](crate::config::ModelConfig::supports_vision).
+
+use base64::Engine as _;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use serde_json::{json, Value};
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::tools::{attach_image, register_tool, sandbox_path, ToolMeta};
+
+/// Default longest side, in pixels, of the image sent to the model.
+pub const DEFAULT_MAX_DIMENSION: u32 = 1024;
+/// Bounds accepted for `max_dimension`.
+const MIN_DIMENSION: u32 = 64;
+const MAX_DIMENSION: u32 = 2048;
+/// Largest image file `view_image` will load.
+const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// JPEG quality used when re-encoding.
+const JPEG_QUALITY: u8 = 85;
+
+/// An image made ready for a model request.
+#[derive(Debug)]
+pub struct PreparedImage {
+    /// `data:image/…;base64,…` URI.
+    pub data_uri: String,
+    /// Format of the source file (e.g. "png").
+    pub source_format: String,
+    /// Format it was re-encoded as: "jpeg" or "png".
+    pub format: &'static str,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Size of the encoded image, before base64.
+    pub bytes: usize,
+}
+
+/// Decode `bytes`, shrink the image to fit `max_dimension` and encode it
+/// as a data URI.
+pub fn prepare(bytes: &[u8], max_dimension: u32) -> anyhow::Result<PreparedImage> {
+    let source = image::guess_format(bytes)
+        .map_err(|_| anyhow::anyhow!("not a supported image (PNG, JPEG, GIF or WebP)"))?;
+    let img = image::load_from_memory_with_format(bytes, source)
+        .map_err(|e| anyhow::anyhow!("cannot decode image: {e}"))?;
+    let (original_width, original_height) = img.dimensions();
+    let img = if original_width > max_dimension || original_height > max_dimension {
+        img.thumbnail(max_dimension, max_dimension)
+    } else {
+        img
+    };
+    let (width, height) = img.dimensions();
+
+    let mut out = Cursor::new(Vec::new());
+    let (format, mime) = if img.color().has_alpha() {
+        img.write_to(&mut out, ImageFormat::Png)?;
+        ("png", "image/png")
+    } else {
+        let rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY);
+        rgb.write_with_encoder(encoder)?;
+        ("jpeg", "image/jpeg")
+    };
+    let encoded = out.into_inner();
+
+    Ok(PreparedImage {
+        data_uri: format!(
+            "data:{mime};base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&encoded)
+        ),
+        source_format: source
+            .extensions_str()
+            .first()
+            .copied()
+            .unwrap_or("image")
+            .to_string(),
+        format,
+        original_width,
+        original_height,
+        width,
+        height,
+        bytes: encoded.len(),
+    })
+}
+
+/// Load a workspace image and attach it to the next model call.
+///
+/// Args: `{ "path": "…", "max_dimension?": N }`
+/// Returns: `{ "path", "format", "sent_as", "width", "height", "original_width", "original_height", "bytes", "attached": true }`
+pub async fn view_image(workspace: &Path, args: Value) -> anyhow::Result<Value> {
+    let raw = args
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("view_image: missing `path` argument"))?;
+    let max_dimension = args
+        .get("max_dimension")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_MAX_DIMENSION, |d| {
+            (d as u32).clamp(MIN_DIMENSION, MAX_DIMENSION)
+        });
+
+    // Unknown means no: a text-only model would be sent an image.
+    let cfg = Config::current()
+        .await
+        .and_then(|c| c.ok_or_else(|| anyhow::anyhow!("no config file")))
+        .map_err(|e| {
+            anyhow::anyhow!("view_image: cannot check the model for image support: {e}")
+        })?;
+    let model = cfg
+        .agent_for_workspace(workspace)
+        .and_then(|agent| cfg.agent_model(&agent.id))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "view_image: this agent has no configured model, so image support is unknown"
+            )
+        })?;
+    if !model.supports_vision() {
+        anyhow::bail!(
+            "view_image: model `{}` does not accept images — set `vision: true` on its \
+             `models:` entry if it does",
+            model.model.as_deref().unwrap_or(&model.id)
+        );
+    }
+
+    let path = sandbox_path(workspace, raw)?;
+    let size = tokio::fs::metadata(&path)
+        .await
+        .map_err(|e| anyhow::anyhow!("view_image: cannot read {}: {e}", path.display()))?
+        .len();
+    if size > MAX_IMAGE_BYTES {
+        anyhow::bail!(
+            "view_image: {} is {size} bytes; images over {MAX_IMAGE_BYTES} bytes are not loaded",
+            path.display()
+        );
+    }
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| anyhow::anyhow!("view_image: cannot read {}: {e}", path.display()))?;
+    let prepared = tokio::task::spawn_blocking(move || prepare(&bytes, max_dimension))
+        .await?
+        .map_err(|e| anyhow::anyhow!("view_image: {raw}: {e}"))?;
+
+    let result = json!({
+        "path": raw,
+        "format": prepared.source_format,
+        "sent_as": prepared.format,
+        "width": prepared.width,
+        "height": prepared.height,
+        "original_width": prepared.original_width,
+        "original_height": prepared.original_height,
+        "bytes": prepared.bytes,
+        "attached": true,
+    });
+    if !attach_image(prepared.data_uri) {
+        anyhow::bail!("view_image: images can only be shown to the model during an agent turn");
+    }
+    Ok(result)
+}
+
+/// Register the `view_image` tool metadata in the global registry.
+pub fn register() {
+    register_tool(ToolMeta {
+        name: "view_image".into(),
+        description: "Look at an image file (PNG, JPEG, GIF or WebP) in the agent workspace — screenshots, photos, diagrams, charts. The image is downscaled and shown to you right after this call. Only works with models that accept images.".into(),
+        args_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Workspace-relative path to the image."
+                },
+                "max_dimension": {
+                    "type": "integer",
+                    "minimum": MIN_DIMENSION,
+                    "maximum": MAX_DIMENSION,
+                    "description": "Longest side in pixels after downscaling (default 1024). Raise it to read small text."
+                }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        }),
+        exclusive: false,
+        timeout_secs: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelConfig;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn encode(img: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        img.write_to(&mut out, format).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn downscales_to_fit_and_keeps_aspect_ratio() {
+        let photo = DynamicImage::ImageRgb8(RgbImage::from_pixel(3000, 1500, Rgb([10, 20, 30])));
+        let prepared = prepare(&encode(photo, ImageFormat::Png), 1024).unwrap();
+        assert_eq!(
+            (prepared.original_width, prepared.original_height),
+            (3000, 1500)
+        );
+        assert_eq!((prepared.width, prepared.height), (1024, 512));
+        assert_eq!(prepared.source_format, "png");
+        assert_eq!(prepared.format, "jpeg");
+        assert!(prepared.data_uri.starts_with("data:image/jpeg;base64,"));
+
+        let small = DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 30, Rgb([0, 0, 0])));
+        let prepared = prepare(&encode(small, ImageFormat::Jpeg), 1024).unwrap();
+        assert_eq!((prepared.width, prepared.height), (40, 30));
+    }
+
+    #[test]
+    fn transparent_images_stay_png() {
+        let icon = DynamicImage::ImageRgba8(RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 0])));
+        let prepared = prepare(&encode(icon, ImageFormat::Png), 32).unwrap();
+        assert_eq!(prepared.format, "png");
+        assert_eq!((prepared.width, prepared.height), (32, 32));
+        assert!(prepared.data_uri.starts_with("data:image/png;base64,"));
+
+        assert!(prepare(b"plain text", 1024).is_err());
+    }
+
+    #[test]
+    fn vision_support_is_configurable_or_guessed() {
+        let model = |name: &str, vision: Option<bool>| ModelConfig {
+            id: "m".into(),
+            provider: "openai".into(),
+            model: Some(name.into()),
+            api_key: None,
+            endpoint: None,
+            api_version: None,
+            embedding_deployment: None,
+            embedding_model: None,
+            headers: None,
+            vision,
+        };
+        for name in [
+            "gpt-4o",
+            "gpt-4.1-mini",
+            "gpt-5",
+            "o3",
+            "claude-sonnet-4",
+            "anthropic/claude-3-5-sonnet",
+            "gemini-2.5-pro",
+            "llava:13b",
+            "qwen2.5-vl-7b",
+        ] {
+            assert!(model(name, None).supports_vision(), "{name}");
+        }
+        for name in [
+            "gpt-3.5-turbo",
+            "o3-mini",
+            "deepseek-chat",
+            "llama3",
+            "claude-2.1",
+        ] {
+            assert!(!model(name, None).supports_vision(), "{name}");
+        }
+        assert!(model("llama3", Some(true)).supports_vision());
+        assert!(!model("gpt-4o", Some(false)).supports_vision());
+    }
+}
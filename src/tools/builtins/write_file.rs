@@ -23,6 +23,7 @@ pub async fn write_file(workspace: &Path, args: Value) -> anyhow::Result<Value>
     let append = args.get("append").and_then(Value::as_bool).unwrap_or(false);
 
     let path = sandbox_path(workspace, raw)?;
+    crate::journal::record(workspace, &path, "write_file").await;
 
     // Ensure parent directories exist.
     if let Some(parent) = path.parent() {
@@ -79,5 +80,7 @@ pub fn register() {
             "required": ["path", "content"],
             "additionalProperties": false
         }),
+        exclusive: true,
+        timeout_secs: None,
     });
 }
@@ -11,6 +11,9 @@
 
 pub mod builtins;
 pub mod parsing;
+pub mod policy;
+pub mod retrieval;
+pub mod schema;
 
 use async_trait::async_trait;
 use serde_json::Value;
@@ -39,6 +42,14 @@ pub struct ToolMeta {
     pub description: String,
     /// JSON Schema object describing the expected `args` value.
     pub args_schema: Value,
+    /// The tool has side effects, so it must not run concurrently with
+    /// other calls from the same batch.
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Default limit for one call, in seconds; `None` leaves it to the
+    /// tool.  Agents override it per tool with `tool_timeouts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
 }
 
 /// Async handler function that tools register for dispatch.
@@ -54,6 +65,15 @@ pub struct SkillEntry {
     pub instructions: String,
     pub description: String,
     pub operator_managed: Option<bool>,
+    /// Names of the callable tools the skill declares.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Free-text `compatibility` from the manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<String>,
+    /// Unmet requirements; the skill is unavailable when non-empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unmet: Vec<String>,
 }
 
 /// Combined registry entry: metadata + optional handler + optional skill data.
@@ -63,9 +83,42 @@ struct ToolEntry {
     /// Set for entries loaded from SKILL.md manifests (instruction-based context).
     skill: Option<SkillEntry>,
     /// When `true` the tool is not injected into the prompt upfront —
-    /// it is auto-injected when the user's message contains relevant
-    /// keywords (see `auto_pluck_deferred`).
+    /// it is auto-injected on turns it is relevant to (see
+    /// [`retrieval::select`]).
     deferred: bool,
+    /// Skill that declared this tool in its `SKILL.md`; such tools are
+    /// dropped and re-registered on every [`sync_skills`].
+    declared_by: Option<String>,
+}
+
+tokio::task_local! {
+    /// Channel of the message whose turn made the running tool call.
+    pub static CALL_CHANNEL: String;
+    /// Images (data URIs) the running tool call shows the model.
+    static CALL_IMAGES: Arc<Mutex<Vec<String>>>;
+}
+
+/// Channel of the message that led to the current tool call, when
+/// running inside the tool loop.
+pub fn call_channel() -> Option<String> {
+    CALL_CHANNEL.try_with(|c| c.clone()).ok()
+}
+
+/// Queue an image (a `data:` URI) for the model to see alongside the
+/// current tool call's result.  Returns `false` outside the tool loop,
+/// where there is no next model call to attach it to.
+pub fn attach_image(data_uri: String) -> bool {
+    CALL_IMAGES
+        .try_with(|images| images.lock().expect("call images poisoned").push(data_uri))
+        .is_ok()
+}
+
+/// Run `call`, collecting the images it queues with [`attach_image`].
+pub async fn collect_images<F: Future>(call: F) -> (F::Output, Vec<String>) {
+    let images = Arc::new(Mutex::new(Vec::new()));
+    let output = CALL_IMAGES.scope(images.clone(), call).await;
+    let images = std::mem::take(&mut *images.lock().expect("call images poisoned"));
+    (output, images)
 }
 
 /// Global tool registry.
@@ -84,6 +137,7 @@ pub fn register_tool(meta: ToolMeta) {
         handler: None,
         skill: None,
         deferred: false,
+        declared_by: None,
     });
 }
 
@@ -99,6 +153,7 @@ pub fn register_tool_deferred(meta: ToolMeta) {
         handler: None,
         skill: None,
         deferred: true,
+        declared_by: None,
     });
 }
 
@@ -112,6 +167,47 @@ pub fn register_handler(name: &str, handler: SkillHandler) {
     }
 }
 
+/// Remove a tool (metadata + handler) from the registry.
+pub fn unregister_tool(name: &str) {
+    let mut reg = REGISTRY.lock().expect("tool registry poisoned");
+    reg.retain(|e| e.meta.name != name);
+}
+
+/// Whether `name` is registered as exclusive (see [`ToolMeta::exclusive`]).
+/// Unknown tools are not exclusive; they fail without running.
+pub fn is_exclusive(name: &str) -> bool {
+    let reg = REGISTRY.lock().expect("tool registry poisoned");
+    reg.iter()
+        .find(|e| e.meta.name == name)
+        .is_some_and(|e| e.meta.exclusive)
+}
+
+/// Time limit for one call of `name`: the agent's `tool_timeouts` entry,
+/// else the tool's [`ToolMeta::timeout_secs`].  `None` means unlimited.
+pub fn timeout_for(
+    name: &str,
+    agent: Option<&crate::config::AgentConfig>,
+) -> Option<std::time::Duration> {
+    let secs = agent
+        .and_then(|a| a.tool_timeouts.get(name).copied())
+        .or_else(|| {
+            let reg = REGISTRY.lock().expect("tool registry poisoned");
+            reg.iter()
+                .find(|e| e.meta.name == name)
+                .and_then(|e| e.meta.timeout_secs)
+        })?;
+    Some(std::time::Duration::from_secs(secs.max(1)))
+}
+
+/// Argument schema of tool `name`, if it is registered with one.
+pub fn args_schema(name: &str) -> Option<Value> {
+    let reg = REGISTRY.lock().expect("tool registry poisoned");
+    reg.iter()
+        .find(|e| e.meta.name == name)
+        .map(|e| e.meta.args_schema.clone())
+        .filter(|s| !s.is_null())
+}
+
 /// Return metadata for every registered tool (including deferred).
 pub fn list_tools() -> Vec<ToolMeta> {
     REGISTRY
@@ -214,7 +310,7 @@ pub fn search_tools_registry(query: &str, limit: usize) -> Vec<ToolMeta> {
         .collect();
 
     // Sort by score descending.
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.sort_by_key(|b| std::cmp::Reverse(b.0));
     scored
         .into_iter()
         .take(limit)
@@ -256,6 +352,12 @@ fn synonyms(term: &str) -> Vec<String> {
             vec!["cron".into(), "job".into(), "schedule".into()]
         }
         "cron" => vec!["schedule".into(), "job".into(), "timer".into()],
+        "remind" | "reminder" | "reminders" | "alarm" => {
+            vec!["remind".into(), "cron".into(), "schedule".into()]
+        }
+        "image" | "images" | "picture" | "photo" | "screenshot" => {
+            vec!["view_image".into(), "image".into()]
+        }
         "remember" | "memorize" | "store" | "knowledge" => {
             vec!["memory".into(), "save".into(), "recall".into()]
         }
@@ -282,6 +384,15 @@ fn synonyms(term: &str) -> Vec<String> {
                 "activate_skill".into(),
             ]
         }
+        "search" | "grep" | "find" | "rg" => {
+            vec!["search_files".into(), "list_file".into()]
+        }
+        "git" | "repo" | "repository" | "commit" | "branch" | "diff" | "stash" => {
+            vec!["git".into()]
+        }
+        "sql" | "sqlite" | "sqlite3" | "database" | "db" => {
+            vec!["sql_query".into()]
+        }
         "run" | "execute" | "shell" | "command" | "cmd" | "bash" => {
             vec!["exec".into(), "shell".into(), "exec_shell".into()]
         }
@@ -295,7 +406,7 @@ fn synonyms(term: &str) -> Vec<String> {
             ]
         }
         "browse" | "web" | "url" | "http" | "page" | "website" | "scrape" | "crawl" => {
-            vec!["exec_shell".into()]
+            vec!["http_fetch".into(), "exec_shell".into()]
         }
         "send" | "message" | "notify" | "notification" | "discord" | "channel" => {
             vec!["send_message".into(), "message".into()]
@@ -348,7 +459,41 @@ const AUTO_PLUCK_RULES: &[(&[&str], &[&str])] = &[
             "delete_cron_job",
             "run_cron_job",
             "cron_job_history",
+            "remind",
+        ],
+    ),
+    (
+        &[
+            "remind",
+            "reminder",
+            "reminders",
+            "alarm",
+            "nudge",
+            "don't forget",
+            "tomorrow",
+            "tonight",
+        ],
+        &["remind"],
+    ),
+    (
+        &[
+            "image",
+            "images",
+            "picture",
+            "pictures",
+            "photo",
+            "photos",
+            "screenshot",
+            "screenshots",
+            "diagram",
+            "chart",
+            "png",
+            "jpg",
+            "jpeg",
+            "gif",
+            "webp",
         ],
+        &["view_image"],
     ),
     (
         &["agent", "agents", "bot", "bots"],
@@ -388,7 +533,37 @@ const AUTO_PLUCK_RULES: &[(&[&str], &[&str])] = &[
         ],
         &["delegate", "list_agents"],
     ),
+    (
+        &[
+            "http", "https", "url", "fetch", "web", "website", "webpage", "page", "article",
+            "download", "api", "link",
+        ],
+        &["http_fetch"],
+    ),
     (&["update", "upgrade", "version"], &["self_update"]),
+    (
+        &[
+            "git",
+            "repo",
+            "repository",
+            "commit",
+            "commits",
+            "branch",
+            "branches",
+            "diff",
+            "stash",
+            "push",
+            "checkout",
+            "merge",
+        ],
+        &["git"],
+    ),
+    (
+        &[
+            "sql", "sqlite", "sqlite3", "database", "db", "query", "table", "tables", "schema",
+        ],
+        &["sql_query"],
+    ),
     (
         &[
             "message",
@@ -407,7 +582,8 @@ const AUTO_PLUCK_RULES: &[(&[&str], &[&str])] = &[
 ];
 
 /// Scan `user_message` for domain keywords and return deferred tools that
-/// should be auto-injected into function definitions.
+/// should be auto-injected into function definitions.  Used by
+/// [`retrieval::select`] when embeddings are unavailable.
 pub fn auto_pluck_deferred(user_message: &str) -> Vec<ToolMeta> {
     let lower = user_message.to_lowercase();
     let mut plucked_names: HashSet<String> = HashSet::new();
@@ -427,13 +603,24 @@ pub fn auto_pluck_deferred(user_message: &str) -> Vec<ToolMeta> {
         }
     }
 
-    if plucked_names.is_empty() {
+    // Deferred MCP servers: naming the server plucks all of its tools.
+    let mcp_prefixes: Vec<String> = crate::mcp::client::deferred_server_names()
+        .into_iter()
+        .filter(|server| lower.contains(&server.to_lowercase()))
+        .map(|server| format!("mcp__{server}__"))
+        .collect();
+
+    if plucked_names.is_empty() && mcp_prefixes.is_empty() {
         return Vec::new();
     }
 
     let reg = REGISTRY.lock().expect("tool registry poisoned");
     reg.iter()
-        .filter(|e| e.deferred && plucked_names.contains(&e.meta.name))
+        .filter(|e| {
+            e.deferred
+                && (plucked_names.contains(&e.meta.name)
+                    || mcp_prefixes.iter().any(|p| e.meta.name.starts_with(p)))
+        })
         .map(|e| e.meta.clone())
         .collect()
 }
@@ -468,14 +655,17 @@ pub fn get_skill_agent_id() -> Option<String> {
 pub fn sync_skills(registry: &crate::skills::SkillRegistry) {
     let mut reg = REGISTRY.lock().expect("tool registry poisoned");
 
-    // Remove previous skill entries (keep builtins).
-    reg.retain(|e| e.skill.is_none());
+    // Remove previous skill entries and skill-declared tools (keep builtins).
+    reg.retain(|e| e.skill.is_none() && e.declared_by.is_none());
 
     for (id, skill) in &registry.skills {
         let skill_data = SkillEntry {
             instructions: skill.instructions.clone(),
             description: skill.meta.description.clone(),
             operator_managed: skill.meta.operator_managed,
+            tools: skill.meta.tools.iter().map(|t| t.name.clone()).collect(),
+            compatibility: skill.meta.compatibility.clone(),
+            unmet: skill.unmet.clone(),
         };
 
         // If a builtin tool already exists with this name, enrich it
@@ -491,13 +681,54 @@ pub fn sync_skills(registry: &crate::skills::SkillRegistry) {
                 name: id.clone(),
                 description: skill.meta.description.clone(),
                 args_schema: serde_json::json!(null),
+                exclusive: false,
+                timeout_secs: None,
             },
             handler: None,
             skill: Some(skill_data),
             deferred: false,
+            declared_by: None,
         });
     }
 
+    // Script-backed tools declared in SKILL.md front-matter.  Skills with
+    // unmet requirements keep their listing but get no callable tools.
+    for (id, skill) in registry.skills.iter().filter(|(_, s)| s.unmet.is_empty()) {
+        for tool in &skill.meta.tools {
+            let meta = ToolMeta {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                args_schema: tool.args_schema.clone(),
+                exclusive: tool.exclusive,
+                timeout_secs: tool.timeout_secs,
+            };
+            let handler = skill_tool_handler(&skill.path, &tool.entrypoint, &tool.name);
+            match reg.iter_mut().find(|e| e.meta.name == tool.name) {
+                // A tool named after its own skill takes over the
+                // instruction-only entry.
+                Some(existing) if tool.name == *id && existing.handler.is_none() => {
+                    existing.meta = meta;
+                    existing.handler = Some(handler);
+                    existing.declared_by = Some(id.clone());
+                }
+                Some(_) => {
+                    tracing::warn!(
+                        skill = %id,
+                        tool = %tool.name,
+                        "skill tool name is already taken — skipped"
+                    );
+                }
+                None => reg.push(ToolEntry {
+                    meta,
+                    handler: Some(handler),
+                    skill: None,
+                    deferred: false,
+                    declared_by: Some(id.clone()),
+                }),
+            }
+        }
+    }
+
     info!(
         total = reg.len(),
         skills = reg.iter().filter(|e| e.skill.is_some()).count(),
@@ -505,6 +736,43 @@ pub fn sync_skills(registry: &crate::skills::SkillRegistry) {
     );
 }
 
+/// Whether an agent limited to `enabled_ids` (`None` = every skill) may
+/// see and call tool `name`.  A tool declared by a skill belongs to the
+/// agents that enable that skill; other tools are unaffected.
+pub fn skill_tool_enabled(name: &str, enabled_ids: Option<&[String]>) -> bool {
+    let Some(ids) = enabled_ids else {
+        return true;
+    };
+    let reg = REGISTRY.lock().expect("tool registry poisoned");
+    match reg
+        .iter()
+        .find(|e| e.meta.name == name)
+        .and_then(|e| e.declared_by.as_ref())
+    {
+        Some(skill) => ids.iter().any(|id| id == skill),
+        None => true,
+    }
+}
+
+/// Handler running a skill tool's entrypoint script via
+/// [`builtins::exec_shell::run_script`].  The entrypoint is resolved on
+/// every call so a script swapped for an escaping symlink is refused.
+fn skill_tool_handler(skill_dir: &Path, entrypoint: &str, tool: &str) -> SkillHandler {
+    let skill_dir = skill_dir.to_path_buf();
+    let entrypoint = entrypoint.to_string();
+    let tool = tool.to_string();
+    Arc::new(move |args, ws| {
+        let skill_dir = skill_dir.clone();
+        let entrypoint = entrypoint.clone();
+        let tool = tool.clone();
+        Box::pin(async move {
+            let script = crate::skills::resolve_entrypoint(&skill_dir, &entrypoint)
+                .map_err(|e| anyhow::anyhow!("{tool}: {e}"))?;
+            builtins::exec_shell::run_script(&ws, &tool, &script, &args).await
+        })
+    })
+}
+
 /// Reload skills from disk and re-sync to the unified registry.
 ///
 /// Used after skill creation/deletion to pick up changes without restart.
@@ -524,6 +792,7 @@ pub fn prompt_instructions(enabled_ids: Option<&[String]>) -> String {
     let reg = REGISTRY.lock().expect("tool registry poisoned");
     let mut parts: Vec<String> = Vec::new();
     let mut seen = HashSet::new();
+    let mut any_unmet = false;
     for entry in reg.iter() {
         let skill = match &entry.skill {
             Some(s) => s,
@@ -537,20 +806,15 @@ pub fn prompt_instructions(enabled_ids: Option<&[String]>) -> String {
                 continue;
             }
         }
-        parts.push(format!(
-            "  <skill>\n    <name>{}</name>\n    <description>{}</description>\n  </skill>",
-            entry.meta.name, skill.description,
+        any_unmet |= !skill.unmet.is_empty();
+        parts.push(crate::skills::prompt_entry(
+            &entry.meta.name,
+            &skill.description,
+            &skill.tools,
+            &skill.unmet,
         ));
     }
-    if parts.is_empty() {
-        return String::new();
-    }
-    format!(
-        "<available_skills>\n{}\n</available_skills>\n\n\
-         To use a skill, call `activate_skill` with its name. \
-         This loads the full instructions into context.",
-        parts.join("\n")
-    )
+    crate::skills::wrap_prompt_entries(&parts, any_unmet)
 }
 
 /// Return full skill instructions for a specific skill (on-demand activation).
@@ -560,8 +824,16 @@ pub fn get_skill_instructions(name: &str) -> Option<String> {
         .find(|e| e.meta.name == name && e.skill.is_some())
         .map(|e| {
             let skill = e.skill.as_ref().unwrap();
+            let unavailable = if skill.unmet.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "<unavailable>Requirements not met: {}</unavailable>\n",
+                    skill.unmet.join("; ")
+                )
+            };
             format!(
-                "<skill_activated>\n<name>{}</name>\n<instructions>\n{}\n</instructions>\n</skill_activated>",
+                "<skill_activated>\n<name>{}</name>\n{unavailable}<instructions>\n{}\n</instructions>\n</skill_activated>",
                 e.meta.name,
                 skill.instructions.trim()
             )
@@ -582,6 +854,10 @@ pub fn list_skill_entries() -> Vec<serde_json::Value> {
                 "id": entry.meta.name,
                 "description": skill.description,
                 "operator_managed": skill.operator_managed,
+                "tools": skill.tools,
+                "compatibility": skill.compatibility,
+                "available": skill.unmet.is_empty(),
+                "unmet": skill.unmet,
             }));
         }
     }
@@ -719,6 +995,7 @@ pub async fn call_skill(name: &str, args: Value, workspace: &Path) -> anyhow::Re
         "read_file" => builtins::read_file::read_file(workspace, args).await,
         "write_file" => builtins::write_file::write_file(workspace, args).await,
         "exec_shell" => builtins::exec_shell::exec_shell(workspace, args).await,
+        "http_fetch" => builtins::http_fetch::http_fetch(workspace, args).await,
         "save_memory" => builtins::memory::save_memory(workspace, args).await,
         "recall_memory" => builtins::memory::recall_memory(workspace, args).await,
         "forget_memory" => builtins::memory::forget_memory(workspace, args).await,
@@ -729,6 +1006,8 @@ pub async fn call_skill(name: &str, args: Value, workspace: &Path) -> anyhow::Re
         "activate_skill" => builtins::skill_author::activate_skill(workspace, args).await,
         "edit_file" => builtins::edit_file::edit_file(workspace, args).await,
         "list_files" => builtins::list_files::list_files(workspace, args).await,
+        "search_files" => builtins::search_files::search_files(workspace, args).await,
+        "read_artifact" => builtins::artifacts::read_artifact(workspace, args).await,
         "list_agents" => builtins::agent::list_agents(workspace, args).await,
         "get_agent" => builtins::agent::get_agent(workspace, args).await,
         "create_agent" => builtins::agent::create_agent(workspace, args).await,
@@ -745,6 +1024,10 @@ pub async fn call_skill(name: &str, args: Value, workspace: &Path) -> anyhow::Re
         "send_message" => builtins::send_message::send_message(workspace, args).await,
         "self_update" => builtins::self_update::self_update(workspace, args).await,
         "apply_patch" => builtins::apply_patch::apply_patch(workspace, args).await,
+        "git" => builtins::git::git(workspace, args).await,
+        "sql_query" => builtins::sql_query::sql_query(workspace, args).await,
+        "remind" => builtins::remind::remind(workspace, args).await,
+        "view_image" => builtins::view_image::view_image(workspace, args).await,
         other => {
             // If the name matches a registered skill that is instruction-only
             // (no handler), tell the agent clearly that this is not a callable
@@ -777,6 +1060,24 @@ pub async fn call_skill(name: &str, args: Value, workspace: &Path) -> anyhow::Re
                     let skill_args = serde_json::json!({ "name": resolved });
                     return builtins::skill_author::activate_skill(workspace, skill_args).await;
                 }
+                let declared: Vec<String> = {
+                    let reg = REGISTRY.lock().expect("tool registry poisoned");
+                    reg.iter()
+                        .find(|e| e.meta.name == resolved.as_str())
+                        .and_then(|e| e.skill.as_ref())
+                        .map(|s| s.tools.clone())
+                        .unwrap_or_default()
+                };
+                if !declared.is_empty() {
+                    return Ok(serde_json::json!({
+                        "error": format!("'{}' is a SKILL, not a callable tool.", resolved),
+                        "instructions": format!(
+                            "Call one of the tools it provides instead: {}. activate_skill({{ \"name\": \"{}\" }}) loads its instructions.",
+                            declared.join(", "),
+                            resolved
+                        ),
+                    }));
+                }
                 // Instruction-only skill: return a clear message
                 return Ok(serde_json::json!({
                     "error": format!("'{}' is a SKILL (instructions), not a callable tool.", resolved),
@@ -796,7 +1097,10 @@ pub fn builtin_skill_names() -> &'static [&'static str] {
         "write_file",
         "edit_file",
         "list_files",
+        "search_files",
+        "read_artifact",
         "exec_shell",
+        "http_fetch",
         "save_memory",
         "recall_memory",
         "forget_memory",
@@ -821,6 +1125,10 @@ pub fn builtin_skill_names() -> &'static [&'static str] {
         "send_message",
         "self_update",
         "apply_patch",
+        "git",
+        "sql_query",
+        "remind",
+        "view_image",
     ]
 }
 
@@ -833,11 +1141,18 @@ pub fn init() {
     builtins::edit_file::register();
     builtins::apply_patch::register();
     builtins::list_files::register();
+    builtins::search_files::register();
+    builtins::artifacts::register();
     builtins::exec_shell::register();
+    builtins::git::register();
+    builtins::sql_query::register();
+    builtins::http_fetch::register();
     builtins::memory::register();
     builtins::skill_author::register();
     builtins::agent::register();
     builtins::cron::register();
+    builtins::remind::register();
+    builtins::view_image::register();
     builtins::delegate::register();
     builtins::session::register();
     builtins::send_message::register();
@@ -874,12 +1189,50 @@ pub fn init() {
             Box::pin(async move { builtins::list_files::list_files(&ws, args).await })
         }),
     );
+    register_handler(
+        "search_files",
+        Arc::new(|args, ws| {
+            Box::pin(async move { builtins::search_files::search_files(&ws, args).await })
+        }),
+    );
+    register_handler(
+        "read_artifact",
+        Arc::new(|args, ws| {
+            Box::pin(async move { builtins::artifacts::read_artifact(&ws, args).await })
+        }),
+    );
     register_handler(
         "exec_shell",
         Arc::new(|args, ws| {
             Box::pin(async move { builtins::exec_shell::exec_shell(&ws, args).await })
         }),
     );
+    register_handler(
+        "git",
+        Arc::new(|args, ws| Box::pin(async move { builtins::git::git(&ws, args).await })),
+    );
+    register_handler(
+        "sql_query",
+        Arc::new(|args, ws| {
+            Box::pin(async move { builtins::sql_query::sql_query(&ws, args).await })
+        }),
+    );
+    register_handler(
+        "remind",
+        Arc::new(|args, ws| Box::pin(async move { builtins::remind::remind(&ws, args).await })),
+    );
+    register_handler(
+        "view_image",
+        Arc::new(|args, ws| {
+            Box::pin(async move { builtins::view_image::view_image(&ws, args).await })
+        }),
+    );
+    register_handler(
+        "http_fetch",
+        Arc::new(|args, ws| {
+            Box::pin(async move { builtins::http_fetch::http_fetch(&ws, args).await })
+        }),
+    );
     register_handler(
         "save_memory",
         Arc::new(|args, ws| {
@@ -1041,6 +1394,11 @@ pub fn init() {
             "self_update",
             "send_message",
             "delegate",
+            "http_fetch",
+            "git",
+            "sql_query",
+            "remind",
+            "view_image",
         ];
         let mut reg = REGISTRY.lock().expect("tool registry poisoned");
         for entry in reg.iter_mut() {
@@ -0,0 +1,298 @@
+//! Per-agent tool policy enforcement.
+//!
+//! Evaluates an agent's [`ToolPolicy`] (the `tools:` block in its
+//! config) against a tool name — to hide tools from function
+//! definitions — and against a concrete call's arguments before it runs.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::config::ToolPolicy;
+use crate::utils::glob_match;
+
+/// Tools whose `path` argument is subject to `paths` constraints.
+const PATH_TOOLS: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "list_files",
+    "search_files",
+    "git",
+    "sql_query",
+    "view_image",
+];
+
+/// Whether the policy lets the agent see and call `name` at all.
+pub fn tool_allowed(policy: &ToolPolicy, name: &str) -> bool {
+    if policy.deny.iter().any(|p| glob_match(p, name)) {
+        return false;
+    }
+    policy.allow.is_empty() || policy.allow.iter().any(|p| glob_match(p, name))
+}
+
+/// Check a tool call against the policy.  Returns the reason when the
+/// call must not run.
+///
+/// `default_channel` is used for `send_message` calls that omit
+/// `channel_id`, mirroring the tool's own fallback.
+pub fn check_call(
+    policy: &ToolPolicy,
+    tool: &str,
+    args: &Value,
+    workspace: &Path,
+    default_channel: Option<&str>,
+) -> Result<(), String> {
+    if !tool_allowed(policy, tool) {
+        return Err(format!("tool `{tool}` is not permitted for this agent"));
+    }
+
+    if !policy.paths.is_empty() {
+        let paths: Vec<String> = if PATH_TOOLS.contains(&tool) {
+            vec![args["path"].as_str().unwrap_or(".").to_string()]
+        } else if tool == "apply_patch" {
+            args["patch"]
+                .as_str()
+                .map(crate::tools::builtins::apply_patch::patched_paths)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        for path in &paths {
+            if !path_permitted(&policy.paths, path, workspace) {
+                return Err(format!(
+                    "`{tool}` may not access '{path}' — allowed paths: {}",
+                    policy.paths.join(", ")
+                ));
+            }
+        }
+    }
+
+    if tool == "exec_shell" && !policy.commands.is_empty() {
+        if let Some(command) = args["command"].as_str() {
+            if let Some(construct) = unsplittable_construct(command) {
+                return Err(format!(
+                    "exec_shell: {construct} is not allowed when this agent's commands are restricted"
+                ));
+            }
+            let names = crate::tools::extract_command_names(command);
+            if names.is_empty() {
+                return Err("exec_shell: could not determine the command to run".into());
+            }
+            if let Some(bad) = names.iter().find(|n| !policy.commands.contains(n)) {
+                return Err(format!(
+                    "exec_shell: command '{bad}' is not permitted for this agent — allowed: {}",
+                    policy.commands.join(", ")
+                ));
+            }
+        }
+    }
+
+    if tool == "send_message" && !policy.channels.is_empty() {
+        let target = args["channel_id"]
+            .as_str()
+            .filter(|s| !s.trim().is_empty())
+            .or(default_channel)
+            .unwrap_or("");
+        if !policy.channels.iter().any(|p| glob_match(p, target)) {
+            return Err(format!(
+                "send_message: channel '{target}' is not permitted for this agent"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Find shell syntax that can start a command [`extract_command_names`]
+/// would not see: newlines, background `&`, command and process
+/// substitution, and subshells.  With a command allowlist in force these
+/// are refused outright rather than parsed.
+///
+/// [`extract_command_names`]: crate::tools::extract_command_names
+fn unsplittable_construct(command: &str) -> Option<&'static str> {
+    if command.contains(['\n', '\r']) {
+        return Some("a multi-line command");
+    }
+    if command.contains('`') || command.contains("$(") {
+        return Some("command substitution");
+    }
+    if command.contains("<(") || command.contains(">(") {
+        return Some("process substitution");
+    }
+    if command.contains(['(', ')']) {
+        return Some("a subshell");
+    }
+    // `&&` is split on; `>&` / `<&` duplicate file descriptors (`2>&1`).
+    let bytes = command.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b != b'&' {
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|j| bytes[j]);
+        let next = bytes.get(i + 1).copied();
+        if prev == Some(b'&') || next == Some(b'&') || matches!(prev, Some(b'>' | b'<')) {
+            continue;
+        }
+        return Some("a background `&`");
+    }
+    None
+}
+
+/// Check a `git` push target against the policy's `git_push` globs.  The
+/// `git` tool calls this itself once it has resolved the default remote
+/// and branch.
+pub fn check_git_push(policy: &ToolPolicy, remote: &str, branch: &str) -> Result<(), String> {
+    let target = format!("{remote}/{branch}");
+    if policy.git_push.is_empty() || policy.git_push.iter().any(|p| glob_match(p, &target)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "git: pushing to '{target}' is not permitted for this agent — allowed: {}",
+            policy.git_push.join(", ")
+        ))
+    }
+}
+
+/// Whether `path` (as passed to a file tool) falls under one of the
+/// workspace-relative `prefixes`.  Matching is per path component, so
+/// `notes` covers `notes/a.md` but not `notes2/a.md`.
+fn path_permitted(prefixes: &[String], path: &str, workspace: &Path) -> bool {
+    let Some(rel) = workspace_relative(path, workspace) else {
+        return false;
+    };
+    prefixes.iter().any(|prefix| {
+        workspace_relative(prefix, workspace).is_some_and(|prefix| rel.starts_with(prefix))
+    })
+}
+
+/// Lexically normalise `path` relative to the workspace.  Returns `None`
+/// when it points outside.
+fn workspace_relative(path: &str, workspace: &Path) -> Option<PathBuf> {
+    let raw = Path::new(path);
+    let rel = if raw.is_absolute() {
+        let canonical_ws = workspace.canonicalize().ok();
+        raw.strip_prefix(workspace)
+            .ok()
+            .or_else(|| raw.strip_prefix(canonical_ws.as_deref()?).ok())?
+    } else {
+        raw
+    };
+    let mut out = PathBuf::new();
+    for comp in rel.components() {
+        match comp {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy() -> ToolPolicy {
+        ToolPolicy {
+            allow: vec!["*_file".into(), "list_files".into(), "exec_shell".into()],
+            deny: vec!["write_file".into()],
+            paths: vec!["notes/".into()],
+            commands: vec!["ls".into(), "grep".into()],
+            channels: Vec::new(),
+            git_push: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn allow_and_deny_globs() {
+        let p = policy();
+        assert!(tool_allowed(&p, "read_file"));
+        assert!(!tool_allowed(&p, "write_file"));
+        assert!(!tool_allowed(&p, "send_message"));
+        assert!(tool_allowed(&ToolPolicy::default(), "anything"));
+    }
+
+    #[test]
+    fn path_prefixes_are_component_wise() {
+        let ws = Path::new("/tmp/ws");
+        let p = policy();
+        let read = |path: &str| check_call(&p, "read_file", &json!({ "path": path }), ws, None);
+        assert!(read("notes/todo.md").is_ok());
+        assert!(read("./notes/sub/../todo.md").is_ok());
+        assert!(read("/tmp/ws/notes/todo.md").is_ok());
+        assert!(read("notes2/todo.md").is_err());
+        assert!(read("notes/../secrets.txt").is_err());
+        assert!(read("../ws/notes/todo.md").is_err());
+        assert!(check_call(&p, "list_files", &json!({}), ws, None).is_err());
+        let p = ToolPolicy {
+            paths: p.paths,
+            ..Default::default()
+        };
+        let query = |path: &str| check_call(&p, "sql_query", &json!({ "path": path }), ws, None);
+        assert!(query("notes/app.db").is_ok());
+        assert!(query("memory.db").is_err());
+        let view = |path: &str| check_call(&p, "view_image", &json!({ "path": path }), ws, None);
+        assert!(view("notes/chart.png").is_ok());
+        assert!(view("private/photo.jpg").is_err());
+    }
+
+    #[test]
+    fn exec_shell_command_allowlist() {
+        let ws = Path::new("/tmp/ws");
+        let p = policy();
+        let run = |cmd: &str| check_call(&p, "exec_shell", &json!({ "command": cmd }), ws, None);
+        assert!(run("ls -la | grep foo").is_ok());
+        assert!(run("ls && rm -rf notes").is_err());
+        assert!(run("ls 2>&1 | grep foo").is_ok());
+        for bypass in [
+            "ls\nrm -rf notes",
+            "ls\r\nrm -rf notes",
+            "ls & rm -rf notes",
+            "ls &rm -rf notes",
+            "ls &> out; grep x out",
+            "ls $(rm -rf notes)",
+            "ls `rm -rf notes`",
+            "grep foo <(rm -rf notes)",
+            "ls >(rm -rf notes)",
+            "(rm -rf notes)",
+            "ls; (rm -rf notes)",
+        ] {
+            assert!(run(bypass).is_err(), "{bypass:?} was allowed");
+        }
+        // Background management actions carry no command.
+        assert!(check_call(&p, "exec_shell", &json!({ "action": "list" }), ws, None).is_ok());
+    }
+
+    #[test]
+    fn send_message_channels() {
+        let ws = Path::new("/tmp/ws");
+        let p = ToolPolicy {
+            channels: vec!["discord:123*".into()],
+            ..Default::default()
+        };
+        let send = |args: Value, default| check_call(&p, "send_message", &args, ws, default);
+        assert!(send(json!({ "channel_id": "discord:12345" }), None).is_ok());
+        assert!(send(json!({ "channel_id": "discord:999" }), None).is_err());
+        assert!(send(json!({}), Some("discord:1234")).is_ok());
+        assert!(send(json!({}), None).is_err());
+    }
+
+    #[test]
+    fn git_push_targets() {
+        let p = ToolPolicy {
+            git_push: vec!["origin/feature-*".into()],
+            ..Default::default()
+        };
+        assert!(check_git_push(&p, "origin", "feature-x").is_ok());
+        assert!(check_git_push(&p, "origin", "main").is_err());
+        assert!(check_git_push(&p, "upstream", "feature-x").is_err());
+        assert!(check_git_push(&ToolPolicy::default(), "origin", "main").is_ok());
+    }
+}
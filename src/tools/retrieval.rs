@@ -0,0 +1,328 @@
+//! Semantic selection of deferred tools and skills for a turn.
+//!
+//! Deferred tool and skill descriptions are embedded once through the
+//! turn's [`ProviderManager`] and cached in [`PinchyDb`] (keyed by a hash
+//! of the embedded text, so edited descriptions are re-embedded).  Each
+//! turn embeds the recent conversation and picks the closest tools and
+//! skills.  When no provider can embed, or no database is available, the
+//! keyword rules in [`auto_pluck_deferred`](super::auto_pluck_deferred)
+//! are used instead.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use super::{ToolMeta, REGISTRY};
+use crate::models::ProviderManager;
+use crate::store::PinchyDb;
+
+/// Most deferred tools injected per turn.
+const TOOL_LIMIT: usize = 6;
+/// Most skills suggested per turn.
+const SKILL_LIMIT: usize = 2;
+/// Cosine similarity below which a tool or skill is not considered relevant.
+const MIN_SIMILARITY: f64 = 0.3;
+/// Longest conversation excerpt embedded as the query, in characters.
+const MAX_QUERY_CHARS: usize = 4000;
+/// Give up on embeddings (and fall back to keywords) after this long.
+const EMBED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Deferred tools and skills picked for one turn.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Deferred tools to add to the function definitions.
+    pub tools: Vec<ToolMeta>,
+    /// Skills worth activating, most relevant first.
+    pub skills: Vec<String>,
+}
+
+/// A description that takes part in retrieval.
+struct Document {
+    /// Cache key: `tool:<name>` or `skill:<name>`.
+    key: String,
+    text: String,
+    /// Set for deferred tools.
+    tool: Option<ToolMeta>,
+}
+
+impl Document {
+    fn tool(meta: &ToolMeta) -> Self {
+        Self {
+            key: format!("tool:{}", meta.name),
+            text: format!("{}: {}", meta.name, meta.description),
+            tool: Some(meta.clone()),
+        }
+    }
+
+    fn skill(name: &str, description: &str) -> Self {
+        Self {
+            key: format!("skill:{name}"),
+            text: format!("{name}: {description}"),
+            tool: None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.key.split_once(':').map_or(&self.key, |(_, n)| n)
+    }
+
+    fn hash(&self) -> String {
+        ring::digest::digest(&ring::digest::SHA256, self.text.as_bytes())
+            .as_ref()
+            .iter()
+            .take(16)
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// Pick the deferred tools and skills relevant to `text` (the current
+/// message plus recent context).  `enabled_skills` limits the skills
+/// considered, as in [`prompt_instructions`](super::prompt_instructions).
+pub async fn select(
+    text: &str,
+    manager: &ProviderManager,
+    db: Option<&PinchyDb>,
+    enabled_skills: Option<&[String]>,
+) -> Selection {
+    if let Some(db) = db {
+        if let Some(selection) = select_from(documents(enabled_skills), text, manager, db).await {
+            return selection;
+        }
+    }
+    Selection {
+        tools: super::auto_pluck_deferred(text),
+        skills: Vec::new(),
+    }
+}
+
+/// System-prompt hint naming the skills picked for this turn.
+pub fn skill_hint(skills: &[String]) -> Option<String> {
+    if skills.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = skills.iter().map(|s| format!("`{s}`")).collect();
+    Some(format!(
+        "Skills likely relevant to this request: {}. Load one with `activate_skill` before starting if it fits.",
+        names.join(", ")
+    ))
+}
+
+/// Deferred tools and available skills from the registry.
+fn documents(enabled_skills: Option<&[String]>) -> Vec<Document> {
+    let reg = REGISTRY.lock().expect("tool registry poisoned");
+    let mut docs = Vec::new();
+    let mut seen_skills = HashSet::new();
+    for entry in reg.iter() {
+        if entry.deferred {
+            docs.push(Document::tool(&entry.meta));
+        }
+        let Some(skill) = &entry.skill else { continue };
+        let enabled = enabled_skills.is_none_or(|ids| ids.contains(&entry.meta.name));
+        if enabled && skill.unmet.is_empty() && seen_skills.insert(&entry.meta.name) {
+            docs.push(Document::skill(&entry.meta.name, &skill.description));
+        }
+    }
+    docs
+}
+
+/// Rank `docs` against `text`.  `None` when embeddings are unavailable.
+async fn select_from(
+    docs: Vec<Document>,
+    text: &str,
+    manager: &ProviderManager,
+    db: &PinchyDb,
+) -> Option<Selection> {
+    if docs.is_empty() {
+        return None;
+    }
+    let query_text: String = text.chars().take(MAX_QUERY_CHARS).collect();
+    let query = embed(manager, &[query_text.as_str()]).await?.pop()?;
+
+    let mut cache = match db.load_tool_embeddings() {
+        Ok(c) => c,
+        Err(e) => {
+            warn!(error = %e, "failed to load tool embeddings");
+            HashMap::new()
+        }
+    };
+    // A different vector size means the embedding model changed.
+    let stale: Vec<(&Document, String)> = docs
+        .iter()
+        .map(|d| (d, d.hash()))
+        .filter(|(d, hash)| {
+            cache
+                .get(&d.key)
+                .is_none_or(|(h, v)| h != hash || v.len() != query.len())
+        })
+        .collect();
+    if !stale.is_empty() {
+        let texts: Vec<&str> = stale.iter().map(|(d, _)| d.text.as_str()).collect();
+        let vectors = embed(manager, &texts).await?;
+        if vectors.len() != stale.len() {
+            warn!(
+                expected = stale.len(),
+                got = vectors.len(),
+                "embedding provider returned the wrong number of vectors"
+            );
+            return None;
+        }
+        let rows: Vec<(String, String, Vec<f32>)> = stale
+            .into_iter()
+            .zip(vectors)
+            .map(|((d, hash), v)| (d.key.clone(), hash, v))
+            .collect();
+        if let Err(e) = db.save_tool_embeddings(&rows) {
+            warn!(error = %e, "failed to cache tool embeddings");
+        }
+        debug!(count = rows.len(), "embedded tool and skill descriptions");
+        cache.extend(rows.into_iter().map(|(k, h, v)| (k, (h, v))));
+    }
+
+    let mut scored: Vec<(f64, &Document)> = docs
+        .iter()
+        .filter_map(|d| {
+            let (_, v) = cache.get(&d.key)?;
+            let sim = crate::memory::cosine_similarity(&query, v);
+            (sim >= MIN_SIMILARITY).then_some((sim, d))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let tools = scored
+        .iter()
+        .filter_map(|(_, d)| d.tool.clone())
+        .take(TOOL_LIMIT)
+        .collect();
+    let skills = scored
+        .iter()
+        .filter(|(_, d)| d.tool.is_none())
+        .map(|(_, d)| d.name().to_string())
+        .take(SKILL_LIMIT)
+        .collect();
+    Some(Selection { tools, skills })
+}
+
+/// Embed `texts`, or `None` when no provider can (or it takes too long).
+async fn embed(manager: &ProviderManager, texts: &[&str]) -> Option<Vec<Vec<f32>>> {
+    match tokio::time::timeout(EMBED_TIMEOUT, manager.embed(texts)).await {
+        Ok(Ok(Some(vectors))) => Some(vectors),
+        Ok(Ok(None)) => None,
+        Ok(Err(e)) => {
+            warn!(error = %e, "embedding for tool retrieval failed");
+            None
+        }
+        Err(_) => {
+            warn!("embedding for tool retrieval timed out");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChatMessage, ModelProvider};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const VOCAB: &[&str] = &["database", "git", "weather", "tables", "commit"];
+
+    /// Embeds text as counts of a few vocabulary words.
+    struct BagOfWords {
+        inputs: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ModelProvider for BagOfWords {
+        async fn send_chat(&self, _messages: &[ChatMessage]) -> anyhow::Result<String> {
+            Ok(String::new())
+        }
+        fn send_chat_stream<'a>(
+            &'a self,
+            _messages: &'a [ChatMessage],
+        ) -> std::pin::Pin<Box<dyn futures_core::Stream<Item = anyhow::Result<String>> + Send + 'a>>
+        {
+            Box::pin(tokio_stream::empty())
+        }
+        async fn embed(&self, texts: &[&str]) -> anyhow::Result<Option<Vec<Vec<f32>>>> {
+            self.inputs.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(Some(
+                texts
+                    .iter()
+                    .map(|t| {
+                        let t = t.to_lowercase();
+                        VOCAB.iter().map(|w| t.matches(w).count() as f32).collect()
+                    })
+                    .collect(),
+            ))
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn docs() -> Vec<Document> {
+        let tool = |name: &str, description: &str| {
+            Document::tool(&ToolMeta {
+                name: name.into(),
+                description: description.into(),
+                args_schema: serde_json::json!({}),
+                exclusive: false,
+                timeout_secs: None,
+            })
+        };
+        vec![
+            tool("sql_query", "Query tables in a SQLite database"),
+            tool("git", "Inspect history and commit changes"),
+            Document::skill("forecast", "Look up the weather"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn picks_closest_documents_and_caches_embeddings() {
+        let inputs = Arc::new(AtomicUsize::new(0));
+        let manager = ProviderManager::new(
+            vec![Box::new(BagOfWords {
+                inputs: inputs.clone(),
+            })],
+            1,
+        );
+        let db = PinchyDb::open_memory().unwrap();
+
+        let sel = select_from(docs(), "which tables are in my database?", &manager, &db)
+            .await
+            .unwrap();
+        let names: Vec<_> = sel.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["sql_query"]);
+        assert!(sel.skills.is_empty());
+        assert_eq!(inputs.load(Ordering::SeqCst), 4, "query + three documents");
+        assert_eq!(db.load_tool_embeddings().unwrap().len(), 3);
+
+        let sel = select_from(docs(), "will the weather hold?", &manager, &db)
+            .await
+            .unwrap();
+        assert!(sel.tools.is_empty());
+        assert_eq!(sel.skills, ["forecast"]);
+        assert_eq!(
+            inputs.load(Ordering::SeqCst),
+            5,
+            "documents come from the cache"
+        );
+    }
+
+    #[tokio::test]
+    async fn without_embeddings_there_is_no_semantic_selection() {
+        let manager = ProviderManager::new(Vec::new(), 1);
+        let db = PinchyDb::open_memory().unwrap();
+        assert!(select_from(docs(), "database", &manager, &db)
+            .await
+            .is_none());
+        assert_eq!(skill_hint(&[]), None);
+        assert!(skill_hint(&["forecast".into()])
+            .unwrap()
+            .contains("`forecast`"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Tool-argument validation against [`ToolMeta::args_schema`](super::ToolMeta).
+//!
+//! Covers the JSON-Schema keywords tool schemas actually use — `type`,
+//! `properties`, `required`, `additionalProperties`, `enum`, `const`,
+//! `items`, numeric and length bounds, `anyOf`/`oneOf`/`allOf` — and
+//! ignores the rest, so richer schemas (e.g. from MCP servers) are
+//! checked as far as possible without false rejections.
+//!
+//! `null` for an optional property counts as absent: models often send
+//! explicit nulls for arguments they mean to leave out.
+
+use serde_json::Value;
+
+/// Violations of `schema` by `args`, one human-readable line each; empty
+/// when the arguments are valid.
+pub fn validate(schema: &Value, args: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, args, "arguments", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            errors.push(format!(
+                "`{path}`: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let listed: Vec<String> = options.iter().map(Value::to_string).collect();
+            errors.push(format!("`{path}`: must be one of {}", listed.join(", ")));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("`{path}`: must be {expected}"));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|r| r.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            for name in &required {
+                if map.get(*name).is_none_or(Value::is_null) {
+                    errors.push(format!(
+                        "`{}`: required property is missing",
+                        child(path, name)
+                    ));
+                }
+            }
+            for (name, v) in map {
+                match properties.and_then(|p| p.get(name)) {
+                    Some(_) if v.is_null() && !required.contains(&name.as_str()) => {}
+                    Some(sub) => check(sub, v, &child(path, name), errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            let mut known: Vec<&str> = properties
+                                .map(|p| p.keys().map(String::as_str).collect())
+                                .unwrap_or_default();
+                            known.sort_unstable();
+                            errors.push(format!(
+                                "`{}`: unknown property (expected: {})",
+                                child(path, name),
+                                known.join(", ")
+                            ));
+                        }
+                        Some(sub @ Value::Object(_)) => check(sub, v, &child(path, name), errors),
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(sub) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(sub, item, &format!("{path}[{i}]"), errors);
+                }
+            }
+            bound(
+                schema,
+                "minItems",
+                "maxItems",
+                items.len() as f64,
+                "items",
+                path,
+                errors,
+            );
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as f64;
+            bound(
+                schema,
+                "minLength",
+                "maxLength",
+                len,
+                "characters",
+                path,
+                errors,
+            );
+        }
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                bound(schema, "minimum", "maximum", n, "", path, errors);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            check(sub, value, path, errors);
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(branches) = schema.get(key).and_then(Value::as_array) {
+            let matches = branches.iter().any(|sub| {
+                let mut branch_errors = Vec::new();
+                check(sub, value, path, &mut branch_errors);
+                branch_errors.is_empty()
+            });
+            if !branches.is_empty() && !matches {
+                errors.push(format!("`{path}`: does not match any allowed form"));
+            }
+        }
+    }
+}
+
+/// Report `actual` outside the schema's `min_key`/`max_key` bounds.
+fn bound(
+    schema: &serde_json::Map<String, Value>,
+    min_key: &str,
+    max_key: &str,
+    actual: f64,
+    unit: &str,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let unit = if unit.is_empty() {
+        String::new()
+    } else {
+        format!(" {unit}")
+    };
+    if let Some(min) = schema.get(min_key).and_then(Value::as_f64) {
+        if actual < min {
+            errors.push(format!("`{path}`: must be at least {min}{unit}"));
+        }
+    }
+    if let Some(max) = schema.get(max_key).and_then(Value::as_f64) {
+        if actual > max {
+            errors.push(format!("`{path}`: must be at most {max}{unit}"));
+        }
+    }
+}
+
+fn child(path: &str, name: &str) -> String {
+    if path == "arguments" {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        // Unknown type names are not ours to reject.
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "minLength": 1 },
+                "mode": { "type": "string", "enum": ["read", "write"] },
+                "limit": { "type": "integer", "minimum": 1 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+            },
+            "required": ["path"],
+            "additionalProperties": false,
+        })
+    }
+
+    #[test]
+    fn accepts_valid_arguments_and_optional_nulls() {
+        assert!(validate(&schema(), &json!({"path": "a.txt", "limit": 3})).is_empty());
+        assert!(validate(&schema(), &json!({"path": "a.txt", "mode": null})).is_empty());
+        assert!(validate(&json!(null), &json!({"anything": 1})).is_empty());
+        assert!(validate(&json!({}), &json!("free-form")).is_empty());
+    }
+
+    #[test]
+    fn lists_each_violation() {
+        let mut errors = validate(
+            &schema(),
+            &json!({"mode": "append", "limit": 0, "tags": ["a", 2], "force": true}),
+        );
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "`force`: unknown property (expected: limit, mode, path, tags)",
+                "`limit`: must be at least 1",
+                "`mode`: must be one of \"read\", \"write\"",
+                "`path`: required property is missing",
+                "`tags[1]`: expected string, got integer",
+            ]
+        );
+        assert_eq!(
+            validate(&schema(), &json!("a.txt")),
+            ["`arguments`: expected object, got string"]
+        );
+    }
+
+    #[test]
+    fn any_of_needs_one_matching_branch() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"type": "integer"}]});
+        assert!(validate(&schema, &json!(5)).is_empty());
+        assert_eq!(
+            validate(&schema, &json!(true)),
+            ["`arguments`: does not match any allowed form"]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Minimal `.gitignore` matching for workspace traversal.
+//!
+//! Supports comments, `!` negation, trailing-`/` directory patterns,
+//! anchored patterns (containing a `/`) and `**` segments.  Each segment
+//! is matched with [`glob_match`](super::glob_match), so `*` and `?` work
+//! but character classes do not.
+
+use std::path::Path;
+
+use super::glob_match;
+
+/// One parsed `.gitignore` line.
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Pattern split on `/`, without leading/trailing slashes.
+    segments: Vec<String>,
+    /// Pattern contained a `/` before its end: match from the base
+    /// directory rather than at any depth.
+    anchored: bool,
+    /// Trailing `/`: only matches directories.
+    dir_only: bool,
+    /// Leading `!`: re-includes a previously ignored path.
+    negated: bool,
+}
+
+/// Rules from a single `.gitignore`, relative to the directory holding it.
+#[derive(Debug, Clone, Default)]
+pub struct GitIgnore {
+    rules: Vec<Rule>,
+}
+
+impl GitIgnore {
+    /// Parse `.gitignore` contents.
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let segments: Vec<String> = line
+                    .trim_start_matches('/')
+                    .split('/')
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect();
+                if segments.is_empty() {
+                    return None;
+                }
+                Some(Rule {
+                    segments,
+                    anchored,
+                    dir_only,
+                    negated,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Load `dir/.gitignore`, if present.
+    pub fn load(dir: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(dir.join(".gitignore")).ok()?;
+        let parsed = Self::parse(&contents);
+        (!parsed.rules.is_empty()).then_some(parsed)
+    }
+
+    /// Decide `rel` (a `/`-separated path relative to this file's
+    /// directory).  `Some(true)` = ignored, `Some(false)` = explicitly
+    /// re-included, `None` = no rule applies.  The last matching rule wins.
+    pub fn matches(&self, rel: &str, is_dir: bool) -> Option<bool> {
+        let parts: Vec<&str> = rel.split('/').filter(|s| !s.is_empty()).collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                if rule.dir_only && !is_dir {
+                    return false;
+                }
+                if rule.anchored {
+                    segments_match(&rule.segments, &parts)
+                } else {
+                    parts
+                        .last()
+                        .is_some_and(|name| glob_match(&rule.segments[0], name))
+                }
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Match a `/`-separated path against a glob where `**` spans any number
+/// of directories and `*` / `?` stay within one segment.
+pub fn path_glob_match(pattern: &str, path: &str) -> bool {
+    let segments: Vec<String> = pattern
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    segments_match(&segments, &parts)
+}
+
+fn segments_match(pattern: &[String], parts: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => parts.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=parts.len()).any(|skip| segments_match(rest, &parts[skip..]))
+        }
+        Some((first, rest)) => match parts.split_first() {
+            Some((part, tail)) => glob_match(first, part) && segments_match(rest, tail),
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitignore_rules() {
+        let ig = GitIgnore::parse(
+            "# build output\n\
+             target/\n\
+             *.log\n\
+             !keep.log\n\
+             /docs/generated\n\
+             src/**/fixtures\n",
+        );
+        assert_eq!(ig.matches("target", true), Some(true));
+        assert_eq!(ig.matches("target", false), None);
+        assert_eq!(ig.matches("a/b/debug.log", false), Some(true));
+        assert_eq!(ig.matches("a/keep.log", false), Some(false));
+        assert_eq!(ig.matches("docs/generated", true), Some(true));
+        assert_eq!(ig.matches("sub/docs/generated", true), None);
+        assert_eq!(ig.matches("src/fixtures", true), Some(true));
+        assert_eq!(ig.matches("src/a/b/fixtures", true), Some(true));
+        assert_eq!(ig.matches("src/main.rs", false), None);
+    }
+
+    #[test]
+    fn path_globs() {
+        assert!(path_glob_match("src/**/*.rs", "src/a/b/c.rs"));
+        assert!(path_glob_match("src/**/*.rs", "src/c.rs"));
+        assert!(!path_glob_match("src/*.rs", "src/a/c.rs"));
+        assert!(path_glob_match("**/test_*", "a/test_x"));
+    }
+}
@@ -5,6 +5,7 @@ use std::hash::{BuildHasher, Hasher};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub mod browser_detect;
+pub mod gitignore;
 
 /// Generate a random 16-char hex nonce.
 pub fn generate_nonce() -> String {
@@ -46,3 +47,38 @@ pub fn truncate_str(s: &str, max: usize) -> String {
         format!("{}…", &s[..end])
     }
 }
+
+/// Simple glob matching supporting `*` (any chars) and `?` (single char).
+///
+/// Operates on byte indices into `&str` to avoid allocating `Vec<char>` (#16).
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pat = pattern.as_bytes();
+    let txt = name.as_bytes();
+    let mut pi = 0usize;
+    let mut ti = 0usize;
+    let mut star_pi: Option<usize> = None;
+    let mut star_ti = 0usize;
+
+    while ti < txt.len() {
+        if pi < pat.len() && (pat[pi] == b'?' || pat[pi] == txt[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < pat.len() && pat[pi] == b'*' {
+            star_pi = Some(pi);
+            star_ti = ti;
+            pi += 1;
+        } else if let Some(sp) = star_pi {
+            pi = sp + 1;
+            star_ti += 1;
+            ti = star_ti;
+        } else {
+            return false;
+        }
+    }
+
+    while pi < pat.len() && pat[pi] == b'*' {
+        pi += 1;
+    }
+
+    pi == pat.len()
+}
@@ -2,6 +2,7 @@
 //!
 //! When configured, watches directories and automatically saves
 //! changed text files as memory entries keyed by their relative path.
+//! PDF, DOCX, ODT, HTML and EPUB files are saved as their extracted text.
 
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -151,14 +152,25 @@ async fn ingest_files(
     paths: Vec<PathBuf>,
 ) {
     for path in paths {
-        // Skip binary/large files.
-        if !is_text_file(&path) {
-            continue;
-        }
-
-        let content = match tokio::fs::read_to_string(&path).await {
-            Ok(c) => c,
-            Err(_) => continue,
+        let content = if crate::documents::DocumentKind::from_path(&path).is_some() {
+            let doc_path = path.clone();
+            match tokio::task::spawn_blocking(move || crate::documents::extract(&doc_path)).await {
+                Ok(Ok(doc)) => doc.text(),
+                Ok(Err(e)) => {
+                    debug!(path = %path.display(), error = %e, "skipping unreadable document");
+                    continue;
+                }
+                Err(_) => continue,
+            }
+        } else {
+            // Skip binary/large files.
+            if !is_text_file(&path) {
+                continue;
+            }
+            match tokio::fs::read_to_string(&path).await {
+                Ok(c) => c,
+                Err(_) => continue,
+            }
         };
 
         // Skip large files (>100KB).
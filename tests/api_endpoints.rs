@@ -320,6 +320,7 @@ agents: []
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     })
     .unwrap();
     db.upsert_cron_job(&PersistedCronJob {
@@ -334,6 +335,7 @@ agents: []
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     })
     .unwrap();
 
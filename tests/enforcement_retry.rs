@@ -106,6 +106,7 @@ async fn enforcement_retry_coaxes_provider_and_processes_tool_call() {
     let msg = IncomingMessage {
         agent_id: Some("test-agent".into()),
         author: "tester".into(),
+        author_id: None,
         content: "please write output.txt with the text: enforcement retry worked".into(),
         channel: "test".into(),
         timestamp: 0,
@@ -0,0 +1,137 @@
+//! Integration tests for the `http_fetch` tool.
+//!
+//! The mock server listens on 127.0.0.1, which `http_fetch` refuses
+//! unless the agent allows it, so these tests run against a config with
+//! one agent that does (`fetcher`) and one that does not (`plain`).
+
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use mini_claw::tools;
+use serde_json::json;
+use tempfile::TempDir;
+
+/// Shared `PINCHY_HOME`, set up once for every test in this file.
+static HOME: LazyLock<TempDir> = LazyLock::new(|| {
+    let home = tempfile::tempdir().expect("tempdir");
+    let root = |id: &str| home.path().join("agents").join(id);
+    for id in ["fetcher", "plain"] {
+        std::fs::create_dir_all(root(id).join("workspace")).unwrap();
+    }
+    std::fs::write(
+        home.path().join("config.yaml"),
+        format!(
+            "models: []\nchannels: {{}}\nagents:\n  - id: fetcher\n    root: {}\n    http_fetch:\n      allow_domains: [\"127.0.0.1\"]\n  - id: plain\n    root: {}\n",
+            root("fetcher").display(),
+            root("plain").display()
+        ),
+    )
+    .unwrap();
+    unsafe {
+        std::env::set_var("PINCHY_HOME", home.path());
+    }
+    home
+});
+
+/// Workspace of the agent `id`.
+fn workspace(id: &str) -> PathBuf {
+    HOME.path().join("agents").join(id).join("workspace")
+}
+
+#[tokio::test]
+async fn http_fetch_extracts_html_and_pretty_prints_json() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            "<html><head><title>Doc</title></head><body><nav>menu</nav>\
+             <main><h2>Intro</h2><p>Readable text that is long enough to count as the main \
+             content of this test page, with a <a href=\"/next\">link</a>.</p></main></body></html>",
+            "text/html; charset=utf-8",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api"))
+        .respond_with(ResponseTemplate::new(201).set_body_raw(r#"{"ok":true}"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let ws = workspace("fetcher");
+    let page = tools::call_skill(
+        "http_fetch",
+        json!({ "url": format!("{}/page", server.uri()) }),
+        &ws,
+    )
+    .await
+    .unwrap();
+    assert_eq!(page["status"], 200);
+    assert_eq!(page["title"], "Doc");
+    let content = page["content"].as_str().unwrap();
+    assert!(content.starts_with("## Intro"), "{content}");
+    assert!(content.contains(&format!("[link]({}/next)", server.uri())));
+    assert!(!content.contains("menu"));
+
+    let api = tools::call_skill(
+        "http_fetch",
+        json!({ "url": format!("{}/api", server.uri()), "method": "POST", "json": { "q": 1 } }),
+        &ws,
+    )
+    .await
+    .unwrap();
+    assert_eq!(api["status"], 201);
+    assert_eq!(api["content"], "{\n  \"ok\": true\n}");
+}
+
+#[tokio::test]
+async fn http_fetch_truncates_at_max_bytes() {
+    use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_body_raw("x".repeat(4096), "text/plain"))
+        .mount(&server)
+        .await;
+
+    let result = tools::call_skill(
+        "http_fetch",
+        json!({ "url": server.uri(), "max_bytes": 100 }),
+        &workspace("fetcher"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["bytes"], 100);
+    assert_eq!(result["truncated"], true);
+}
+
+#[tokio::test]
+async fn http_fetch_refuses_local_addresses_by_default() {
+    use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_body_string("secret"))
+        .mount(&server)
+        .await;
+
+    let ws = workspace("plain");
+    let err = tools::call_skill("http_fetch", json!({ "url": server.uri() }), &ws)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("loopback"), "{err}");
+
+    // A name that resolves to loopback is refused just the same.
+    let port = server.address().port();
+    let err = tools::call_skill(
+        "http_fetch",
+        json!({ "url": format!("http://localhost:{port}/") }),
+        &ws,
+    )
+    .await
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("loopback"), "{err:#}");
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -20,6 +20,7 @@ fn test_config(workspace: &std::path::Path, agent_id: &str) -> Config {
             embedding_deployment: None,
             embedding_model: None,
             headers: None,
+            vision: None,
         }],
         channels: ChannelsConfig {
             discord: None,
@@ -32,6 +33,8 @@ fn test_config(workspace: &std::path::Path, agent_id: &str) -> Config {
             heartbeat_secs: None,
             cron_jobs: vec![],
             max_tool_iterations: None,
+            max_parallel_tools: None,
+            tool_timeouts: Default::default(),
             enabled_skills: None,
             fallback_models: Vec::new(),
             webhook_secret: None,
@@ -42,6 +45,15 @@ fn test_config(workspace: &std::path::Path, agent_id: &str) -> Config {
             timezone: None,
             watch_paths: Vec::new(),
             reasoning_effort: None,
+            approvals: Vec::new(),
+            approvers: Vec::new(),
+            approval_timeout_secs: None,
+            mcp_servers: Vec::new(),
+            http_fetch: None,
+            tools: None,
+            sandbox: None,
+            journal_exec_shell: false,
+            shared_memory: None,
         }],
         secrets: None,
         routing: None,
@@ -50,6 +62,8 @@ fn test_config(workspace: &std::path::Path, agent_id: &str) -> Config {
         cron_session_expiry_days: None,
         cron_events_max_keep: None,
         chromium_path: None,
+        mcp_servers: Vec::new(),
+        mcp_serve: None,
         timezone: None,
     }
 }
@@ -94,6 +108,7 @@ fn round_trip_new_fields() {
         condition: None,
         retry_count: 2,
         last_status: Some("FAILED".into()),
+        deliver_to: None,
     };
     let json = serde_json::to_string(&job).unwrap();
     let decoded: PersistedCronJob = serde_json::from_str(&json).unwrap();
@@ -209,6 +224,7 @@ async fn oneshot_job_removed_after_success() {
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
     db.upsert_cron_job(&job).unwrap();
 
@@ -262,6 +278,7 @@ async fn retry_records_multiple_failures() {
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
     db.upsert_cron_job(&job).unwrap();
 
@@ -311,6 +328,7 @@ async fn retry_succeeds_after_initial_failures() {
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
     db.upsert_cron_job(&job).unwrap();
 
@@ -369,6 +387,7 @@ async fn dependency_prevents_job_execution() {
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
     db.upsert_cron_job(&job).unwrap();
 
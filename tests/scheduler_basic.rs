@@ -17,6 +17,7 @@ fn test_config(workspace: &std::path::Path, agent_id: &str, heartbeat_secs: u64)
             embedding_deployment: None,
             embedding_model: None,
             headers: None,
+            vision: None,
         }],
         channels: ChannelsConfig {
             discord: None,
@@ -29,6 +30,8 @@ fn test_config(workspace: &std::path::Path, agent_id: &str, heartbeat_secs: u64)
             heartbeat_secs: Some(heartbeat_secs),
             cron_jobs: vec![],
             max_tool_iterations: None,
+            max_parallel_tools: None,
+            tool_timeouts: Default::default(),
             enabled_skills: None,
             fallback_models: Vec::new(),
             webhook_secret: None,
@@ -39,6 +42,15 @@ fn test_config(workspace: &std::path::Path, agent_id: &str, heartbeat_secs: u64)
             timezone: None,
             watch_paths: Vec::new(),
             reasoning_effort: None,
+            approvals: Vec::new(),
+            approvers: Vec::new(),
+            approval_timeout_secs: None,
+            mcp_servers: Vec::new(),
+            http_fetch: None,
+            tools: None,
+            sandbox: None,
+            journal_exec_shell: false,
+            shared_memory: None,
         }],
         secrets: None,
         routing: None,
@@ -48,6 +60,8 @@ fn test_config(workspace: &std::path::Path, agent_id: &str, heartbeat_secs: u64)
         cron_events_max_keep: None,
         timezone: None,
         chromium_path: None,
+        mcp_servers: Vec::new(),
+        mcp_serve: None,
     }
 }
 
@@ -166,6 +180,7 @@ async fn cron_merge_persisted_jobs_on_startup() {
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     };
     db.upsert_cron_job(&persisted)
         .expect("insert persisted job");
@@ -184,6 +199,7 @@ async fn cron_merge_persisted_jobs_on_startup() {
             embedding_deployment: None,
             embedding_model: None,
             headers: None,
+            vision: None,
         }],
         channels: ChannelsConfig {
             discord: None,
@@ -207,6 +223,8 @@ async fn cron_merge_persisted_jobs_on_startup() {
                 },
             ],
             max_tool_iterations: None,
+            max_parallel_tools: None,
+            tool_timeouts: Default::default(),
             enabled_skills: None,
             fallback_models: Vec::new(),
             webhook_secret: None,
@@ -217,6 +235,15 @@ async fn cron_merge_persisted_jobs_on_startup() {
             timezone: None,
             watch_paths: Vec::new(),
             reasoning_effort: None,
+            approvals: Vec::new(),
+            approvers: Vec::new(),
+            approval_timeout_secs: None,
+            mcp_servers: Vec::new(),
+            http_fetch: None,
+            tools: None,
+            sandbox: None,
+            journal_exec_shell: false,
+            shared_memory: None,
         }],
         secrets: None,
         routing: None,
@@ -226,6 +253,8 @@ async fn cron_merge_persisted_jobs_on_startup() {
         cron_events_max_keep: None,
         timezone: None,
         chromium_path: None,
+        mcp_servers: Vec::new(),
+        mcp_serve: None,
     };
 
     let handle = mini_claw::scheduler::start(&cfg)
@@ -0,0 +1,96 @@
+//! Integration test for shared memory scopes across agents.
+
+use mini_claw::tools;
+use serde_json::json;
+
+#[tokio::test]
+async fn agents_share_memory_scopes_by_permission() {
+    let home = tempfile::tempdir().expect("tempdir");
+    unsafe {
+        std::env::set_var("PINCHY_HOME", home.path());
+    }
+    let root = |id: &str| home.path().join("agents").join(id);
+    for id in ["mum", "kid"] {
+        std::fs::create_dir_all(root(id).join("workspace")).unwrap();
+    }
+    std::fs::write(
+        home.path().join("config.yaml"),
+        format!(
+            "models: []\nchannels: {{}}\nagents:\n  - id: mum\n    root: {}\n    shared_memory:\n      write: [family]\n  - id: kid\n    root: {}\n    shared_memory:\n      read: [family]\n",
+            root("mum").display(),
+            root("kid").display()
+        ),
+    )
+    .unwrap();
+    let mum = root("mum").join("workspace");
+    let kid = root("kid").join("workspace");
+
+    let saved = tools::call_skill(
+        "save_memory",
+        json!({ "key": "home_address", "value": "12 Harbour Street", "scope": "family" }),
+        &mum,
+    )
+    .await
+    .unwrap();
+    assert_eq!(saved["scope"], "family");
+    tools::call_skill(
+        "save_memory",
+        json!({ "key": "homework", "value": "Harbour history essay due Friday" }),
+        &kid,
+    )
+    .await
+    .unwrap();
+    assert!(home.path().join("memory/family.db").exists());
+
+    // The kid sees its own memory and the family scope, fused.
+    let result = tools::call_skill("recall_memory", json!({ "query": "harbour" }), &kid)
+        .await
+        .unwrap();
+    let memories = result["memories"].as_array().unwrap();
+    assert_eq!(memories.len(), 2, "{result}");
+    let scopes: Vec<&str> = memories
+        .iter()
+        .map(|m| m["scope"].as_str().unwrap())
+        .collect();
+    assert!(scopes.contains(&"agent") && scopes.contains(&"family"));
+
+    let result = tools::call_skill("recall_memory", json!({ "scope": "family" }), &kid)
+        .await
+        .unwrap();
+    assert_eq!(result["memories"][0]["key"], "home_address");
+
+    // Read-only and unlisted scopes are refused.
+    let err = tools::call_skill(
+        "save_memory",
+        json!({ "key": "x", "value": "y", "scope": "family" }),
+        &kid,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("not writable"), "{err}");
+    let err = tools::call_skill("recall_memory", json!({ "scope": "global" }), &mum)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not readable"), "{err}");
+    let err = tools::call_skill(
+        "forget_memory",
+        json!({ "key": "home_address", "scope": "family" }),
+        &kid,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("not writable"), "{err}");
+
+    let forgot = tools::call_skill(
+        "forget_memory",
+        json!({ "key": "home_address", "scope": "family" }),
+        &mum,
+    )
+    .await
+    .unwrap();
+    assert_eq!(forgot["status"], "deleted");
+
+    unsafe {
+        std::env::remove_var("PINCHY_HOME");
+    }
+}
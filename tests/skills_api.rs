@@ -47,6 +47,15 @@ Test skill instructions.
     )
     .unwrap();
 
+    // A skill whose requirements cannot be met.
+    let needy_dir = home.join("agents/test-agent/skills/needs-bin");
+    std::fs::create_dir_all(&needy_dir).unwrap();
+    std::fs::write(
+        needy_dir.join("SKILL.md"),
+        "---\nname: needs-bin\ndescription: Needs a missing binary\nrequires:\n  bins: [pinchy-test-no-such-bin]\n---\nUse it.\n",
+    )
+    .unwrap();
+
     // Point pinchy_home() at our temp dir.
     unsafe {
         std::env::set_var("PINCHY_HOME", home.as_os_str());
@@ -59,6 +68,7 @@ Test skill instructions.
     let mut reg = mini_claw::skills::SkillRegistry::new(Some("test-agent".into()));
     reg.load_skills().unwrap();
     mini_claw::tools::sync_skills(&reg);
+    mini_claw::tools::set_skill_agent_id(Some("test-agent".into()));
 
     // ── Start the gateway ───────────────────────────────────────────────
     let addr: SocketAddr = "127.0.0.1:4020".parse().unwrap();
@@ -83,6 +93,19 @@ Test skill instructions.
         "should contain test-skill; got: {skills:?}"
     );
 
+    let needy = skills
+        .iter()
+        .find(|s| s["id"] == "needs-bin")
+        .expect("unavailable skills stay listed");
+    assert_eq!(needy["available"], false);
+    assert!(needy["unmet"][0]
+        .as_str()
+        .unwrap()
+        .contains("pinchy-test-no-such-bin"));
+    assert!(skills
+        .iter()
+        .any(|s| s["id"] == "test-skill" && s["available"] == true));
+
     // ── 2. PUT /api/agents/test-agent with valid enabled_skills ─────────
     let resp = client
         .put(format!("{base}/api/agents/test-agent"))
@@ -155,6 +178,105 @@ Test skill instructions.
         "enabled_skills should be null after clearing; got: {body}"
     );
 
+    // ── 6. Install, upgrade, export and roll back a package ──────────────
+    let pkg = home.join("pkg/greeter");
+    std::fs::create_dir_all(&pkg).unwrap();
+    let write_pkg = |version: &str| {
+        std::fs::write(
+            pkg.join("SKILL.md"),
+            format!(
+                "---\nname: greeter\ndescription: Says hello\nmetadata:\n  version: \"{version}\"\n---\nSay hello.\n"
+            ),
+        )
+        .unwrap();
+    };
+    write_pkg("1.0.0");
+    let resp = client
+        .post(format!("{base}/api/skills/install?agent=test-agent"))
+        .json(&serde_json::json!({ "source": pkg }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["version"], "1.0.0");
+    assert_eq!(body["checksum"].as_str().unwrap().len(), 64);
+
+    let resp = client
+        .get(format!("{base}/api/skills/greeter/export?agent=test-agent"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/gzip");
+    let archive = resp.bytes().await.unwrap();
+
+    write_pkg("2.0.0");
+    let resp = client
+        .post(format!("{base}/api/skills/install?agent=test-agent"))
+        .json(&serde_json::json!({ "source": pkg }))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["previous_version"], "1.0.0");
+    let skills: serde_json::Value = client
+        .get(format!("{base}/api/skills"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(skills["skills"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|s| s["id"] == "greeter"));
+
+    let body: serde_json::Value = client
+        .get(format!(
+            "{base}/api/skills/greeter/versions?agent=test-agent"
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["versions"][0]["version"], "1.0.0");
+
+    let resp = client
+        .post(format!(
+            "{base}/api/skills/greeter/rollback?agent=test-agent"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["version"], "1.0.0");
+
+    // Re-uploading the exported archive is a no-op now that v1 is back.
+    let resp = client
+        .post(format!("{base}/api/skills/install?agent=test-agent"))
+        .header("content-type", "application/gzip")
+        .body(archive)
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["unchanged"], true, "{body}");
+
+    let resp = client
+        .post(format!(
+            "{base}/api/skills/missing/rollback?agent=test-agent"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
     // ── Cleanup ─────────────────────────────────────────────────────────
     std::env::set_current_dir(orig_dir).unwrap();
     unsafe {
@@ -20,6 +20,7 @@ fn pjob(agent_id: &str, name: &str, schedule: &str, message: Option<&str>) -> Pe
         condition: None,
         retry_count: 0,
         last_status: None,
+        deliver_to: None,
     }
 }
 
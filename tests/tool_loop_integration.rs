@@ -74,6 +74,7 @@ async fn tool_loop_invokes_read_file_and_persists() {
     let msg = IncomingMessage {
         agent_id: Some("test-agent".into()),
         author: "tester".into(),
+        author_id: None,
         content: "please read test.txt".into(),
         channel: "test".into(),
         timestamp: 0,
@@ -147,6 +148,7 @@ async fn tool_loop_stops_on_plain_reply() {
     let msg = IncomingMessage {
         agent_id: Some("test-agent".into()),
         author: "tester".into(),
+        author_id: None,
         content: "hi".into(),
         channel: "test".into(),
         timestamp: 0,
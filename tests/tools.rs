@@ -38,6 +38,58 @@ async fn read_file_subdirectory() {
     assert_eq!(result["content"], "nested");
 }
 
+#[tokio::test]
+async fn read_file_extracts_documents_by_page() {
+    use std::io::Write;
+
+    let ws = workspace();
+    std::fs::write(
+        ws.path().join("page.html"),
+        "<html><head><title>Notes</title><script>x()</script></head><body><p>Hello <b>there</b></p></body></html>",
+    )
+    .unwrap();
+    let raw = tools::read_file(ws.path(), json!({ "path": "page.html" }))
+        .await
+        .expect("read_file should read HTML");
+    assert!(raw["content"].as_str().unwrap().starts_with("<html>"));
+    assert!(raw.get("format").is_none());
+    let result = tools::read_file(ws.path(), json!({ "path": "page.html", "extract": true }))
+        .await
+        .expect("read_file should extract HTML");
+    assert_eq!(result["content"], "# Notes\n\nHello **there**");
+    assert_eq!(result["format"], "html");
+    assert_eq!(result["total_pages"], 1);
+
+    let body = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>one</w:t></w:r></w:p><w:p><w:r><w:br w:type="page"/><w:t>two</w:t></w:r></w:p><w:p><w:r><w:br w:type="page"/><w:t>three</w:t></w:r></w:p></w:body></w:document>"#;
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(ws.path().join("doc.docx")).unwrap());
+    zip.start_file(
+        "word/document.xml",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(body.as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let result = tools::read_file(
+        ws.path(),
+        json!({ "path": "doc.docx", "start_page": 2, "end_page": 3 }),
+    )
+    .await
+    .expect("read_file should extract DOCX pages");
+    assert_eq!(
+        result["content"],
+        "--- page 2 of 3 ---\ntwo\n\n--- page 3 of 3 ---\nthree"
+    );
+    assert_eq!(result["start_page"], 2);
+    assert_eq!(result["end_page"], 3);
+
+    std::fs::write(ws.path().join("plain.txt"), "text").unwrap();
+    let err = tools::read_file(ws.path(), json!({ "path": "plain.txt", "start_page": 1 }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("start_page"));
+}
+
 #[tokio::test]
 async fn read_file_missing_returns_error() {
     let ws = workspace();
@@ -104,6 +156,43 @@ async fn write_file_escape_blocked() {
     assert!(result.is_err(), "path traversal should be blocked on write");
 }
 
+#[tokio::test]
+async fn file_tools_are_journalled_and_undone() {
+    let ws = workspace();
+    std::fs::write(ws.path().join("keep.txt"), "original").unwrap();
+
+    let guard = mini_claw::journal::begin(ws.path(), "turn-1");
+    tools::call_skill(
+        "write_file",
+        json!({ "path": "notes/new.txt", "content": "fresh" }),
+        ws.path(),
+    )
+    .await
+    .unwrap();
+    tools::call_skill(
+        "edit_file",
+        json!({ "path": "keep.txt", "mode": "search_replace", "search": "original", "content": "changed" }),
+        ws.path(),
+    )
+    .await
+    .unwrap();
+    drop(guard);
+
+    let changed = mini_claw::journal::changed_files(ws.path(), "turn-1");
+    assert!(changed.contains(&"keep.txt".to_string()), "{changed:?}");
+    assert!(
+        changed.contains(&"notes/new.txt".to_string()),
+        "{changed:?}"
+    );
+
+    mini_claw::journal::undo_last(ws.path()).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(ws.path().join("keep.txt")).unwrap(),
+        "original"
+    );
+    assert!(!ws.path().join("notes").exists());
+}
+
 // ── call_skill dispatcher ────────────────────────────────────
 
 #[tokio::test]
@@ -125,6 +214,64 @@ async fn call_skill_unknown_returns_error() {
     assert!(result.is_err());
 }
 
+// ── search_files ─────────────────────────────────────────────
+
+#[tokio::test]
+async fn search_files_respects_gitignore_and_skips_binary() {
+    let ws = workspace();
+    let root = ws.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target")).unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(
+        root.join("src/main.rs"),
+        "fn main() {\n    todo!(\"a.b\");\n}\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("src/notes.md"), "todo: write docs\n").unwrap();
+    std::fs::write(root.join("target/out.rs"), "todo!()\n").unwrap();
+    std::fs::write(root.join("blob.bin"), b"todo\0\x01").unwrap();
+
+    let result = tools::call_skill(
+        "search_files",
+        json!({ "pattern": "todo", "context": 1 }),
+        root,
+    )
+    .await
+    .unwrap();
+    let paths: Vec<&str> = result["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["src/main.rs", "src/notes.md"]);
+    assert_eq!(result["skipped_binary"], 1);
+    let hit = &result["results"][0]["matches"][0];
+    assert_eq!(hit["line"], 2);
+    assert_eq!(hit["before"], json!(["fn main() {"]));
+
+    let literal = tools::call_skill(
+        "search_files",
+        json!({ "pattern": "a.b", "literal": true, "include": ["*.rs"] }),
+        root,
+    )
+    .await
+    .unwrap();
+    assert_eq!(literal["total_matches"], 1);
+
+    let escaped = tools::call_skill(
+        "search_files",
+        json!({ "pattern": "x", "path": "../" }),
+        root,
+    )
+    .await;
+    assert!(
+        escaped.is_err(),
+        "searching outside the workspace should fail"
+    );
+}
+
 // ── exec_shell ───────────────────────────────────────────────
 
 #[tokio::test]
@@ -149,6 +296,119 @@ async fn exec_shell_stderr() {
     assert_eq!(result["stderr"], "oops");
 }
 
+#[tokio::test]
+async fn exec_shell_background_read_follows_cursor() {
+    let ws = workspace();
+    let started = tools::exec_shell(
+        ws.path(),
+        json!({ "command": "echo one; sleep 0.3; echo two >&2", "background": true }),
+    )
+    .await
+    .unwrap();
+    let pid = started["process_id"].clone();
+
+    let mut cursor = 0;
+    let mut seen = String::new();
+    for _ in 0..20 {
+        let chunk = tools::exec_shell(
+            ws.path(),
+            json!({ "action": "read", "process_id": pid, "cursor": cursor, "wait_ms": 1000 }),
+        )
+        .await
+        .unwrap();
+        seen.push_str(chunk["output"].as_str().unwrap());
+        cursor = chunk["cursor"].as_u64().unwrap();
+        if chunk["done"] == true {
+            break;
+        }
+    }
+    assert_eq!(seen, "one\ntwo\n");
+
+    let output = tools::exec_shell(ws.path(), json!({ "action": "output", "process_id": pid }))
+        .await
+        .unwrap();
+    assert_eq!(output["stdout"], "one");
+    assert_eq!(output["stderr"], "two");
+    assert_eq!(output["exit_code"], 0);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn exec_shell_interactive_session_takes_input() {
+    let ws = workspace();
+    let started = tools::exec_shell(
+        ws.path(),
+        json!({ "command": "read name; echo \"hello $name\"; cat", "interactive": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(started["interactive"], true);
+    let pid = started["process_id"].clone();
+
+    tools::exec_shell(
+        ws.path(),
+        json!({ "action": "write", "process_id": pid, "input": "pinchy\n" }),
+    )
+    .await
+    .unwrap();
+
+    let mut cursor = 0;
+    let mut seen = String::new();
+    for _ in 0..10 {
+        if seen.contains("hello pinchy") {
+            break;
+        }
+        let chunk = tools::exec_shell(
+            ws.path(),
+            json!({ "action": "read", "process_id": pid, "cursor": cursor, "wait_ms": 5000 }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(chunk["done"], false, "session ended early: {seen}");
+        seen.push_str(chunk["output"].as_str().unwrap());
+        cursor = chunk["cursor"].as_u64().unwrap();
+    }
+    assert!(seen.contains("hello pinchy"), "{seen}");
+
+    // Wait for `cat` to echo a line back so the interrupt cannot land
+    // while the shell is still forking it.
+    tools::exec_shell(
+        ws.path(),
+        json!({ "action": "write", "process_id": pid, "input": "ping\n" }),
+    )
+    .await
+    .unwrap();
+    for _ in 0..10 {
+        if seen.matches("ping").count() >= 2 {
+            break;
+        }
+        let chunk = tools::exec_shell(
+            ws.path(),
+            json!({ "action": "read", "process_id": pid, "cursor": cursor, "wait_ms": 5000 }),
+        )
+        .await
+        .unwrap();
+        seen.push_str(chunk["output"].as_str().unwrap());
+        cursor = chunk["cursor"].as_u64().unwrap();
+    }
+    assert!(seen.matches("ping").count() >= 2, "{seen}");
+
+    // `cat` keeps running until interrupted.
+    tools::exec_shell(
+        ws.path(),
+        json!({ "action": "signal", "process_id": pid, "signal": "INT" }),
+    )
+    .await
+    .unwrap();
+    let done = tools::exec_shell(
+        ws.path(),
+        json!({ "action": "read", "process_id": pid, "cursor": cursor, "wait_ms": 5000 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(done["done"], true);
+}
+
 // ── roundtrip: write then read ───────────────────────────────
 
 #[tokio::test]
@@ -84,6 +84,8 @@ fn duplicate_registration_ignored() {
         name: "read_file".into(),
         description: "DUPLICATE".into(),
         args_schema: serde_json::json!({}),
+        exclusive: false,
+        timeout_secs: None,
     });
 
     let after = tools::list_tools();
@@ -210,3 +212,46 @@ fn search_sessions_plural() {
         results
     );
 }
+
+/// Tools with side effects are marked exclusive so batches never run them
+/// alongside other calls; read-only tools may run in parallel.
+#[test]
+fn side_effecting_tools_are_exclusive() {
+    tools::init();
+
+    for name in ["write_file", "edit_file", "apply_patch", "exec_shell"] {
+        assert!(tools::is_exclusive(name), "{name} should be exclusive");
+    }
+    for name in ["read_file", "list_files", "search_files", "http_fetch"] {
+        assert!(!tools::is_exclusive(name), "{name} should not be exclusive");
+    }
+    assert!(!tools::is_exclusive("no_such_tool"));
+}
+
+/// Agent `tool_timeouts` override a tool's default limit.
+#[test]
+fn tool_timeouts_resolve_from_config_then_meta() {
+    use std::time::Duration;
+    tools::init();
+
+    assert_eq!(
+        tools::timeout_for("exec_shell", None),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(tools::timeout_for("read_file", None), None);
+
+    let agent: mini_claw::config::AgentConfig = serde_json::from_value(serde_json::json!({
+        "id": "a",
+        "root": "agents/a",
+        "tool_timeouts": { "exec_shell": 300, "read_file": 5 },
+    }))
+    .expect("agent config");
+    assert_eq!(
+        tools::timeout_for("exec_shell", Some(&agent)),
+        Some(Duration::from_secs(300))
+    );
+    assert_eq!(
+        tools::timeout_for("read_file", Some(&agent)),
+        Some(Duration::from_secs(5))
+    );
+}
@@ -98,6 +98,10 @@ const skillSchema = z.object({
   id: z.string(),
   description: z.string().nullable().optional(),
   operator_managed: z.boolean().nullable().optional(),
+  compatibility: z.string().nullable().optional(),
+  tools: z.array(z.string()).optional(),
+  available: z.boolean().optional(),
+  unmet: z.array(z.string()).optional(),
 });
 
 const heartbeatAgentSchema = z.object({
@@ -248,7 +252,9 @@ export interface RawReceipt {
   prompt_tokens?: number;
   completion_tokens?: number;
   total_tokens?: number;
-  tool_calls?: Array<{ tool?: string; args_summary?: string; success?: boolean; duration_ms?: number; error?: string }>;
+  tool_calls?: Array<{ tool?: string; args_summary?: string; success?: boolean; duration_ms?: number; error?: string; artifact_id?: string; invalid_args?: boolean }>;
+  cancelled?: boolean;
+  invalid_tool_calls?: number;
 }
 
 export interface GetReceiptsResponse {
@@ -350,6 +356,12 @@ export async function cloneAgent(id: string, newId: string): Promise<{ id: strin
   });
 }
 
+export async function cancelAgentTurn(id: string): Promise<{ agent: string; cancelled: boolean }> {
+  return request<{ agent: string; cancelled: boolean }>(`/api/agents/${encodeURIComponent(id)}/cancel`, {
+    method: "POST",
+  });
+}
+
 export async function getConfig(): Promise<Record<string, unknown>> {
   return request<Record<string, unknown>>("/api/config");
 }
@@ -540,6 +552,18 @@ export async function getReceipts(agentId: string, sessionId: string): Promise<G
   );
 }
 
+export interface Artifact {
+  artifact_id: string;
+  bytes: number;
+  content: string;
+}
+
+export async function getArtifact(agentId: string, artifactId: string): Promise<Artifact> {
+  return request<Artifact>(
+    `/api/agents/${encodeURIComponent(agentId)}/artifacts/${encodeURIComponent(artifactId)}`,
+  );
+}
+
 export async function listSlashCommands(): Promise<SlashCommand[]> {
   const res = await request<unknown>("/api/slash/commands");
   const parsed = z.object({ commands: z.array(slashCommandSchema) }).parse(res);
@@ -7,7 +7,9 @@ import {
   type SessionMessage,
   type SlashCommand,
   type RawReceipt,
+  cancelAgentTurn,
   deleteSession,
+  getArtifact,
   getCurrentSession,
   getReceipts,
   getSession,
@@ -46,6 +48,7 @@ import {
   ImagePlus,
   PanelLeftClose,
   PanelLeft,
+  Square,
 } from "lucide-react";
 import { useUiStore } from "@/state/ui";
 import { SessionSidebar } from "@/components/SessionSidebar";
@@ -115,6 +118,7 @@ type ReceiptItem = {
     success: boolean;
     duration: number;
     error?: string;
+    artifactId?: string;
   }>;
   userPrompt?: string;
   replySummary?: string;
@@ -145,7 +149,7 @@ type GatewayEvent = {
   model_id?: string;
   estimated_cost_usd?: number;
   call_details?: Array<{ model?: string; prompt_tokens?: number; completion_tokens?: number; cached_tokens?: number; reasoning_tokens?: number; cost_usd?: number; latency_ms?: number }>;
-  tool_calls?: Array<{ tool?: string; success?: boolean; duration_ms?: number; args_summary?: string; error?: string }>;
+  tool_calls?: Array<{ tool?: string; success?: boolean; duration_ms?: number; args_summary?: string; error?: string; artifact_id?: string }>;
   summary?: string;
   messages_compacted?: number;
   messages_kept?: number;
@@ -379,6 +383,7 @@ export function ChatRoute() {
         success: tc.success ?? true,
         duration: tc.duration_ms ?? 0,
         error: tc.error,
+        artifactId: tc.artifact_id,
       })) : [];
       return {
         timestamp: typeof r.started_at === "number" ? (r.started_at > 1e12 ? r.started_at : r.started_at * 1000) : Date.now(),
@@ -634,6 +639,7 @@ export function ChatRoute() {
                 success: tc.success ?? true,
                 duration: tc.duration_ms ?? 0,
                 error: tc.error,
+                artifactId: tc.artifact_id,
               }))
             : [];
           const startedAt = payload.timestamp ?? payload.started_at;
@@ -1224,7 +1230,7 @@ export function ChatRoute() {
                   )}
                   {/* Inline receipt for this assistant message */}
                   {!isUser && !isSystem && !isCompactedHistory && receiptByMsgIndex.has(index) && (() => {
-                    return <InlineReceipt receipt={receiptByMsgIndex.get(index)!} index={index} expanded={expandedInlineReceipt === index} onToggle={() => setExpandedInlineReceipt(expandedInlineReceipt === index ? null : index)} />;
+                    return <InlineReceipt receipt={receiptByMsgIndex.get(index)!} agentId={selectedAgent} index={index} expanded={expandedInlineReceipt === index} onToggle={() => setExpandedInlineReceipt(expandedInlineReceipt === index ? null : index)} />;
                   })()}
                 </React.Fragment>
               );
@@ -1382,6 +1388,8 @@ export function ChatRoute() {
                               </div>
                               {tc.args && <p className="mt-1 text-slate-500 font-mono text-[10px] break-all">{tc.args}</p>}
                               {tc.error && <p className="mt-1 text-rose-300 text-[10px]">{tc.error}</p>}
+                  {tc.artifactId && <ArtifactLink agentId={agentId} artifactId={tc.artifactId} />}
+                              {tc.artifactId && <ArtifactLink agentId={selectedAgent} artifactId={tc.artifactId} />}
                             </div>
                           ))}
                         </div>
@@ -1481,6 +1489,16 @@ export function ChatRoute() {
                 className="min-h-[44px] max-h-36 py-3 px-3 flex-1"
                 style={{ fieldSizing: "content" } as React.CSSProperties}
               />
+              {typing && (
+                <button
+                  type="button"
+                  title="Stop the running turn"
+                  onClick={() => { void cancelAgentTurn(selectedAgent).catch(() => undefined); }}
+                  className="mb-1.5 h-8 w-8 shrink-0 rounded-lg bg-white/[0.06] text-slate-300 flex items-center justify-center hover:bg-red-400/20 hover:text-red-300 transition-all duration-200"
+                >
+                  <Square className="h-3.5 w-3.5" />
+                </button>
+              )}
               <button
                 type="button"
                 onClick={sendMessage}
@@ -1554,7 +1572,7 @@ function toText(value: unknown): string {
   return String(value);
 }
 
-function InlineReceipt({ receipt: r, index, expanded, onToggle }: { receipt: ReceiptItem; index: number; expanded: boolean; onToggle: () => void }) {
+function InlineReceipt({ receipt: r, agentId, index, expanded, onToggle }: { receipt: ReceiptItem; agentId: string; index: number; expanded: boolean; onToggle: () => void }) {
   const okCount = r.tools.filter(t => t.success).length;
   const failCount = r.tools.length - okCount;
   const grouped = new Map<string, { total: number; ok: number }>();
@@ -1679,6 +1697,7 @@ function InlineReceipt({ receipt: r, index, expanded, onToggle }: { receipt: Rec
                   </div>
                   {tc.args && <p className="mt-1 text-slate-500 font-mono text-[10px] break-all">{tc.args}</p>}
                   {tc.error && <p className="mt-1 text-rose-300 text-[10px]">{tc.error}</p>}
+                  {tc.artifactId && <ArtifactLink agentId={agentId} artifactId={tc.artifactId} />}
                 </div>
               ))}
             </div>
@@ -1707,6 +1726,30 @@ function InlineReceipt({ receipt: r, index, expanded, onToggle }: { receipt: Rec
   );
 }
 
+/** Opens the full output of a tool call that was stored as an artifact. */
+function ArtifactLink({ agentId, artifactId }: { agentId: string; artifactId: string }) {
+  const [error, setError] = useState<string | null>(null);
+  const open = useCallback(async () => {
+    try {
+      const artifact = await getArtifact(agentId, artifactId);
+      const url = URL.createObjectURL(new Blob([artifact.content], { type: "text/plain" }));
+      window.open(url, "_blank", "noopener");
+      setTimeout(() => URL.revokeObjectURL(url), 60_000);
+      setError(null);
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    }
+  }, [agentId, artifactId]);
+  return (
+    <p className="mt-1 text-[10px]">
+      <button type="button" className="text-sky-300 hover:underline" onClick={open}>
+        Full output ({artifactId})
+      </button>
+      {error && <span className="ml-2 text-rose-300">{error}</span>}
+    </p>
+  );
+}
+
 function CompactSummaryCard({ summary }: { summary: { summary: string; messagesCompacted: number; messagesKept: number; timestamp: number } }) {
   const [expanded, setExpanded] = useState(true);
 
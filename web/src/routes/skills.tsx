@@ -104,6 +104,11 @@ export function SkillsRoute() {
                           operator
                         </Badge>
                       )}
+                      {skill.available === false && (
+                        <Badge variant="warning" className="!text-[9px]">
+                          unavailable
+                        </Badge>
+                      )}
                       {isExpanded
                         ? <ChevronDown className="h-3.5 w-3.5 text-slate-500" />
                         : <ChevronRight className="h-3.5 w-3.5 text-slate-500" />
@@ -123,6 +128,16 @@ export function SkillsRoute() {
                           Managed by operator — changes may be overwritten on restart.
                         </p>
                       )}
+                      {skill.compatibility && (
+                        <p className="text-[11px] text-slate-500">{skill.compatibility}</p>
+                      )}
+                      {skill.unmet && skill.unmet.length > 0 && (
+                        <ul className="text-[10px] text-amber-400/70 list-disc pl-4">
+                          {skill.unmet.map((reason) => (
+                            <li key={reason}>{reason}</li>
+                          ))}
+                        </ul>
+                      )}
                       <div className="pt-1">
                         {isConfirming ? (
                           <div className="flex items-center gap-2">